@@ -0,0 +1,109 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+#ifndef DATES_TOOL_H
+#define DATES_TOOL_H
+
+/* Generated by cbindgen from src/ffi.rs.  Do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/*
+ Returned when the call succeeded
+ */
+#define DATES_OK 0
+
+/*
+ Returned when the input doesn't match any supported format
+ */
+#define DATES_ERR_UNRECOGNIZED 1
+
+/*
+ Returned when the input is well-formed but out of range
+ */
+#define DATES_ERR_OUT_OF_RANGE 2
+
+/*
+ Returned when the input could mean more than one thing
+ */
+#define DATES_ERR_AMBIGUOUS 3
+
+/*
+ Returned when an argument is NULL or not valid UTF-8, or a value can't be
+ represented
+ */
+#define DATES_ERR_INVALID -1
+
+/*
+ Parses a timestamp in one of the formats built into `dates`, storing
+ microseconds since the Unix epoch into `*out_micros`.
+
+ These are the formats that `dates_tool::parse_timestamp` accepts, which,
+ unlike `dates TIME`, don't include relative times, "now", or times
+ without an offset (other than the few formats defined to be in UTC).
+
+ On failure, returns a nonzero `DATES_ERR_*` code and, if `error_out` is
+ not NULL, stores into `*error_out` a message that the caller must free
+ with [`dates_string_free`].
+
+ # Safety
+
+ `input` must be a NUL-terminated string.  `out_micros` must be valid for
+ writes.  `error_out` must be NULL or valid for writes.
+ */
+int dates_parse_timestamp(const char *input, int64_t *out_micros, char **error_out);
+
+/*
+ Parses a delta the way `dates [+-]DELTA` does, storing its length in
+ microseconds into `*out_micros`.
+
+ Errors are reported as for [`dates_parse_timestamp`].
+
+ # Safety
+
+ As for [`dates_parse_timestamp`].
+ */
+int dates_parse_duration(const char *input, int64_t *out_micros, char **error_out);
+
+/*
+ Formats a time (given as microseconds since the Unix epoch) the way `dates`
+ prints it, under `label`.
+
+ Returns NULL if `label` is invalid or the time can't be represented.
+ Otherwise, the caller must free the result with [`dates_string_free`].
+
+ # Safety
+
+ `label` must be a NUL-terminated string.
+ */
+char *dates_format_timestamp(const char *label, int64_t micros);
+
+/*
+ Formats a delta (given in microseconds) the way `dates` prints it, under
+ `label`.
+
+ The result is as for [`dates_format_timestamp`].
+
+ # Safety
+
+ `label` must be a NUL-terminated string.
+ */
+char *dates_format_duration(const char *label, int64_t micros);
+
+/*
+ Frees a string returned by this library.  Does nothing if `s` is NULL.
+
+ # Safety
+
+ `s` must be NULL or a string returned by this library that has not
+ already been freed.
+ */
+void dates_string_free(char *s);
+
+#endif  /* DATES_TOOL_H */
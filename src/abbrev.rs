@@ -0,0 +1,276 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Time zone abbreviations like "PST" and "IST"
+//!
+//! Abbreviations aren't standardized, and several common ones mean different
+//! things in different places: "IST" is used in India (UTC+05:30), Ireland
+//! (UTC+01:00), and Israel (UTC+02:00).  An ambiguous abbreviation is only
+//! resolved if the caller says which regions to prefer.  Otherwise, it's
+//! reported as [`Error::Ambiguous`] rather than guessed.
+//!
+//! Each abbreviation denotes a fixed offset: "CST" means UTC-06:00 even when
+//! Chicago is observing daylight saving time.
+
+use crate::Error;
+use crate::parser::{Assumption, TimestampParser};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use chrono::{DateTime, FixedOffset, NaiveDateTime, TimeZone, Utc};
+use core::fmt;
+
+/// A part of the world, for choosing among meanings of an abbreviation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Region {
+    Africa,
+    Asia,
+    Europe,
+    MiddleEast,
+    NorthAmerica,
+    Oceania,
+    SouthAmerica,
+}
+
+const REGIONS: [(Region, &str); 7] = [
+    (Region::Africa, "africa"),
+    (Region::Asia, "asia"),
+    (Region::Europe, "europe"),
+    (Region::MiddleEast, "middle-east"),
+    (Region::NorthAmerica, "north-america"),
+    (Region::Oceania, "oceania"),
+    (Region::SouthAmerica, "south-america"),
+];
+
+impl Region {
+    /// Parses a region name like "asia" or "north-america".
+    pub fn parse(s: &str) -> Result<Region, Error> {
+        REGIONS
+            .iter()
+            .find(|(_, name)| name.eq_ignore_ascii_case(s))
+            .map(|(region, _)| *region)
+            .ok_or_else(|| {
+                Error::unrecognized(
+                    s,
+                    "a region (africa, asia, europe, middle-east, \
+                     north-america, oceania, or south-america)",
+                )
+            })
+    }
+
+    /// Parses a comma-separated list of regions, most preferred first.
+    pub fn parse_list(s: &str) -> Result<Vec<Region>, Error> {
+        s.split(',').map(Region::parse).collect()
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = REGIONS.iter().find(|(r, _)| r == self).unwrap().1;
+        write!(f, "{name}")
+    }
+}
+
+/// One meaning of a time zone abbreviation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Abbreviation {
+    pub name: &'static str,
+    /// seconds east of UTC
+    pub offset_seconds: i32,
+    /// a representative IANA zone that uses this abbreviation
+    pub zone: &'static str,
+    pub region: Region,
+}
+
+impl Abbreviation {
+    pub fn offset(&self) -> FixedOffset {
+        FixedOffset::east_opt(self.offset_seconds).unwrap()
+    }
+}
+
+const fn abbr(
+    name: &'static str,
+    minutes: i32,
+    zone: &'static str,
+    region: Region,
+) -> Abbreviation {
+    Abbreviation { name, offset_seconds: minutes * 60, zone, region }
+}
+
+/// Known abbreviations.  When one has several meanings, the most widely used
+/// comes first.
+const ABBREVIATIONS: &[Abbreviation] = {
+    use Region::*;
+    &[
+        abbr("ACDT", 630, "Australia/Adelaide", Oceania),
+        abbr("ACST", 570, "Australia/Darwin", Oceania),
+        abbr("ADT", -180, "America/Halifax", NorthAmerica),
+        abbr("AEDT", 660, "Australia/Sydney", Oceania),
+        abbr("AEST", 600, "Australia/Brisbane", Oceania),
+        abbr("AKDT", -480, "America/Anchorage", NorthAmerica),
+        abbr("AKST", -540, "America/Anchorage", NorthAmerica),
+        abbr("ART", -180, "America/Argentina/Buenos_Aires", SouthAmerica),
+        abbr("AST", -240, "America/Halifax", NorthAmerica),
+        abbr("AST", 180, "Asia/Riyadh", MiddleEast),
+        abbr("AWST", 480, "Australia/Perth", Oceania),
+        abbr("BRT", -180, "America/Sao_Paulo", SouthAmerica),
+        abbr("BST", 60, "Europe/London", Europe),
+        abbr("BST", 360, "Asia/Dhaka", Asia),
+        abbr("CAT", 120, "Africa/Maputo", Africa),
+        abbr("CDT", -300, "America/Chicago", NorthAmerica),
+        abbr("CDT", -240, "America/Havana", NorthAmerica),
+        abbr("CEST", 120, "Europe/Paris", Europe),
+        abbr("CET", 60, "Europe/Paris", Europe),
+        abbr("CST", -360, "America/Chicago", NorthAmerica),
+        abbr("CST", 480, "Asia/Shanghai", Asia),
+        abbr("CST", -300, "America/Havana", NorthAmerica),
+        abbr("EAT", 180, "Africa/Nairobi", Africa),
+        abbr("EDT", -240, "America/New_York", NorthAmerica),
+        abbr("EEST", 180, "Europe/Athens", Europe),
+        abbr("EET", 120, "Europe/Athens", Europe),
+        abbr("EST", -300, "America/New_York", NorthAmerica),
+        abbr("GMT", 0, "Europe/London", Europe),
+        abbr("HKT", 480, "Asia/Hong_Kong", Asia),
+        abbr("HST", -600, "Pacific/Honolulu", Oceania),
+        abbr("IDT", 180, "Asia/Jerusalem", MiddleEast),
+        abbr("IST", 330, "Asia/Kolkata", Asia),
+        abbr("IST", 60, "Europe/Dublin", Europe),
+        abbr("IST", 120, "Asia/Jerusalem", MiddleEast),
+        abbr("JST", 540, "Asia/Tokyo", Asia),
+        abbr("KST", 540, "Asia/Seoul", Asia),
+        abbr("MDT", -360, "America/Denver", NorthAmerica),
+        abbr("MSK", 180, "Europe/Moscow", Europe),
+        abbr("MST", -420, "America/Denver", NorthAmerica),
+        abbr("NZDT", 780, "Pacific/Auckland", Oceania),
+        abbr("NZST", 720, "Pacific/Auckland", Oceania),
+        abbr("PDT", -420, "America/Los_Angeles", NorthAmerica),
+        abbr("PHT", 480, "Asia/Manila", Asia),
+        abbr("PKT", 300, "Asia/Karachi", Asia),
+        abbr("PST", -480, "America/Los_Angeles", NorthAmerica),
+        abbr("PST", 480, "Asia/Manila", Asia),
+        abbr("SAST", 120, "Africa/Johannesburg", Africa),
+        abbr("SGT", 480, "Asia/Singapore", Asia),
+        abbr("WAT", 60, "Africa/Lagos", Africa),
+        abbr("WEST", 60, "Europe/Lisbon", Europe),
+        abbr("WET", 0, "Europe/Lisbon", Europe),
+        abbr("WIB", 420, "Asia/Jakarta", Asia),
+    ]
+};
+
+/// Returns every known meaning of abbreviation `name` (matched without
+/// regard to case), most widely used first.
+pub fn candidates(name: &str) -> impl Iterator<Item = &'static Abbreviation> {
+    ABBREVIATIONS.iter().filter(move |a| a.name.eq_ignore_ascii_case(name))
+}
+
+/// Resolves abbreviation `name` to one meaning.
+///
+/// If it has only one meaning, that's used.  Otherwise, the meaning in the
+/// earliest region in `prefer` is used, and if there are several in that
+/// region, the most widely used among them.  If none of the meanings are in
+/// any region in `prefer`, the abbreviation is ambiguous.
+pub fn resolve(name: &str, prefer: &[Region]) -> Result<Abbreviation, Error> {
+    resolve_at(name, 0..name.len(), name, prefer)
+}
+
+/// Implements [`resolve`] for abbreviation `name`, found at `span` of
+/// `input`, for error reporting.
+fn resolve_at(
+    input: &str,
+    span: core::ops::Range<usize>,
+    name: &str,
+    prefer: &[Region],
+) -> Result<Abbreviation, Error> {
+    let all: Vec<_> = candidates(name).collect();
+    match all.as_slice() {
+        [] => {
+            Err(Error::unrecognized_at(input, span, "a time zone abbreviation"))
+        }
+        [only] => Ok(**only),
+        _ => prefer
+            .iter()
+            .find_map(|region| all.iter().find(|a| a.region == *region))
+            .map(|a| **a)
+            .ok_or_else(|| {
+                let meanings: Vec<String> = all
+                    .iter()
+                    .map(|a| {
+                        format!("UTC{} ({}, {})", a.offset(), a.zone, a.region)
+                    })
+                    .collect();
+                Error::ambiguous_at(
+                    input,
+                    span,
+                    format!(
+                        "could mean {}; a preferred region is needed",
+                        meanings.join(" or ")
+                    ),
+                )
+            }),
+    }
+}
+
+/// Parses timestamps like "2024-03-05 13:55:36 CST", with a date, a time of
+/// day (optionally with fractional seconds, and optionally separated from the
+/// date by "T"), and a time zone abbreviation
+pub struct AbbreviationParser {
+    prefer: Vec<Region>,
+}
+
+impl AbbreviationParser {
+    /// Returns a parser that resolves ambiguous abbreviations according to
+    /// `prefer` (as for [`resolve`]).
+    pub const fn new(prefer: Vec<Region>) -> AbbreviationParser {
+        AbbreviationParser { prefer }
+    }
+
+    /// Splits `s` into its wall-clock time and resolved abbreviation.
+    fn split(&self, s: &str) -> Result<(NaiveDateTime, Abbreviation), Error> {
+        let mismatch =
+            || Error::unrecognized(s, "a time with a zone abbreviation");
+        let (wall, name) = s.rsplit_once(' ').ok_or_else(mismatch)?;
+        if !(2..=5).contains(&name.len())
+            || !name.chars().all(|c| c.is_ascii_uppercase())
+        {
+            return Err(mismatch());
+        }
+        let wall = ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"]
+            .iter()
+            .find_map(|f| NaiveDateTime::parse_from_str(wall, f).ok())
+            .ok_or_else(mismatch)?;
+
+        let start = s.len() - name.len();
+        let abbreviation = resolve_at(s, start..s.len(), name, &self.prefer)?;
+        Ok((wall, abbreviation))
+    }
+}
+
+impl TimestampParser for AbbreviationParser {
+    fn name(&self) -> &str {
+        "abbreviated"
+    }
+
+    fn parse(&self, s: &str) -> Result<DateTime<Utc>, Error> {
+        let (wall, abbreviation) = self.split(s)?;
+        abbreviation
+            .offset()
+            .from_local_datetime(&wall)
+            .single()
+            .map(|t| t.to_utc())
+            .ok_or_else(|| Error::out_of_range(s, "no such date or time"))
+    }
+
+    fn assumptions(&self, s: &str) -> Vec<Assumption> {
+        match self.split(s) {
+            Ok((_, a)) if candidates(a.name).count() > 1 => {
+                vec![Assumption::Abbreviation {
+                    name: a.name.to_string(),
+                    offset: a.offset().to_string(),
+                    zone: a.zone.to_string(),
+                }]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
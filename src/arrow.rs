@@ -0,0 +1,218 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Integers with Arrow and Parquet temporal types
+//!
+//! Columnar formats store times as plain integers, and what an integer means
+//! depends on the column's type in the schema: a timestamp counts a unit
+//! since the Unix epoch, a date counts days (or, for Arrow's `date64`,
+//! milliseconds) since it, a time of day counts a unit since midnight, and a
+//! duration counts a unit.  A timestamp with a time zone (in Arrow) or
+//! adjusted to UTC (in Parquet) is an instant; one without is a wall-clock
+//! time in no particular zone.
+//!
+//! [`ArrowType::parse`] reads a type the way Arrow and Parquet tools print
+//! it, like "timestamp[us, tz=UTC]", "date32[day]", "time64[ns]",
+//! "TIMESTAMP(MICROS,true)", or "DATE", and [`ArrowType::interpret`] reads
+//! an integer of that type.
+
+use crate::Error;
+use crate::parser::EpochUnit;
+use alloc::string::{String, ToString};
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+
+/// A temporal type of a column
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArrowType {
+    /// a count of `unit` since the Unix epoch, which is an instant if there's
+    /// a zone (as written in the schema, like "UTC" or "America/New_York")
+    /// and a wall-clock time otherwise
+    Timestamp { unit: EpochUnit, zone: Option<String> },
+    /// days since the Unix epoch (Arrow's `date32` and Parquet's `DATE`)
+    Date32,
+    /// milliseconds since the Unix epoch, which must be a whole number of
+    /// days (Arrow's `date64`)
+    Date64,
+    /// a count of `unit` since midnight
+    Time(EpochUnit),
+    /// a count of `unit`
+    Duration(EpochUnit),
+}
+
+/// What an integer of some [`ArrowType`] means
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    /// an instant, from a timestamp with a zone
+    Instant(DateTime<Utc>),
+    /// a wall-clock time, from a timestamp without a zone
+    WallClock(NaiveDateTime),
+    Date(NaiveDate),
+    Time(NaiveTime),
+    Duration(Duration),
+}
+
+impl ArrowType {
+    /// Parses a type written as Arrow (like "timestamp[ms, tz=UTC]",
+    /// "date32[day]", "date64[ms]", "time32[ms]", "time64[us]", or
+    /// "duration[ns]") or Parquet (like "TIMESTAMP(NANOS,false)",
+    /// "TIMESTAMP_MILLIS", "DATE", "TIME(MICROS,true)", or "TIME_MILLIS")
+    /// writes it.  Arrow's "date32[day]" and "date64[ms]" may be written
+    /// without the unit.
+    pub fn parse(s: &str) -> Result<ArrowType, Error> {
+        let mismatch = || {
+            Error::unrecognized(
+                s,
+                "an Arrow or Parquet type (like \"timestamp[us, tz=UTC]\", \
+                 \"date32\", \"time64[ns]\", or \"TIMESTAMP(MICROS,true)\")",
+            )
+        };
+        let compact: String =
+            s.chars().filter(|c| !c.is_whitespace()).collect();
+        // Arrow: a name, then the unit and any zone in brackets
+        let (name, params) = match compact.split_once('[') {
+            Some((name, rest)) => {
+                (name, rest.strip_suffix(']').ok_or_else(mismatch)?)
+            }
+            None => match compact.split_once('(') {
+                Some((name, rest)) => {
+                    return parse_parquet(
+                        name,
+                        rest.strip_suffix(')').ok_or_else(mismatch)?,
+                    )
+                    .ok_or_else(mismatch);
+                }
+                None => (&compact[..], ""),
+            },
+        };
+        let (unit, zone) = match params.split_once(',') {
+            Some((unit, zone)) => {
+                let zone = zone.strip_prefix("tz=").ok_or_else(mismatch)?;
+                (unit, Some(zone.to_string()))
+            }
+            None => (params, None),
+        };
+        let arrow_unit = || match unit {
+            "s" => Some(EpochUnit::Seconds),
+            "ms" => Some(EpochUnit::Millis),
+            "us" => Some(EpochUnit::Micros),
+            "ns" => Some(EpochUnit::Nanos),
+            _ => None,
+        };
+        let parsed = match (name, unit, zone) {
+            ("timestamp", _, zone) => {
+                arrow_unit().map(|unit| ArrowType::Timestamp { unit, zone })
+            }
+            ("date32", "" | "day", None) => Some(ArrowType::Date32),
+            ("date64", "" | "ms", None) => Some(ArrowType::Date64),
+            ("time32", "s" | "ms", None) | ("time64", "us" | "ns", None) => {
+                arrow_unit().map(ArrowType::Time)
+            }
+            ("duration", _, None) => arrow_unit().map(ArrowType::Duration),
+            (_, "", None) => parse_parquet(&compact, ""),
+            _ => None,
+        };
+        parsed.ok_or_else(mismatch)
+    }
+
+    /// Returns what `value` means in this type, or `None` if it's out of
+    /// range (or, for a time of day, not within a day).
+    pub fn interpret(&self, value: i64) -> Option<Value> {
+        let nanos = |unit: EpochUnit| i128::from(value) * unit.nanos();
+        Some(match self {
+            ArrowType::Timestamp { unit, zone } => {
+                let t = from_nanos(nanos(*unit))?;
+                match zone {
+                    Some(_) => Value::Instant(t),
+                    None => Value::WallClock(t.naive_utc()),
+                }
+            }
+            ArrowType::Date32 => {
+                let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+                let days = Duration::try_days(value)?;
+                Value::Date(epoch.checked_add_signed(days)?)
+            }
+            ArrowType::Date64 => {
+                if value % 86_400_000 != 0 {
+                    return None;
+                }
+                Value::Date(
+                    DateTime::from_timestamp_millis(value)?.date_naive(),
+                )
+            }
+            ArrowType::Time(unit) => {
+                let nanos = nanos(*unit);
+                if !(0..86_400 * 1_000_000_000).contains(&nanos) {
+                    return None;
+                }
+                let seconds = (nanos / 1_000_000_000) as u32;
+                Value::Time(NaiveTime::from_num_seconds_from_midnight_opt(
+                    seconds,
+                    (nanos % 1_000_000_000) as u32,
+                )?)
+            }
+            ArrowType::Duration(unit) => {
+                let nanos = nanos(*unit);
+                let seconds = i64::try_from(nanos / 1_000_000_000).ok()?;
+                Value::Duration(
+                    Duration::try_seconds(seconds)?
+                        + Duration::nanoseconds((nanos % 1_000_000_000) as i64),
+                )
+            }
+        })
+    }
+}
+
+/// Parses a Parquet logical type: `name`, like "TIMESTAMP", with `params`
+/// from within parentheses, like "MICROS,true" or (as pyarrow writes them)
+/// "isAdjustedToUTC=true,timeUnit=microseconds", or a converted type like
+/// "TIMESTAMP_MILLIS", with no `params`.
+fn parse_parquet(name: &str, params: &str) -> Option<ArrowType> {
+    let mut unit = None;
+    let mut utc = None;
+    for (i, param) in params.split(',').enumerate() {
+        let param = param.to_ascii_lowercase();
+        let (key, value) = match param.split_once('=') {
+            Some((key, value)) => (key, value),
+            None if i == 0 => ("timeunit", &param[..]),
+            None if i == 1 => ("isadjustedtoutc", &param[..]),
+            None => return None,
+        };
+        match key {
+            "timeunit" => {
+                unit = Some(match value {
+                    "millis" | "milliseconds" => EpochUnit::Millis,
+                    "micros" | "microseconds" => EpochUnit::Micros,
+                    "nanos" | "nanoseconds" => EpochUnit::Nanos,
+                    "" if params.is_empty() => continue,
+                    _ => return None,
+                })
+            }
+            "isadjustedtoutc" => utc = Some(value.parse::<bool>().ok()?),
+            _ => (),
+        }
+    }
+    let zone = || utc.unwrap_or(true).then(|| String::from("UTC"));
+    match (&name.to_ascii_uppercase()[..], unit) {
+        ("TIMESTAMP", Some(unit)) => {
+            Some(ArrowType::Timestamp { unit, zone: zone() })
+        }
+        ("TIME", Some(unit)) => Some(ArrowType::Time(unit)),
+        ("DATE", None) => Some(ArrowType::Date32),
+        ("TIMESTAMP_MILLIS", None) => {
+            Some(ArrowType::Timestamp { unit: EpochUnit::Millis, zone: zone() })
+        }
+        ("TIMESTAMP_MICROS", None) => {
+            Some(ArrowType::Timestamp { unit: EpochUnit::Micros, zone: zone() })
+        }
+        ("TIME_MILLIS", None) => Some(ArrowType::Time(EpochUnit::Millis)),
+        ("TIME_MICROS", None) => Some(ArrowType::Time(EpochUnit::Micros)),
+        _ => None,
+    }
+}
+
+/// Returns the time `nanos` nanoseconds after the Unix epoch.
+fn from_nanos(nanos: i128) -> Option<DateTime<Utc>> {
+    let seconds = i64::try_from(nanos.div_euclid(1_000_000_000)).ok()?;
+    DateTime::from_timestamp(seconds, nanos.rem_euclid(1_000_000_000) as u32)
+}
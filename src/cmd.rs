@@ -0,0 +1,57 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Subcommands of the `dates` command-line tool
+
+pub mod age;
+pub mod arrow;
+pub mod aws;
+pub mod bson;
+pub mod cal;
+pub mod cbor;
+pub mod cert;
+pub mod check;
+pub mod count_weekdays;
+pub mod dmesg;
+pub mod dos;
+pub mod dtrace;
+pub mod encoding;
+pub mod eval;
+pub mod exif;
+pub mod extreme;
+pub mod infer;
+pub mod journald;
+pub mod jwt;
+pub mod ksuid;
+pub mod leap_seconds;
+pub mod mail;
+pub mod midpoint;
+pub mod msgpack;
+#[cfg(feature = "ntp")]
+pub mod ntp;
+pub mod objectid;
+pub mod overflow;
+pub mod pcap;
+pub mod perf;
+pub mod postgres;
+pub mod protobuf;
+pub mod ptp;
+pub mod quarter;
+pub mod sct;
+#[cfg(feature = "http")]
+pub mod skew_http;
+pub mod snowflake;
+pub mod sqlite;
+pub mod strace;
+pub mod sum;
+pub mod timecode;
+pub mod timeline;
+pub mod tod;
+pub mod totp_step;
+pub mod tsc;
+pub mod ulid;
+pub mod uuid;
+pub mod vms;
+pub mod w3c;
+pub mod week;
@@ -0,0 +1,92 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates age`: calendar-aware age and next-anniversary calculation
+//!
+//! Ages are computed in whole calendar months, so an anniversary falls on the
+//! same day of the month as the original date.  When that day does not exist
+//! in the target month (e.g., February 29 in a non-leap year, or the 31st in
+//! a 30-day month), the anniversary falls on the last day of that month.
+
+use anyhow::{Context, bail};
+use chrono::{Datelike, Months, NaiveDate};
+use dates_tool::context;
+use dates_tool::locale::{Lang, Unit};
+
+/// Entry point for `dates age DATE [ASOF]`
+pub fn run(
+    args: &[String],
+    ctx: &context::Context,
+    lang: Lang,
+) -> anyhow::Result<()> {
+    let (birth, asof) = match args {
+        [birth] => (ctx.parse_date(birth)?, ctx.today()),
+        [birth, asof] => (ctx.parse_date(birth)?, ctx.parse_date(asof)?),
+        _ => bail!("age: expected a date and an optional \"as of\" date"),
+    };
+    if asof < birth {
+        bail!("age: {asof} is before {birth}");
+    }
+
+    let age = Age::between(birth, asof)?;
+    let next_years = age.years + 1;
+    let next = add_months(birth, next_years * 12)?;
+
+    let weekday = |date: NaiveDate| lang.weekday_short(date.weekday());
+    println!("{:<8} {} ({})", "date", birth, weekday(birth));
+    println!("{:<8} {} ({})", "as of", asof, weekday(asof));
+    println!(
+        "{:<8} {}, {}, {} ({})",
+        "age",
+        lang.quantity(age.years.into(), Unit::Year),
+        lang.quantity(age.months.into(), Unit::Month),
+        lang.quantity(age.days, Unit::Day),
+        lang.total_days((asof - birth).num_days()),
+    );
+    if age.months == 0 && age.days == 0 && age.years > 0 {
+        println!("{:<8} {}", "", lang.anniversary_today(age.years));
+    }
+    println!(
+        "{:<8} {} ({}, {}, {})",
+        "next",
+        next,
+        weekday(next),
+        lang.anniversary(next_years),
+        lang.relative((next - asof).num_days(), Unit::Day),
+    );
+    Ok(())
+}
+
+/// An elapsed span expressed in whole years, months, and days
+struct Age {
+    years: u32,
+    months: u32,
+    days: i64,
+}
+
+impl Age {
+    /// Computes the calendar difference between `from` and `to`, which must
+    /// not be earlier than `from`.
+    fn between(from: NaiveDate, to: NaiveDate) -> anyhow::Result<Age> {
+        // This ignores the day of the month and so may overshoot by one
+        // month; walk back until we land on or before `to`.
+        let mut total_months = ((to.year() - from.year()) * 12
+            + to.month() as i32
+            - from.month() as i32) as u32;
+        while total_months > 0 && add_months(from, total_months)? > to {
+            total_months -= 1;
+        }
+        let anchor = add_months(from, total_months)?;
+        Ok(Age {
+            years: total_months / 12,
+            months: total_months % 12,
+            days: (to - anchor).num_days(),
+        })
+    }
+}
+
+fn add_months(date: NaiveDate, months: u32) -> anyhow::Result<NaiveDate> {
+    date.checked_add_months(Months::new(months))
+        .with_context(|| format!("{date} + {months} months is out of range"))
+}
@@ -0,0 +1,71 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates arrow`: what integers of an Arrow or Parquet type mean
+//!
+//! See [`dates_tool::arrow`] for the types.  A timestamp with a zone is
+//! printed like any other time, and also in its zone, if that's one `dates`
+//! knows.  Other values aren't instants, so they're printed by themselves.
+
+use crate::{print_delta, print_time};
+use anyhow::bail;
+use dates_tool::arrow::{ArrowType, Value};
+use dates_tool::zone::Zone;
+use dates_tool::{Error, context};
+
+/// Entry point for `dates arrow TYPE VALUE...`
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let Some((kind, values)) = args.split_first() else {
+        bail!("arrow: expected a type and a value");
+    };
+    let kind = ArrowType::parse(kind)?;
+    if values.is_empty() {
+        bail!("arrow: expected a value");
+    }
+    let zone = match &kind {
+        // UTC is already printed.
+        ArrowType::Timestamp { zone: Some(zone), .. } => {
+            Zone::parse(zone).ok().filter(|z| !matches!(z, Zone::Utc))
+        }
+        _ => None,
+    };
+
+    for (i, input) in values.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        let value: i64 = input
+            .parse()
+            .map_err(|_| Error::unrecognized(input, "a 64-bit integer"))?;
+        let Some(value) = kind.interpret(value) else {
+            let detail = match kind {
+                ArrowType::Time(_) => "it's not within a day",
+                ArrowType::Date64 => "it's not a whole number of days",
+                _ => "it's out of the supported range",
+            };
+            return Err(Error::out_of_range(input, detail).into());
+        };
+        match value {
+            Value::Instant(t) => {
+                print_time(ctx, "time", t);
+                if let Some(zone) = zone {
+                    println!("{:<8} {} ({})", "in zone", zone.rfc3339(t), zone);
+                }
+            }
+            Value::WallClock(t) => println!(
+                "{:<8} {} (in no particular zone)",
+                "wall",
+                t.format("%Y-%m-%dT%H:%M:%S%.f")
+            ),
+            Value::Date(date) => {
+                println!("{:<8} {} ({})", "date", date, date.format("%A"))
+            }
+            Value::Time(time) => {
+                println!("{:<8} {}", "time", time.format("%H:%M:%S%.f"))
+            }
+            Value::Duration(d) => print_delta("duration", d),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,60 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates aws`: times as AWS request signing and S3 write them
+//!
+//! A Signature Version 4 request is signed with its time, like
+//! "20240301T120000Z" (in the `X-Amz-Date` header or parameter), and the
+//! date alone, like "20240301", in the credential scope.  AWS rejects a
+//! request signed more than 15 minutes from its own clock, with
+//! `RequestTimeTooSkewed` (or `SignatureDoesNotMatch`, if the date in the
+//! scope is wrong).  S3 lists objects' times like "2024-03-01T12:00:00.000Z"
+//! and sends them in `Last-Modified` as HTTP dates.
+
+use crate::{ReadOptions, print_delta, print_time};
+use anyhow::bail;
+use chrono::{Duration, SecondsFormat};
+use dates_tool::context;
+
+/// How far from AWS's clock a request may be signed
+const MAX_SKEW: Duration = Duration::minutes(15);
+
+/// Entry point for `dates aws [TIME]`
+///
+/// With TIME, this also prints how far it is from the local clock, as when
+/// checking the time a request was signed with.  TIME is read as `dates
+/// TIME` reads times, with the same options.
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let mut options = ReadOptions::default();
+    let mut inputs = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if !options.parse_option(arg, &mut iter)? {
+            inputs.push(arg);
+        }
+    }
+    let reader = options.reader(ctx)?;
+    let now = ctx.now();
+    let t = match inputs[..] {
+        [] => now,
+        [arg] => reader.parse(arg)?,
+        _ => bail!("aws: expected at most one time"),
+    };
+    print_time(ctx, "time", t);
+    println!("{:<8} {}", "amz-date", t.format("%Y%m%dT%H%M%SZ"));
+    println!("{:<8} {} (credential scope)", "scope", t.format("%Y%m%d"));
+    println!("{:<8} {}", "http", t.format("%a, %d %b %Y %H:%M:%S GMT"));
+    println!("{:<8} {}", "s3", t.to_rfc3339_opts(SecondsFormat::Millis, true));
+    if !inputs.is_empty() {
+        let skew = t - now;
+        print_delta("skew", skew);
+        let verdict = if skew.abs() > MAX_SKEW {
+            "rejected: more than 15 minutes from now (RequestTimeTooSkewed)"
+        } else {
+            "accepted: within 15 minutes of now"
+        };
+        println!("{:<8} {verdict}", "signing");
+    }
+    Ok(())
+}
@@ -0,0 +1,134 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates bson`: the times in MongoDB Extended JSON
+//!
+//! A BSON datetime is a signed 64-bit count of milliseconds since the Unix
+//! epoch.  Extended JSON, as `mongoexport` and `mongosh` write it, wraps one
+//! as `{"$date": ...}`, whose value is an ISO 8601 string (in "relaxed"
+//! mode), `{"$numberLong": "..."}` (in "canonical" mode), or a bare number
+//! (in older tools).  A BSON timestamp, as in the oplog, is written
+//! `{"$timestamp": {"t": SECONDS, "i": INCREMENT}}`.  This prints each of
+//! these in the given documents, along with where in them it was found.
+
+use crate::print_time;
+use anyhow::{Context, bail};
+use chrono::{DateTime, Utc};
+use dates_tool::context;
+use serde_json::Value;
+use std::io::Read;
+
+/// Entry point for `dates bson [JSON...]`
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    // With no arguments, documents are read from stdin, which may have any
+    // number of them, as from mongoexport.
+    let documents = if args.is_empty() {
+        let mut text = String::new();
+        std::io::stdin().read_to_string(&mut text).context("reading stdin")?;
+        serde_json::Deserializer::from_str(&text)
+            .into_iter::<Value>()
+            .collect::<Result<Vec<_>, _>>()
+            .context("bson: stdin isn't JSON")?
+    } else {
+        args.iter()
+            .map(|arg| {
+                serde_json::from_str(arg)
+                    .with_context(|| format!("bson: {arg:?} isn't JSON"))
+            })
+            .collect::<anyhow::Result<_>>()?
+    };
+
+    let mut times = Vec::new();
+    for (i, document) in documents.iter().enumerate() {
+        let path =
+            if documents.len() > 1 { format!("/{i}") } else { String::new() };
+        find(document, &path, &mut times)?;
+    }
+    if times.is_empty() {
+        bail!("bson: no $date or $timestamp values");
+    }
+    for (i, (path, label, time, increment)) in times.into_iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        if !path.is_empty() {
+            println!("{:<8} {}", "at", path);
+        }
+        print_time(ctx, label, time);
+        if let Some(increment) = increment {
+            println!("{:<8} {}", "i", increment);
+        }
+    }
+    Ok(())
+}
+
+/// One time found in a document: its path, the label to print it with, the
+/// time, and (for a timestamp) its increment
+type Found = (String, &'static str, DateTime<Utc>, Option<u64>);
+
+/// Appends each `$date` and `$timestamp` in `value`, which is at `path`, to
+/// `times`.
+fn find(
+    value: &Value,
+    path: &str,
+    times: &mut Vec<Found>,
+) -> anyhow::Result<()> {
+    match value {
+        Value::Object(object) => {
+            let at = if path.is_empty() {
+                String::new()
+            } else {
+                format!(" at {path}")
+            };
+            if let Some(date) = object.get("$date") {
+                let time = datetime(date).with_context(|| {
+                    format!("bson: $date{at} holds {date}, not a datetime")
+                })?;
+                times.push((path.to_string(), "date", time, None));
+            } else if let Some(timestamp) = object.get("$timestamp") {
+                let (time, increment) = bson_timestamp(timestamp)
+                    .with_context(|| {
+                        format!(
+                            "bson: $timestamp{at} holds {timestamp}, not \
+                             {{\"t\": SECONDS, \"i\": INCREMENT}}"
+                        )
+                    })?;
+                times.push((path.to_string(), "ts", time, Some(increment)));
+            } else {
+                for (key, value) in object {
+                    find(value, &format!("{path}/{key}"), times)?;
+                }
+            }
+        }
+        Value::Array(array) => {
+            for (i, value) in array.iter().enumerate() {
+                find(value, &format!("{path}/{i}"), times)?;
+            }
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+/// Converts the value of a `$date`.
+fn datetime(value: &Value) -> Option<DateTime<Utc>> {
+    let millis = match value {
+        Value::String(s) => {
+            return DateTime::parse_from_rfc3339(s).ok().map(|t| t.to_utc());
+        }
+        Value::Number(n) => n.as_i64()?,
+        Value::Object(object) => {
+            object.get("$numberLong")?.as_str()?.parse().ok()?
+        }
+        _ => return None,
+    };
+    DateTime::from_timestamp_millis(millis)
+}
+
+/// Converts the value of a `$timestamp` to its time and increment.
+fn bson_timestamp(value: &Value) -> Option<(DateTime<Utc>, u64)> {
+    let seconds = value.get("t")?.as_u64().filter(|&t| t <= 0xffff_ffff)?;
+    let increment = value.get("i")?.as_u64().filter(|&i| i <= 0xffff_ffff)?;
+    Some((DateTime::from_timestamp(seconds as i64, 0)?, increment))
+}
@@ -0,0 +1,215 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates cal`: print a cal(1)-style calendar with one date highlighted
+
+use crate::UsageError;
+use crate::cmd::week;
+use anyhow::{Context, bail};
+use chrono::{Datelike, Month, NaiveDate, Weekday};
+use dates_tool::context;
+use dates_tool::locale::Lang;
+use dates_tool::week::WeekRules;
+
+/// Options controlling how a calendar is laid out
+struct CalOptions {
+    first_day: Weekday,
+    week_numbers: bool,
+    week_rules: WeekRules,
+    lang: Lang,
+}
+
+/// Entry point for `dates cal [--year] [-w] [--first-day DAY]
+/// [--weeks iso|us] [--week-start DAY] [--min-days N] [DATE]`
+///
+/// Without any of the week-numbering options, calendars starting on Monday
+/// use ISO 8601 week numbers and others use US-style numbering (week 1 is the
+/// week containing January 1).  With a week-numbering option but no
+/// `--first-day`, the calendar's rows start on the week's first day.
+pub fn run(
+    args: &[String],
+    ctx: &context::Context,
+    lang: Lang,
+) -> anyhow::Result<()> {
+    let mut first_day = None;
+    let mut week_numbers = false;
+    let mut week_rules = WeekRules::US;
+    let mut custom_weeks = false;
+    let mut year_view = false;
+    let mut date_arg = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if let Some(result) =
+            week::parse_option(arg, &mut args, &mut week_rules, lang)
+        {
+            result?;
+            custom_weeks = true;
+            continue;
+        }
+        match arg.as_str() {
+            "-y" | "--year" => year_view = true,
+            "-w" | "--week-numbers" => week_numbers = true,
+            "--first-day" => {
+                let value = args.next().with_context(|| {
+                    UsageError::new("cal: --first-day requires an argument")
+                })?;
+                first_day = Some(lang.parse_weekday(value)?);
+            }
+            _ if date_arg.is_none() => date_arg = Some(arg.as_str()),
+            _ => bail!(UsageError::new(format!(
+                "cal: unexpected argument {arg:?}"
+            ))),
+        }
+    }
+
+    let first_day = first_day.unwrap_or(if custom_weeks {
+        week_rules.first_day
+    } else {
+        Weekday::Sun
+    });
+    if !custom_weeks {
+        week_rules = if first_day == Weekday::Mon {
+            WeekRules::ISO
+        } else {
+            WeekRules { first_day, min_days: 1 }
+        };
+    }
+    let options = CalOptions { first_day, week_numbers, week_rules, lang };
+
+    let date = match date_arg {
+        None => ctx.today(),
+        Some(s) => ctx.parse_date(s)?,
+    };
+
+    let lines = if year_view {
+        render_year(date, &options)
+    } else {
+        render_month(date.year(), date.month(), Some(date), &options, true)
+    };
+    for line in lines {
+        println!("{}", line.trim_end());
+    }
+    Ok(())
+}
+
+/// Width in characters of one rendered month (without week numbers)
+const MONTH_WIDTH: usize = 22;
+
+/// Width of the week-number column, when present
+const WEEK_WIDTH: usize = 2;
+
+/// Renders a single month as a list of lines of equal width.
+///
+/// The grid always has six week rows so that months can be laid out side by
+/// side.  If `highlight` falls in this month, that day is bracketed.
+fn render_month(
+    year: i32,
+    month: u32,
+    highlight: Option<NaiveDate>,
+    options: &CalOptions,
+    with_year: bool,
+) -> Vec<String> {
+    let width = block_width(options);
+    let mut lines = Vec::new();
+
+    let name = options.lang.month_name(Month::try_from(month as u8).unwrap());
+    let title =
+        if with_year { format!("{name} {year}") } else { name.to_string() };
+    lines.push(format!("{title:^width$}"));
+
+    let mut header = String::new();
+    if options.week_numbers {
+        header.push_str(&format!("{:>WEEK_WIDTH$}", "Wk"));
+    }
+    let mut day = options.first_day;
+    for _ in 0..7 {
+        header.push(' ');
+        header.extend(options.lang.weekday_short(day).chars().take(2));
+        day = day.succ();
+    }
+    header.push(' ');
+    lines.push(header);
+
+    let first = NaiveDate::from_ymd_opt(year, month, 1).unwrap();
+    let lead = first.weekday().days_since(options.first_day) as usize;
+    let ndays = days_in_month(year, month);
+    let mut cells: Vec<Option<u32>> = vec![None; lead];
+    cells.extend((1..=ndays).map(Some));
+    cells.resize(42, None);
+
+    for week in cells.chunks(7) {
+        let mut row = String::new();
+        if options.week_numbers {
+            let label = week
+                .iter()
+                .flatten()
+                .next()
+                .map(|&d| {
+                    let date = NaiveDate::from_ymd_opt(year, month, d).unwrap();
+                    options.week_rules.week_of(date).1.to_string()
+                })
+                .unwrap_or_default();
+            row.push_str(&format!("{label:>WEEK_WIDTH$}"));
+        }
+
+        let mut gutters = [' '; 8];
+        if let Some(h) =
+            highlight.filter(|h| h.year() == year && h.month() == month)
+            && let Some(k) = week.iter().position(|&d| d == Some(h.day()))
+        {
+            gutters[k] = '[';
+            gutters[k + 1] = ']';
+        }
+
+        for (k, cell) in week.iter().enumerate() {
+            row.push(gutters[k]);
+            match cell {
+                Some(d) => row.push_str(&format!("{d:>2}")),
+                None => row.push_str("  "),
+            }
+        }
+        row.push(gutters[7]);
+        lines.push(row);
+    }
+
+    lines
+}
+
+/// Renders all twelve months of `date`'s year, three months across.
+fn render_year(date: NaiveDate, options: &CalOptions) -> Vec<String> {
+    let width = block_width(options);
+    let total = width * 3 + 4;
+    let mut lines = vec![format!("{:^total$}", date.year()), String::new()];
+
+    for quarter in 0..4 {
+        let blocks: Vec<_> = (1..=3)
+            .map(|m| {
+                let month = quarter * 3 + m;
+                render_month(date.year(), month, Some(date), options, false)
+            })
+            .collect();
+        for i in 0..blocks[0].len() {
+            let row: Vec<&str> = blocks.iter().map(|b| b[i].as_str()).collect();
+            lines.push(row.join("  "));
+        }
+        if quarter < 3 {
+            lines.push(String::new());
+        }
+    }
+
+    lines
+}
+
+fn block_width(options: &CalOptions) -> usize {
+    if options.week_numbers { MONTH_WIDTH + WEEK_WIDTH } else { MONTH_WIDTH }
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    // This avoids looking at the next month, which may not be representable.
+    (28..=31)
+        .rev()
+        .find(|&day| NaiveDate::from_ymd_opt(year, month, day).is_some())
+        .unwrap()
+}
@@ -0,0 +1,255 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates cbor`: the times in CBOR data
+//!
+//! CBOR (RFC 8949) marks times with tags: tag 0 for an RFC 3339 string, and
+//! tag 1 for a number of seconds since the Unix epoch, which may be an
+//! integer or a float.  This walks a CBOR data item, given in hexadecimal or
+//! base64, and prints each tagged time in it, along with where in the item
+//! it was found.  Other tags, and untagged numbers (like the claims in a
+//! CWT), are left alone.
+
+use crate::cmd::encoding::decode_bytes;
+use crate::print_time;
+use anyhow::{Context, bail};
+use chrono::{DateTime, Utc};
+use dates_tool::context;
+
+/// How deeply arrays, maps, and tags may nest, so that malicious input can't
+/// overflow the stack
+const MAX_DEPTH: usize = 128;
+
+/// Entry point for `dates cbor DATA...`
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    if args.is_empty() {
+        bail!("cbor: expected CBOR data in hexadecimal or base64");
+    }
+    for (i, arg) in args.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        let data = decode_bytes(arg).context("cbor: reading the data")?;
+        let mut rest = &data[..];
+        let mut times = Vec::new();
+        item(&mut rest, "", 0, &mut times)
+            .with_context(|| format!("cbor: decoding {arg:?}"))?;
+        if !rest.is_empty() {
+            bail!("cbor: {} bytes follow the data item in {arg:?}", rest.len());
+        }
+        if times.is_empty() {
+            bail!("cbor: no tag 0 or tag 1 times in {arg:?}");
+        }
+        for (j, (path, tag, time)) in times.into_iter().enumerate() {
+            if j > 0 {
+                println!();
+            }
+            if !path.is_empty() {
+                println!("{:<8} {}", "at", path);
+            }
+            print_time(ctx, &format!("tag {tag}"), time);
+        }
+    }
+    Ok(())
+}
+
+/// What the walk needs to know about a data item: enough to describe a map
+/// key, or to read the content of tag 1
+enum Item {
+    Integer(i128),
+    Float(f64),
+    Text(String),
+    Other,
+}
+
+/// Decodes the data item at the start of `data`, which is at `path` within
+/// the top-level item, advancing `data` past it.  Each tagged time in it is
+/// appended to `times` with its path and tag.
+fn item(
+    data: &mut &[u8],
+    path: &str,
+    depth: usize,
+    times: &mut Vec<(String, u64, DateTime<Utc>)>,
+) -> anyhow::Result<Item> {
+    if depth > MAX_DEPTH {
+        bail!("items are nested too deeply");
+    }
+    let (major, info) = head(data)?;
+    Ok(match major {
+        0 => Item::Integer(i128::from(argument(data, info)?)),
+        1 => Item::Integer(-1 - i128::from(argument(data, info)?)),
+        2 => {
+            string(data, major, info)?;
+            Item::Other
+        }
+        3 => {
+            let bytes = string(data, major, info)?;
+            Item::Text(String::from_utf8(bytes).context("invalid UTF-8")?)
+        }
+        4 => {
+            let len = length(data, info)?;
+            let mut i = 0;
+            while more(data, len, i)? {
+                item(data, &format!("{path}/{i}"), depth + 1, times)?;
+                i += 1;
+            }
+            Item::Other
+        }
+        5 => {
+            let len = length(data, info)?;
+            let mut i = 0;
+            while more(data, len, i)? {
+                let key = item(data, path, depth + 1, times)?;
+                let key = match key {
+                    Item::Integer(n) => n.to_string(),
+                    Item::Text(s) => s,
+                    _ => String::from("?"),
+                };
+                item(data, &format!("{path}/{key}"), depth + 1, times)?;
+                i += 1;
+            }
+            Item::Other
+        }
+        6 => {
+            let tag = argument(data, info)?;
+            let content = item(data, path, depth + 1, times)?;
+            let time = match (tag, content) {
+                (0, Item::Text(s)) => DateTime::parse_from_rfc3339(&s)
+                    .map(|t| t.to_utc())
+                    .with_context(|| {
+                        format!("tag 0 holds {s:?}, not an RFC 3339 time")
+                    })?,
+                (0, _) => bail!("tag 0 holds something other than text"),
+                (1, Item::Integer(seconds)) => i64::try_from(seconds)
+                    .ok()
+                    .and_then(|s| DateTime::from_timestamp(s, 0))
+                    .context("tag 1 holds a time out of range")?,
+                (1, Item::Float(seconds)) => float_time(seconds)
+                    .context("tag 1 holds a time out of range")?,
+                (1, _) => bail!("tag 1 holds something other than a number"),
+                _ => return Ok(Item::Other),
+            };
+            times.push((path.to_string(), tag, time));
+            Item::Other
+        }
+        _ => match info {
+            25 => Item::Float(half(take(data, 2)?)),
+            26 => Item::Float(f64::from(f32::from_be_bytes(
+                take(data, 4)?.try_into().unwrap(),
+            ))),
+            27 => Item::Float(f64::from_be_bytes(
+                take(data, 8)?.try_into().unwrap(),
+            )),
+            31 => bail!("unexpected \"break\""),
+            _ => {
+                argument(data, info)?;
+                Item::Other
+            }
+        },
+    })
+}
+
+/// Reads the initial byte of a data item, returning its major type and
+/// additional information.
+fn head(data: &mut &[u8]) -> anyhow::Result<(u8, u8)> {
+    let byte = take(data, 1)?[0];
+    Ok((byte >> 5, byte & 0x1f))
+}
+
+/// Reads the argument that the additional information `info` describes.
+fn argument(data: &mut &[u8], info: u8) -> anyhow::Result<u64> {
+    let size = match info {
+        0..24 => return Ok(u64::from(info)),
+        24 => 1,
+        25 => 2,
+        26 => 4,
+        27 => 8,
+        _ => bail!("invalid additional information {info}"),
+    };
+    Ok(take(data, size)?.iter().fold(0, |n, &b| n << 8 | u64::from(b)))
+}
+
+/// Reads the number of elements of an array or map, or `None` if it has
+/// indefinite length.
+fn length(data: &mut &[u8], info: u8) -> anyhow::Result<Option<u64>> {
+    if info == 31 { Ok(None) } else { Ok(Some(argument(data, info)?)) }
+}
+
+/// Returns whether an array or map of `len` elements (or, if `None`, of
+/// indefinite length) has an element after the first `i`, consuming the
+/// "break" that ends one of indefinite length.
+fn more(data: &mut &[u8], len: Option<u64>, i: u64) -> anyhow::Result<bool> {
+    if let Some(len) = len {
+        return Ok(i < len);
+    }
+    if data.first() == Some(&0xff) {
+        *data = &data[1..];
+        return Ok(false);
+    }
+    if data.is_empty() {
+        bail!("the data ends in the middle of an item");
+    }
+    Ok(true)
+}
+
+/// Reads the content of a byte or text string, joining the chunks of one of
+/// indefinite length.
+fn string(data: &mut &[u8], major: u8, info: u8) -> anyhow::Result<Vec<u8>> {
+    if info != 31 {
+        let len = argument(data, info)?;
+        let len = usize::try_from(len).context("a string is too long")?;
+        return Ok(take(data, len)?.to_vec());
+    }
+    let mut bytes = Vec::new();
+    loop {
+        let (chunk_major, chunk_info) = head(data)?;
+        if chunk_major == 7 && chunk_info == 31 {
+            return Ok(bytes);
+        }
+        if chunk_major != major || chunk_info == 31 {
+            bail!("a chunk of a string isn't a string of the same type");
+        }
+        bytes.extend(string(data, major, chunk_info)?);
+    }
+}
+
+/// Reads `n` bytes.
+fn take<'a>(data: &mut &'a [u8], n: usize) -> anyhow::Result<&'a [u8]> {
+    if data.len() < n {
+        bail!("the data ends in the middle of an item");
+    }
+    let (taken, rest) = data.split_at(n);
+    *data = rest;
+    Ok(taken)
+}
+
+/// Converts an IEEE 754 half-precision float.
+fn half(bytes: &[u8]) -> f64 {
+    let bits = u16::from_be_bytes([bytes[0], bytes[1]]);
+    let exponent = i32::from(bits >> 10 & 0x1f);
+    let mantissa = f64::from(bits & 0x3ff);
+    let magnitude = match exponent {
+        0 => mantissa * 2f64.powi(-24),
+        31 if mantissa == 0.0 => f64::INFINITY,
+        31 => f64::NAN,
+        _ => (mantissa + 1024.0) * 2f64.powi(exponent - 25),
+    };
+    if bits & 0x8000 != 0 { -magnitude } else { magnitude }
+}
+
+/// Converts a float number of seconds since the Unix epoch, to the
+/// nanosecond.
+fn float_time(seconds: f64) -> Option<DateTime<Utc>> {
+    if !seconds.is_finite() || seconds.abs() >= i64::MAX as f64 {
+        return None;
+    }
+    let whole = seconds.floor();
+    let nanos = ((seconds - whole) * 1e9).round() as u32;
+    let (whole, nanos) = if nanos >= 1_000_000_000 {
+        (whole as i64 + 1, 0)
+    } else {
+        (whole as i64, nanos)
+    };
+    DateTime::from_timestamp(whole, nanos)
+}
@@ -0,0 +1,199 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates cert`: when X.509 certificates are valid
+//!
+//! This reads each certificate in a PEM file (or one DER certificate, with
+//! `--der`) and prints its validity period, from its `notBefore` time to its
+//! `notAfter` time, and how long it has left.  With `--warn-within DELTA`, it
+//! exits with status 1 if any certificate isn't valid now or expires within
+//! DELTA, as for a monitoring check.  Nothing else in the certificate is
+//! read, and signatures aren't checked.
+
+use crate::cmd::check::parse_tolerance;
+use crate::cmd::encoding::decode_base64;
+use crate::{UsageError, print_delta, print_time};
+use anyhow::{Context, bail};
+use chrono::{DateTime, Utc};
+use dates_tool::context;
+use dates_tool::parser::{GeneralizedTime, TimestampParser, UtcTime};
+use std::io::Read;
+use std::ops::Range;
+
+/// Entry point for `dates cert [--der] [--warn-within DELTA] FILE`
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let mut der = false;
+    let mut warn_within = None;
+    let mut path = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--der" => der = true,
+            "--warn-within" => {
+                let value = args.next().with_context(|| {
+                    UsageError::new("cert: --warn-within requires an argument")
+                })?;
+                warn_within = Some((value, parse_tolerance(value)?));
+            }
+            _ if path.is_none() => path = Some(arg.as_str()),
+            _ => bail!(UsageError::new(format!(
+                "cert: unexpected argument {arg:?}"
+            ))),
+        }
+    }
+    let Some(path) = path else {
+        bail!("cert: expected a file (or \"-\" for stdin)");
+    };
+
+    let mut data = Vec::new();
+    if path == "-" {
+        std::io::stdin().read_to_end(&mut data).context("reading stdin")?;
+    } else {
+        data = std::fs::read(path)
+            .with_context(|| format!("cert: reading {path:?}"))?;
+    }
+    let certs = if der {
+        vec![data]
+    } else {
+        let text = String::from_utf8(data)
+            .context("cert: a PEM file must be text (use --der for DER)")?;
+        pem_certificates(&text)?
+    };
+    if certs.is_empty() {
+        bail!("cert: no certificates in {path:?}");
+    }
+
+    let now = ctx.now();
+    let mut warnings = Vec::new();
+    for (i, cert) in certs.iter().enumerate() {
+        let label = format!("certificate {}", i + 1);
+        let validity =
+            validity(cert).with_context(|| format!("cert: reading {label}"))?;
+        if i > 0 {
+            println!();
+        }
+        if certs.len() > 1 {
+            println!("{label}");
+        }
+        print_time(ctx, "from", validity.start);
+        print_time(ctx, "until", validity.end);
+        print_delta("length", validity.end - validity.start);
+        if now < validity.start {
+            print_delta("early", validity.start - now);
+            warnings.push(format!("{label} isn't valid yet"));
+        } else if now >= validity.end {
+            print_delta("expired", now - validity.end);
+            warnings.push(format!("{label} has expired"));
+        } else {
+            let left = validity.end - now;
+            print_delta("left", left);
+            if let Some((input, within)) = warn_within
+                && left <= within
+            {
+                warnings.push(format!("{label} expires within {input}"));
+            }
+        }
+    }
+
+    if warn_within.is_some() && !warnings.is_empty() {
+        for warning in warnings {
+            eprintln!("dates: cert: {warning}");
+        }
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Returns the DER of each certificate in PEM text.
+fn pem_certificates(text: &str) -> anyhow::Result<Vec<Vec<u8>>> {
+    const BEGIN: &str = "-----BEGIN CERTIFICATE-----";
+    const END: &str = "-----END CERTIFICATE-----";
+    let mut certs = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(BEGIN) {
+        let body = &rest[start + BEGIN.len()..];
+        let end = body.find(END).context("cert: a certificate has no end")?;
+        let base64: String =
+            body[..end].chars().filter(|c| !c.is_whitespace()).collect();
+        certs.push(
+            decode_base64(&base64)
+                .context("cert: a certificate isn't valid base64")?,
+        );
+        rest = &body[end + END.len()..];
+    }
+    Ok(certs)
+}
+
+/// Returns the validity period of a DER-encoded certificate.
+fn validity(der: &[u8]) -> anyhow::Result<Range<DateTime<Utc>>> {
+    let malformed = || anyhow::anyhow!("not a valid X.509 certificate");
+    // Certificate ::= SEQUENCE { tbsCertificate, ... }
+    let cert = element(der, 0x30).ok_or_else(malformed)?;
+    let tbs = element(cert, 0x30).ok_or_else(malformed)?;
+    // TBSCertificate ::= SEQUENCE { [0] version OPTIONAL, serialNumber,
+    // signature, issuer, validity, ... }
+    let mut fields = tbs;
+    let (mut tag, mut content) = next(&mut fields).ok_or_else(malformed)?;
+    if tag == 0xa0 {
+        (tag, content) = next(&mut fields).ok_or_else(malformed)?;
+    }
+    for expected in [0x02, 0x30, 0x30] {
+        if tag != expected {
+            return Err(malformed());
+        }
+        (tag, content) = next(&mut fields).ok_or_else(malformed)?;
+    }
+    // Validity ::= SEQUENCE { notBefore Time, notAfter Time }
+    if tag != 0x30 {
+        return Err(malformed());
+    }
+    let (tag1, time1, rest) = split(content).ok_or_else(malformed)?;
+    let (tag2, time2, _) = split(rest).ok_or_else(malformed)?;
+    let start = time(tag1, time1).context("reading notBefore")?;
+    let end = time(tag2, time2).context("reading notAfter")?;
+    Ok(start..end)
+}
+
+/// Returns the content of the DER element at the start of `data`, if it has
+/// tag `tag`.
+fn element(data: &[u8], tag: u8) -> Option<&[u8]> {
+    split(data).filter(|(t, _, _)| *t == tag).map(|(_, content, _)| content)
+}
+
+/// Returns the tag and content of the DER element at the start of `data`,
+/// and advances `data` past it.
+fn next<'a>(data: &mut &'a [u8]) -> Option<(u8, &'a [u8])> {
+    let (tag, content, rest) = split(data)?;
+    *data = rest;
+    Some((tag, content))
+}
+
+/// Splits the DER element at the start of `data` into its tag, its content,
+/// and whatever follows it.
+fn split(data: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, rest) = data.split_first()?;
+    let (&first, rest) = rest.split_first()?;
+    let (len, rest) = if first < 0x80 {
+        (usize::from(first), rest)
+    } else {
+        let n = usize::from(first & 0x7f);
+        if n == 0 || n > 4 || rest.len() < n {
+            return None;
+        }
+        let len = rest[..n].iter().fold(0, |l, &b| l << 8 | usize::from(b));
+        (len, &rest[n..])
+    };
+    (rest.len() >= len).then(|| (tag, &rest[..len], &rest[len..]))
+}
+
+/// Parses an X.509 time: a UTCTime (with a two-digit year, meaning 1950
+/// through 2049) or a GeneralizedTime.
+fn time(tag: u8, content: &[u8]) -> anyhow::Result<DateTime<Utc>> {
+    let text = std::str::from_utf8(content).context("not text")?;
+    Ok(match tag {
+        0x17 => UtcTime.parse(text)?,
+        0x18 => GeneralizedTime.parse(text)?,
+        _ => bail!("not a UTCTime or GeneralizedTime"),
+    })
+}
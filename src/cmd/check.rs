@@ -0,0 +1,92 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates check`: test a temporal condition and report it via exit status
+//!
+//! Exit status is 0 if the condition holds and 1 if it does not.  If the
+//! arguments could not be parsed, the exit status is 2 or greater, as for any
+//! other `dates` failure.  Nothing is printed on success or on a false
+//! condition.
+
+use crate::ReadOptions;
+use anyhow::{Context, bail};
+use chrono::Duration;
+use dates_tool::{context, parse_duration};
+
+/// Entry point for `dates check A (--before B | --after B | --within D B)`
+///
+/// A and B are read as the main command reads times, with the same options
+/// (like `--input-tz` and `--strict`), so either may be a relative time like
+/// "now-5m".
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let mut options = ReadOptions::default();
+    let mut condition = None;
+    let mut times = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if options.parse_option(arg, &mut args)? {
+            continue;
+        }
+        let op = arg.as_str();
+        let parsed = match op {
+            "--before" | "--after" => {
+                let b = args
+                    .next()
+                    .with_context(|| format!("check: {op} requires a time"))?;
+                if op == "--before" {
+                    Condition::Before(b)
+                } else {
+                    Condition::After(b)
+                }
+            }
+            "--within" => match (args.next(), args.next()) {
+                (Some(d), Some(b)) => Condition::Within(parse_tolerance(d)?, b),
+                _ => bail!("check: --within requires a delta and a time"),
+            },
+            _ => {
+                times.push(op);
+                continue;
+            }
+        };
+        if condition.replace(parsed).is_some() {
+            bail!("check: expected only one condition");
+        }
+    }
+    let (Some(condition), [a]) = (condition, &times[..]) else {
+        bail!("check: expected a time followed by a condition");
+    };
+
+    let reader = options.reader(ctx)?;
+    let a = reader.parse(a)?;
+    let holds = match condition {
+        Condition::Before(b) => a < reader.parse(b)?,
+        Condition::After(b) => a > reader.parse(b)?,
+        Condition::Within(tolerance, b) => {
+            (a - reader.parse(b)?).abs() <= tolerance
+        }
+    };
+
+    if !holds {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// A condition on the first time, with the time it's compared to
+enum Condition<'a> {
+    Before(&'a str),
+    After(&'a str),
+    Within(Duration, &'a str),
+}
+
+/// Parses the tolerance for `--within`, for which the sign is optional and
+/// ignored.
+pub fn parse_tolerance(s: &str) -> anyhow::Result<Duration> {
+    let delta = if s.starts_with(['+', '-']) {
+        parse_duration(s)
+    } else {
+        parse_duration(&format!("+{s}"))
+    };
+    Ok(delta?.abs())
+}
@@ -0,0 +1,75 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates count-weekdays`: count occurrences of weekdays in a date range
+
+use crate::UsageError;
+use anyhow::{Context, bail};
+use chrono::{Datelike, NaiveDate, Weekday};
+use dates_tool::context;
+use dates_tool::locale::Lang;
+
+/// Entry point for `dates count-weekdays START END [--weekday DAY[,DAY...]]`
+pub fn run(
+    args: &[String],
+    ctx: &context::Context,
+    lang: Lang,
+) -> anyhow::Result<()> {
+    let mut weekdays = Vec::new();
+    let mut dates = Vec::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--weekday" {
+            let value = args.next().with_context(|| {
+                UsageError::new(
+                    "count-weekdays: --weekday requires an argument",
+                )
+            })?;
+            for day in value.split(',') {
+                weekdays.push(lang.parse_weekday(day)?);
+            }
+        } else {
+            dates.push(ctx.parse_date(arg)?);
+        }
+    }
+
+    let [start, end] = dates[..] else {
+        bail!("count-weekdays: expected a start date and an end date");
+    };
+    if end < start {
+        bail!("count-weekdays: end date {end} is before start date {start}");
+    }
+    if weekdays.is_empty() {
+        let mut day = Weekday::Mon;
+        for _ in 0..7 {
+            weekdays.push(day);
+            day = day.succ();
+        }
+    }
+
+    let name = |date: NaiveDate| lang.weekday_short(date.weekday());
+    println!("{:<8} {} ({})", "start", start, name(start));
+    println!("{:<8} {} ({})", "end", end, name(end));
+    let mut total = 0;
+    for &day in &weekdays {
+        let n = count(start, end, day);
+        println!("{:<8} {:>10}", lang.weekday_short(day), n);
+        total += n;
+    }
+    if weekdays.len() > 1 {
+        println!("{:<8} {:>10}", "total", total);
+    }
+    Ok(())
+}
+
+/// Returns how many dates from `start` through `end` (inclusive) fall on
+/// `weekday`.
+fn count(start: NaiveDate, end: NaiveDate, weekday: Weekday) -> i64 {
+    let ndays = (end - start).num_days() + 1;
+    let full_weeks = ndays / 7;
+    let remainder = ndays % 7;
+    let offset = i64::from(weekday.days_since(start.weekday()));
+    full_weeks + i64::from(offset < remainder)
+}
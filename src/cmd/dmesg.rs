@@ -0,0 +1,196 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates dmesg`: the wall-clock times of kernel log messages
+//!
+//! The kernel stamps each message with the seconds since boot, like
+//! "[12345.678901]".  This adds those to the time the system booted, which
+//! comes from `--boot-time` or, by default, the `btime` line of /proc/stat
+//! (which is only to the second).  Given stamps as arguments, it prints the
+//! time of each; otherwise, it copies dmesg output from stdin with each
+//! line's stamp replaced by the time.
+//!
+//! The kernel's clock doesn't count time spent suspended, so on a system
+//! that has been suspended, messages after the suspension are later than
+//! these times by however long it was suspended.
+
+use crate::{ReadOptions, UsageError, print_delta, print_time};
+use anyhow::{Context, bail};
+use chrono::{DateTime, Duration, Utc};
+use dates_tool::{Error, context};
+use std::io::{BufRead, Write};
+use std::ops::Range;
+
+/// Entry point for `dates dmesg [--boot-time TIME] [STAMP...]`
+///
+/// The boot time is read as `dates TIME` reads times, with the same options.
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let mut options = ReadOptions::default();
+    let mut boot_time = None;
+    let mut stamps = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--boot-time" => {
+                let value = args.next().with_context(|| {
+                    UsageError::new("dmesg: --boot-time requires an argument")
+                })?;
+                boot_time = Some(value);
+            }
+            _ if options.parse_option(arg, &mut args)? => (),
+            _ => stamps.push(arg.as_str()),
+        }
+    }
+    let boot = match boot_time {
+        Some(value) => options.reader(ctx)?.parse(value)?,
+        None => {
+            let boot = proc_boot_time()?;
+            warn_if_suspended();
+            boot
+        }
+    };
+
+    if stamps.is_empty() {
+        return filter(std::io::stdin().lock(), boot, ctx);
+    }
+    let uptimes = stamps
+        .iter()
+        .map(|stamp| {
+            let trimmed = stamp.trim();
+            let inner = trimmed
+                .strip_prefix('[')
+                .and_then(|s| s.strip_suffix(']'))
+                .unwrap_or(trimmed);
+            parse_uptime(inner.trim()).ok_or_else(|| {
+                Error::unrecognized(
+                    stamp,
+                    "a kernel timestamp (like [12.345678])",
+                )
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    print_time(ctx, "boot", boot);
+    for (stamp, uptime) in stamps.into_iter().zip(uptimes) {
+        let time = boot.checked_add_signed(uptime).ok_or_else(|| {
+            Error::out_of_range(stamp, "too large for a timestamp")
+        })?;
+        println!();
+        print_delta("uptime", uptime);
+        print_time(ctx, "time", time);
+    }
+    Ok(())
+}
+
+/// Returns the time the system booted, to the second, from /proc/stat.
+pub fn proc_boot_time() -> anyhow::Result<DateTime<Utc>> {
+    let stat = std::fs::read_to_string("/proc/stat").context(
+        "reading /proc/stat for the boot time (use --boot-time to give it)",
+    )?;
+    let Some(line) = stat.lines().find(|l| l.starts_with("btime ")) else {
+        bail!("/proc/stat has no btime line (use --boot-time to give it)");
+    };
+    line["btime ".len()..]
+        .trim()
+        .parse()
+        .ok()
+        .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+        .with_context(|| format!("/proc/stat has an invalid {line:?}"))
+}
+
+/// Returns the time the system booted, to the hundredth of a second, from
+/// /proc/uptime, which is the time since then (including any time spent
+/// suspended).
+pub fn uptime_boot_time() -> anyhow::Result<DateTime<Utc>> {
+    let now = Utc::now();
+    let uptime = std::fs::read_to_string("/proc/uptime").context(
+        "reading /proc/uptime for the boot time (use --boot-time to give it)",
+    )?;
+    uptime
+        .split_whitespace()
+        .next()
+        .and_then(parse_uptime)
+        .and_then(|uptime| now.checked_sub_signed(uptime))
+        .with_context(|| format!("/proc/uptime has an invalid {uptime:?}"))
+}
+
+/// Warns if this system has been suspended since it booted, in which case
+/// times counted by a clock that stops during suspension (like the kernel
+/// log's and `CLOCK_MONOTONIC`) are earlier than they should be.
+pub fn warn_if_suspended() {
+    let count = std::fs::read_to_string("/sys/power/suspend_stats/success")
+        .ok()
+        .and_then(|s| s.trim().parse::<u64>().ok())
+        .unwrap_or(0);
+    if count > 0 {
+        eprintln!(
+            "dates: warning: this system has been suspended {count} time(s) \
+             since it booted, so times from a clock that stops while \
+             suspended (like CLOCK_MONOTONIC) really happened later"
+        );
+    }
+}
+
+/// Copies dmesg output from `input` to stdout, replacing each line's stamp
+/// with the time it means, in the local zone.  Lines without a stamp are
+/// copied as they are.
+fn filter(
+    mut input: impl BufRead,
+    boot: DateTime<Utc>,
+    ctx: &context::Context,
+) -> anyhow::Result<()> {
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if input.read_until(b'\n', &mut line).context("reading stdin")? == 0 {
+            return Ok(());
+        }
+        match stamp(&line) {
+            Some((range, uptime)) => {
+                let time = boot
+                    .checked_add_signed(uptime)
+                    .map(|t| ctx.local().rfc3339(t))
+                    .unwrap_or_else(|| String::from("out of range"));
+                out.write_all(&line[..range.start])?;
+                write!(out, "[{time}]")?;
+                out.write_all(&line[range.end..])?;
+            }
+            None => out.write_all(&line)?,
+        }
+    }
+}
+
+/// Finds the stamp at the start of a line of dmesg output, which may follow
+/// a priority like "<6>" (as `dmesg -r` prints), returning where it is
+/// (including the brackets) and the time since boot it says.
+fn stamp(line: &[u8]) -> Option<(Range<usize>, Duration)> {
+    let start = match line.strip_prefix(b"<") {
+        Some(rest) => {
+            let digits = rest.iter().take_while(|b| b.is_ascii_digit()).count();
+            (digits > 0 && rest.get(digits) == Some(&b'>'))
+                .then_some(digits + 2)?
+        }
+        None => 0,
+    };
+    let rest = line[start..].strip_prefix(b"[")?;
+    let len = rest.iter().position(|&b| b == b']')?;
+    let inner = std::str::from_utf8(&rest[..len]).ok()?;
+    let uptime = parse_uptime(inner.trim())?;
+    Some((start..start + len + 2, uptime))
+}
+
+/// Parses a number of seconds with a fraction, like "12345.678901".
+pub fn parse_uptime(s: &str) -> Option<Duration> {
+    let (whole, fraction) = s.split_once('.').unwrap_or((s, ""));
+    if whole.is_empty()
+        || fraction.len() > 9
+        || !whole.bytes().chain(fraction.bytes()).all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    let seconds: i64 = whole.parse().ok()?;
+    let nanos = format!("{fraction:0<9}").parse::<i64>().ok()?;
+    Some(Duration::try_seconds(seconds)? + Duration::nanoseconds(nanos))
+}
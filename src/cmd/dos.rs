@@ -0,0 +1,130 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates dos`: DOS (FAT and ZIP) packed dates and times
+//!
+//! A DOS date and time packs a local wall-clock time, to two seconds, into
+//! 32 bits (see [`dates_tool::dos`]).  This reads one either as a number or
+//! as the four bytes a FAT directory entry or ZIP header stores, or packs a
+//! time in any other form, and prints its fields and the time it means.
+//! There's no zone recorded, so it's read in `--tz` (or the local zone), as
+//! is a time without an offset to pack.
+
+use crate::{UsageError, print_time};
+use anyhow::{Context, bail};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use dates_tool::parser::Registry;
+use dates_tool::zone::{
+    AmbiguousPolicy, DstPolicy, NonexistentPolicy, WallClockParser, Zone,
+};
+use dates_tool::{Error, context, dos};
+
+/// Entry point for `dates dos [--tz ZONE] VALUE...`
+///
+/// Each VALUE is a packed date and time in decimal or (with "0x")
+/// hexadecimal, with the date in the high 16 bits; four bytes in file
+/// order, like "21 60 61 58"; or a time in any other form, to pack.
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let mut zone = ctx.local();
+    let mut values = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--tz" => {
+                let value = args.next().with_context(|| {
+                    UsageError::new("dos: --tz requires an argument")
+                })?;
+                zone = Zone::parse(value)?;
+            }
+            flag if flag.starts_with("--") => {
+                bail!(UsageError::new(format!(
+                    "dos: unexpected argument {flag:?}"
+                )))
+            }
+            _ => values.push(arg.as_str()),
+        }
+    }
+    if values.is_empty() {
+        bail!("dos: expected at least one DOS date and time");
+    }
+    // A time to pack without an offset is a wall-clock time in the same zone,
+    // as with `dates --tz`.  One that occurs twice packs the same either way,
+    // but one that's skipped can't have been read on a clock that followed
+    // the zone.
+    let policy = DstPolicy {
+        ambiguous: AmbiguousPolicy::Earliest,
+        nonexistent: NonexistentPolicy::Error,
+    };
+    let mut registry = Registry::empty();
+    registry.register(WallClockParser::new(zone, policy)).register_builtin();
+    let packed = values
+        .iter()
+        .map(|value| match parse_packed(value) {
+            Some(packed) => Ok(packed),
+            None => {
+                let wall = zone.wall_clock(registry.parse(value)?);
+                dos::encode(wall)
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for (i, packed) in packed.into_iter().enumerate() {
+        let wall = dos::decode(packed)?;
+        if i > 0 {
+            println!();
+        }
+        let (date, time) = (packed >> 16, packed & 0xffff);
+        println!(
+            "{:<8} 0x{packed:08x} (date 0x{date:04x}, time 0x{time:04x})",
+            "dos"
+        );
+        let bytes = packed.to_le_bytes().map(|b| format!("{b:02x}"));
+        println!("{:<8} {}", "bytes", bytes.join(" "));
+        println!("{:<8} {}", "wall", wall);
+        print_time(ctx, "time", resolve(zone, wall)?);
+        println!("{:<8} none recorded, so this was read in {}", "offset", zone);
+    }
+    Ok(())
+}
+
+/// Parses a packed date and time as a number or as four bytes in file order
+/// (separated by spaces or colons), returning `None` if `s` isn't either.
+fn parse_packed(s: &str) -> Option<u32> {
+    let bytes: Vec<&str> =
+        s.split([' ', ':']).filter(|b| !b.is_empty()).collect();
+    if bytes.len() == 4 {
+        let mut le = [0; 4];
+        for (byte, text) in le.iter_mut().zip(bytes) {
+            if text.len() != 2 {
+                return None;
+            }
+            *byte = u8::from_str_radix(text, 16).ok()?;
+        }
+        return Some(u32::from_le_bytes(le));
+    }
+    match s.strip_prefix("0x") {
+        Some(hex) if hex.bytes().all(|b| b.is_ascii_hexdigit()) => {
+            u32::from_str_radix(hex, 16).ok()
+        }
+        Some(_) => None,
+        None if s.bytes().all(|b| b.is_ascii_digit()) => s.parse().ok(),
+        None => None,
+    }
+}
+
+/// Returns the time that the wall-clock time `wall` was in `zone`, for a
+/// format that records no zone.
+pub fn resolve(
+    zone: Zone,
+    wall: NaiveDateTime,
+) -> Result<DateTime<Utc>, Error> {
+    // Like cameras, DOS (and VMS) clocks don't know about daylight saving
+    // time, so there's no way to do better than a guess for times in the gap
+    // or overlap.
+    let policy = DstPolicy {
+        ambiguous: AmbiguousPolicy::Earliest,
+        nonexistent: NonexistentPolicy::Shift,
+    };
+    zone.resolve(&wall.to_string(), wall, policy)
+}
@@ -0,0 +1,163 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates dtrace`: the wall-clock times of DTrace's `timestamp` values
+//!
+//! DTrace's `timestamp` (like `gethrtime()`) counts nanoseconds from an
+//! arbitrary point, usually boot, so it has no meaning by itself.  Given one
+//! pair of a `timestamp` and the wall-clock time at that moment, as a D
+//! script can print with
+//!
+//! ```text
+//! BEGIN { printf("%d %d\n", timestamp, walltimestamp); }
+//! ```
+//!
+//! this prints the time of other `timestamp` values, which are in
+//! nanoseconds relative to that one.  Without values, it copies the output
+//! of a D script from stdin, replacing each number with at least as many
+//! digits as the anchor's `timestamp` with the time it means.  (CPU ids,
+//! process ids, and the like are much shorter than a `timestamp` on any
+//! system that's been up for more than a few seconds.)
+
+use crate::{ReadOptions, Reader, UsageError, print_delta, print_time};
+use anyhow::{Context, bail};
+use chrono::{DateTime, Duration, Utc};
+use dates_tool::{Error, context};
+use std::io::{BufRead, Write};
+
+/// Entry point for `dates dtrace --anchor HRTIME=WALLTIME [HRTIME...]`
+///
+/// A WALLTIME that isn't a number of nanoseconds is read as `dates TIME`
+/// reads times, with the same options.
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let mut options = ReadOptions::default();
+    let mut anchor = None;
+    let mut values = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--anchor" => {
+                let value = args.next().with_context(|| {
+                    UsageError::new("dtrace: --anchor requires an argument")
+                })?;
+                anchor = Some(value);
+            }
+            _ if options.parse_option(arg, &mut args)? => (),
+            _ => values.push(arg.as_str()),
+        }
+    }
+    let Some(anchor) = anchor else {
+        bail!("dtrace: --anchor HRTIME=WALLTIME is required");
+    };
+    let anchor = Anchor::parse(anchor, &options.reader(ctx)?)?;
+
+    if values.is_empty() {
+        return filter(std::io::stdin().lock(), &anchor, ctx);
+    }
+    let hrtimes = values
+        .iter()
+        .map(|value| {
+            parse_hrtime(value).ok_or_else(|| {
+                Error::unrecognized(value, "a DTrace timestamp (nanoseconds)")
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    print_time(ctx, "anchor", anchor.wall);
+    for (value, hrtime) in values.into_iter().zip(hrtimes) {
+        let (delta, time) = anchor.time(hrtime).ok_or_else(|| {
+            Error::out_of_range(value, "too far from the anchor")
+        })?;
+        println!();
+        print_delta("offset", delta);
+        print_time(ctx, "time", time);
+    }
+    Ok(())
+}
+
+/// A `timestamp` value and the wall-clock time at the same moment
+struct Anchor {
+    hrtime: u64,
+    digits: usize,
+    wall: DateTime<Utc>,
+}
+
+impl Anchor {
+    /// Parses "HRTIME=WALLTIME", where WALLTIME is a time `reader` reads or
+    /// a number of nanoseconds since 1970 (as DTrace's `walltimestamp` is).
+    fn parse(s: &str, reader: &Reader) -> anyhow::Result<Anchor> {
+        let Some((hrtime, wall)) = s.split_once('=') else {
+            bail!("dtrace: --anchor {s:?} isn't HRTIME=WALLTIME");
+        };
+        let digits = hrtime.len();
+        let hrtime = parse_hrtime(hrtime).ok_or_else(|| {
+            Error::unrecognized(hrtime, "a DTrace timestamp (nanoseconds)")
+        })?;
+        let wall = match parse_hrtime(wall) {
+            Some(nanos) => i64::try_from(nanos)
+                .map(DateTime::from_timestamp_nanos)
+                .map_err(|_| {
+                    Error::out_of_range(wall, "too large for a timestamp")
+                })?,
+            None => reader.parse(wall)?,
+        };
+        Ok(Anchor { hrtime, digits, wall })
+    }
+
+    /// Returns how long after the anchor `hrtime` was, and the time it
+    /// means, or `None` if that's out of range.
+    fn time(&self, hrtime: u64) -> Option<(Duration, DateTime<Utc>)> {
+        let nanos = i128::from(hrtime) - i128::from(self.hrtime);
+        let delta = Duration::nanoseconds(i64::try_from(nanos).ok()?);
+        Some((delta, self.wall.checked_add_signed(delta)?))
+    }
+}
+
+/// Copies the output of a D script from `input` to stdout, replacing each
+/// number that could be a `timestamp` with the time it means, in the local
+/// zone.
+fn filter(
+    mut input: impl BufRead,
+    anchor: &Anchor,
+    ctx: &context::Context,
+) -> anyhow::Result<()> {
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if input.read_until(b'\n', &mut line).context("reading stdin")? == 0 {
+            return Ok(());
+        }
+        let mut rest = &line[..];
+        while !rest.is_empty() {
+            let start = rest.iter().position(u8::is_ascii_digit);
+            let start = start.unwrap_or(rest.len());
+            let len =
+                rest[start..].iter().take_while(|b| b.is_ascii_digit()).count();
+            let (before, number) = (&rest[..start], &rest[start..start + len]);
+            rest = &rest[start + len..];
+            out.write_all(before)?;
+            let time = (len >= anchor.digits
+                && !before.last().is_some_and(u8::is_ascii_alphanumeric)
+                && !rest.first().is_some_and(u8::is_ascii_alphanumeric))
+            .then(|| std::str::from_utf8(number).ok()?.parse().ok())
+            .flatten()
+            .and_then(|hrtime| anchor.time(hrtime));
+            match time {
+                Some((_, time)) => {
+                    write!(out, "{}", ctx.local().rfc3339(time))?
+                }
+                None => out.write_all(number)?,
+            }
+        }
+    }
+}
+
+/// Parses a `timestamp` value, which is an unsigned number of nanoseconds.
+fn parse_hrtime(s: &str) -> Option<u64> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok()
+}
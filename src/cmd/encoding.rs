@@ -0,0 +1,54 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Decoding the binary data that subcommands like `dates cbor` and `dates
+//! jwt` are given as text
+
+use dates_tool::Error;
+
+/// Decodes bytes written in hexadecimal (optionally with a leading "0x") or,
+/// if they're not valid hexadecimal, base64.  Whitespace is ignored.
+pub fn decode_bytes(s: &str) -> anyhow::Result<Vec<u8>> {
+    let text: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+    let hex = text.strip_prefix("0x").unwrap_or(&text);
+    if hex.len().is_multiple_of(2) && hex.bytes().all(|b| b.is_ascii_hexdigit())
+    {
+        return Ok((0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect());
+    }
+    Ok(decode_base64(&text)?)
+}
+
+/// Decodes base64url (RFC 4648), which JWTs use without padding.  The
+/// standard alphabet and padding are accepted too.
+pub fn decode_base64(s: &str) -> Result<Vec<u8>, Error> {
+    let mut bytes = Vec::new();
+    let mut bits = 0u32;
+    let mut count = 0;
+    for (i, c) in s.trim_end_matches('=').char_indices() {
+        let value = match c {
+            'A'..='Z' => c as u32 - 'A' as u32,
+            'a'..='z' => c as u32 - 'a' as u32 + 26,
+            '0'..='9' => c as u32 - '0' as u32 + 52,
+            '-' | '+' => 62,
+            '_' | '/' => 63,
+            _ => {
+                return Err(Error::unrecognized_at(
+                    s,
+                    i..i + c.len_utf8(),
+                    "a base64 digit",
+                ));
+            }
+        };
+        bits = (bits << 6 | value) & 0xfff;
+        count += 6;
+        if count >= 8 {
+            count -= 8;
+            bytes.push((bits >> count) as u8);
+        }
+    }
+    Ok(bytes)
+}
@@ -0,0 +1,288 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates eval`: evaluate arithmetic expressions over times and deltas
+//!
+//! An expression is made of times, deltas, plain numbers, and variables,
+//! combined with `+`, `-`, `*`, `/`, and parentheses.  Because timestamps
+//! themselves contain `-`, `+`, and `:`, binary operators must be separated
+//! from their operands by whitespace: `2018-02-08 + 3d`, not `2018-02-08+3d`.
+//!
+//! Deltas may be written without a leading sign (`3d`).  Plain numbers are
+//! scalars (`(t2 - t1) * 2`), except that a number added to or subtracted
+//! from a time or delta is taken to be a millisecond Unix timestamp, just as
+//! it would be on the command line.  `now` is the current time, unless it's
+//! bound to something else.
+
+use crate::{print_delta, print_time};
+use anyhow::{Context, anyhow, bail};
+use chrono::{DateTime, Duration, Utc};
+use dates_tool::context;
+use dates_tool::{parse_duration, parse_timestamp};
+use std::collections::BTreeMap;
+
+#[derive(Clone, Copy, Debug)]
+enum Value {
+    Time(DateTime<Utc>),
+    Delta(Duration),
+    Number(f64),
+}
+
+/// Entry point for `dates eval EXPR [NAME=VALUE...]`
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let (expr, bindings) = match args {
+        [expr, bindings @ ..] => (expr, bindings),
+        [] => bail!("eval: expected an expression"),
+    };
+
+    let mut vars =
+        BTreeMap::from([(String::from("now"), Value::Time(ctx.now()))]);
+    for binding in bindings {
+        let (name, value) = binding.split_once('=').with_context(|| {
+            format!("eval: expected NAME=VALUE, found {binding:?}")
+        })?;
+        if !is_identifier(name) {
+            bail!("eval: invalid variable name {name:?}");
+        }
+        let value = parse_atom(value, &vars)
+            .with_context(|| format!("eval: parsing value for {name:?}"))?;
+        vars.insert(name.to_string(), value);
+    }
+
+    match evaluate(expr, &vars)? {
+        Value::Time(t) => print_time(ctx, "result", t),
+        Value::Delta(d) => print_delta("result", d),
+        Value::Number(n) => println!("{:<8} {}", "result", n),
+    }
+    Ok(())
+}
+
+fn evaluate(
+    expr: &str,
+    vars: &BTreeMap<String, Value>,
+) -> anyhow::Result<Value> {
+    let tokens = tokenize(expr);
+    let mut parser = Parser { tokens: &tokens, pos: 0, vars };
+    let value = parser.expr()?;
+    if let Some(token) = parser.peek() {
+        bail!("eval: unexpected {token:?}");
+    }
+    Ok(value)
+}
+
+/// Splits `expr` on whitespace, additionally separating out parentheses.
+fn tokenize(expr: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    for word in expr.split_whitespace() {
+        let mut rest = word;
+        while let Some(i) = rest.find(['(', ')']) {
+            if i > 0 {
+                tokens.push(&rest[..i]);
+            }
+            tokens.push(&rest[i..i + 1]);
+            rest = &rest[i + 1..];
+        }
+        if !rest.is_empty() {
+            tokens.push(rest);
+        }
+    }
+    tokens
+}
+
+struct Parser<'a> {
+    tokens: &'a [&'a str],
+    pos: usize,
+    vars: &'a BTreeMap<String, Value>,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<&'a str> {
+        let token = self.tokens.get(self.pos).copied();
+        self.pos += 1;
+        token
+    }
+
+    /// expr := term (("+" | "-") term)*
+    fn expr(&mut self) -> anyhow::Result<Value> {
+        let mut left = self.term()?;
+        while let Some(op @ ("+" | "-")) = self.peek() {
+            self.pos += 1;
+            let right = self.term()?;
+            left =
+                if op == "+" { add(left, right)? } else { sub(left, right)? };
+        }
+        Ok(left)
+    }
+
+    /// term := unary (("*" | "/") unary)*
+    fn term(&mut self) -> anyhow::Result<Value> {
+        let mut left = self.unary()?;
+        while let Some(op @ ("*" | "/")) = self.peek() {
+            self.pos += 1;
+            let right = self.unary()?;
+            left =
+                if op == "*" { mul(left, right)? } else { div(left, right)? };
+        }
+        Ok(left)
+    }
+
+    /// unary := "-" unary | primary
+    fn unary(&mut self) -> anyhow::Result<Value> {
+        if self.peek() == Some("-") {
+            self.pos += 1;
+            return match self.unary()? {
+                Value::Delta(d) => Ok(Value::Delta(-d)),
+                Value::Number(n) => Ok(Value::Number(-n)),
+                Value::Time(_) => bail!("eval: cannot negate a time"),
+            };
+        }
+        self.primary()
+    }
+
+    /// primary := "(" expr ")" | atom
+    fn primary(&mut self) -> anyhow::Result<Value> {
+        match self.next() {
+            None => bail!("eval: unexpected end of expression"),
+            Some("(") => {
+                let value = self.expr()?;
+                match self.next() {
+                    Some(")") => Ok(value),
+                    _ => bail!("eval: expected \")\""),
+                }
+            }
+            Some(token @ (")" | "+" | "*" | "/")) => {
+                bail!("eval: unexpected {token:?}")
+            }
+            Some(token) => parse_atom(token, self.vars),
+        }
+    }
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn parse_atom(
+    s: &str,
+    vars: &BTreeMap<String, Value>,
+) -> anyhow::Result<Value> {
+    if let Some(value) = vars.get(s) {
+        return Ok(*value);
+    }
+    if let Ok(n) = s.parse::<f64>() {
+        return Ok(Value::Number(n));
+    }
+    let delta = if s.starts_with(['+', '-']) {
+        parse_duration(s)
+    } else {
+        parse_duration(&format!("+{s}"))
+    };
+    if let Ok(d) = delta {
+        return Ok(Value::Delta(d));
+    }
+    if let Ok(t) = parse_timestamp(s) {
+        return Ok(Value::Time(t));
+    }
+    if is_identifier(s) {
+        bail!("eval: undefined variable {s:?}");
+    }
+    bail!("eval: could not parse {s:?} as a time, delta, or number")
+}
+
+/// Interprets a number used where a time is expected as a millisecond Unix
+/// timestamp.
+fn as_time(value: Value) -> anyhow::Result<Value> {
+    match value {
+        Value::Number(n) if n.fract() == 0.0 => parse_timestamp(&n.to_string())
+            .map(Value::Time)
+            .map_err(|_| anyhow!("eval: {n} is out of range for a timestamp")),
+        Value::Number(n) => {
+            bail!("eval: {n} cannot be used as a millisecond timestamp")
+        }
+        other => Ok(other),
+    }
+}
+
+fn add(a: Value, b: Value) -> anyhow::Result<Value> {
+    use Value::*;
+    match (a, b) {
+        (Number(x), Number(y)) => Ok(Number(x + y)),
+        (Number(_), _) => add(as_time(a)?, b),
+        (_, Number(_)) => add(a, as_time(b)?),
+        (Time(t), Delta(d)) | (Delta(d), Time(t)) => t
+            .checked_add_signed(d)
+            .map(Time)
+            .ok_or_else(|| anyhow!("eval: time out of range")),
+        (Delta(x), Delta(y)) => x
+            .checked_add(&y)
+            .map(Delta)
+            .ok_or_else(|| anyhow!("eval: delta out of range")),
+        (Time(_), Time(_)) => bail!("eval: cannot add two times"),
+    }
+}
+
+fn sub(a: Value, b: Value) -> anyhow::Result<Value> {
+    use Value::*;
+    match (a, b) {
+        (Number(x), Number(y)) => Ok(Number(x - y)),
+        (Number(_), _) => sub(as_time(a)?, b),
+        (_, Number(_)) => sub(a, as_time(b)?),
+        (Time(t), Delta(d)) => t
+            .checked_sub_signed(d)
+            .map(Time)
+            .ok_or_else(|| anyhow!("eval: time out of range")),
+        (Time(x), Time(y)) => Ok(Delta(x - y)),
+        (Delta(x), Delta(y)) => x
+            .checked_sub(&y)
+            .map(Delta)
+            .ok_or_else(|| anyhow!("eval: delta out of range")),
+        (Delta(_), Time(_)) => {
+            bail!("eval: cannot subtract a time from a delta")
+        }
+    }
+}
+
+fn mul(a: Value, b: Value) -> anyhow::Result<Value> {
+    use Value::*;
+    match (a, b) {
+        (Number(x), Number(y)) => Ok(Number(x * y)),
+        (Delta(d), Number(k)) | (Number(k), Delta(d)) => scale(d, k),
+        _ => bail!("eval: can only multiply a delta by a number"),
+    }
+}
+
+fn div(a: Value, b: Value) -> anyhow::Result<Value> {
+    use Value::*;
+    match (a, b) {
+        (_, Number(0.0)) => bail!("eval: division by zero"),
+        (Number(x), Number(y)) => Ok(Number(x / y)),
+        (Delta(d), Number(k)) => scale(d, 1.0 / k),
+        (Delta(x), Delta(y)) => {
+            let (x, y) = (micros(x)?, micros(y)?);
+            if y == 0 {
+                bail!("eval: division by zero");
+            }
+            Ok(Number(x as f64 / y as f64))
+        }
+        _ => bail!("eval: can only divide a delta by a number or a delta"),
+    }
+}
+
+fn micros(d: Duration) -> anyhow::Result<i64> {
+    d.num_microseconds().ok_or_else(|| anyhow!("eval: delta out of range"))
+}
+
+fn scale(d: Duration, k: f64) -> anyhow::Result<Value> {
+    let scaled = (micros(d)? as f64 * k).round();
+    if !scaled.is_finite() || scaled.abs() >= i64::MAX as f64 {
+        bail!("eval: delta out of range");
+    }
+    Ok(Value::Delta(Duration::microseconds(scaled as i64)))
+}
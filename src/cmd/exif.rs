@@ -0,0 +1,388 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates exif`: the times in a photo's EXIF metadata
+//!
+//! EXIF records when a photo was taken (`DateTimeOriginal`), when it was
+//! digitized (`CreateDate`), and when the file was last changed
+//! (`ModifyDate`), each as a wall-clock time like "2024:03:01 12:00:00",
+//! with the fraction of a second in a separate `SubSecTime` tag and the UTC
+//! offset, if the camera recorded one, in a separate `OffsetTime` tag.  Many
+//! cameras record no offset, so the time is only as good as whatever the
+//! camera's clock was set to; this reads such a time in `--tz` (or the local
+//! zone) and says so.  A photo with GPS data also has the time from the GPS
+//! receiver, which is in UTC, and comparing that with `DateTimeOriginal`
+//! shows how far off the camera's clock was.
+//!
+//! This reads JPEG files and TIFF-based files, which include the raw formats
+//! of most cameras.
+
+use crate::{UsageError, print_delta, print_time};
+use anyhow::{Context, bail};
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, NaiveDateTime, Utc};
+use dates_tool::context;
+use dates_tool::zone::{AmbiguousPolicy, DstPolicy, NonexistentPolicy, Zone};
+
+/// Tags in IFD0
+const MODIFY_DATE: u16 = 0x0132;
+const EXIF_IFD: u16 = 0x8769;
+const GPS_IFD: u16 = 0x8825;
+
+/// Tags in the Exif IFD
+const DATE_TIME_ORIGINAL: u16 = 0x9003;
+const CREATE_DATE: u16 = 0x9004;
+const OFFSET_TIME: u16 = 0x9010;
+const OFFSET_TIME_ORIGINAL: u16 = 0x9011;
+const OFFSET_TIME_DIGITIZED: u16 = 0x9012;
+const SUB_SEC_TIME: u16 = 0x9290;
+const SUB_SEC_TIME_ORIGINAL: u16 = 0x9291;
+const SUB_SEC_TIME_DIGITIZED: u16 = 0x9292;
+
+/// Tags in the GPS IFD
+const GPS_TIME_STAMP: u16 = 0x0007;
+const GPS_DATE_STAMP: u16 = 0x001d;
+
+/// Entry point for `dates exif [--tz ZONE] FILE...`
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let mut zone = ctx.local();
+    let mut paths = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--tz" => {
+                let value = args.next().with_context(|| {
+                    UsageError::new("exif: --tz requires an argument")
+                })?;
+                zone = Zone::parse(value)?;
+            }
+            _ => paths.push(arg.as_str()),
+        }
+    }
+    if paths.is_empty() {
+        bail!("exif: expected a JPEG or TIFF file");
+    }
+
+    for (i, path) in paths.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        if paths.len() > 1 {
+            println!("{:<8} {}", "file", path);
+        }
+        let data = std::fs::read(path)
+            .with_context(|| format!("exif: reading {path:?}"))?;
+        let times = times(&data, zone)
+            .with_context(|| format!("exif: reading {path:?}"))?;
+        if times.is_empty() {
+            bail!("exif: no times in {path:?}");
+        }
+        let original = times.iter().find(|t| t.label == "original");
+        let gps = times.iter().find(|t| t.label == "gps");
+        for time in &times {
+            print_time(ctx, time.label, time.time);
+            match &time.offset {
+                Offset::Recorded(offset) => {
+                    println!("{:<8} {}", "offset", offset)
+                }
+                Offset::Assumed => println!(
+                    "{:<8} none recorded, so this was read in {}",
+                    "offset", zone
+                ),
+                Offset::Utc => (),
+            }
+        }
+        // GPS times are whole seconds, so a skew of less than a second
+        // doesn't mean much.
+        if let (Some(original), Some(gps)) = (original, gps) {
+            print_delta("skew", original.time - gps.time);
+        }
+    }
+    Ok(())
+}
+
+/// One time from a file's metadata
+struct Time {
+    label: &'static str,
+    time: DateTime<Utc>,
+    offset: Offset,
+}
+
+/// Where the UTC offset of a [`Time`] came from
+enum Offset {
+    /// an `OffsetTime` tag, as written there
+    Recorded(String),
+    /// nowhere, so the time was read in the given zone
+    Assumed,
+    /// a GPS time, which is always in UTC
+    Utc,
+}
+
+/// Returns the times in the EXIF metadata of the JPEG or TIFF file `data`,
+/// reading those without an offset in `zone`.
+fn times(data: &[u8], zone: Zone) -> anyhow::Result<Vec<Time>> {
+    let tiff = Tiff::new(exif_data(data)?)?;
+    let ifd0 = tiff.ifd(tiff.u32(4)?)?;
+    let sub_ifd = |tag| -> anyhow::Result<Vec<Entry>> {
+        match ifd0.iter().find(|e| e.tag == tag) {
+            Some(entry) => tiff.ifd(tiff.u32_of(entry)?),
+            None => Ok(Vec::new()),
+        }
+    };
+    let exif = sub_ifd(EXIF_IFD)?;
+    let gps = sub_ifd(GPS_IFD)?;
+
+    let mut times = Vec::new();
+    let kinds = [
+        (
+            "original",
+            DATE_TIME_ORIGINAL,
+            SUB_SEC_TIME_ORIGINAL,
+            OFFSET_TIME_ORIGINAL,
+        ),
+        ("created", CREATE_DATE, SUB_SEC_TIME_DIGITIZED, OFFSET_TIME_DIGITIZED),
+        ("modified", MODIFY_DATE, SUB_SEC_TIME, OFFSET_TIME),
+    ];
+    for (label, date_tag, sub_sec_tag, offset_tag) in kinds {
+        let ascii = |tag| {
+            ifd0.iter()
+                .chain(&exif)
+                .find(|e| e.tag == tag)
+                .map(|e| ascii(e.value))
+                .filter(|s| !s.is_empty())
+        };
+        let Some(wall) = ascii(date_tag) else { continue };
+        let Some(wall) = wall_clock(&wall, ascii(sub_sec_tag).as_deref())?
+        else {
+            continue;
+        };
+        let offset = ascii(offset_tag).and_then(|s| parse_offset(&s));
+        let time = match offset {
+            Some((offset, text)) => Time {
+                label,
+                time: (wall - offset).and_utc(),
+                offset: Offset::Recorded(text),
+            },
+            None => {
+                // Cameras don't know about daylight saving time either, so
+                // there's no way to do better than a guess here.
+                let policy = DstPolicy {
+                    ambiguous: AmbiguousPolicy::Earliest,
+                    nonexistent: NonexistentPolicy::Shift,
+                };
+                let input = wall.to_string();
+                Time {
+                    label,
+                    time: zone.resolve(&input, wall, policy)?,
+                    offset: Offset::Assumed,
+                }
+            }
+        };
+        times.push(time);
+    }
+
+    let gps_date = gps.iter().find(|e| e.tag == GPS_DATE_STAMP);
+    let gps_time = gps.iter().find(|e| e.tag == GPS_TIME_STAMP);
+    if let (Some(date), Some(time)) = (gps_date, gps_time) {
+        let date = ascii(date.value);
+        let date = NaiveDate::parse_from_str(&date, "%Y:%m:%d")
+            .with_context(|| format!("GPSDateStamp {date:?} isn't a date"))?;
+        if time.count != 3 || time.kind != RATIONAL {
+            bail!("GPSTimeStamp isn't three rationals");
+        }
+        // Hours, minutes, and seconds, any of which may have a fraction
+        let mut nanos = 0;
+        for (i, scale) in [3600, 60, 1].into_iter().enumerate() {
+            let numerator = tiff.u32_at(time.value, 8 * i)?;
+            let denominator = tiff.u32_at(time.value, 8 * i + 4)?;
+            if denominator == 0 {
+                bail!("GPSTimeStamp divides by zero");
+            }
+            nanos += i64::from(numerator) * scale * 1_000_000_000
+                / i64::from(denominator);
+        }
+        let time = date.and_hms_opt(0, 0, 0).unwrap().and_utc()
+            + Duration::nanoseconds(nanos);
+        times.push(Time { label: "gps", time, offset: Offset::Utc });
+    }
+    Ok(times)
+}
+
+/// Returns the EXIF data (a TIFF structure) in the JPEG or TIFF file `data`.
+fn exif_data(data: &[u8]) -> anyhow::Result<&[u8]> {
+    if data.starts_with(b"II*\0") || data.starts_with(b"MM\0*") {
+        return Ok(data);
+    }
+    let Some(mut rest) = data.strip_prefix(b"\xff\xd8") else {
+        bail!("not a JPEG or TIFF file");
+    };
+    // The APP1 segment holding EXIF data comes before the image data.
+    loop {
+        let Some((&0xff, after)) = rest.split_first() else {
+            bail!("no EXIF data");
+        };
+        // Any number of 0xff bytes may precede a marker.
+        let padding = after.iter().take_while(|&&b| b == 0xff).count();
+        let Some((&marker, after)) = after[padding..].split_first() else {
+            bail!("no EXIF data");
+        };
+        rest = after;
+        match marker {
+            // markers with no segment
+            0x01 | 0xd0..=0xd7 => (),
+            // start of scan, or end of image
+            0xda | 0xd9 => bail!("no EXIF data"),
+            _ => {
+                let Some(&[high, low]) = rest.get(..2) else {
+                    bail!("a JPEG segment is truncated");
+                };
+                let len = usize::from(u16::from_be_bytes([high, low]));
+                let Some(segment) = rest.get(2..len.max(2)) else {
+                    bail!("a JPEG segment is truncated");
+                };
+                if marker == 0xe1
+                    && let Some(exif) = segment.strip_prefix(b"Exif\0\0")
+                {
+                    return Ok(exif);
+                }
+                rest = &rest[len.max(2)..];
+            }
+        }
+    }
+}
+
+/// The TIFF type of rational numbers (two LONGs)
+const RATIONAL: u16 = 5;
+
+/// One entry in an IFD
+struct Entry<'a> {
+    tag: u16,
+    kind: u16,
+    count: u32,
+    /// the entry's value, whether it was stored in the entry or elsewhere
+    value: &'a [u8],
+}
+
+/// A TIFF structure, as EXIF data is
+struct Tiff<'a> {
+    data: &'a [u8],
+    big_endian: bool,
+}
+
+impl<'a> Tiff<'a> {
+    fn new(data: &'a [u8]) -> anyhow::Result<Tiff<'a>> {
+        let big_endian = match data.get(..4) {
+            Some(b"II*\0") => false,
+            Some(b"MM\0*") => true,
+            _ => bail!("EXIF data doesn't start with a TIFF header"),
+        };
+        Ok(Tiff { data, big_endian })
+    }
+
+    /// Reads the 16-bit integer at `offset` in `bytes`.
+    fn u16_at(&self, bytes: &[u8], offset: usize) -> anyhow::Result<u16> {
+        let bytes =
+            bytes.get(offset..offset + 2).context("EXIF data is truncated")?;
+        let bytes = [bytes[0], bytes[1]];
+        Ok(if self.big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    }
+
+    /// Reads the 32-bit integer at `offset` in `bytes`.
+    fn u32_at(&self, bytes: &[u8], offset: usize) -> anyhow::Result<u32> {
+        let bytes =
+            bytes.get(offset..offset + 4).context("EXIF data is truncated")?;
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        Ok(if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    }
+
+    /// Reads the 32-bit integer at `offset` in the TIFF structure, as an
+    /// offset within it.
+    fn u32(&self, offset: usize) -> anyhow::Result<usize> {
+        Ok(self.u32_at(self.data, offset)? as usize)
+    }
+
+    /// Reads the value of `entry` as an offset within the TIFF structure.
+    fn u32_of(&self, entry: &Entry) -> anyhow::Result<usize> {
+        Ok(self.u32_at(entry.value, 0)? as usize)
+    }
+
+    /// Reads the IFD at `offset`.
+    fn ifd(&self, offset: usize) -> anyhow::Result<Vec<Entry<'a>>> {
+        let count = self.u16_at(self.data, offset)?;
+        (0..usize::from(count))
+            .map(|i| {
+                let at = offset + 2 + 12 * i;
+                let tag = self.u16_at(self.data, at)?;
+                let kind = self.u16_at(self.data, at + 2)?;
+                let count = self.u32_at(self.data, at + 4)?;
+                let size: usize = match kind {
+                    3 => 2,
+                    4 | 9 => 4,
+                    5 | 10 | 12 => 8,
+                    _ => 1,
+                };
+                let size = size
+                    .checked_mul(count as usize)
+                    .context("EXIF data is truncated")?;
+                let start = if size <= 4 { at + 8 } else { self.u32(at + 8)? };
+                let value = start
+                    .checked_add(size)
+                    .and_then(|end| self.data.get(start..end))
+                    .context("EXIF data is truncated")?;
+                Ok(Entry { tag, kind, count, value })
+            })
+            .collect()
+    }
+}
+
+/// Returns an ASCII value, without the NUL at its end or padding.
+fn ascii(value: &[u8]) -> String {
+    let value = value.split(|&b| b == 0).next().unwrap_or_default();
+    String::from_utf8_lossy(value).trim().to_string()
+}
+
+/// Parses a wall-clock time like "2024:03:01 12:00:00" with fraction
+/// `sub_sec` (the digits after the decimal point), or returns `None` if it's
+/// blank, as cameras write it when they don't know the time.
+fn wall_clock(
+    s: &str,
+    sub_sec: Option<&str>,
+) -> anyhow::Result<Option<NaiveDateTime>> {
+    if s.bytes().all(|b| matches!(b, b'0' | b':' | b' ')) {
+        return Ok(None);
+    }
+    let wall = NaiveDateTime::parse_from_str(s, "%Y:%m:%d %H:%M:%S")
+        .with_context(|| format!("{s:?} isn't an EXIF date and time"))?;
+    let digits = sub_sec.unwrap_or_default();
+    if !digits.bytes().all(|b| b.is_ascii_digit()) {
+        bail!("{digits:?} isn't a fraction of a second");
+    }
+    let mut nanos = 0;
+    let mut scale = 1_000_000_000;
+    for digit in digits.bytes().take(9) {
+        scale /= 10;
+        nanos += i64::from(digit - b'0') * scale;
+    }
+    Ok(Some(wall + Duration::nanoseconds(nanos)))
+}
+
+/// Parses an offset like "+09:00", returning it along with the text, or
+/// returns `None` if it's blank or malformed.
+fn parse_offset(s: &str) -> Option<(Duration, String)> {
+    let offset = DateTime::parse_from_str(
+        &format!("2000-01-01 00:00 {s}"),
+        "%Y-%m-%d %H:%M %:z",
+    )
+    .ok()?;
+    let offset: FixedOffset = *offset.offset();
+    let seconds = offset.local_minus_utc();
+    Some((Duration::seconds(i64::from(seconds)), s.to_string()))
+}
@@ -0,0 +1,85 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates min` and `dates max`: find the earliest or latest of several times
+//!
+//! Along with the earliest (or latest) time, these print how far each of the
+//! others is from it, numbered in the order they were given.  If several
+//! times are equally early, the first of them counts.
+
+use crate::{ReadOptions, Reader, print_delta, print_time};
+use anyhow::{Context, bail};
+use chrono::{DateTime, Utc};
+use dates_tool::context;
+use std::io::BufRead;
+
+/// Which extreme to find
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Extreme {
+    Min,
+    Max,
+}
+
+/// Entry point for `dates min [TIME...]` and `dates max [TIME...]`
+///
+/// With no arguments, times are read from stdin, one per line.  As for `dates
+/// timeline`, anything after the first whitespace on a line is ignored.
+/// Times are read as `dates TIME` reads them, with the same options.
+pub fn run(
+    args: &[String],
+    ctx: &context::Context,
+    extreme: Extreme,
+) -> anyhow::Result<()> {
+    let mut options = ReadOptions::default();
+    let mut inputs = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if !options.parse_option(arg, &mut args)? {
+            inputs.push(arg.as_str());
+        }
+    }
+    let reader = options.reader(ctx)?;
+    let times = if inputs.is_empty() {
+        read_times(&reader, std::io::stdin().lock())?
+    } else {
+        inputs.iter().map(|a| reader.parse(a)).collect::<Result<Vec<_>, _>>()?
+    };
+
+    let (label, found) = match extreme {
+        Extreme::Min => ("min", times.iter().enumerate().min_by_key(|t| t.1)),
+        Extreme::Max => (
+            "max",
+            // `max_by_key` would pick the last of several equal times.
+            times.iter().enumerate().rev().max_by_key(|t| t.1),
+        ),
+    };
+    let Some((i, &found)) = found else {
+        bail!("{label}: no timestamps given");
+    };
+
+    print_time(ctx, &format!("{label} ({})", i + 1), found);
+    for (j, &t) in times.iter().enumerate() {
+        if j != i {
+            print_delta(&format!("time {}", j + 1), t - found);
+        }
+    }
+    Ok(())
+}
+
+fn read_times(
+    reader: &Reader,
+    input: impl BufRead,
+) -> anyhow::Result<Vec<DateTime<Utc>>> {
+    let mut times = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        let line = line.context("reading stdin")?;
+        let Some(time) = line.split_whitespace().next() else {
+            continue;
+        };
+        let time =
+            reader.parse(time).with_context(|| format!("line {}", i + 1))?;
+        times.push(time);
+    }
+    Ok(times)
+}
@@ -0,0 +1,30 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates infer`: guess a `strftime` pattern from an example timestamp
+
+use crate::print_time;
+use anyhow::bail;
+use dates_tool::context;
+use dates_tool::infer::infer_format;
+use dates_tool::parser::{Strftime, TimestampParser};
+
+/// Entry point for `dates infer EXAMPLE`
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let [example] = args else {
+        bail!("infer: expected one example timestamp");
+    };
+
+    let inference = infer_format(example)?;
+    println!("{:<8} {}", "pattern", inference.pattern);
+    if let Ok(time) =
+        Strftime::new("inferred", &inference.pattern).parse(example)
+    {
+        print_time(ctx, "parsed", time);
+    }
+    for note in &inference.notes {
+        println!("{:<8} {}", "note", note);
+    }
+    Ok(())
+}
@@ -0,0 +1,89 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates journald`: times as journald and journalctl write and read them
+//!
+//! journald records the time of each entry in `__REALTIME_TIMESTAMP`, in
+//! microseconds since the Unix epoch, and the time since boot in
+//! `__MONOTONIC_TIMESTAMP`, also in microseconds.  `journalctl -o
+//! short-unix` prints the former as seconds with a fraction.  This prints a
+//! time in both forms, and as arguments to `journalctl --since` and
+//! `--until`, which read times like "2024-03-01 12:00:00 UTC".
+
+use crate::{ReadOptions, print_delta, print_time};
+use anyhow::bail;
+use chrono::{DateTime, Duration, Utc};
+use dates_tool::{Error, context};
+
+/// Entry point for `dates journald [TIME [TIME]]`
+///
+/// Each TIME is read as `dates TIME` reads times, with the same options.
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let mut options = ReadOptions::default();
+    let mut inputs = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if !options.parse_option(arg, &mut iter)? {
+            inputs.push(arg);
+        }
+    }
+    if inputs.len() > 2 {
+        bail!("journald: expected at most two times, for --since and --until");
+    }
+    let reader = options.reader(ctx)?;
+    let mut times = Vec::new();
+    for arg in inputs.iter().copied() {
+        // A time since boot can't be converted without knowing when the
+        // system booted.
+        if let Some(digits) = arg.strip_prefix("__MONOTONIC_TIMESTAMP=") {
+            let micros: i64 = digits.parse().map_err(|_| {
+                Error::unrecognized(arg, "a number of microseconds since boot")
+            })?;
+            print_delta("monotonic", Duration::microseconds(micros));
+            continue;
+        }
+        times.push(reader.parse(arg)?);
+    }
+    if inputs.is_empty() {
+        times.push(ctx.now());
+    }
+
+    for &t in &times {
+        print_time(ctx, "time", t);
+        println!(
+            "{:<8} __REALTIME_TIMESTAMP={}",
+            "realtime",
+            t.timestamp_micros()
+        );
+        println!(
+            "{:<8} {}.{:06} (short-unix)",
+            "unix",
+            t.timestamp(),
+            t.timestamp_subsec_micros().min(999_999)
+        );
+    }
+    match times[..] {
+        [since] => println!("{:<8} --since {}", "args", journalctl(since)),
+        [since, until] => println!(
+            "{:<8} --since {} --until {}",
+            "args",
+            journalctl(since),
+            journalctl(until)
+        ),
+        _ => (),
+    }
+    Ok(())
+}
+
+/// Formats `t` as an argument that journalctl reads, like
+/// "'2024-03-01 12:00:00.5 UTC'".  It's in UTC so that it means the same
+/// thing wherever journalctl runs.
+fn journalctl(t: DateTime<Utc>) -> String {
+    let micros = t.timestamp_subsec_micros().min(999_999);
+    let fraction = match micros {
+        0 => String::new(),
+        _ => format!(".{micros:06}").trim_end_matches('0').to_string(),
+    };
+    format!("'{}{fraction} UTC'", t.format("%Y-%m-%d %H:%M:%S"))
+}
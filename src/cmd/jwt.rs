@@ -0,0 +1,79 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates jwt`: the times in a JSON Web Token
+//!
+//! A JWT's payload may have three claims about time (RFC 7519), each in
+//! seconds since the Unix epoch: `iat`, when it was issued; `nbf`, before
+//! which it must not be accepted; and `exp`, when it expires.  This prints
+//! each of them and how far the current time is from the window in which the
+//! token is valid.  The signature isn't checked, since only the times matter
+//! here.
+
+use crate::cmd::encoding::decode_base64;
+use crate::{print_delta, print_time};
+use anyhow::{Context, bail};
+use chrono::{DateTime, Utc};
+use dates_tool::{Error, context};
+
+/// The time claims, in the order they're printed
+const CLAIMS: [(&str, &str); 3] =
+    [("iat", "issued"), ("nbf", "not before"), ("exp", "expires")];
+
+/// Entry point for `dates jwt TOKEN`
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let [token] = args else {
+        bail!("jwt: expected one token");
+    };
+    let parts: Vec<_> = token.trim().split('.').collect();
+    if parts.len() != 3 {
+        return Err(Error::unrecognized(
+            token,
+            "a JSON Web Token (three parts separated by \".\")",
+        )
+        .into());
+    }
+    let start = parts[0].len() + 1;
+    let payload = decode_base64(parts[1])
+        .map_err(|e| e.within(token, start))
+        .context("jwt: decoding the payload")?;
+    let claims: serde_json::Value = serde_json::from_slice(&payload)
+        .context("jwt: the payload isn't JSON")?;
+
+    let now = ctx.now();
+    print_time(ctx, "now", now);
+    let mut times = [None; 3];
+    for (i, (claim, meaning)) in CLAIMS.iter().enumerate() {
+        let Some(value) = claims.get(claim) else {
+            continue;
+        };
+        let time = numeric_date(value).with_context(|| {
+            format!("jwt: \"{claim}\" ({meaning}) isn't a time: {value}")
+        })?;
+        print_time(ctx, claim, time);
+        times[i] = Some(time);
+    }
+
+    let [_, nbf, exp] = times;
+    match (nbf, exp) {
+        (Some(nbf), _) if now < nbf => print_delta("early", nbf - now),
+        (_, Some(exp)) if now >= exp => print_delta("expired", now - exp),
+        (_, Some(exp)) => print_delta("left", exp - now),
+        _ => println!("{:<8} never expires", "left"),
+    }
+    Ok(())
+}
+
+/// Converts a JWT "NumericDate", which is a number of seconds since the
+/// Unix epoch that may have a fraction.
+fn numeric_date(value: &serde_json::Value) -> Option<DateTime<Utc>> {
+    if let Some(seconds) = value.as_i64() {
+        return DateTime::from_timestamp(seconds, 0);
+    }
+    let millis = value.as_f64()? * 1000.0;
+    if !millis.is_finite() || millis.abs() >= i64::MAX as f64 {
+        return None;
+    }
+    DateTime::from_timestamp_millis(millis.round() as i64)
+}
@@ -0,0 +1,29 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates ksuid`: when a KSUID was generated
+//!
+//! See [`dates_tool::ksuid`] for the details.
+
+use crate::print_time;
+use anyhow::bail;
+use dates_tool::context;
+use dates_tool::ksuid::Ksuid;
+
+/// Entry point for `dates ksuid KSUID...`
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    if args.is_empty() {
+        bail!("ksuid: expected a KSUID");
+    }
+    for (i, arg) in args.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        let ksuid = Ksuid::parse(arg)?;
+        println!("{:<8} {}", "ksuid", arg);
+        print_time(ctx, "time", ksuid.timestamp());
+        println!("{:<8} {:032x}", "payload", ksuid.payload());
+    }
+    Ok(())
+}
@@ -0,0 +1,83 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates leap-seconds`: print the table of leap seconds and TAI - UTC
+
+use crate::{ReadOptions, UsageError, print_delta, print_time};
+use anyhow::{Context, bail};
+use chrono::SecondsFormat;
+use dates_tool::context;
+use dates_tool::leap::LeapSeconds;
+
+/// Entry point for `dates leap-seconds [--file PATH] [TIME]`
+///
+/// With `--file`, the table is read from a `leap-seconds.list` file (as
+/// published by the IERS) instead of the one built into `dates`.  TIME is
+/// read as `dates TIME` reads times, with the same options.
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let mut options = ReadOptions::default();
+    let mut table = LeapSeconds::builtin();
+    let mut input = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--file" => {
+                let path = args.next().with_context(|| {
+                    UsageError::new("leap-seconds: --file requires an argument")
+                })?;
+                let text =
+                    std::fs::read_to_string(path).with_context(|| {
+                        format!("leap-seconds: reading {path:?}")
+                    })?;
+                table = LeapSeconds::parse_list(&text)?;
+            }
+            _ if options.parse_option(arg, &mut args)? => (),
+            _ if input.is_none() => input = Some(arg.as_str()),
+            _ => bail!(UsageError::new(format!(
+                "leap-seconds: unexpected argument {arg:?}"
+            ))),
+        }
+    }
+
+    let reader = options.reader(ctx)?;
+    let time = match input {
+        Some(s) => reader.parse(s)?,
+        None => ctx.now(),
+    };
+    let offset = table.offset_at(time)?;
+    let tai = table.utc_to_tai(time)?;
+    let gps = table.utc_to_gps(time)?;
+
+    for (start, offset) in table.entries() {
+        println!(
+            "{:<8} {}  TAI - UTC = {}s",
+            "since",
+            start.date_naive(),
+            offset
+        );
+    }
+    match table.expires() {
+        Some(expires) if expires < time => println!(
+            "{:<8} {} (a leap second since then would be missing)",
+            "expired",
+            expires.date_naive()
+        ),
+        Some(expires) => {
+            println!("{:<8} {}", "expires", expires.date_naive())
+        }
+        None => (),
+    }
+    print_time(ctx, "time", time);
+    println!("{:<8} {}s", "tai-utc", offset);
+    println!(
+        "{:<8} {} TAI",
+        "tai",
+        tai.and_utc()
+            .to_rfc3339_opts(SecondsFormat::Micros, true)
+            .trim_end_matches('Z')
+    );
+    print_delta("gps", gps);
+    Ok(())
+}
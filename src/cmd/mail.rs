@@ -0,0 +1,148 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates mail`: how long an email message took to be delivered
+//!
+//! Each server that relays a message adds a `Received:` header to the top,
+//! ending with when it received the message, like "Received: from
+//! mail.example.com by mx.example.net; Fri, 1 Mar 2024 12:00:02 +0000".  The
+//! `Date:` header says when the message was written (or sent).  Reading them
+//! from the bottom up gives the message's path, and the differences between
+//! them how long each hop took.  Each time comes from a different server's
+//! clock, so a hop can appear to take negative time.
+
+use crate::{print_delta, print_time};
+use anyhow::{Context, bail};
+use chrono::{DateTime, Utc};
+use dates_tool::context;
+use std::io::Read;
+
+/// Entry point for `dates mail [FILE]`
+///
+/// The message is read from FILE, or from stdin.  Only its headers are used.
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let mut text = Vec::new();
+    match args {
+        [] => std::io::stdin()
+            .lock()
+            .read_to_end(&mut text)
+            .context("mail: reading stdin")?,
+        [path] => std::fs::File::open(path)
+            .and_then(|mut f| f.read_to_end(&mut text))
+            .with_context(|| format!("mail: reading {path:?}"))?,
+        _ => bail!("mail: expected at most one file"),
+    };
+    let text = String::from_utf8_lossy(&text);
+
+    let mut sent = None;
+    let mut hops = Vec::new();
+    for (name, value) in headers(&text) {
+        if name.eq_ignore_ascii_case("date") {
+            sent = Some(mail_date(&value).with_context(|| {
+                format!("mail: unrecognized Date header {value:?}")
+            })?);
+        } else if name.eq_ignore_ascii_case("received") {
+            match Hop::parse(&value) {
+                Some(hop) => hops.push(hop),
+                None => eprintln!(
+                    "dates: warning: ignoring a Received header without a \
+                     date: {value:?}"
+                ),
+            }
+        }
+    }
+    if sent.is_none() && hops.is_empty() {
+        bail!("mail: no Date or Received headers");
+    }
+
+    // The last server to receive the message put its header first.
+    hops.reverse();
+    let mut previous = None;
+    if let Some(sent) = sent {
+        print_time(ctx, "date", sent);
+        previous = Some(sent);
+    }
+    for (i, hop) in hops.iter().enumerate() {
+        if previous.is_some() {
+            println!();
+        }
+        println!("{:<8} {}", format!("hop {}", i + 1), hop.route());
+        print_time(ctx, "received", hop.time);
+        if let Some(previous) = previous {
+            print_delta("delay", hop.time - previous);
+        }
+        previous = Some(hop.time);
+    }
+    let first = sent.or(hops.first().map(|h| h.time));
+    if let (Some(first), Some(last)) = (first, hops.last()) {
+        println!();
+        print_delta("total", last.time - first);
+    }
+    Ok(())
+}
+
+/// A `Received:` header: who received the message, from whom, and when
+struct Hop {
+    from: Option<String>,
+    by: Option<String>,
+    time: DateTime<Utc>,
+}
+
+impl Hop {
+    /// Parses the value of a `Received:` header, returning `None` if it has
+    /// no date.  The date follows the last semicolon.
+    fn parse(value: &str) -> Option<Hop> {
+        let (route, date) = value.rsplit_once(';')?;
+        let time = mail_date(date)?;
+        let words: Vec<_> = route.split_whitespace().collect();
+        let after = |keyword: &str| {
+            let i =
+                words.iter().position(|w| w.eq_ignore_ascii_case(keyword))?;
+            words.get(i + 1).map(|w| w.to_string())
+        };
+        Some(Hop { from: after("from"), by: after("by"), time })
+    }
+
+    /// Describes the hop, like "from mail.example.com by mx.example.net"
+    fn route(&self) -> String {
+        match (&self.from, &self.by) {
+            (Some(from), Some(by)) => format!("from {from} by {by}"),
+            (Some(from), None) => format!("from {from}"),
+            (None, Some(by)) => format!("by {by}"),
+            (None, None) => String::from("(no hosts given)"),
+        }
+    }
+}
+
+/// Parses an email date, like "Fri, 1 Mar 2024 12:00:02 +0000 (UTC)"
+fn mail_date(s: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(s.trim()).ok().map(|t| t.to_utc())
+}
+
+/// Returns the names and values of the headers of a message, with folded
+/// lines joined.  The headers end at the first empty line.
+fn headers(text: &str) -> Vec<(&str, String)> {
+    let mut headers: Vec<(&str, String)> = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        if line.is_empty() {
+            break;
+        }
+        // An mbox file starts each message with a line like "From sender
+        // date", which isn't a header.
+        if i == 0 && line.starts_with("From ") {
+            continue;
+        }
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+            continue;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim(), value.trim().to_string()));
+        }
+    }
+    headers
+}
@@ -0,0 +1,57 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates midpoint`: find the instant a given fraction of the way between two
+//! times
+//!
+//! The fraction defaults to 0.5.  It may be outside 0 to 1, to extrapolate
+//! before the first time or after the second.
+
+use crate::{ReadOptions, UsageError, print_delta, print_time, scale_delta};
+use anyhow::{Context, bail};
+use dates_tool::{Error, context};
+
+/// Entry point for `dates midpoint [--fraction F] T1 T2`
+///
+/// T1 and T2 are read as `dates TIME` reads times, with the same options.
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let mut options = ReadOptions::default();
+    let mut fraction = None;
+    let mut times = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--fraction" => {
+                let value = iter.next().with_context(|| {
+                    UsageError::new("--fraction requires an argument")
+                })?;
+                let f: f64 = value
+                    .parse()
+                    .ok()
+                    .filter(|f: &f64| f.is_finite())
+                    .ok_or_else(|| Error::unrecognized(value, "a number"))?;
+                fraction = Some((value.as_str(), f));
+            }
+            _ if options.parse_option(arg, &mut iter)? => (),
+            _ => times.push(arg.as_str()),
+        }
+    }
+    let [t1, t2] = times[..] else {
+        bail!("midpoint: expected two times");
+    };
+    let reader = options.reader(ctx)?;
+    let (t1, t2) = (reader.parse(t1)?, reader.parse(t2)?);
+
+    let (input, f) = fraction.unwrap_or(("0.5", 0.5));
+    let offset = scale_delta(input, t2 - t1, f)?;
+    let point = t1.checked_add_signed(offset).ok_or_else(|| {
+        Error::out_of_range(input, "the result is past the supported range")
+    })?;
+    print_time(ctx, "time 1", t1);
+    print_time(ctx, "time 2", t2);
+    print_delta("offset", offset);
+    let label = if fraction.is_some() { "point" } else { "midpoint" };
+    print_time(ctx, label, point);
+    Ok(())
+}
@@ -0,0 +1,198 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates msgpack`: the times in MessagePack data
+//!
+//! MessagePack represents times with extension type -1, in one of three
+//! sizes: "timestamp 32" is 32 bits of seconds since the Unix epoch;
+//! "timestamp 64" is 30 bits of nanoseconds followed by 34 bits of seconds;
+//! and "timestamp 96" is 32 bits of nanoseconds followed by 64 bits of
+//! signed seconds.  This walks MessagePack data, given in hexadecimal or
+//! base64, and prints each timestamp in it, along with where in the data it
+//! was found.
+
+use crate::cmd::encoding::decode_bytes;
+use crate::print_time;
+use anyhow::{Context, bail};
+use chrono::{DateTime, Utc};
+use dates_tool::context;
+
+/// How deeply arrays and maps may nest, so that malicious input can't
+/// overflow the stack
+const MAX_DEPTH: usize = 128;
+
+/// The extension type of timestamps
+const TIMESTAMP: u8 = 0xff;
+
+/// Entry point for `dates msgpack DATA...`
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    if args.is_empty() {
+        bail!("msgpack: expected MessagePack data in hexadecimal or base64");
+    }
+    for (i, arg) in args.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        let data = decode_bytes(arg).context("msgpack: reading the data")?;
+        let mut rest = &data[..];
+        let mut times = Vec::new();
+        item(&mut rest, "", 0, &mut times)
+            .with_context(|| format!("msgpack: decoding {arg:?}"))?;
+        if !rest.is_empty() {
+            bail!("msgpack: {} bytes follow the object in {arg:?}", rest.len());
+        }
+        if times.is_empty() {
+            bail!("msgpack: no timestamps in {arg:?}");
+        }
+        for (j, (path, bits, time)) in times.into_iter().enumerate() {
+            if j > 0 {
+                println!();
+            }
+            if !path.is_empty() {
+                println!("{:<8} {}", "at", path);
+            }
+            print_time(ctx, &format!("ts {bits}"), time);
+        }
+    }
+    Ok(())
+}
+
+/// What the walk needs to know about an object: enough to describe a map
+/// key
+enum Object {
+    Integer(i128),
+    Text(String),
+    Other,
+}
+
+/// Decodes the object at the start of `data`, which is at `path` within the
+/// top-level object, advancing `data` past it.  Each timestamp in it is
+/// appended to `times` with its path and size in bits.
+fn item(
+    data: &mut &[u8],
+    path: &str,
+    depth: usize,
+    times: &mut Vec<(String, usize, DateTime<Utc>)>,
+) -> anyhow::Result<Object> {
+    if depth > MAX_DEPTH {
+        bail!("objects are nested too deeply");
+    }
+    let byte = take(data, 1)?[0];
+    let (elements, pairs) = match byte {
+        0x00..=0x7f => return Ok(Object::Integer(i128::from(byte))),
+        0xe0..=0xff => return Ok(Object::Integer(i128::from(byte as i8))),
+        0x80..=0x8f => (0, usize::from(byte & 0x0f)),
+        0x90..=0x9f => (usize::from(byte & 0x0f), 0),
+        0xa0..=0xbf => return text(data, usize::from(byte & 0x1f)),
+        0xc0 | 0xc2 | 0xc3 => return Ok(Object::Other),
+        0xc1 => bail!("0xc1 is never used"),
+        0xc4..=0xc6 => {
+            let len = length(data, 1 << (byte - 0xc4))?;
+            take(data, len)?;
+            return Ok(Object::Other);
+        }
+        0xc7..=0xc9 => {
+            let len = length(data, 1 << (byte - 0xc7))?;
+            return ext(data, len, path, times);
+        }
+        0xca => {
+            take(data, 4)?;
+            return Ok(Object::Other);
+        }
+        0xcb => {
+            take(data, 8)?;
+            return Ok(Object::Other);
+        }
+        0xcc..=0xcf => {
+            let n = uint(data, 1 << (byte - 0xcc))?;
+            return Ok(Object::Integer(i128::from(n)));
+        }
+        0xd0..=0xd3 => {
+            let size = 1 << (byte - 0xd0);
+            let n = uint(data, size)?;
+            // Sign-extend from `size` bytes.
+            let shift = 64 - 8 * size;
+            return Ok(Object::Integer(i128::from(
+                (n << shift) as i64 >> shift,
+            )));
+        }
+        0xd4..=0xd8 => return ext(data, 1 << (byte - 0xd4), path, times),
+        0xd9..=0xdb => {
+            let len = length(data, 1 << (byte - 0xd9))?;
+            return text(data, len);
+        }
+        0xdc | 0xdd => (length(data, 2 << (byte - 0xdc))?, 0),
+        0xde | 0xdf => (0, length(data, 2 << (byte - 0xde))?),
+    };
+    for i in 0..elements {
+        item(data, &format!("{path}/{i}"), depth + 1, times)?;
+    }
+    for _ in 0..pairs {
+        let key = match item(data, path, depth + 1, times)? {
+            Object::Integer(n) => n.to_string(),
+            Object::Text(s) => s,
+            Object::Other => String::from("?"),
+        };
+        item(data, &format!("{path}/{key}"), depth + 1, times)?;
+    }
+    Ok(Object::Other)
+}
+
+/// Reads an extension object of `len` bytes after its type, recording it in
+/// `times` if it's a timestamp.
+fn ext(
+    data: &mut &[u8],
+    len: usize,
+    path: &str,
+    times: &mut Vec<(String, usize, DateTime<Utc>)>,
+) -> anyhow::Result<Object> {
+    let kind = take(data, 1)?[0];
+    let payload = take(data, len)?;
+    if kind != TIMESTAMP {
+        return Ok(Object::Other);
+    }
+    let n = |bytes: &[u8]| bytes.iter().fold(0, |n, &b| n << 8 | u64::from(b));
+    let (seconds, nanos) = match len {
+        4 => (n(payload) as i64, 0),
+        8 => {
+            let n = n(payload);
+            ((n & 0x3_ffff_ffff) as i64, n >> 34)
+        }
+        12 => (n(&payload[4..]) as i64, n(&payload[..4])),
+        _ => bail!("a timestamp must be 4, 8, or 12 bytes, not {len}"),
+    };
+    if nanos >= 1_000_000_000 {
+        bail!("a timestamp has {nanos} nanoseconds, which is over a second");
+    }
+    let time = DateTime::from_timestamp(seconds, nanos as u32)
+        .context("a timestamp is out of range")?;
+    times.push((path.to_string(), len * 8, time));
+    Ok(Object::Other)
+}
+
+/// Reads a string of `len` bytes.
+fn text(data: &mut &[u8], len: usize) -> anyhow::Result<Object> {
+    let bytes = take(data, len)?.to_vec();
+    Ok(Object::Text(String::from_utf8(bytes).context("invalid UTF-8")?))
+}
+
+/// Reads a big-endian unsigned integer of `size` bytes.
+fn uint(data: &mut &[u8], size: usize) -> anyhow::Result<u64> {
+    Ok(take(data, size)?.iter().fold(0, |n, &b| n << 8 | u64::from(b)))
+}
+
+/// Reads a length, as a big-endian unsigned integer of `size` bytes.
+fn length(data: &mut &[u8], size: usize) -> anyhow::Result<usize> {
+    usize::try_from(uint(data, size)?).context("a length is too large")
+}
+
+/// Reads `n` bytes.
+fn take<'a>(data: &mut &'a [u8], n: usize) -> anyhow::Result<&'a [u8]> {
+    if data.len() < n {
+        bail!("the data ends in the middle of an object");
+    }
+    let (taken, rest) = data.split_at(n);
+    *data = rest;
+    Ok(taken)
+}
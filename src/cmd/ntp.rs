@@ -0,0 +1,131 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates ntp`: how far the local clock is from an NTP server's
+//!
+//! This sends one SNTP request (RFC 4330) and compares the server's
+//! timestamps with the local clock's, as `ntpdate -q` does.  With T1 and T4
+//! the local times the request was sent and the response received, and T2
+//! and T3 the server's times it received the request and sent the response,
+//! the local clock's offset from the server's is ((T2 - T1) + (T3 - T4)) / 2,
+//! and the round-trip delay is (T4 - T1) - (T3 - T2).  The offset is only
+//! accurate to within half the delay.
+//!
+//! This is only built with the "ntp" feature, since it uses the network.
+
+use crate::{print_delta, print_time};
+use anyhow::{Context, bail};
+use chrono::{DateTime, Duration, Utc};
+use dates_tool::context;
+use std::net::{ToSocketAddrs, UdpSocket};
+
+/// The server queried by default
+const DEFAULT_SERVER: &str = "pool.ntp.org";
+
+/// How long to wait for a response
+const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// The seconds from the NTP epoch (1900-01-01T00:00:00Z) to the Unix epoch
+const NTP_UNIX_OFFSET: i64 = 2_208_988_800;
+
+/// Entry point for `dates ntp [SERVER]`
+///
+/// SERVER is a host name or address, optionally with a port (by default,
+/// 123).
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let server = match args {
+        [] => DEFAULT_SERVER,
+        [server] => server.as_str(),
+        _ => bail!("ntp: expected at most one server"),
+    };
+    // Without a port, the address is the host alone.
+    let address = server
+        .to_socket_addrs()
+        .or_else(|_| (server, 123).to_socket_addrs())
+        .with_context(|| format!("ntp: resolving {server:?}"))?
+        .next()
+        .with_context(|| format!("ntp: {server:?} has no addresses"))?;
+
+    let bind = if address.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+    let socket = UdpSocket::bind(bind).context("ntp: opening a socket")?;
+    socket.set_read_timeout(Some(TIMEOUT))?;
+    socket
+        .connect(address)
+        .with_context(|| format!("ntp: connecting to {address}"))?;
+
+    // The request is a version 4 client packet, empty except for its
+    // transmit timestamp, which the server echoes back so that responses to
+    // other requests can be told apart.
+    let mut request = [0u8; 48];
+    request[0] = 4 << 3 | 3;
+    let t1 = Utc::now();
+    let sent = to_ntp(t1);
+    request[40..48].copy_from_slice(&sent.to_be_bytes());
+    socket
+        .send(&request)
+        .with_context(|| format!("ntp: sending to {address}"))?;
+    let mut response = [0u8; 48];
+    let length = socket
+        .recv(&mut response)
+        .with_context(|| format!("ntp: no response from {address}"))?;
+    let t4 = Utc::now();
+
+    if length < 48 {
+        bail!("ntp: response from {address} is too short ({length} bytes)");
+    }
+    let leap = response[0] >> 6;
+    let mode = response[0] & 7;
+    let stratum = response[1];
+    if mode != 4 {
+        bail!("ntp: response from {address} isn't from a server (mode {mode})");
+    }
+    if stratum == 0 {
+        // A "kiss-o'-death" packet, whose reference ID says why.
+        let code = String::from_utf8_lossy(&response[12..16]);
+        bail!("ntp: {address} refused the request ({code})");
+    }
+    if leap == 3 {
+        bail!("ntp: {address}'s clock isn't synchronized");
+    }
+    let timestamp =
+        |i: usize| u64::from_be_bytes(response[i..i + 8].try_into().unwrap());
+    if timestamp(24) != sent {
+        bail!("ntp: response from {address} isn't to this request");
+    }
+    let t2 = from_ntp(timestamp(32))?;
+    let t3 = from_ntp(timestamp(40))?;
+
+    let offset = ((t2 - t1) + (t3 - t4)) / 2;
+    let delay = (t4 - t1) - (t3 - t2);
+    println!("{:<8} {server} ({address}), stratum {stratum}", "server");
+    print_time(ctx, "time", t4 + offset);
+    print_time(ctx, "local", t4);
+    print_delta("offset", offset);
+    print_delta("delay", delay);
+    Ok(())
+}
+
+/// Returns the 64-bit NTP timestamp for `t`: seconds since 1900 in the high
+/// 32 bits (wrapping every 136 years), and the fraction in the low 32.
+fn to_ntp(t: DateTime<Utc>) -> u64 {
+    let seconds = (t.timestamp() + NTP_UNIX_OFFSET) as u64 & 0xffff_ffff;
+    let fraction =
+        (u64::from(t.timestamp_subsec_nanos()) << 32) / 1_000_000_000;
+    seconds << 32 | fraction
+}
+
+/// Returns the time of a 64-bit NTP timestamp.
+///
+/// The seconds wrap in 2036, so as RFC 4330 suggests, a timestamp whose top
+/// bit is clear is taken to be after that.
+fn from_ntp(timestamp: u64) -> anyhow::Result<DateTime<Utc>> {
+    let mut seconds = (timestamp >> 32) as i64 - NTP_UNIX_OFFSET;
+    if timestamp >> 63 == 0 {
+        seconds += 1 << 32;
+    }
+    let nanos = ((timestamp & 0xffff_ffff) * 1_000_000_000) >> 32;
+    DateTime::from_timestamp(seconds, 0)
+        .map(|t| t + Duration::nanoseconds(nanos as i64))
+        .context("ntp: server's time is out of range")
+}
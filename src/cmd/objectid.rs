@@ -0,0 +1,68 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates objectid`: when a MongoDB ObjectId was generated, or the range of
+//! ObjectIds for a time
+//!
+//! See [`dates_tool::objectid`] for the details.
+
+use crate::{ReadOptions, print_time};
+use anyhow::bail;
+use dates_tool::Error;
+use dates_tool::context;
+use dates_tool::objectid::ObjectId;
+
+/// Entry point for `dates objectid ID...` and `dates objectid --encode
+/// [TIME]`
+///
+/// TIME is read as `dates TIME` reads times, with the same options.
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let mut options = ReadOptions::default();
+    let mut encode = false;
+    let mut inputs = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--encode" => encode = true,
+            _ if options.parse_option(arg, &mut iter)? => (),
+            _ => inputs.push(arg),
+        }
+    }
+
+    if encode {
+        let reader = options.reader(ctx)?;
+        let (input, time) = match inputs.as_slice() {
+            [] => (ctx.now().to_rfc3339(), ctx.now()),
+            [input] => (input.to_string(), reader.parse(input)?),
+            _ => bail!("objectid: --encode takes at most one time"),
+        };
+        let (Some(min), Some(max)) = (ObjectId::min(time), ObjectId::max(time))
+        else {
+            return Err(Error::out_of_range(
+                &input,
+                "an ObjectId can only hold times from 1970 to 2106",
+            )
+            .into());
+        };
+        print_time(ctx, "time", min.timestamp());
+        println!("{:<8} {}", "min", min);
+        println!("{:<8} {}", "max", max);
+        return Ok(());
+    }
+
+    if inputs.is_empty() {
+        bail!("objectid: expected an ObjectId");
+    }
+    for (i, arg) in inputs.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        let id = ObjectId::parse(arg)?;
+        println!("{:<8} {}", "objectid", id);
+        print_time(ctx, "time", id.timestamp());
+        println!("{:<8} {:010x}", "random", id.random());
+        println!("{:<8} {}", "counter", id.counter());
+    }
+    Ok(())
+}
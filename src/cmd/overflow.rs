@@ -0,0 +1,163 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates overflow`: check whether a time fits in fixed-width integers
+//!
+//! Many formats and protocols store a Unix time in a 32-bit integer, which
+//! runs out in 2038 if it's signed (the "year 2038 problem") or in 2106 if
+//! it's unsigned.  A value that doesn't fit is usually truncated to its low
+//! bits, so for each integer type this also shows what the truncated value
+//! decodes to.
+
+use crate::{ReadOptions, UsageError, print_delta, print_time};
+use anyhow::{Context, bail};
+use chrono::{DateTime, SecondsFormat, Utc};
+use dates_tool::context;
+use dates_tool::parser::EpochUnit;
+use std::fmt;
+
+/// Entry point for `dates overflow [--width BITS] [--signed | --unsigned]
+/// [--unit s|ms|us|ns] [TIME]`
+///
+/// TIME is read as `dates TIME` reads times, with the same options.
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let mut options = ReadOptions::default();
+    let mut widths = vec![32, 64];
+    let mut signs = vec![true, false];
+    let mut unit = EpochUnit::Seconds;
+    let mut input = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--width" => {
+                let value = args.next().with_context(|| {
+                    UsageError::new("overflow: --width requires an argument")
+                })?;
+                match value.parse() {
+                    Ok(bits @ 1..=64) => widths = vec![bits],
+                    _ => bail!("overflow: --width must be between 1 and 64"),
+                }
+            }
+            "--signed" => signs = vec![true],
+            "--unsigned" => signs = vec![false],
+            "--unit" => {
+                let value = args.next().with_context(|| {
+                    UsageError::new("overflow: --unit requires an argument")
+                })?;
+                unit = EpochUnit::parse(value)?;
+            }
+            _ if options.parse_option(arg, &mut args)? => (),
+            _ if input.is_none() => input = Some(arg.as_str()),
+            _ => bail!(UsageError::new(format!(
+                "overflow: unexpected argument {arg:?}"
+            ))),
+        }
+    }
+
+    let reader = options.reader(ctx)?;
+    let time = match input {
+        Some(s) => reader.parse(s)?,
+        None => ctx.now(),
+    };
+    let value = count(unit, time);
+    print_time(ctx, "time", time);
+    for &bits in &widths {
+        for &signed in &signs {
+            report(ctx, Int { bits, signed }, unit, time, value);
+        }
+    }
+    Ok(())
+}
+
+/// Prints whether `value`, the count of `unit` since the epoch at `time`,
+/// fits in `int`, and what it would be truncated to if not.
+fn report(
+    ctx: &context::Context,
+    int: Int,
+    unit: EpochUnit,
+    time: DateTime<Utc>,
+    value: i128,
+) {
+    let show = |count| match time_of(unit, count) {
+        Some(t) => t.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        None => String::from("beyond any supported time"),
+    };
+    let range = match (time_of(unit, int.min()), time_of(unit, int.max())) {
+        (None, None) => String::from("holds any supported time"),
+        _ => format!("{} to {}", show(int.min()), show(int.max())),
+    };
+    println!(
+        "{:<8} {} (in {} since 1970)",
+        int.to_string(),
+        range,
+        unit.name()
+    );
+
+    if (int.min()..=int.max()).contains(&value) {
+        println!("{:<8} fits, as {}", "", value);
+        if let Some(end) = time_of(unit, int.max() + 1) {
+            print_delta("left", end - time);
+        }
+        return;
+    }
+
+    let wrapped = int.wrap(value);
+    println!(
+        "{:<8} doesn't fit; truncated to {} bits, it's {}",
+        "", int.bits, wrapped
+    );
+    match time_of(unit, wrapped) {
+        Some(t) => print_time(ctx, "wrapped", t),
+        None => println!("{:<8} beyond any supported time", "wrapped"),
+    }
+}
+
+/// An integer type, like `i32`
+#[derive(Clone, Copy)]
+struct Int {
+    bits: u32,
+    signed: bool,
+}
+
+impl Int {
+    fn min(self) -> i128 {
+        if self.signed { -(1 << (self.bits - 1)) } else { 0 }
+    }
+
+    fn max(self) -> i128 {
+        if self.signed {
+            (1 << (self.bits - 1)) - 1
+        } else {
+            (1 << self.bits) - 1
+        }
+    }
+
+    /// Returns the value of the low `bits` bits of `value` in this type.
+    fn wrap(self, value: i128) -> i128 {
+        let modulus = 1 << self.bits;
+        let low = value.rem_euclid(modulus);
+        if low > self.max() { low - modulus } else { low }
+    }
+}
+
+impl fmt::Display for Int {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", if self.signed { "i" } else { "u" }, self.bits)
+    }
+}
+
+/// Returns the number of whole `unit`s from the epoch to `t`, rounding down.
+fn count(unit: EpochUnit, t: DateTime<Utc>) -> i128 {
+    let nanos = i128::from(t.timestamp()) * 1_000_000_000
+        + i128::from(t.timestamp_subsec_nanos());
+    nanos.div_euclid(unit.nanos())
+}
+
+/// Returns the time `count` `unit`s after the epoch, if it's supported.
+fn time_of(unit: EpochUnit, count: i128) -> Option<DateTime<Utc>> {
+    let nanos = count.checked_mul(unit.nanos())?;
+    let secs = i64::try_from(nanos.div_euclid(1_000_000_000)).ok()?;
+    DateTime::from_timestamp(secs, nanos.rem_euclid(1_000_000_000) as u32)
+}
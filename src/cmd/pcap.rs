@@ -0,0 +1,357 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates pcap`: when the packets in a capture file were captured
+//!
+//! This reads a capture file in the classic pcap format (with microsecond or
+//! nanosecond timestamps) or in pcapng (with whatever resolution each
+//! interface has), and prints when the first and last packets were captured
+//! and how long the capture is.  With `--packets`, it also prints when each
+//! packet was captured, relative to the first, and with `--deltas`, the
+//! smallest, mean, median, and largest gaps between consecutive packets.
+//! Only the timestamps are read, not the packets themselves.
+
+use crate::{UsageError, print_delta, print_time};
+use anyhow::{Context, bail};
+use chrono::{DateTime, Duration, Utc};
+use dates_tool::context;
+use std::io::{BufReader, Read};
+
+/// The magic numbers of classic pcap files, as read in their own byte order
+const PCAP_MICROS: u32 = 0xa1b2_c3d4;
+const PCAP_NANOS: u32 = 0xa1b2_3c4d;
+
+/// The block types of pcapng files
+const SECTION_HEADER: u32 = 0x0a0d_0d0a;
+const INTERFACE_DESCRIPTION: u32 = 1;
+const PACKET: u32 = 2;
+const ENHANCED_PACKET: u32 = 6;
+
+/// The byte-order magic number of a pcapng section
+const BYTE_ORDER: u32 = 0x1a2b_3c4d;
+
+/// The options of an interface description block
+const IF_TSRESOL: u16 = 9;
+const IF_TSOFFSET: u16 = 14;
+
+/// Entry point for `dates pcap [--packets] [--deltas] FILE`
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let mut packets = false;
+    let mut deltas = false;
+    let mut path = None;
+    for arg in args {
+        match arg.as_str() {
+            "--packets" => packets = true,
+            "--deltas" => deltas = true,
+            _ if path.is_none() => path = Some(arg.as_str()),
+            _ => bail!(UsageError::new(format!(
+                "pcap: unexpected argument {arg:?}"
+            ))),
+        }
+    }
+    let Some(path) = path else {
+        bail!("pcap: expected a capture file (or \"-\" for stdin)");
+    };
+
+    let times = if path == "-" {
+        read_times(BufReader::new(std::io::stdin().lock()))
+    } else {
+        let file = std::fs::File::open(path)
+            .with_context(|| format!("pcap: opening {path:?}"))?;
+        read_times(BufReader::new(file))
+    }
+    .with_context(|| format!("pcap: reading {path:?}"))?;
+    let (Some(&first), Some(&last)) = (times.iter().min(), times.iter().max())
+    else {
+        bail!("pcap: no packets in {path:?}");
+    };
+
+    if packets {
+        for (i, &t) in times.iter().enumerate() {
+            print_delta(&format!("packet {}", i + 1), t - times[0]);
+        }
+        println!();
+    }
+    println!("{:<8} {}", "packets", times.len());
+    print_time(ctx, "first", first);
+    print_time(ctx, "last", last);
+    print_delta("length", last - first);
+    // Packets captured on different interfaces, or with a clock that was
+    // stepped backward, may not be in order.
+    let gaps: Vec<Duration> = times.windows(2).map(|w| w[1] - w[0]).collect();
+    let backward = gaps.iter().filter(|&&gap| gap < Duration::zero()).count();
+    if backward > 0 {
+        println!(
+            "{:<8} {backward} of {} packets are out of order",
+            "order",
+            times.len()
+        );
+    }
+    if deltas && !gaps.is_empty() {
+        let mut sorted = gaps.clone();
+        sorted.sort();
+        let nanos = |gap: &Duration| {
+            i128::from(gap.num_seconds()) * 1_000_000_000
+                + i128::from(gap.subsec_nanos())
+        };
+        let duration = |nanos: i128| {
+            Duration::seconds((nanos / 1_000_000_000) as i64)
+                + Duration::nanoseconds((nanos % 1_000_000_000) as i64)
+        };
+        let total: i128 = gaps.iter().map(nanos).sum();
+        let mean = duration(total / gaps.len() as i128);
+        // With an even number of gaps, the median is the mean of the two in
+        // the middle.
+        let middle = sorted.len() / 2;
+        let median = if sorted.len().is_multiple_of(2) {
+            duration((nanos(&sorted[middle - 1]) + nanos(&sorted[middle])) / 2)
+        } else {
+            sorted[middle]
+        };
+        print_delta("min gap", sorted[0]);
+        print_delta("mean gap", mean);
+        print_delta("med gap", median);
+        print_delta("max gap", sorted[sorted.len() - 1]);
+    }
+    Ok(())
+}
+
+/// Returns the time of each packet in a pcap or pcapng file.
+fn read_times(mut input: impl Read) -> anyhow::Result<Vec<DateTime<Utc>>> {
+    let mut magic = [0; 4];
+    input.read_exact(&mut magic).context("the file is empty")?;
+    if u32::from_le_bytes(magic) == SECTION_HEADER {
+        return pcapng(magic, input);
+    }
+    let (big_endian, nanos) =
+        match (u32::from_le_bytes(magic), u32::from_be_bytes(magic)) {
+            (PCAP_MICROS, _) => (false, false),
+            (PCAP_NANOS, _) => (false, true),
+            (_, PCAP_MICROS) => (true, false),
+            (_, PCAP_NANOS) => (true, true),
+            _ => bail!("not a pcap or pcapng file"),
+        };
+    let order = ByteOrder { big_endian };
+    // The rest of the file header says nothing about the timestamps.
+    let mut header = [0; 20];
+    input.read_exact(&mut header).context("the file header is truncated")?;
+    let mut times = Vec::new();
+    let mut record = [0; 16];
+    while fill(&mut input, &mut record)? {
+        let seconds = i64::from(order.u32(&record[0..4]));
+        let fraction = order.u32(&record[4..8]);
+        let time = if nanos {
+            DateTime::from_timestamp(seconds, fraction)
+        } else {
+            fraction
+                .checked_mul(1000)
+                .and_then(|nanos| DateTime::from_timestamp(seconds, nanos))
+        };
+        let Some(time) = time else {
+            bail!(
+                "packet {} has a fraction of a second over a second",
+                times.len() + 1
+            );
+        };
+        times.push(time);
+        skip(&mut input, u64::from(order.u32(&record[8..12])))?;
+    }
+    Ok(times)
+}
+
+/// An interface in a pcapng section, which says how to read the timestamps
+/// of packets captured on it
+struct Interface {
+    /// how many ticks of the timestamp make a second
+    ticks_per_second: u128,
+    /// seconds to add to each timestamp
+    offset: i64,
+}
+
+/// Returns the time of each packet in a pcapng file, whose first four bytes,
+/// `magic`, have already been read.
+fn pcapng(
+    magic: [u8; 4],
+    mut input: impl Read,
+) -> anyhow::Result<Vec<DateTime<Utc>>> {
+    let mut times = Vec::new();
+    let mut interfaces = Vec::new();
+    let mut order = ByteOrder { big_endian: false };
+    let mut kind = magic;
+    loop {
+        let block_type = u32::from_le_bytes(kind);
+        let mut len = [0; 4];
+        input.read_exact(&mut len).context("a block is truncated")?;
+        let mut body = Vec::new();
+        if block_type == SECTION_HEADER {
+            // Each section has its own byte order and interfaces.
+            let mut magic_order = [0; 4];
+            input
+                .read_exact(&mut magic_order)
+                .context("a block is truncated")?;
+            order = if u32::from_le_bytes(magic_order) == BYTE_ORDER {
+                ByteOrder { big_endian: false }
+            } else if u32::from_be_bytes(magic_order) == BYTE_ORDER {
+                ByteOrder { big_endian: true }
+            } else {
+                bail!("a section has no byte-order magic number");
+            };
+            interfaces.clear();
+            body.extend_from_slice(&magic_order);
+        }
+        let block_type = order.u32(&kind);
+        let len = order.u32(&len) as usize;
+        if len < 12 + body.len() || !len.is_multiple_of(4) {
+            bail!("a block has an invalid length ({len} bytes)");
+        }
+        // The body is everything but the type and the length at each end.
+        let rest = len - 12 - body.len();
+        match block_type {
+            INTERFACE_DESCRIPTION | PACKET | ENHANCED_PACKET => {
+                // Packet blocks are read only as far as their timestamps.
+                let wanted = if block_type == INTERFACE_DESCRIPTION {
+                    rest
+                } else {
+                    rest.min(12)
+                };
+                body.resize(wanted, 0);
+                input.read_exact(&mut body).context("a block is truncated")?;
+                skip(&mut input, (rest - wanted) as u64)?;
+            }
+            _ => skip(&mut input, rest as u64)?,
+        }
+        skip(&mut input, 4)?;
+
+        match block_type {
+            INTERFACE_DESCRIPTION => {
+                interfaces.push(interface(&body, order)?);
+            }
+            PACKET | ENHANCED_PACKET if body.len() == 12 => {
+                let id = match block_type {
+                    PACKET => usize::from(order.u16(&body[0..2])),
+                    _ => order.u32(&body[0..4]) as usize,
+                };
+                let Some(interface) = interfaces.get(id) else {
+                    bail!(
+                        "packet {} is from an unknown interface",
+                        times.len() + 1
+                    );
+                };
+                let ticks = u128::from(order.u32(&body[4..8])) << 32
+                    | u128::from(order.u32(&body[8..12]));
+                let nanos = ticks * 1_000_000_000 / interface.ticks_per_second;
+                let time = i64::try_from(nanos)
+                    .ok()
+                    .map(DateTime::from_timestamp_nanos)
+                    .and_then(|t| {
+                        t.checked_add_signed(Duration::try_seconds(
+                            interface.offset,
+                        )?)
+                    })
+                    .with_context(|| {
+                        format!("packet {} is out of range", times.len() + 1)
+                    })?;
+                times.push(time);
+            }
+            PACKET | ENHANCED_PACKET => bail!("a packet block is truncated"),
+            _ => (),
+        }
+
+        if !fill(&mut input, &mut kind)? {
+            return Ok(times);
+        }
+    }
+}
+
+/// Reads the options of an interface description block with body `body`.
+fn interface(body: &[u8], order: ByteOrder) -> anyhow::Result<Interface> {
+    // Timestamps are in microseconds unless an option says otherwise.
+    let mut interface = Interface { ticks_per_second: 1_000_000, offset: 0 };
+    // The link type, a reserved field, and the snapshot length come first.
+    let mut options = body.get(8..).unwrap_or_default();
+    while let [c0, c1, l0, l1, rest @ ..] = options {
+        let code = order.u16(&[*c0, *c1]);
+        let len = usize::from(order.u16(&[*l0, *l1]));
+        let Some(value) = rest.get(..len) else {
+            bail!("an interface's options are truncated");
+        };
+        match (code, value) {
+            (0, _) => break,
+            (IF_TSRESOL, &[resolution]) => {
+                // The high bit says whether it's a power of 2 or of 10.
+                let exponent = u32::from(resolution & 0x7f);
+                let base: u128 = if resolution & 0x80 != 0 { 2 } else { 10 };
+                interface.ticks_per_second = base
+                    .checked_pow(exponent)
+                    .filter(|&n| n <= u128::from(u64::MAX))
+                    .context("an interface's resolution is too fine")?;
+            }
+            (IF_TSOFFSET, &[_, _, _, _, _, _, _, _]) => {
+                interface.offset = order.i64(value);
+            }
+            _ => (),
+        }
+        options = rest.get(len.next_multiple_of(4)..).unwrap_or_default();
+    }
+    Ok(interface)
+}
+
+/// The byte order of a file (or of a pcapng section)
+#[derive(Clone, Copy)]
+struct ByteOrder {
+    big_endian: bool,
+}
+
+impl ByteOrder {
+    fn u16(self, bytes: &[u8]) -> u16 {
+        let bytes = [bytes[0], bytes[1]];
+        if self.big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        }
+    }
+
+    fn u32(self, bytes: &[u8]) -> u32 {
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        }
+    }
+
+    fn i64(self, bytes: &[u8]) -> i64 {
+        let mut array = [0; 8];
+        array.copy_from_slice(&bytes[..8]);
+        if self.big_endian {
+            i64::from_be_bytes(array)
+        } else {
+            i64::from_le_bytes(array)
+        }
+    }
+}
+
+/// Fills `buf` from `input`, returning `false` if `input` was already at its
+/// end.
+fn fill(input: &mut impl Read, buf: &mut [u8]) -> anyhow::Result<bool> {
+    let mut read = 0;
+    while read < buf.len() {
+        match input.read(&mut buf[read..])? {
+            0 if read == 0 => return Ok(false),
+            0 => bail!("the file ends in the middle of a record"),
+            n => read += n,
+        }
+    }
+    Ok(true)
+}
+
+/// Skips `n` bytes of `input`.
+fn skip(input: &mut impl Read, n: u64) -> anyhow::Result<()> {
+    let skipped = std::io::copy(&mut input.take(n), &mut std::io::sink())?;
+    if skipped < n {
+        bail!("the file ends in the middle of a record");
+    }
+    Ok(())
+}
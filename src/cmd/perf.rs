@@ -0,0 +1,194 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates perf`: the wall-clock times of `perf script` samples
+//!
+//! `perf script` prints each sample's time as seconds on the clock `perf
+//! record` used, like "12345.678901:", which counts from an arbitrary point
+//! (usually boot).  Given one pair of a perf time and the wall-clock time at
+//! the same moment, this prints the time of other perf times.  Without
+//! values, it copies `perf script` output from stdin, replacing each
+//! sample's time with the time it means.
+//!
+//! `perf record -k CLOCK_MONOTONIC` (or any other `-k`) records such a pair,
+//! which `perf script --header` prints like this, so without `--anchor`,
+//! that's used:
+//!
+//! ```text
+//! # reference time: 2024-03-01 12:00:00.000000 = 1709294400.000000 (TOD) = 12345.678901234 (monotonic)
+//! ```
+
+use crate::cmd::dmesg::parse_uptime;
+use crate::{ReadOptions, Reader, UsageError, print_delta, print_time};
+use anyhow::{Context, bail};
+use chrono::{DateTime, Duration, Utc};
+use dates_tool::{Error, context};
+use std::io::{BufRead, Write};
+use std::ops::Range;
+
+/// Entry point for `dates perf [--anchor PERFTIME=WALLTIME] [PERFTIME...]`
+///
+/// WALLTIME is read as `dates TIME` reads times, with the same options.
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let mut options = ReadOptions::default();
+    let mut anchor = None;
+    let mut values = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--anchor" => {
+                let value = args.next().with_context(|| {
+                    UsageError::new("perf: --anchor requires an argument")
+                })?;
+                anchor = Some(value);
+            }
+            _ if options.parse_option(arg, &mut args)? => (),
+            _ => values.push(arg.as_str()),
+        }
+    }
+    let reader = options.reader(ctx)?;
+    let anchor = match anchor {
+        Some(value) => {
+            let Some((perf, wall)) = value.split_once('=') else {
+                bail!("perf: --anchor {value:?} isn't PERFTIME=WALLTIME");
+            };
+            Some(Anchor { perf: perf_time(perf)?, wall: reader.parse(wall)? })
+        }
+        None => None,
+    };
+
+    if values.is_empty() {
+        return filter(std::io::stdin().lock(), anchor, &reader, ctx);
+    }
+    let Some(anchor) = anchor else {
+        bail!("perf: --anchor PERFTIME=WALLTIME is required with values");
+    };
+    let perfs =
+        values.iter().map(|v| perf_time(v)).collect::<Result<Vec<_>, _>>()?;
+    print_time(ctx, "anchor", anchor.wall);
+    for (value, perf) in values.into_iter().zip(perfs) {
+        let time = anchor.time(perf).ok_or_else(|| {
+            Error::out_of_range(value, "too far from the anchor")
+        })?;
+        println!();
+        print_delta("offset", perf - anchor.perf);
+        print_time(ctx, "time", time);
+    }
+    Ok(())
+}
+
+/// A perf time and the wall-clock time at the same moment
+struct Anchor {
+    perf: Duration,
+    wall: DateTime<Utc>,
+}
+
+impl Anchor {
+    /// Parses the pair from a "# reference time:" line of `perf script
+    /// --header` output, returning `None` if `line` isn't one.  The wall-clock
+    /// time is read by `reader`.
+    fn from_header(
+        line: &str,
+        reader: &Reader,
+    ) -> Option<anyhow::Result<Anchor>> {
+        let rest = line.strip_prefix("# reference time:")?;
+        let mut parts = rest.split(" = ").skip(1);
+        let mut number = || {
+            let part = parts.next()?;
+            Some(part.split_once(' ').map_or(part, |(n, _)| n).trim())
+        };
+        let (Some(wall), Some(perf)) = (number(), number()) else {
+            return Some(Err(anyhow::anyhow!(
+                "perf: unrecognized header {:?}",
+                line.trim_end()
+            )));
+        };
+        Some(
+            perf_time(perf).and_then(|perf| {
+                Ok(Anchor { perf, wall: reader.parse(wall)? })
+            }),
+        )
+    }
+
+    /// Returns the time that `perf` means, or `None` if it's out of range.
+    fn time(&self, perf: Duration) -> Option<DateTime<Utc>> {
+        self.wall.checked_add_signed(perf.checked_sub(&self.perf)?)
+    }
+}
+
+/// Copies `perf script` output from `input` to stdout, replacing each
+/// sample's time with the time it means, in the local zone.  Without
+/// `anchor`, this uses the reference time in the header, read by `reader`.
+fn filter(
+    mut input: impl BufRead,
+    mut anchor: Option<Anchor>,
+    reader: &Reader,
+    ctx: &context::Context,
+) -> anyhow::Result<()> {
+    let explicit = anchor.is_some();
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if input.read_until(b'\n', &mut line).context("reading stdin")? == 0 {
+            return Ok(());
+        }
+        if line.starts_with(b"#") {
+            let header = String::from_utf8_lossy(&line);
+            if let Some(found) = Anchor::from_header(&header, reader)
+                && !explicit
+            {
+                anchor = Some(found?);
+            }
+            out.write_all(&line)?;
+            continue;
+        }
+        let Some((range, perf)) = sample_time(&line) else {
+            out.write_all(&line)?;
+            continue;
+        };
+        let Some(anchor) = &anchor else {
+            bail!(
+                "perf: there's no reference time before the first sample \
+                 (use --anchor, or perf record -k and perf script --header)"
+            );
+        };
+        let time = anchor
+            .time(perf)
+            .map(|t| ctx.local().rfc3339(t))
+            .unwrap_or_else(|| String::from("out of range"));
+        out.write_all(&line[..range.start])?;
+        out.write_all(time.as_bytes())?;
+        out.write_all(&line[range.end..])?;
+    }
+}
+
+/// Finds the time in a line of `perf script` output: the first word that's
+/// seconds with a fraction followed by a colon, like "12345.678901:"
+fn sample_time(line: &[u8]) -> Option<(Range<usize>, Duration)> {
+    let mut start = 0;
+    for word in line.split(|b| b.is_ascii_whitespace()) {
+        let range = start..start + word.len();
+        start = range.end + 1;
+        let Some(number) = word.strip_suffix(b":") else {
+            continue;
+        };
+        if !number.contains(&b'.') {
+            continue;
+        }
+        let time = std::str::from_utf8(number).ok().and_then(parse_uptime);
+        if let Some(time) = time {
+            return Some((range.start..range.end - 1, time));
+        }
+    }
+    None
+}
+
+/// Parses a perf time, which is seconds with a fraction.
+fn perf_time(s: &str) -> anyhow::Result<Duration> {
+    let time = parse_uptime(s.strip_suffix(':').unwrap_or(s))
+        .ok_or_else(|| Error::unrecognized(s, "a perf time (seconds)"))?;
+    Ok(time)
+}
@@ -0,0 +1,54 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates postgres`: PostgreSQL's internal timestamps
+//!
+//! This reads each value as PostgreSQL stores it (an integer count of
+//! microseconds since 2000-01-01T00:00:00Z), as `infinity` or `-infinity`, or
+//! as a time, and prints it in both forms, along with how PostgreSQL prints
+//! it as a `timestamptz` in UTC.  See [`dates_tool::postgres`] for the
+//! details.
+
+use crate::print_time;
+use anyhow::bail;
+use dates_tool::Error;
+use dates_tool::context;
+use dates_tool::postgres::Timestamp;
+
+/// Entry point for `dates postgres VALUE...`
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    if args.is_empty() {
+        bail!("postgres: expected a timestamp, infinity, or microseconds");
+    }
+    for (i, arg) in args.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        let timestamp = parse(arg)?;
+        match timestamp {
+            Timestamp::Finite(t) => print_time(ctx, "time", t),
+            _ => println!("{:<8} {}", "time", timestamp),
+        }
+        println!("{:<8} {}", "micros", timestamp.micros());
+        println!("{:<8} {}", "text", timestamp);
+    }
+    Ok(())
+}
+
+/// Parses an integer as microseconds since the PostgreSQL epoch, and anything
+/// else as `Timestamp::parse` does.
+fn parse(s: &str) -> Result<Timestamp, Error> {
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Timestamp::parse(s);
+    }
+    let range = || {
+        Error::out_of_range(
+            s,
+            "outside the range of PostgreSQL timestamps that can be shown",
+        )
+    };
+    let micros: i64 = s.parse().map_err(|_| range())?;
+    Timestamp::from_micros(micros).ok_or_else(range)
+}
@@ -0,0 +1,113 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates protobuf`: protobuf `Timestamp` and `Duration` values
+//!
+//! This reads a value as gRPC tools print it, either in protobuf's JSON
+//! mapping (like "2024-03-01T12:00:00.500Z" or "3.500s", with or without the
+//! quotes) or as its `seconds` and `nanos`, and prints it in both forms.
+//! See [`dates_tool::protobuf`] for the details.
+
+use crate::{ReadOptions, print_delta, print_time};
+use anyhow::{Context, bail};
+use dates_tool::protobuf;
+use dates_tool::{Error, context};
+
+/// Entry point for `dates protobuf VALUE` and `dates protobuf [--duration]
+/// SECONDS NANOS`
+///
+/// A VALUE that isn't a duration is read as `dates TIME` reads times, with
+/// the same options.
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let mut options = ReadOptions::default();
+    let mut is_duration = false;
+    let mut values = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--duration" => is_duration = true,
+            _ if options.parse_option(arg, &mut iter)? => (),
+            _ => values.push(arg),
+        }
+    }
+    match values.as_slice() {
+        [value] if !is_duration => {
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value);
+            if value.ends_with('s')
+                && let Ok(d) = protobuf::parse_duration_json(value)
+            {
+                return show_duration(value, d);
+            }
+            let t = options.reader(ctx)?.parse(value)?;
+            let (seconds, nanos) = protobuf::timestamp_parts(t)
+                .ok_or_else(|| Error::out_of_range(value, TIMESTAMP_RANGE))?;
+            print_time(ctx, "time", t);
+            print_parts(seconds, nanos);
+            println!(
+                "{:<8} {:?}",
+                "json",
+                protobuf::timestamp_json(t).unwrap()
+            );
+        }
+        [seconds, nanos] => {
+            let seconds_value: i64 = seconds.parse().map_err(|_| {
+                Error::unrecognized(seconds, "an integer number of seconds")
+            })?;
+            let nanos_value: i32 = nanos.parse().map_err(|_| {
+                Error::unrecognized(nanos, "an integer number of nanoseconds")
+            })?;
+            let input = format!("{seconds} {nanos}");
+            if is_duration {
+                let d = protobuf::duration(seconds_value, nanos_value)
+                    .ok_or_else(|| {
+                        Error::out_of_range(&input, DURATION_RANGE)
+                    })?;
+                return show_duration(&input, d);
+            }
+            let t = protobuf::timestamp(seconds_value, nanos_value)
+                .ok_or_else(|| Error::out_of_range(&input, TIMESTAMP_RANGE))?;
+            print_time(ctx, "time", t);
+            print_parts(seconds_value, nanos_value);
+            println!(
+                "{:<8} {:?}",
+                "json",
+                protobuf::timestamp_json(t).unwrap()
+            );
+        }
+        _ if is_duration => {
+            bail!("protobuf: --duration expects SECONDS and NANOS")
+        }
+        _ => bail!("protobuf: expected a value, or SECONDS and NANOS"),
+    }
+    Ok(())
+}
+
+/// Why a `Timestamp` can't be made
+const TIMESTAMP_RANGE: &str = "a protobuf Timestamp must be from 0001 to \
+                               9999, with nanos from 0 to 999999999";
+
+/// Why a `Duration` can't be made
+const DURATION_RANGE: &str = "a protobuf Duration must be at most 10,000 \
+                              years, with nanos of the same sign under a \
+                              second";
+
+/// Prints a `Duration`, which was read from `input`.
+fn show_duration(input: &str, d: chrono::Duration) -> anyhow::Result<()> {
+    let (seconds, nanos) = protobuf::duration_parts(d)
+        .with_context(|| format!("protobuf: {input:?}: {DURATION_RANGE}"))?;
+    print_delta("duration", d);
+    print_parts(seconds, nanos);
+    println!("{:<8} {:?}", "json", protobuf::duration_json(d).unwrap());
+    Ok(())
+}
+
+/// Prints the `seconds` and `nanos` fields.
+fn print_parts(seconds: i64, nanos: i32) {
+    println!("{:<8} {}", "seconds", seconds);
+    println!("{:<8} {}", "nanos", nanos);
+}
@@ -0,0 +1,122 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates ptp`: PTP (IEEE 1588) timestamps
+//!
+//! A PTP timestamp counts seconds and nanoseconds since 1970-01-01T00:00:00
+//! TAI, which is 1969-12-31T23:59:51.999918 UTC, on the TAI timescale, so
+//! it's ahead of a Unix time by TAI - UTC (37 seconds since 2017).  On the
+//! wire, it's 80 bits: 48 bits of seconds followed by 32 bits of
+//! nanoseconds, both big-endian.  This reads a timestamp either way, or a
+//! time in any other form, and prints it both ways and on both timescales.
+
+use crate::{ReadOptions, Reader, UsageError, print_time};
+use anyhow::{Context, bail};
+use chrono::{DateTime, NaiveDateTime, SecondsFormat};
+use dates_tool::leap::LeapSeconds;
+use dates_tool::{Error, context};
+
+/// The largest number of seconds in a PTP timestamp
+const MAX_SECONDS: u64 = (1 << 48) - 1;
+
+/// Entry point for `dates ptp [--file PATH] SECONDS NANOS | HEX | TIME`
+///
+/// With `--file`, the table of leap seconds is read from a
+/// `leap-seconds.list` file (as published by the IERS) instead of the one
+/// built into `dates`.  TIME is read as `dates TIME` reads times, with the
+/// same options.
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let mut options = ReadOptions::default();
+    let mut table = LeapSeconds::builtin();
+    let mut values = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--file" => {
+                let path = args.next().with_context(|| {
+                    UsageError::new("ptp: --file requires an argument")
+                })?;
+                let text = std::fs::read_to_string(path)
+                    .with_context(|| format!("ptp: reading {path:?}"))?;
+                table = LeapSeconds::parse_list(&text)?;
+            }
+            _ if options.parse_option(arg, &mut args)? => (),
+            _ => values.push(arg.as_str()),
+        }
+    }
+
+    let (seconds, nanos) = match values[..] {
+        [seconds, nanos] => (
+            seconds
+                .parse::<u64>()
+                .ok()
+                .filter(|&s| s <= MAX_SECONDS)
+                .ok_or_else(|| {
+                    Error::unrecognized(seconds, "PTP seconds (48 bits)")
+                })?,
+            nanos
+                .parse::<u32>()
+                .map_err(|_| Error::unrecognized(nanos, "nanoseconds"))?,
+        ),
+        [value] => match parse_wire(value) {
+            Some(wire) => wire,
+            None => from_utc(&table, &options.reader(ctx)?, value)?,
+        },
+        _ => bail!("ptp: expected SECONDS NANOS, HEX, or TIME"),
+    };
+
+    if nanos >= 1_000_000_000 {
+        bail!("ptp: {nanos} nanoseconds is more than a second");
+    }
+    let tai = DateTime::from_timestamp(seconds as i64, nanos)
+        .map(|t| t.naive_utc())
+        .context("ptp: timestamp out of range")?;
+    let utc = table.tai_to_utc(tai)?;
+    let offset = table.offset_at(utc)?;
+    println!("{:<8} {seconds} s {nanos} ns", "ptp");
+    println!("{:<8} {seconds:012x}{nanos:08x}", "wire");
+    println!("{:<8} {} TAI", "tai", tai_text(tai));
+    println!("{:<8} {}s", "tai-utc", offset);
+    print_time(ctx, "time", utc);
+    Ok(())
+}
+
+/// Parses the 80-bit wire form, as 20 hexadecimal digits (which may be
+/// separated by spaces or colons, as a packet dump shows them), returning
+/// `None` if `s` isn't that.
+fn parse_wire(s: &str) -> Option<(u64, u32)> {
+    let hex: String = s
+        .strip_prefix("0x")
+        .unwrap_or(s)
+        .chars()
+        .filter(|c| *c != ' ' && *c != ':')
+        .collect();
+    if hex.len() != 20 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let seconds = u64::from_str_radix(&hex[..12], 16).ok()?;
+    let nanos = u32::from_str_radix(&hex[12..], 16).ok()?;
+    Some((seconds, nanos))
+}
+
+/// Returns the PTP timestamp for a time in any other form, read by `reader`.
+fn from_utc(
+    table: &LeapSeconds,
+    reader: &Reader,
+    s: &str,
+) -> anyhow::Result<(u64, u32)> {
+    let tai = table.utc_to_tai(reader.parse(s)?)?.and_utc();
+    let seconds = u64::try_from(tai.timestamp())
+        .ok()
+        .filter(|&s| s <= MAX_SECONDS)
+        .ok_or_else(|| Error::out_of_range(s, "outside PTP's range"))?;
+    Ok((seconds, tai.timestamp_subsec_nanos()))
+}
+
+/// Formats a TAI time like "2024-03-01T12:00:37.000000000", with all nine
+/// fractional digits, since PTP timestamps are often that precise.
+fn tai_text(tai: NaiveDateTime) -> String {
+    let text = tai.and_utc().to_rfc3339_opts(SecondsFormat::Nanos, true);
+    text.trim_end_matches('Z').to_string()
+}
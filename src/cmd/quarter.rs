@@ -0,0 +1,84 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates quarter`: print the calendar quarter and fiscal year of a time
+
+use crate::{ReadOptions, UsageError, print_time};
+use anyhow::{Context, bail};
+use chrono::Month;
+use dates_tool::context;
+use dates_tool::locale::Lang;
+use dates_tool::quarter::{quarter_of, year_of};
+
+/// Entry point for `dates quarter [--fy-start MONTH] [--tz ZONE] [TIME]`
+///
+/// TIME is read as `dates TIME` reads times, with the same options.  The
+/// quarter is the one it falls in on the wall clock in `--tz` (or the local
+/// zone).
+pub fn run(
+    args: &[String],
+    ctx: &context::Context,
+    lang: Lang,
+) -> anyhow::Result<()> {
+    let mut options = ReadOptions::default();
+    let mut fy_start = Month::January;
+    let mut time_arg = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--fy-start" => {
+                let value = args.next().with_context(|| {
+                    UsageError::new("quarter: --fy-start requires an argument")
+                })?;
+                fy_start = lang.parse_month(value)?;
+            }
+            _ if options.parse_option(arg, &mut args)? => (),
+            _ if time_arg.is_none() => time_arg = Some(arg.as_str()),
+            _ => bail!(UsageError::new(format!(
+                "quarter: unexpected argument {arg:?}"
+            ))),
+        }
+    }
+
+    let reader = options.reader(ctx)?;
+    let time = match time_arg {
+        None => ctx.now(),
+        Some(s) => reader.parse(s)?,
+    };
+    let date = reader.display_zone.date_of(time);
+
+    let out_of_range = || {
+        let input = date.to_string();
+        dates_tool::Error::out_of_range(
+            &input,
+            "its fiscal year extends past the supported range of dates",
+        )
+    };
+    let q = quarter_of(date, Month::January).ok_or_else(out_of_range)?;
+    let fq = quarter_of(date, fy_start).ok_or_else(out_of_range)?;
+    let fy = year_of(date, fy_start).ok_or_else(out_of_range)?;
+
+    print_time(ctx, "time", time);
+    println!(
+        "{:<8} {}-Q{}: {} to {}",
+        "quarter", q.year, q.quarter, q.start, q.end
+    );
+    if fy_start != Month::January {
+        println!(
+            "{:<8} FY{}-Q{}: {} to {}",
+            "fiscal", fq.year, fq.quarter, fq.start, fq.end
+        );
+    }
+    println!(
+        "{:<8} FY{}: {} to {} (starting in {}, dates in {})",
+        "fiscal",
+        fy.year,
+        fy.start,
+        fy.end,
+        lang.month_name(fy_start),
+        reader.display_zone
+    );
+    Ok(())
+}
@@ -0,0 +1,148 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates sct`: when Certificate Transparency logs saw a certificate
+//!
+//! A signed certificate timestamp (SCT, RFC 6962) is a log's promise to
+//! include a certificate, and says when the log received it, in
+//! milliseconds since the Unix epoch.  Certificates carry a list of them in
+//! an X.509 extension, and TLS servers may also send one in a handshake
+//! extension or an OCSP response.  This decodes a single SCT, a list of them
+//! (as in the TLS extension), or the X.509 extension's value (the same list,
+//! wrapped in a DER OCTET STRING), given in hexadecimal or base64, and
+//! prints the time in each, with the ID of the log that signed it.
+
+use crate::cmd::encoding::decode_bytes;
+use crate::print_time;
+use anyhow::{Context, bail};
+use chrono::DateTime;
+use dates_tool::context;
+
+/// Entry point for `dates sct DATA...`
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    if args.is_empty() {
+        bail!("sct: expected SCTs in hexadecimal or base64");
+    }
+    for (i, arg) in args.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        // openssl prints bytes in hexadecimal separated by colons.
+        let data = decode_bytes(&arg.replace(':', ""))
+            .context("sct: reading the data")?;
+        let scts =
+            scts(&data).with_context(|| format!("sct: decoding {arg:?}"))?;
+        for (j, sct) in scts.iter().enumerate() {
+            if j > 0 {
+                println!();
+            }
+            println!("{:<8} {}", format!("sct {}", j + 1), base64(&sct.log_id));
+            let time = DateTime::from_timestamp_millis(sct.millis as i64)
+                .context("sct: timestamp out of range")?;
+            print_time(ctx, "time", time);
+        }
+    }
+    Ok(())
+}
+
+/// What this needs from an SCT: the log's ID (the SHA-256 hash of its
+/// public key) and when the log received the certificate
+struct Sct {
+    log_id: Vec<u8>,
+    millis: u64,
+}
+
+/// Decodes `data`, which is a list of SCTs, that list wrapped in a DER OCTET
+/// STRING, or a single SCT.
+fn scts(data: &[u8]) -> anyhow::Result<Vec<Sct>> {
+    let list = |mut data: &[u8]| -> anyhow::Result<Vec<Sct>> {
+        let length = usize::from(u16::from_be_bytes(
+            take(&mut data, 2)?.try_into().unwrap(),
+        ));
+        if length != data.len() {
+            bail!("the list's length doesn't match the data");
+        }
+        let mut scts = Vec::new();
+        while !data.is_empty() {
+            let length =
+                u16::from_be_bytes(take(&mut data, 2)?.try_into().unwrap());
+            scts.push(sct(take(&mut data, usize::from(length))?)?);
+        }
+        Ok(scts)
+    };
+    if let Ok(scts) = list(data) {
+        return Ok(scts);
+    }
+    if let Some(inner) = octet_string(data) {
+        return list(inner);
+    }
+    Ok(vec![sct(data)?])
+}
+
+/// Returns the content of `data` if it's exactly one DER OCTET STRING.
+fn octet_string(data: &[u8]) -> Option<&[u8]> {
+    let (&tag, rest) = data.split_first()?;
+    let (&first, rest) = rest.split_first()?;
+    let (length, rest) = match first {
+        0..=0x7f => (usize::from(first), rest),
+        0x81 | 0x82 => {
+            let n = usize::from(first & 0x7f);
+            let bytes = rest.get(..n)?;
+            let length = bytes.iter().fold(0, |l, &b| l << 8 | usize::from(b));
+            (length, &rest[n..])
+        }
+        _ => return None,
+    };
+    (tag == 0x04 && length == rest.len()).then_some(rest)
+}
+
+/// Decodes one SCT, which must be all of `data`.
+fn sct(mut data: &[u8]) -> anyhow::Result<Sct> {
+    let data = &mut data;
+    let version = take(data, 1)?[0];
+    if version != 0 {
+        bail!("unsupported SCT version {}", u32::from(version) + 1);
+    }
+    let log_id = take(data, 32)?.to_vec();
+    let millis = u64::from_be_bytes(take(data, 8)?.try_into().unwrap());
+    let extensions = u16::from_be_bytes(take(data, 2)?.try_into().unwrap());
+    take(data, usize::from(extensions))?;
+    // The signature: hash and signature algorithms, then the signature.
+    take(data, 2)?;
+    let signature = u16::from_be_bytes(take(data, 2)?.try_into().unwrap());
+    take(data, usize::from(signature))?;
+    if !data.is_empty() {
+        bail!("{} bytes follow the SCT", data.len());
+    }
+    Ok(Sct { log_id, millis })
+}
+
+/// Reads `n` bytes.
+fn take<'a>(data: &mut &'a [u8], n: usize) -> anyhow::Result<&'a [u8]> {
+    if data.len() < n {
+        bail!("the data ends in the middle of an SCT");
+    }
+    let (taken, rest) = data.split_at(n);
+    *data = rest;
+    Ok(taken)
+}
+
+/// Encodes `bytes` in base64, as log lists identify logs.
+fn base64(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 64] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut text = String::new();
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().fold(0u32, |n, &b| n << 8 | u32::from(b))
+            << (8 * (3 - chunk.len()));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                text.push(DIGITS[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
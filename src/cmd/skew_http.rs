@@ -0,0 +1,145 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates skew-http`: how far the local clock is from a web server's
+//!
+//! This sends a HEAD request and compares the response's `Date` header with
+//! the local clock, as a rough check where NTP is blocked but HTTP isn't.
+//! The server wrote the header sometime between when the request was sent
+//! and when the response started to arrive, so it's compared with the local
+//! time halfway between.  It's also only to the second (truncated), so it's
+//! taken to be half a second later than it says.  The result is accurate to
+//! within half the round trip, plus half a second.
+//!
+//! Plain HTTP is spoken directly.  For HTTPS, this runs `curl`, which reports
+//! when it sent the request and started receiving the response, so that the
+//! time spent connecting and negotiating TLS isn't counted.
+//!
+//! This is only built with the "http" feature, since it uses the network.
+
+use crate::{print_delta, print_time};
+use anyhow::{Context, bail};
+use chrono::{DateTime, Duration, Utc};
+use dates_tool::{context, parse_timestamp};
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::process::Command;
+
+/// How long to wait for a connection, or a response
+const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// A response's headers, and the local times the request was sent and the
+/// response started to arrive
+struct Response {
+    headers: String,
+    sent: DateTime<Utc>,
+    received: DateTime<Utc>,
+}
+
+/// Entry point for `dates skew-http URL`
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let [url] = args else {
+        bail!("skew-http: expected a URL");
+    };
+    let response = if let Some(rest) = url.strip_prefix("http://") {
+        head(rest)?
+    } else if url.starts_with("https://") {
+        head_with_curl(url)?
+    } else {
+        bail!("skew-http: expected an http:// or https:// URL");
+    };
+
+    let date = response
+        .headers
+        .lines()
+        .find(|line| {
+            line.split_once(':')
+                .is_some_and(|(name, _)| name.eq_ignore_ascii_case("date"))
+        })
+        .context("skew-http: the response has no Date header")?;
+    let server = parse_timestamp(date.trim())? + Duration::milliseconds(500);
+    let rtt = response.received - response.sent;
+    let local = response.sent + rtt / 2;
+    let status = response.headers.lines().next().unwrap_or("").trim();
+    println!("{:<8} {url} ({status})", "server");
+    print_time(ctx, "date", server - Duration::milliseconds(500));
+    print_time(ctx, "local", local);
+    print_delta("offset", server - local);
+    print_delta("delay", rtt);
+    print_delta("accuracy", rtt / 2 + Duration::milliseconds(500));
+    Ok(())
+}
+
+/// Sends a HEAD request over plain HTTP, given the URL after "http://".
+fn head(rest: &str) -> anyhow::Result<Response> {
+    let (authority, path) = match rest.find('/') {
+        Some(i) => rest.split_at(i),
+        None => (rest, "/"),
+    };
+    let address = authority
+        .to_socket_addrs()
+        .or_else(|_| (authority, 80).to_socket_addrs())
+        .with_context(|| format!("skew-http: resolving {authority:?}"))?
+        .next()
+        .with_context(|| {
+            format!("skew-http: {authority:?} has no addresses")
+        })?;
+    let mut stream = TcpStream::connect_timeout(&address, TIMEOUT)
+        .with_context(|| format!("skew-http: connecting to {address}"))?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    let request = format!(
+        "HEAD {path} HTTP/1.1\r\nHost: {authority}\r\nUser-Agent: dates\r\n\
+         Connection: close\r\n\r\n"
+    );
+    let sent = Utc::now();
+    stream.write_all(request.as_bytes())?;
+    let mut headers = Vec::new();
+    let mut buffer = [0u8; 4096];
+    let mut received = None;
+    loop {
+        let n = stream
+            .read(&mut buffer)
+            .with_context(|| format!("skew-http: reading from {address}"))?;
+        received.get_or_insert_with(Utc::now);
+        headers.extend_from_slice(&buffer[..n]);
+        if n == 0 || headers.windows(4).any(|w| w == b"\r\n\r\n") {
+            break;
+        }
+    }
+    Ok(Response {
+        headers: String::from_utf8_lossy(&headers).into_owned(),
+        sent,
+        received: received.unwrap(),
+    })
+}
+
+/// Sends a HEAD request with `curl`, for HTTPS.
+fn head_with_curl(url: &str) -> anyhow::Result<Response> {
+    let start = Utc::now();
+    let output = Command::new("curl")
+        .args(["--head", "--silent", "--show-error", "--max-time", "10"])
+        .args(["--write-out", "\n%{time_pretransfer} %{time_starttransfer}"])
+        .arg(url)
+        .output()
+        .context("skew-http: running curl, which is needed for https")?;
+    if !output.status.success() {
+        bail!(
+            "skew-http: curl failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let text = String::from_utf8_lossy(&output.stdout);
+    let (headers, timing) = text.rsplit_once('\n').unwrap_or(("", &text));
+    let seconds = |s: Option<&str>| {
+        let s: f64 = s?.parse().ok()?;
+        Some(start + Duration::microseconds((s * 1e6) as i64))
+    };
+    let mut words = timing.split_whitespace();
+    let (Some(sent), Some(received)) =
+        (seconds(words.next()), seconds(words.next()))
+    else {
+        bail!("skew-http: unexpected output from curl: {timing:?}");
+    };
+    Ok(Response { headers: headers.to_string(), sent, received })
+}
@@ -0,0 +1,56 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates snowflake`: when a Snowflake ID was generated
+//!
+//! See [`dates_tool::snowflake`] for the details.
+
+use crate::{UsageError, print_time};
+use anyhow::{Context, bail};
+use dates_tool::Error;
+use dates_tool::context;
+use dates_tool::snowflake::{Epoch, Snowflake};
+
+/// Entry point for `dates snowflake [--epoch twitter|discord|MILLIS] ID...`
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let mut epoch = Epoch::TWITTER;
+    let mut ids = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--epoch" => {
+                let value = args.next().with_context(|| {
+                    UsageError::new("snowflake: --epoch requires an argument")
+                })?;
+                epoch = Epoch::parse(value)?;
+            }
+            _ => ids.push(arg),
+        }
+    }
+    if ids.is_empty() {
+        bail!("snowflake: expected an ID");
+    }
+
+    for (i, arg) in ids.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        let id = Snowflake::parse(arg)?;
+        let time = id.timestamp(epoch).ok_or_else(|| {
+            Error::out_of_range(arg, "its time is past the supported range")
+        })?;
+        println!("{:<8} {}", "id", id.0);
+        print_time(ctx, "time", time);
+        let worker = id.worker();
+        println!(
+            "{:<8} {} (as 5 bits each: {} and {})",
+            "worker",
+            worker,
+            worker >> 5,
+            worker & 0x1f
+        );
+        println!("{:<8} {}", "sequence", id.sequence());
+    }
+    Ok(())
+}
@@ -0,0 +1,87 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates sqlite`: a time as SQLite's date and time functions see it
+//!
+//! This prints what `julianday()`, `unixepoch()`, and `datetime()` would
+//! return for a time, for checking date math done in SQL.  The time may be
+//! given in SQLite's own text format, like "2024-03-01 12:00:00", which (as
+//! in SQLite) is in UTC, or as a Julian day number or Unix time, as SQLite
+//! reads them.  See [`dates_tool::sqlite`] for the details.
+
+use crate::{UsageError, print_time};
+use anyhow::bail;
+use dates_tool::parser::{Registry, Strftime};
+use dates_tool::{Error, context, sqlite};
+
+/// Entry point for `dates sqlite [TIME]`, `dates sqlite --julianday JD`, and
+/// `dates sqlite --unixepoch SECONDS`
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let (input, time) = match args {
+        [] => (ctx.now().to_rfc3339(), ctx.now()),
+        [flag, value] if flag == "--julianday" => {
+            let jd: f64 = value.parse().map_err(|_| {
+                Error::unrecognized(value, "a Julian day number")
+            })?;
+            let time = sqlite::from_julian_day(jd)
+                .ok_or_else(|| Error::out_of_range(value, RANGE))?;
+            (value.clone(), time)
+        }
+        [flag, value] if flag == "--unixepoch" => {
+            let seconds: f64 = value.parse().map_err(|_| {
+                Error::unrecognized(value, "a number of seconds")
+            })?;
+            let time =
+                sqlite::from_julian_day(seconds / 86_400.0 + 2_440_587.5)
+                    .ok_or_else(|| Error::out_of_range(value, RANGE))?;
+            (value.clone(), time)
+        }
+        [flag] if flag.starts_with("--") => {
+            bail!(UsageError::new(format!(
+                "sqlite: {flag} requires an argument"
+            )))
+        }
+        [value] => {
+            // SQLite's own formats have no offset, and mean UTC.
+            let mut registry = Registry::empty();
+            registry
+                .register(Strftime::from_static(
+                    "sqlite",
+                    "%Y-%m-%d %H:%M:%S%.f",
+                ))
+                .register(Strftime::from_static(
+                    "sqlite-minutes",
+                    "%Y-%m-%d %H:%M",
+                ))
+                .register(Strftime::from_static(
+                    "sqlite-t",
+                    "%Y-%m-%dT%H:%M:%S%.f",
+                ))
+                .register_builtin();
+            (value.clone(), registry.parse(value)?)
+        }
+        _ => bail!("sqlite: expected at most one time"),
+    };
+
+    let values = (
+        sqlite::truncate(time),
+        sqlite::julian_day(time),
+        sqlite::unix_epoch(time),
+        sqlite::datetime(time, false),
+        sqlite::datetime(time, true),
+    );
+    let (Some(time), Some(jd), Some(unix), Some(text), Some(subsec)) = values
+    else {
+        return Err(Error::out_of_range(&input, RANGE).into());
+    };
+    print_time(ctx, "time", time);
+    println!("{:<8} {} (julianday)", "julian", sqlite::format_julian_day(jd));
+    println!("{:<8} {} (unixepoch)", "unix", unix);
+    println!("{:<8} {} (datetime)", "text", text);
+    println!("{:<8} {} (strftime with %f)", "subsec", subsec);
+    Ok(())
+}
+
+/// Why a time can't be used with SQLite
+const RANGE: &str = "SQLite only supports years 0000 through 9999";
@@ -0,0 +1,211 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates strace`: converts the times in strace output
+//!
+//! `strace -ttt` starts each line with the time as seconds since 1970, like
+//! "1709294400.123456", and `strace -r` with the time since the previous
+//! line, like "     0.000123".  This copies strace output from stdin,
+//! replacing each line's time with the other form, or with the time in the
+//! local zone (the default), as for lining a trace up with an application's
+//! logs.  Lines may start with a process id, as with `-f`.  (A `-ttt` time
+//! can also be given anywhere else a time is, and is read exactly.)
+//!
+//! Each line's form is detected by itself: a time of more than 10^8 seconds
+//! (about three years) is a `-ttt` time.  `-r` times only make sense
+//! relative to when the trace started, so converting them to anything else
+//! requires `--start`.
+
+use crate::{ReadOptions, UsageError};
+use anyhow::{Context, bail};
+use chrono::{DateTime, Utc};
+use dates_tool::context;
+use std::io::{BufRead, Write};
+use std::ops::Range;
+
+/// The smallest `-ttt` time, in seconds
+const MIN_ABSOLUTE: i128 = 100_000_000;
+
+/// The forms of time this can write
+#[derive(Clone, Copy)]
+enum Form {
+    /// seconds since 1970, as `-ttt` prints
+    Absolute,
+    /// seconds since the previous line, as `-r` prints
+    Relative,
+    /// RFC 3339, in the local zone
+    Time,
+}
+
+/// Entry point for `dates strace [--to ttt|r|time] [--start TIME]`
+///
+/// The start is read as `dates TIME` reads times, with the same options.
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let mut options = ReadOptions::default();
+    let mut form = Form::Time;
+    let mut start = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--to" => {
+                let value = args.next().with_context(|| {
+                    UsageError::new("strace: --to requires an argument")
+                })?;
+                form = match value.as_str() {
+                    "ttt" => Form::Absolute,
+                    "r" => Form::Relative,
+                    "time" => Form::Time,
+                    _ => bail!("strace: --to must be ttt, r, or time"),
+                };
+            }
+            "--start" => {
+                let value = args.next().with_context(|| {
+                    UsageError::new("strace: --start requires an argument")
+                })?;
+                start = Some(value);
+            }
+            _ if options.parse_option(arg, &mut args)? => (),
+            _ => bail!(UsageError::new(format!(
+                "strace: unexpected argument {arg:?}"
+            ))),
+        }
+    }
+    let reader = options.reader(ctx)?;
+    let start = start.map(|s| reader.parse(s)).transpose()?;
+    filter(std::io::stdin().lock(), form, start, ctx)
+}
+
+/// Copies strace output from `input` to stdout, replacing each line's time
+/// with `form`.  `start` is when the trace started, for `-r` times.
+fn filter(
+    mut input: impl BufRead,
+    form: Form,
+    start: Option<DateTime<Utc>>,
+    ctx: &context::Context,
+) -> anyhow::Result<()> {
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    let start = start.map(|t| {
+        i128::from(t.timestamp()) * 1_000_000_000
+            + i128::from(t.timestamp_subsec_nanos())
+    });
+    // the time of the previous line, in nanoseconds since 1970
+    let mut previous = None;
+    let mut line = Vec::new();
+    for number in 1.. {
+        line.clear();
+        if input.read_until(b'\n', &mut line).context("reading stdin")? == 0 {
+            break;
+        }
+        let Some(stamp) = stamp(&line) else {
+            out.write_all(&line)?;
+            continue;
+        };
+        let absolute = stamp.nanos >= MIN_ABSOLUTE * 1_000_000_000;
+        let now = match (absolute, previous.or(start)) {
+            (true, _) => Some(stamp.nanos),
+            (false, Some(base)) => Some(base + stamp.nanos),
+            (false, None) => None,
+        };
+        let text = match (form, now) {
+            (Form::Relative, _) if !absolute => None,
+            (Form::Relative, Some(now)) => {
+                let delta = previous.map_or(0, |p| now - p);
+                // strace pads the whole seconds to six digits.
+                let width = 7 + stamp.digits;
+                Some(format!("{:>width$}", seconds(delta, stamp.digits)))
+            }
+            (Form::Absolute, _) if absolute => None,
+            (Form::Absolute, Some(now)) => Some(seconds(now, stamp.digits)),
+            (Form::Time, Some(now)) => {
+                let secs = now.div_euclid(1_000_000_000);
+                let time = i64::try_from(secs).ok().and_then(|secs| {
+                    let nanos = now.rem_euclid(1_000_000_000) as u32;
+                    DateTime::from_timestamp(secs, nanos)
+                });
+                match time {
+                    Some(time) => Some(ctx.local().rfc3339(time)),
+                    None => bail!("strace: line {number}: time out of range"),
+                }
+            }
+            (_, None) => bail!(
+                "strace: line {number}: --start is required to convert -r \
+                 times"
+            ),
+        };
+        previous = now;
+        match text {
+            Some(text) => {
+                out.write_all(&line[..stamp.range.start])?;
+                if stamp.range.start > 0 {
+                    out.write_all(b" ")?;
+                }
+                out.write_all(text.as_bytes())?;
+                out.write_all(&line[stamp.range.end..])?;
+            }
+            None => out.write_all(&line)?,
+        }
+    }
+    Ok(())
+}
+
+/// The time at the start of a line of strace output
+struct Stamp {
+    /// where the time is, including any spaces before it (but not the
+    /// process id)
+    range: Range<usize>,
+    /// the time, in nanoseconds
+    nanos: i128,
+    /// the number of digits after the decimal point
+    digits: usize,
+}
+
+/// Finds the time at the start of a line of strace output, which may follow
+/// a process id (like "[pid  1234]", or "1234" in a file written with `-o`).
+fn stamp(line: &[u8]) -> Option<Stamp> {
+    let spaces = |s: &[u8], i: usize| {
+        i + s[i..].iter().take_while(|b| **b == b' ').count()
+    };
+    let token = |s: &[u8], i: usize| {
+        i + s[i..].iter().take_while(|b| !b.is_ascii_whitespace()).count()
+    };
+    let mut start = 0;
+    if line.starts_with(b"[pid") {
+        start = line.iter().position(|&b| b == b']')? + 1;
+    } else {
+        let end = token(line, spaces(line, 0));
+        if line[..end].iter().all(|b| *b == b' ' || b.is_ascii_digit())
+            && line.get(end) == Some(&b' ')
+        {
+            start = end;
+        }
+    }
+    let begin = spaces(line, start);
+    let end = token(line, begin);
+    let text = std::str::from_utf8(&line[begin..end]).ok()?;
+    let (whole, fraction) = text.split_once('.')?;
+    if whole.is_empty()
+        || fraction.is_empty()
+        || fraction.len() > 9
+        || !whole.bytes().chain(fraction.bytes()).all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    let whole: i128 = whole.parse().ok()?;
+    let fraction: i128 = format!("{fraction:0<9}").parse().ok()?;
+    Some(Stamp {
+        range: start..end,
+        nanos: whole.checked_mul(1_000_000_000)? + fraction,
+        digits: text.len() - text.find('.')? - 1,
+    })
+}
+
+/// Formats `nanos` as seconds with `digits` digits after the decimal point
+/// (truncated), as strace does.
+fn seconds(nanos: i128, digits: usize) -> String {
+    let sign = if nanos < 0 { "-" } else { "" };
+    let nanos = nanos.unsigned_abs();
+    let fraction = nanos % 1_000_000_000 / 10u128.pow(9 - digits as u32);
+    format!("{sign}{}.{fraction:0digits$}", nanos / 1_000_000_000)
+}
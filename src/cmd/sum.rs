@@ -0,0 +1,197 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates sum`: add up durations, like the lines of a timesheet
+//!
+//! Each duration may be a delta as `dates` accepts it (with or without its
+//! sign, like "1h30m" or "-15m"), a clock reading like "1:30" or "0:45:10.5"
+//! (hours, minutes, and optionally seconds), or an ISO 8601 duration like
+//! "PT1H30M" without years or months.  On stdin, each line is a duration,
+//! optionally preceded by a date (for `--daily`) and followed by a label,
+//! which is ignored.
+
+use crate::print_delta;
+use anyhow::{Context, bail};
+use chrono::{Duration, NaiveDate};
+use dates_tool::context;
+use dates_tool::delta::CalendarDelta;
+use dates_tool::{Error, parse_duration};
+use std::collections::BTreeMap;
+use std::io::BufRead;
+
+/// One duration to add, and the date it was given for, if any
+struct Item {
+    date: Option<NaiveDate>,
+    delta: Duration,
+}
+
+/// Entry point for `dates sum [--daily] [--mean] [DURATION...]`
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let mut daily = false;
+    let mut mean = false;
+    let mut values = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "--daily" => daily = true,
+            "--mean" => mean = true,
+            _ => values.push(arg),
+        }
+    }
+
+    let items = if values.is_empty() {
+        read_items(ctx, std::io::stdin().lock())?
+    } else {
+        values
+            .iter()
+            .map(|v| Ok(Item { date: None, delta: parse_item(v)? }))
+            .collect::<anyhow::Result<Vec<_>>>()?
+    };
+    if items.is_empty() {
+        bail!("sum: no durations given");
+    }
+
+    let too_large = || anyhow::anyhow!("sum: the total is too large");
+    let mut total = Duration::zero();
+    for item in &items {
+        total = total.checked_add(&item.delta).ok_or_else(too_large)?;
+    }
+
+    // With --daily, the mean is over days rather than lines, and the labels
+    // are wide enough for a date.
+    let mut count = items.len();
+    let width = if daily { 10 } else { 8 };
+    let label = |s: &str| format!("{s:<width$}");
+    if daily {
+        let mut days = BTreeMap::new();
+        for (i, item) in items.iter().enumerate() {
+            let Some(date) = item.date else {
+                bail!(
+                    "sum: line {} has no date, which --daily requires",
+                    i + 1
+                );
+            };
+            let day = days.entry(date).or_insert_with(Duration::zero);
+            *day = day.checked_add(&item.delta).ok_or_else(too_large)?;
+        }
+        for (date, delta) in &days {
+            print_delta(&date.to_string(), *delta);
+        }
+        count = days.len();
+    }
+    print_delta(&label("total"), total);
+    if mean {
+        let n = i32::try_from(count).context("sum: too many durations")?;
+        println!("{} {:>20}", label("count"), count);
+        print_delta(&label("mean"), total / n);
+    }
+    Ok(())
+}
+
+fn read_items(
+    ctx: &context::Context,
+    input: impl BufRead,
+) -> anyhow::Result<Vec<Item>> {
+    let mut items = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        let line = line.context("reading stdin")?;
+        let mut fields = line.split_whitespace();
+        let Some(first) = fields.next() else {
+            continue;
+        };
+        let item = match parse_item(first) {
+            Ok(delta) => Ok(Item { date: None, delta }),
+            // The first field may instead be the date of the second.
+            Err(error) => match (fields.next(), ctx.parse_date(first)) {
+                (Some(second), Ok(date)) => parse_item(second)
+                    .map(|delta| Item { date: Some(date), delta }),
+                _ => Err(error),
+            },
+        };
+        let item = item.with_context(|| format!("line {}", i + 1))?;
+        items.push(item);
+    }
+    Ok(items)
+}
+
+/// Parses one duration, in any of the forms described in the [module
+/// documentation](self).
+fn parse_item(s: &str) -> Result<Duration, Error> {
+    if s.contains(':') {
+        return parse_clock(s);
+    }
+    if s.starts_with('P') {
+        let delta = CalendarDelta::parse_iso8601(s)?;
+        if !delta.is_exact() {
+            return Err(Error::unrecognized(
+                s,
+                "a duration without years or months, which have no fixed \
+                 length",
+            ));
+        }
+        return Ok(delta.exact);
+    }
+    if s.starts_with(['+', '-']) {
+        parse_duration(s)
+    } else {
+        parse_duration(&format!("+{s}")).map_err(|_| {
+            Error::unrecognized(s, "a duration (like \"1h30m\" or \"1:30\")")
+        })
+    }
+}
+
+/// Parses a clock reading like "1:30" or "-0:45:10.5" as hours, minutes, and
+/// optionally seconds.
+fn parse_clock(s: &str) -> Result<Duration, Error> {
+    let expected = "a duration like \"1:30\" or \"1:30:00\"";
+    let (negative, rest) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+    let mut fields = rest.split(':');
+    let hours = fields.next().unwrap_or("");
+    let minutes = fields.next().unwrap_or("");
+    let seconds = fields.next();
+    let digits =
+        |f: &str| !f.is_empty() && f.bytes().all(|b| b.is_ascii_digit());
+    if fields.next().is_some()
+        || !digits(hours)
+        || minutes.len() != 2
+        || !digits(minutes)
+    {
+        return Err(Error::unrecognized(s, expected));
+    }
+    let (whole, fraction) = match seconds {
+        None => ("00", ""),
+        Some(seconds) => seconds.split_once('.').unwrap_or((seconds, "")),
+    };
+    if whole.len() != 2
+        || !digits(whole)
+        || !fraction.bytes().all(|b| b.is_ascii_digit())
+    {
+        return Err(Error::unrecognized(s, expected));
+    }
+    let (minutes, whole): (i64, i64) =
+        (minutes.parse().unwrap(), whole.parse().unwrap());
+    if minutes >= 60 || whole >= 60 {
+        return Err(Error::out_of_range(
+            s,
+            "minutes and seconds must be less than 60",
+        ));
+    }
+    let nanos = fraction
+        .bytes()
+        .chain(std::iter::repeat(b'0'))
+        .take(9)
+        .fold(0, |n, b| n * 10 + i64::from(b - b'0'));
+    let delta = hours
+        .parse::<i64>()
+        .ok()
+        .and_then(Duration::try_hours)
+        .and_then(|d| {
+            d.checked_add(&Duration::seconds(minutes * 60 + whole))?
+                .checked_add(&Duration::nanoseconds(nanos))
+        })
+        .ok_or_else(|| Error::out_of_range(s, "too large for a delta"))?;
+    Ok(if negative { -delta } else { delta })
+}
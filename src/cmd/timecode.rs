@@ -0,0 +1,81 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates timecode`: converts SMPTE timecodes, frame counts, and durations
+
+use crate::{UsageError, print_delta};
+use anyhow::{Context, bail};
+use dates_tool::timecode::Rate;
+use dates_tool::{Error, parse_duration};
+
+/// Entry point for `dates timecode --fps RATE VALUE...`
+///
+/// Each VALUE is a timecode like "01:02:03:12" (or "01:02:03;12", for
+/// drop-frame), a whole number of frames, or a duration like "1h2m3s".
+pub fn run(args: &[String]) -> anyhow::Result<()> {
+    let mut rate = None;
+    let mut values = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--fps" => {
+                let value = args.next().with_context(|| {
+                    UsageError::new("timecode: --fps requires an argument")
+                })?;
+                rate = Some(Rate::parse(value)?);
+            }
+            _ => values.push(arg.as_str()),
+        }
+    }
+    let Some(rate) = rate else {
+        bail!("timecode: --fps is required");
+    };
+    if values.is_empty() {
+        bail!(
+            "timecode: expected at least one timecode, frame count, or duration"
+        );
+    }
+
+    let frames = values
+        .iter()
+        .map(|value| frame_of(&rate, value))
+        .collect::<Result<Vec<_>, _>>()?;
+    for (i, (value, frame)) in values.into_iter().zip(frames).enumerate() {
+        let duration = rate.duration(frame).ok_or_else(|| {
+            Error::out_of_range(value, "too long for a duration")
+        })?;
+        if i > 0 {
+            println!();
+        }
+        println!("{:<8} {}", "timecode", rate.timecode(frame)?);
+        println!("{:<8} {frame} (at {rate} frames per second)", "frames");
+        print_delta("duration", duration);
+    }
+    Ok(())
+}
+
+/// Returns the number of the frame that `value` is (as a timecode) or starts
+/// at (as a number of frames or a duration).
+fn frame_of(rate: &Rate, value: &str) -> Result<i64, Error> {
+    if value.contains([':', ';']) {
+        return rate.parse_timecode(value);
+    }
+    if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) {
+        return value
+            .parse()
+            .map_err(|_| Error::out_of_range(value, "too many frames"));
+    }
+    let duration = if value.starts_with(['+', '-']) {
+        parse_duration(value)?
+    } else {
+        parse_duration(&format!("+{value}")).map_err(|_| {
+            Error::unrecognized(
+                value,
+                "a timecode, a number of frames, or a duration",
+            )
+        })?
+    };
+    rate.frame_at(duration)
+        .ok_or_else(|| Error::out_of_range(value, "too long for a timecode"))
+}
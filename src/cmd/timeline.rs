@@ -0,0 +1,147 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates timeline`: draw several timestamps on a scaled ASCII axis
+
+use crate::{ReadOptions, Reader};
+use anyhow::{Context, bail};
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use dates_tool::{context, format_breakdown};
+use std::io::BufRead;
+
+/// Number of columns used for the axis itself (labels may extend past it)
+const AXIS_WIDTH: usize = 64;
+
+struct Event {
+    time: DateTime<Utc>,
+    label: Option<String>,
+}
+
+/// Entry point for `dates timeline [TIME...]`
+///
+/// With no arguments, events are read from stdin, one per line.  Each line
+/// is a timestamp optionally followed by whitespace and a label.  Times are
+/// read as `dates TIME` reads them, with the same options.
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let mut options = ReadOptions::default();
+    let mut inputs = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if !options.parse_option(arg, &mut args)? {
+            inputs.push(arg.as_str());
+        }
+    }
+    let reader = options.reader(ctx)?;
+    let mut events = if inputs.is_empty() {
+        read_events(&reader, std::io::stdin().lock())?
+    } else {
+        inputs
+            .iter()
+            .map(|a| {
+                let time = reader.parse(a)?;
+                Ok(Event { time, label: None })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?
+    };
+
+    if events.is_empty() {
+        bail!("timeline: no timestamps given");
+    }
+
+    events.sort_by_key(|e| e.time);
+    print!("{}", render(&events));
+    Ok(())
+}
+
+fn read_events(
+    reader: &Reader,
+    input: impl BufRead,
+) -> anyhow::Result<Vec<Event>> {
+    let mut events = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        let line = line.context("reading stdin")?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let (time_str, label) = match line.split_once(char::is_whitespace) {
+            Some((t, l)) => (t, Some(l.trim().to_string())),
+            None => (line, None),
+        };
+        let time = reader
+            .parse(time_str)
+            .with_context(|| format!("line {}", i + 1))?;
+        events.push(Event { time, label });
+    }
+    Ok(events)
+}
+
+/// Renders sorted `events` as an axis followed by one row per event.
+///
+/// Rows are emitted starting from the latest event so that each row's label
+/// only ever extends over columns whose events have already been labeled.
+fn render(events: &[Event]) -> String {
+    let first = events[0].time;
+    let last = events[events.len() - 1].time;
+    // Seconds as floating-point cover the whole range of times, unlike
+    // microseconds as an integer.
+    let span = seconds(last - first);
+
+    let columns: Vec<usize> = events
+        .iter()
+        .map(|e| {
+            if span == 0.0 {
+                return 0;
+            }
+            let fraction = seconds(e.time - first) / span;
+            (fraction * (AXIS_WIDTH - 1) as f64).round() as usize
+        })
+        .collect();
+
+    let mut out = String::new();
+    let mut axis = vec!['-'; AXIS_WIDTH];
+    for &c in &columns {
+        axis[c] = '*';
+    }
+    out.extend(axis);
+    out.push('\n');
+    out.push_str(stems(&columns, AXIS_WIDTH).trim_end());
+    out.push('\n');
+
+    for (i, event) in events.iter().enumerate().rev() {
+        let col = columns[i];
+        let mut row = stems(&columns[..i], col);
+        row.push_str("+- ");
+        row.push_str(&event.time.to_rfc3339_opts(SecondsFormat::Micros, true));
+        if i > 0 {
+            let gap = event.time - events[i - 1].time;
+            row.push_str(&format!("  (+{})", format_breakdown(gap).trim()));
+        }
+        if let Some(label) = &event.label {
+            row.push_str("  ");
+            row.push_str(label);
+        }
+        out.push_str(&row);
+        out.push('\n');
+    }
+
+    out.push_str(&format!("span {}\n", format_breakdown(last - first).trim()));
+    out
+}
+
+/// Returns `delta` in (fractional) seconds.
+fn seconds(delta: Duration) -> f64 {
+    delta.num_seconds() as f64 + f64::from(delta.subsec_nanos()) / 1e9
+}
+
+/// Returns a `width`-character row that is blank except for a `|` at each of
+/// `columns` that falls within it.
+fn stems(columns: &[usize], width: usize) -> String {
+    let mut row = vec![' '; width];
+    for &c in columns.iter().filter(|&&c| c < width) {
+        row[c] = '|';
+    }
+    row.into_iter().collect()
+}
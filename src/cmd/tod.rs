@@ -0,0 +1,151 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates tod`: IBM mainframe TOD clock values
+//!
+//! The TOD clock of IBM Z (and System/370 before it) is a 64-bit count of
+//! time since 1900-01-01T00:00:00, in which bit 51 (counting from 0 at the
+//! most significant end) is one microsecond, so it's microseconds shifted
+//! left by 12 bits.  It runs out on 2042-09-17.  `STCKE` stores the
+//! extended form, 16 bytes: an epoch index byte counting how many times the
+//! 64-bit clock has wrapped, 13 bytes of which the first 8 are the 64-bit
+//! clock, and a 2-byte programmable field.
+//!
+//! Whether the clock counts leap seconds depends on how it was set.  One set
+//! by STP with a leap second offset runs ahead of UTC by the leap seconds
+//! since 1972 (so it's TAI - 10 seconds), and `--leap-seconds` reads values
+//! that way.  Otherwise they're read as UTC.
+
+use crate::{ReadOptions, Reader, print_time};
+use anyhow::bail;
+use chrono::{DateTime, Duration, Utc};
+use dates_tool::leap;
+use dates_tool::{Error, context};
+
+/// Seconds from 1900-01-01 to the Unix epoch
+const TOD_EPOCH: i64 = 2_208_988_800;
+
+/// Entry point for `dates tod [--leap-seconds] VALUE...`
+///
+/// Each VALUE is a TOD clock value as 16 hexadecimal digits, or an extended
+/// one as 32 (either of which may be separated by spaces or colons, as a
+/// dump shows them), or a time to convert to one, which is read as `dates
+/// TIME` reads times, with the same options.
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let mut options = ReadOptions::default();
+    let mut leap_seconds = false;
+    let mut values = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--leap-seconds" => leap_seconds = true,
+            _ if options.parse_option(arg, &mut args)? => (),
+            _ => values.push(arg.as_str()),
+        }
+    }
+    let reader = options.reader(ctx)?;
+    if values.is_empty() {
+        bail!("tod: expected at least one TOD clock value");
+    }
+    let ticks = values
+        .iter()
+        .map(|value| match parse_tod(value) {
+            Some(ticks) => Ok(ticks),
+            None => from_utc(&reader, value, leap_seconds),
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    for (i, ticks) in ticks.into_iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        match u64::try_from(ticks) {
+            Ok(tod) => println!("{:<8} {tod:016x}", "tod"),
+            Err(_) => println!("{:<8} out of range after 2042-09-17", "tod"),
+        }
+        // The extended form has 40 more bits of the clock, which are always
+        // zero here, followed by the programmable field.
+        println!("{:<8} {:018x}{:014x}", "stcke", ticks, 0);
+        let clock = DateTime::from_timestamp(-TOD_EPOCH, 0).unwrap()
+            + Duration::microseconds((ticks >> 12) as i64)
+            + Duration::nanoseconds((((ticks & 0xfff) * 1000) >> 12) as i64);
+        let utc = if leap_seconds {
+            let utc = tod_to_utc(clock)?;
+            println!(
+                "{:<8} {}s ahead of UTC",
+                "leap",
+                (clock - utc).num_seconds()
+            );
+            utc
+        } else {
+            clock
+        };
+        print_time(ctx, "time", utc);
+    }
+    Ok(())
+}
+
+/// Parses a TOD clock value, returning the number of units of 2^-12
+/// microseconds since 1900, or `None` if `s` isn't one.
+fn parse_tod(s: &str) -> Option<u128> {
+    let hex: String = s
+        .strip_prefix("0x")
+        .unwrap_or(s)
+        .chars()
+        .filter(|c| *c != ' ' && *c != ':')
+        .collect();
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    match hex.len() {
+        16 => u128::from_str_radix(&hex, 16).ok(),
+        // The epoch index and the 64-bit clock, without the extra bits of
+        // precision or the programmable field
+        32 => u128::from_str_radix(&hex[..18], 16).ok(),
+        _ => None,
+    }
+}
+
+/// Returns the TOD clock value for a time in any other form, read by
+/// `reader`.
+fn from_utc(
+    reader: &Reader,
+    s: &str,
+    leap_seconds: bool,
+) -> anyhow::Result<u128> {
+    let utc = reader.parse(s)?;
+    let clock = if leap_seconds && utc >= leap_seconds_start() {
+        leap::utc_to_tai(utc)?.and_utc() - Duration::seconds(10)
+    } else {
+        utc
+    };
+    let seconds = u128::try_from(clock.timestamp() + TOD_EPOCH)
+        .map_err(|_| Error::out_of_range(s, "before the TOD clock's epoch"))?;
+    let nanos = u128::from(clock.timestamp_subsec_nanos());
+    let micros = seconds * 1_000_000 + nanos / 1000;
+    // Round the rest up, so that it reads back as the same nanosecond.
+    let ticks = micros << 12 | ((nanos % 1000) << 12).div_ceil(1000);
+    if ticks >> 72 != 0 {
+        return Err(
+            Error::out_of_range(s, "after the TOD clock runs out").into()
+        );
+    }
+    Ok(ticks)
+}
+
+/// Returns the UTC time at which a clock that counts leap seconds reads
+/// `clock`.
+fn tod_to_utc(clock: DateTime<Utc>) -> Result<DateTime<Utc>, Error> {
+    // Before leap seconds, the clock is just UTC.
+    if clock < leap_seconds_start() {
+        return Ok(clock);
+    }
+    leap::tai_to_utc((clock + Duration::seconds(10)).naive_utc())
+}
+
+/// Returns when UTC started having leap seconds.
+fn leap_seconds_start() -> DateTime<Utc> {
+    // 1972-01-01T00:00:00Z
+    DateTime::from_timestamp(63_072_000, 0).unwrap()
+}
@@ -0,0 +1,79 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates totp-step`: the time step of a TOTP code
+//!
+//! A TOTP code (RFC 6238) is an HOTP code (RFC 4226) for a counter that
+//! counts steps of a fixed period (almost always 30 seconds) since the Unix
+//! epoch.  Both sides compute the counter from their own clocks, so a code
+//! is rejected when the clocks are a step or more apart at the moment it's
+//! checked, unless the verifier also accepts the steps on either side (as
+//! most do).
+
+use crate::{ReadOptions, UsageError, print_delta, print_time};
+use anyhow::{Context, bail};
+use chrono::{DateTime, Duration};
+use dates_tool::{Error, context};
+
+/// Entry point for `dates totp-step [--period SECONDS] [TIME]`
+///
+/// TIME is read as `dates TIME` reads times, with the same options.
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let mut options = ReadOptions::default();
+    let mut period: i64 = 30;
+    let mut time = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--period" => {
+                let value = args.next().with_context(|| {
+                    UsageError::new("totp-step: --period requires an argument")
+                })?;
+                period = value
+                    .strip_suffix('s')
+                    .unwrap_or(value)
+                    .parse()
+                    .ok()
+                    .filter(|&p| p > 0)
+                    .ok_or_else(|| {
+                        Error::unrecognized(value, "a period in seconds")
+                    })?;
+            }
+            _ if options.parse_option(arg, &mut args)? => (),
+            _ if time.is_none() => time = Some(arg.as_str()),
+            _ => bail!(UsageError::new(format!(
+                "totp-step: unexpected argument {arg:?}"
+            ))),
+        }
+    }
+    let reader = options.reader(ctx)?;
+    let t = match time {
+        Some(s) => reader.parse(s)?,
+        None => ctx.now(),
+    };
+
+    // Steps are whole seconds, and a time before 1970 is in a negative step.
+    let counter = t.timestamp().div_euclid(period);
+    let start = DateTime::from_timestamp(counter * period, 0)
+        .context("totp-step: time out of range")?;
+    let next = start + Duration::seconds(period);
+    print_time(ctx, "time", t);
+    // The counter is hashed as 8 bytes, big-endian.
+    println!("{:<8} {counter} (0x{counter:016x})", "counter");
+    println!(
+        "{:<8} {:.3} s of {period} s",
+        "position",
+        (t - start).num_milliseconds() as f64 / 1000.0
+    );
+    print_time(ctx, "step", start);
+    print_time(ctx, "next", next);
+    print_delta("left", next - t);
+    println!(
+        "{:<8} {} to {} (with one step either side)",
+        "window",
+        counter - 1,
+        counter + 1
+    );
+    Ok(())
+}
@@ -0,0 +1,147 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates tsc`: converts timestamp counter values to times
+//!
+//! The timestamp counter (`rdtsc` on x86, `cntvct_el0` on ARM) counts cycles
+//! of a fixed frequency, which this needs to be told.  By itself, a count of
+//! cycles is a duration.  Given one pair of a counter value and the
+//! wall-clock time at the same moment, a counter value is a time.
+
+use crate::{ReadOptions, UsageError, print_delta, print_time};
+use anyhow::{Context, bail};
+use chrono::Duration;
+use dates_tool::{Error, context};
+
+/// Entry point for `dates tsc --freq FREQ [--anchor TSC=WALLTIME] CYCLES...`
+///
+/// WALLTIME is read as `dates TIME` reads times, with the same options.
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let mut options = ReadOptions::default();
+    let mut hz = None;
+    let mut anchor = None;
+    let mut values = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--freq" => {
+                let value = args.next().with_context(|| {
+                    UsageError::new("tsc: --freq requires an argument")
+                })?;
+                hz = Some(parse_frequency(value).ok_or_else(|| {
+                    Error::unrecognized(value, "a frequency (like 2.4GHz)")
+                })?);
+            }
+            "--anchor" => {
+                let value = args.next().with_context(|| {
+                    UsageError::new("tsc: --anchor requires an argument")
+                })?;
+                anchor = Some(value);
+            }
+            _ if options.parse_option(arg, &mut args)? => (),
+            _ => values.push(arg.as_str()),
+        }
+    }
+    let reader = options.reader(ctx)?;
+    let anchor = match anchor {
+        Some(value) => {
+            let Some((tsc, wall)) = value.split_once('=') else {
+                bail!("tsc: --anchor {value:?} isn't TSC=WALLTIME");
+            };
+            Some((parse_cycles(tsc)?, reader.parse(wall)?))
+        }
+        None => None,
+    };
+    let Some(hz) = hz else {
+        bail!("tsc: --freq is required");
+    };
+    if values.is_empty() {
+        bail!("tsc: expected at least one counter value");
+    }
+    let cycles = values
+        .iter()
+        .map(|value| parse_cycles(value))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if let Some((tsc, wall)) = anchor {
+        print_time(ctx, "anchor", wall);
+        for (value, cycles) in values.into_iter().zip(cycles) {
+            let (delta, time) = elapsed(cycles - tsc, hz)
+                .and_then(|d| Some((d, wall.checked_add_signed(d)?)))
+                .ok_or_else(|| {
+                    Error::out_of_range(value, "too far from the anchor")
+                })?;
+            println!();
+            print_delta("offset", delta);
+            print_time(ctx, "time", time);
+        }
+    } else {
+        for (i, (value, cycles)) in values.into_iter().zip(cycles).enumerate() {
+            let delta = elapsed(cycles, hz).ok_or_else(|| {
+                Error::out_of_range(value, "too many cycles for a duration")
+            })?;
+            if i > 0 {
+                println!();
+            }
+            println!("{:<8} {cycles}", "cycles");
+            print_delta("duration", delta);
+            // Counts of cycles are often less than a microsecond.
+            if let Some(nanos) = delta.num_nanoseconds() {
+                println!("{:<8} {nanos} ns", "nanos");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns how long `cycles` cycles at `hz` take, to the nearest nanosecond,
+/// or `None` if that's out of range.
+fn elapsed(cycles: i128, hz: u64) -> Option<Duration> {
+    let hz = i128::from(hz);
+    let nanos = cycles.checked_mul(1_000_000_000)?;
+    let nanos = (2 * nanos + hz).div_euclid(2 * hz);
+    Some(Duration::nanoseconds(i64::try_from(nanos).ok()?))
+}
+
+/// Parses a counter value or a count of cycles, in decimal or (with "0x")
+/// hexadecimal.  A sign is allowed, for a difference of counter values.
+fn parse_cycles(s: &str) -> Result<i128, Error> {
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let parsed = match digits.strip_prefix("0x") {
+        Some(hex) if !hex.starts_with(['+', '-']) => {
+            u64::from_str_radix(hex, 16).ok()
+        }
+        Some(_) => None,
+        None if !digits.starts_with(['+', '-']) => digits.parse::<u64>().ok(),
+        None => None,
+    };
+    let cycles = i128::from(parsed.ok_or_else(|| {
+        Error::unrecognized(s, "a counter value (a 64-bit integer)")
+    })?);
+    Ok(if negative { -cycles } else { cycles })
+}
+
+/// Parses a frequency like "2.4GHz", "2400 MHz", or "2400000000" (in Hz),
+/// returning it in whole Hz.
+fn parse_frequency(s: &str) -> Option<u64> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit() && c != '.');
+    let (number, unit) = s.split_at(split.unwrap_or(s.len()));
+    let scale: u32 = match &unit.trim().to_ascii_lowercase()[..] {
+        "" | "hz" => 0,
+        "khz" => 3,
+        "mhz" => 6,
+        "ghz" => 9,
+        _ => return None,
+    };
+    let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+    if whole.is_empty() || fraction.len() > scale as usize {
+        return None;
+    }
+    let digits = format!("{whole}{fraction:0<width$}", width = scale as usize);
+    digits.parse::<u64>().ok().filter(|&hz| hz > 0)
+}
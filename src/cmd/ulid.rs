@@ -0,0 +1,73 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates ulid`: when a ULID was generated, or ULIDs for a time
+//!
+//! With `--encode`, this makes a ULID for a time, along with the smallest and
+//! largest ULIDs for that millisecond, which bound a range query for rows
+//! created then.  See [`dates_tool::ulid`] for the details.
+
+use crate::{ReadOptions, print_time};
+use anyhow::{Context, bail};
+use dates_tool::Error;
+use dates_tool::context;
+use dates_tool::ulid::Ulid;
+
+/// Entry point for `dates ulid ULID...` and `dates ulid --encode [TIME]`
+///
+/// TIME is read as `dates TIME` reads times, with the same options.
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let mut options = ReadOptions::default();
+    let mut encode = false;
+    let mut inputs = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--encode" => encode = true,
+            _ if options.parse_option(arg, &mut iter)? => (),
+            _ => inputs.push(arg),
+        }
+    }
+
+    if encode {
+        let reader = options.reader(ctx)?;
+        let (input, time) = match inputs.as_slice() {
+            [] => (ctx.now().to_rfc3339(), ctx.now()),
+            [input] => (input.to_string(), reader.parse(input)?),
+            _ => bail!("ulid: --encode takes at most one time"),
+        };
+        let mut random = [0; 16];
+        getrandom::fill(&mut random).context("ulid: getting random bits")?;
+        let (Some(ulid), Some(min), Some(max)) = (
+            Ulid::new(time, u128::from_le_bytes(random)),
+            Ulid::min(time),
+            Ulid::max(time),
+        ) else {
+            return Err(Error::out_of_range(
+                &input,
+                "a ULID can only hold times from 1970 to 10889",
+            )
+            .into());
+        };
+        print_time(ctx, "time", ulid.timestamp());
+        println!("{:<8} {}", "ulid", ulid);
+        println!("{:<8} {}", "min", min);
+        println!("{:<8} {}", "max", max);
+        return Ok(());
+    }
+
+    if inputs.is_empty() {
+        bail!("ulid: expected a ULID");
+    }
+    for (i, arg) in inputs.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        let ulid = Ulid::parse(arg)?;
+        println!("{:<8} {}", "ulid", ulid);
+        print_time(ctx, "time", ulid.timestamp());
+        println!("{:<8} {:020x}", "random", ulid.random());
+    }
+    Ok(())
+}
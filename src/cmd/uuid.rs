@@ -0,0 +1,96 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates uuid`: when a UUID was generated
+//!
+//! This decodes the time embedded in version 1, 6, and 7 UUIDs, along with
+//! the clock sequence and node that distinguish version 1 and 6 UUIDs
+//! generated at the same time.  With `--generate`, it makes a version 7 UUID
+//! for a given time instead.  See [`dates_tool::uuid`] for the details.
+
+use crate::{ReadOptions, print_time};
+use anyhow::{Context, bail};
+use dates_tool::Error;
+use dates_tool::context;
+use dates_tool::uuid::Uuid;
+
+/// Entry point for `dates uuid UUID...` and `dates uuid --generate [TIME]`
+///
+/// TIME is read as `dates TIME` reads times, with the same options.
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let mut options = ReadOptions::default();
+    let mut generate = false;
+    let mut inputs = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--generate" => generate = true,
+            _ if options.parse_option(arg, &mut iter)? => (),
+            _ => inputs.push(arg),
+        }
+    }
+
+    if generate {
+        let reader = options.reader(ctx)?;
+        let time = match inputs.as_slice() {
+            [] => ctx.now(),
+            [input] => reader.parse(input)?,
+            _ => bail!("uuid: --generate takes at most one time"),
+        };
+        let mut random = [0; 16];
+        getrandom::fill(&mut random).context("uuid: getting random bits")?;
+        let Some(uuid) = Uuid::v7(time, u128::from_le_bytes(random)) else {
+            let input = inputs
+                .first()
+                .map_or_else(|| time.to_string(), |s| s.to_string());
+            return Err(Error::out_of_range(
+                &input,
+                "a version 7 UUID can only hold times from 1970 to 10889",
+            )
+            .into());
+        };
+        return show(ctx, uuid, &uuid.to_string());
+    }
+
+    if inputs.is_empty() {
+        bail!("uuid: expected a UUID");
+    }
+    for (i, arg) in inputs.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        show(ctx, Uuid::parse(arg)?, arg)?;
+    }
+    Ok(())
+}
+
+/// Prints what `uuid`, which was parsed from `input`, says about when it was
+/// generated.
+fn show(ctx: &context::Context, uuid: Uuid, input: &str) -> anyhow::Result<()> {
+    let Some(time) = uuid.timestamp() else {
+        let expected = if uuid.is_rfc9562() {
+            format!(
+                "a UUID with a time in it (version 1, 6, or 7, not {})",
+                uuid.version()
+            )
+        } else {
+            String::from("a UUID of the RFC 9562 variant")
+        };
+        return Err(Error::unrecognized(input, expected).into());
+    };
+    println!("{:<8} {} (version {})", "uuid", uuid, uuid.version());
+    print_time(ctx, "time", time);
+    if uuid.version() == 7 {
+        return Ok(());
+    }
+    let clock = uuid.clock_sequence();
+    println!("{:<8} {} (0x{:04x})", "clock", clock, clock);
+    let node = uuid.node();
+    if node.is_random() {
+        println!("{:<8} {} (random)", "node", node);
+    } else {
+        println!("{:<8} {} (MAC address)", "node", node);
+    }
+    Ok(())
+}
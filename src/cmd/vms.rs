@@ -0,0 +1,147 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates vms`: OpenVMS binary times
+//!
+//! An OpenVMS binary time is a signed 64-bit count of 100-nanosecond units
+//! since 1858-11-17T00:00:00 (the epoch of the Modified Julian Date).  It's
+//! the system's local wall-clock time, with no zone recorded, so it's read
+//! in `--tz` (or the local zone).  A negative binary time is a delta time,
+//! a duration, as for a timer.  VMS prints times like "1-MAR-2024
+//! 12:00:00.00" for absolute times and "1 02:03:04.00" (days, then hours,
+//! minutes, and seconds) for delta times.
+
+use crate::cmd::dos;
+use crate::{ReadOptions, print_delta, print_time};
+use anyhow::bail;
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+use dates_tool::{Error, context};
+
+/// Entry point for `dates vms [--tz ZONE] VALUE...`
+///
+/// Each VALUE is a binary time in decimal or (with "0x") hexadecimal; an
+/// absolute time as VMS prints it, like "1-MAR-2024 12:00:00.00"; or a time
+/// to convert to one, which is read as `dates TIME` reads times, with the
+/// same options.  VMS times have no zone, so they're in `--tz` (or the local
+/// zone).
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let mut options = ReadOptions::default();
+    let mut values = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if !options.parse_option(arg, &mut args)? {
+            values.push(arg.as_str());
+        }
+    }
+    let reader = options.reader(ctx)?;
+    let zone = reader.display_zone;
+    if values.is_empty() {
+        bail!("vms: expected at least one binary time");
+    }
+    let binary = values
+        .iter()
+        .map(|value| {
+            if let Some(binary) = parse_binary(value) {
+                return Ok(binary);
+            }
+            let wall = match parse_text(value) {
+                Some(wall) => wall,
+                None => zone.wall_clock(reader.parse(value)?),
+            };
+            to_binary(wall).ok_or_else(|| {
+                Error::out_of_range(value, "outside VMS's range")
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for (i, binary) in binary.into_iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        println!("{:<8} {binary} (0x{binary:016x})", "vms");
+        let ticks = i128::from(binary);
+        let duration = Duration::seconds((ticks.abs() / 10_000_000) as i64)
+            + Duration::nanoseconds((ticks.abs() % 10_000_000) as i64 * 100);
+        if binary < 0 {
+            println!("{:<8} {}", "text", delta_text(duration));
+            print_delta("delta", duration);
+            continue;
+        }
+        let wall = epoch() + duration;
+        println!("{:<8} {}", "text", absolute_text(wall));
+        println!("{:<8} {}", "wall", wall);
+        print_time(ctx, "time", dos::resolve(zone, wall)?);
+        println!("{:<8} none recorded, so this was read in {}", "offset", zone);
+    }
+    Ok(())
+}
+
+/// Returns the VMS epoch, 1858-11-17T00:00:00.
+fn epoch() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(1858, 11, 17).unwrap().and_hms_opt(0, 0, 0).unwrap()
+}
+
+/// Parses a binary time, returning `None` if `s` isn't one.  In
+/// hexadecimal, it's the 64 bits, so a delta time starts with "f".
+fn parse_binary(s: &str) -> Option<i64> {
+    match s.strip_prefix("0x") {
+        Some(hex) if hex.bytes().all(|b| b.is_ascii_hexdigit()) => {
+            u64::from_str_radix(hex, 16).ok().map(|u| u as i64)
+        }
+        Some(_) => None,
+        None => {
+            let digits = s.strip_prefix('-').unwrap_or(s);
+            if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit())
+            {
+                return None;
+            }
+            s.parse().ok()
+        }
+    }
+}
+
+/// Parses an absolute time as VMS prints it, like "1-MAR-2024 12:00:00.00"
+/// (the hundredths of a second are optional), returning `None` if `s`
+/// isn't one.
+fn parse_text(s: &str) -> Option<NaiveDateTime> {
+    let s = s.trim();
+    NaiveDateTime::parse_from_str(s, "%d-%b-%Y %H:%M:%S%.f")
+        .or_else(|_| NaiveDateTime::parse_from_str(s, "%d-%b-%Y %H:%M:%S"))
+        .ok()
+}
+
+/// Returns the binary time for wall-clock time `wall`, or `None` if it's
+/// out of range.  Fractions of 100 nanoseconds are truncated.
+fn to_binary(wall: NaiveDateTime) -> Option<i64> {
+    let since = wall.and_utc().timestamp() - epoch().and_utc().timestamp();
+    let ticks = i128::from(since) * 10_000_000
+        + i128::from(wall.and_utc().timestamp_subsec_nanos() / 100);
+    i64::try_from(ticks).ok().filter(|&ticks| ticks >= 0)
+}
+
+/// Formats an absolute time as VMS does, like "1-MAR-2024 12:00:00.00".
+fn absolute_text(wall: NaiveDateTime) -> String {
+    // chrono writes years after 9999 with a "+", and VMS's go to 31086.
+    let month = wall.format("%b").to_string().to_uppercase();
+    format!(
+        "{}-{month}-{} {}.{:02}",
+        wall.day(),
+        wall.year(),
+        wall.format("%H:%M:%S"),
+        wall.and_utc().timestamp_subsec_nanos() / 10_000_000
+    )
+}
+
+/// Formats a delta time as VMS does, like "1 02:03:04.00".
+fn delta_text(duration: Duration) -> String {
+    let seconds = duration.num_seconds();
+    let hundredths = duration.subsec_nanos() / 10_000_000;
+    format!(
+        "{} {:02}:{:02}:{:02}.{hundredths:02}",
+        seconds / 86_400,
+        seconds / 3600 % 24,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
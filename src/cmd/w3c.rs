@@ -0,0 +1,188 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates w3c`: the times in W3C extended (IIS) logs
+//!
+//! See [`dates_tool::w3c`] for the format.  Without values, this copies a
+//! log from stdin, replacing each entry's `date` and `time` (which are in
+//! UTC) with the local date and the local time with its offset, so that the
+//! fields still line up with `#Fields`.  With `--csv`, it prints the entries
+//! as CSV instead, with the time as one RFC 3339 column, for loading into a
+//! spreadsheet or database.
+
+use crate::{ReadOptions, Reader, print_time};
+use anyhow::{Context, bail};
+use chrono::{DateTime, Days, NaiveDate, NaiveTime, SecondsFormat, Utc};
+use dates_tool::context;
+use dates_tool::w3c::{Fields, parse_date, parse_date_time, parse_time};
+use std::io::{BufRead, Write};
+
+/// Entry point for `dates w3c [--csv] | DATE TIME`
+///
+/// A log's `#Date` directive is read as `dates TIME` reads times, with the
+/// same options.
+pub fn run(args: &[String], ctx: &context::Context) -> anyhow::Result<()> {
+    let mut options = ReadOptions::default();
+    let mut csv = false;
+    let mut values = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--csv" => csv = true,
+            _ if options.parse_option(arg, &mut iter)? => (),
+            _ => values.push(arg),
+        }
+    }
+    match values[..] {
+        [] => {
+            let reader = options.reader(ctx)?;
+            filter(std::io::stdin().lock(), csv, &reader, ctx)
+        }
+        [date, time] if !csv => {
+            print_time(ctx, "time", parse_date_time(date, time)?);
+            Ok(())
+        }
+        _ => bail!("w3c: expected --csv, or a DATE and TIME"),
+    }
+}
+
+/// The state of a log as it's read: the latest `#Date` and `#Fields`
+/// directives, and the time of the latest entry
+#[derive(Default)]
+struct Log {
+    date: Option<NaiveDate>,
+    fields: Option<Fields>,
+    last: Option<NaiveTime>,
+}
+
+impl Log {
+    /// Returns the time of an entry, given its fields.
+    fn time(&mut self, entry: &[&str]) -> anyhow::Result<DateTime<Utc>> {
+        let Some(fields) = &self.fields else {
+            bail!("w3c: there's no #Fields directive before the first entry");
+        };
+        let field = |i: usize| {
+            entry.get(i).copied().with_context(|| {
+                format!("w3c: entry has no field {}", fields.names[i])
+            })
+        };
+        let time = parse_time(field(fields.time)?)?;
+        let date = match fields.date {
+            Some(i) => parse_date(field(i)?)?,
+            // Without a date field, entries are on the date the log started,
+            // until the time goes backwards past midnight.
+            None => {
+                let Some(date) = self.date else {
+                    bail!("w3c: entries have no date, and there's no #Date");
+                };
+                if self.last.is_some_and(|last| time < last) {
+                    self.date = date.checked_add_days(Days::new(1));
+                }
+                self.date.context("w3c: date out of range")?
+            }
+        };
+        self.last = Some(time);
+        Ok(date.and_time(time).and_utc())
+    }
+}
+
+/// Copies a W3C extended log from `input` to stdout, with each entry's
+/// time in the local zone, or (with `csv`) as CSV.  The `#Date` directive is
+/// read by `reader`.
+fn filter(
+    mut input: impl BufRead,
+    csv: bool,
+    reader: &Reader,
+    ctx: &context::Context,
+) -> anyhow::Result<()> {
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    let mut log = Log::default();
+    let mut header: Option<Vec<String>> = None;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if input.read_line(&mut line).context("reading stdin")? == 0 {
+            return Ok(());
+        }
+        let text = line.trim_end_matches(['\r', '\n']);
+        if text.starts_with('#') {
+            if let Some(fields) = Fields::parse(text) {
+                log.fields = Some(fields?);
+            } else if text.starts_with("#Date:") {
+                let started = reader.parse(text)?;
+                log.date = Some(started.date_naive());
+                log.last = Some(started.time());
+            }
+            if !csv {
+                out.write_all(line.as_bytes())?;
+            }
+            continue;
+        }
+        if text.is_empty() {
+            if !csv {
+                out.write_all(line.as_bytes())?;
+            }
+            continue;
+        }
+
+        let mut entry: Vec<&str> = text.split(' ').collect();
+        let t = log.time(&entry)?;
+        let fields = log.fields.as_ref().unwrap();
+        let local = ctx.local().format(t, SecondsFormat::AutoSi);
+        if csv {
+            let names = columns(fields, "time", |i| &fields.names[i]);
+            match &header {
+                None => {
+                    writeln!(out, "{}", names.join(","))?;
+                    header = Some(names);
+                }
+                Some(header) if *header != names => bail!(
+                    "w3c: the fields change partway through, which CSV \
+                     can't represent"
+                ),
+                Some(_) => (),
+            }
+            let row = columns(fields, &local, |i| {
+                entry.get(i).copied().unwrap_or("")
+            });
+            let row: Vec<_> = row.iter().map(|v| csv_value(v)).collect();
+            writeln!(out, "{}", row.join(","))?;
+            continue;
+        }
+        match fields.date {
+            Some(date) => {
+                let (day, time) = local.split_once('T').unwrap();
+                entry[date] = day;
+                entry[fields.time] = time;
+            }
+            None => entry[fields.time] = &local,
+        }
+        out.write_all(entry.join(" ").as_bytes())?;
+        out.write_all(&line.as_bytes()[text.len()..])?;
+    }
+}
+
+/// Returns the values of an entry (or the names of its fields) for a CSV
+/// row: `time` in place of the `time` field, followed by the others, except
+/// for `date`.
+fn columns<'a>(
+    fields: &Fields,
+    time: &'a str,
+    value: impl Fn(usize) -> &'a str,
+) -> Vec<String> {
+    let others = (0..fields.names.len())
+        .filter(|&i| i != fields.time && Some(i) != fields.date)
+        .map(|i| value(i).to_string());
+    std::iter::once(time.to_string()).chain(others).collect()
+}
+
+/// Quotes `value` for CSV, if it needs it.
+fn csv_value(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,102 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! `dates week`: print the week number and week date of a date
+
+use crate::UsageError;
+use anyhow::{Context, bail};
+use chrono::{Datelike, Days};
+use dates_tool::context;
+use dates_tool::locale::Lang;
+use dates_tool::week::WeekRules;
+
+/// Parses the options shared by every command that numbers weeks:
+/// `--weeks iso|us`, `--week-start DAY`, and `--min-days N`.  Returns `None`
+/// if `arg` is not one of these.
+///
+/// `rules` starts out as the command's default and is updated in place.
+/// Weekday names are parsed in `lang`.
+pub fn parse_option<'a>(
+    arg: &str,
+    rest: &mut impl Iterator<Item = &'a String>,
+    rules: &mut WeekRules,
+    lang: Lang,
+) -> Option<anyhow::Result<()>> {
+    let mut value = || {
+        rest.next().with_context(|| {
+            UsageError::new(format!("{arg} requires an argument"))
+        })
+    };
+    let result = match arg {
+        "--weeks" => {
+            value().and_then(|v| Ok(WeekRules::parse(v)?)).map(|r| *rules = r)
+        }
+        "--week-start" => value()
+            .and_then(|v| Ok(lang.parse_weekday(v)?))
+            .map(|day| rules.first_day = day),
+        "--min-days" => value().and_then(|v| match v.parse() {
+            Ok(n @ 1..=7) => {
+                rules.min_days = n;
+                Ok(())
+            }
+            _ => bail!("--min-days must be between 1 and 7"),
+        }),
+        _ => return None,
+    };
+    Some(result)
+}
+
+/// Entry point for `dates week [--weeks iso|us] [--week-start DAY]
+/// [--min-days N] [DATE | WEEKDATE]`
+pub fn run(
+    args: &[String],
+    ctx: &context::Context,
+    lang: Lang,
+) -> anyhow::Result<()> {
+    let mut rules = WeekRules::ISO;
+    let mut input = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if let Some(result) = parse_option(arg, &mut args, &mut rules, lang) {
+            result?;
+        } else if input.is_none() {
+            input = Some(arg.as_str());
+        } else {
+            bail!(UsageError::new(format!(
+                "week: unexpected argument {arg:?}"
+            )));
+        }
+    }
+
+    let date = match input {
+        None => ctx.today(),
+        Some(s) => match rules.parse_week_date(s) {
+            Some(date) => date,
+            None => ctx.parse_date(s)?,
+        },
+    };
+
+    let (year, week) = rules.week_of(date);
+    let day = rules.day_number(date);
+    let start = date.checked_sub_days(Days::new((day - 1).into()));
+    let end = start.and_then(|start| start.checked_add_days(Days::new(6)));
+    let (Some(start), Some(end)) = (start, end) else {
+        let input = date.to_string();
+        return Err(dates_tool::Error::out_of_range(
+            &input,
+            "its week extends past the supported range of dates",
+        )
+        .into());
+    };
+    println!("{:<8} {} ({})", "date", date, lang.weekday_short(date.weekday()));
+    println!("{:<8} {}-W{:02}-{}", "week", year, week, day);
+    println!(
+        "{:<8} {} ({})",
+        "range",
+        lang.date_range(start, end),
+        lang.week_rules(rules.first_day, rules.min_days),
+    );
+    Ok(())
+}
@@ -0,0 +1,118 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! The current time and local zone, determined once
+//!
+//! Anything that depends on "now" or on the local time zone takes a
+//! [`Context`] rather than asking the system directly.  The context reads
+//! its [`Clock`] once, when it's created, so every use within one invocation
+//! of `dates` (or one batch of work in a program) sees the same instant.
+//! Tests and `dates --now` substitute a [`FixedClock`], and a different
+//! local zone can be substituted the same way.  Build pipelines usually say
+//! what time to use with `SOURCE_DATE_EPOCH`, which
+//! [`parse_source_date_epoch`] reads.
+
+use crate::Error;
+use crate::zone::Zone;
+use chrono::{DateTime, NaiveDate, Utc};
+
+/// A source of the current time
+pub trait Clock {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The system's clock (only with the "std" feature)
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+#[cfg(feature = "std")]
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that always reads the same time
+#[derive(Clone, Copy, Debug)]
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+/// The current time and the zone in which to show and interpret local times
+#[derive(Clone, Copy, Debug)]
+pub struct Context {
+    now: DateTime<Utc>,
+    local: Zone,
+}
+
+impl Context {
+    /// Returns a context that reads `clock` now and treats `local` as the
+    /// local zone.
+    pub fn new(clock: &impl Clock, local: Zone) -> Context {
+        Context { now: clock.now(), local }
+    }
+
+    /// Returns a context using the system clock and local time zone.
+    #[cfg(feature = "std")]
+    pub fn system() -> Context {
+        Context::new(&SystemClock, Zone::Local)
+    }
+
+    /// Returns this context with the current time replaced by `now`.
+    pub fn with_now(self, now: DateTime<Utc>) -> Context {
+        Context { now, ..self }
+    }
+
+    /// Returns this context with the local zone replaced by `local`.
+    pub fn with_local(self, local: Zone) -> Context {
+        Context { local, ..self }
+    }
+
+    /// Returns the current time, as read when the context was created.
+    pub fn now(&self) -> DateTime<Utc> {
+        self.now
+    }
+
+    /// Returns the local zone.
+    pub fn local(&self) -> Zone {
+        self.local
+    }
+
+    /// Returns the current date in the local zone.
+    pub fn today(&self) -> NaiveDate {
+        self.local.date_of(self.now)
+    }
+
+    /// Returns the zones in which `dates` shows times by default: the local
+    /// zone, then UTC.
+    pub fn zones(&self) -> [Zone; 2] {
+        [self.local, Zone::Utc]
+    }
+
+    /// Interprets `s` as a calendar date, like [`crate::parse_date`] but
+    /// using this context's local zone.
+    pub fn parse_date(&self, s: &str) -> Result<NaiveDate, Error> {
+        if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+            return Ok(date);
+        }
+        Ok(self.local.date_of(crate::parse_timestamp(s)?))
+    }
+}
+
+/// Parses the value of `SOURCE_DATE_EPOCH`, which reproducible builds use to
+/// fix the current time: a whole number of seconds since the Unix epoch.
+///
+/// See <https://reproducible-builds.org/specs/source-date-epoch/>.
+pub fn parse_source_date_epoch(s: &str) -> Result<DateTime<Utc>, Error> {
+    let seconds: i64 = s.parse().map_err(|_| {
+        Error::unrecognized(s, "SOURCE_DATE_EPOCH (whole seconds since 1970)")
+    })?;
+    DateTime::from_timestamp(seconds, 0)
+        .ok_or_else(|| Error::out_of_range(s, "too large for a timestamp"))
+}
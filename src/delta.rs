@@ -0,0 +1,381 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Deltas that include calendar months and years
+//!
+//! A [`chrono::Duration`] is an exact number of seconds, but "one month
+//! later" isn't: it depends on which month it is.  A [`CalendarDelta`] has a
+//! number of calendar months (a year being 12 of them) in addition to an
+//! exact part.
+//!
+//! Adding a calendar delta to a time works as follows:
+//!
+//! 1. The months are added to the wall-clock date in a particular zone,
+//!    keeping the day of the month and the time of day.  If the day doesn't
+//!    exist in the resulting month, the result is clamped to the last day of
+//!    that month.  So January 31 plus one month is February 28 (or 29, in a
+//!    leap year), and March 31 minus one month is also February 28 or 29.
+//!    With [`MonthEnd::Overflow`], the extra days spill into the following
+//!    month instead, so January 31, 2024 plus one month is March 2.
+//! 2. The resulting wall-clock time is converted back to an instant, as a
+//!    [`DstPolicy`] says.  By default, if the wall clock reads that time
+//!    twice (when clocks go back for daylight saving time), the earlier
+//!    instant is used.  If it doesn't read that time at all (when clocks go
+//!    forward), the addition fails.
+//! 3. The exact part is added to that instant, so "+1d" is always 24 hours
+//!    later.  With [`Arith::Wall`], it's added to the wall-clock time before
+//!    step 2 instead, so "+1d" is the same time of day on the next day even
+//!    if that's 23 or 25 hours away.
+//!
+//! Because of the clamping, adding one month twice may not be the same as
+//! adding two months once: January 31 plus one month plus one month is
+//! March 28, but January 31 plus two months is March 31.
+
+use crate::Error;
+use crate::zone::{AmbiguousPolicy, DstPolicy, NonexistentPolicy, Zone};
+use chrono::{
+    DateTime, Datelike, Duration, MappedLocalTime, Months, NaiveDateTime, Utc,
+};
+use core::ops::Neg;
+
+/// A delta made of whole calendar months plus an exact duration
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CalendarDelta {
+    /// calendar months (negative to go backwards)
+    pub months: i32,
+    /// exact time added after the months
+    pub exact: Duration,
+}
+
+/// What to do when adding months lands on a day past the end of a month
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MonthEnd {
+    /// use the last day of the month (January 31 + 1 month = February 29)
+    #[default]
+    Clamp,
+    /// carry the extra days into the next month (January 31 + 1 month =
+    /// March 2), as JavaScript's `Date` does
+    Overflow,
+}
+
+impl MonthEnd {
+    pub fn parse(s: &str) -> Result<MonthEnd, Error> {
+        match s {
+            "clamp" => Ok(MonthEnd::Clamp),
+            "overflow" => Ok(MonthEnd::Overflow),
+            _ => Err(Error::unrecognized(
+                s,
+                "a month-end rule (clamp or overflow)",
+            )),
+        }
+    }
+}
+
+/// How the exact part of a delta is added
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Arith {
+    /// add elapsed time, so "+1d" means 86400 seconds later
+    #[default]
+    Absolute,
+    /// add to the wall-clock time, so "+1d" means the same time tomorrow
+    Wall,
+}
+
+impl Arith {
+    pub fn parse(s: &str) -> Result<Arith, Error> {
+        match s {
+            "absolute" => Ok(Arith::Absolute),
+            "wall" => Ok(Arith::Wall),
+            _ => Err(Error::unrecognized(
+                s,
+                "a kind of arithmetic (wall or absolute)",
+            )),
+        }
+    }
+}
+
+/// The rules for [`CalendarDelta::add_to_with`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Rules {
+    pub month_end: MonthEnd,
+    pub arith: Arith,
+}
+
+impl CalendarDelta {
+    /// Parses a delta the way `dates [+-]DELTA` does: anything that
+    /// [`crate::parse_duration`] accepts, or a whole number of months or
+    /// years with the unit "mo" or "y" (e.g., "+1mo" or "-2y").
+    pub fn parse(s: &str) -> Result<CalendarDelta, Error> {
+        let (sign, value_span, unit) = crate::split_delta(s)?;
+        let per_unit = match unit {
+            "mo" => 1,
+            "y" => 12,
+            _ => {
+                return crate::parse_duration(s)
+                    .map(CalendarDelta::from)
+                    .map_err(|error| match error {
+                        Error::Unrecognized { span, .. }
+                            if span.start == value_span.end =>
+                        {
+                            Error::unrecognized_at(
+                                s,
+                                span,
+                                "a unit (ms, s, m, h, d, mo, or y)",
+                            )
+                        }
+                        error => error,
+                    });
+            }
+        };
+        let value: i64 = s[value_span.clone()].parse().map_err(|_| {
+            Error::unrecognized_at(s, value_span.clone(), "a whole number")
+        })?;
+        i32::try_from(value)
+            .ok()
+            .and_then(|v| v.checked_mul(per_unit * sign))
+            .map(CalendarDelta::months)
+            .ok_or_else(|| {
+                Error::out_of_range_at(s, value_span, "too large for a delta")
+            })
+    }
+
+    /// Parses an ISO 8601 duration like "P1Y2M3DT4H5M6.5S" or "PT6H".
+    ///
+    /// Years and months are calendar months; weeks, days, hours, minutes,
+    /// and seconds are exact.  Each unit may appear at most once, in that
+    /// order, and only the seconds may have a fraction.
+    pub fn parse_iso8601(s: &str) -> Result<CalendarDelta, Error> {
+        let expected = "an ISO 8601 duration (like \"P1DT6H\")";
+        if !s.starts_with('P') || s.len() == 1 {
+            return Err(Error::unrecognized(s, expected));
+        }
+        let mut delta = CalendarDelta::default();
+        let mut units: &[char] = &['Y', 'M', 'W', 'D'];
+        let mut time = false;
+        let mut pos = 1;
+        while pos < s.len() {
+            if !time && s[pos..].starts_with('T') {
+                time = true;
+                units = &['H', 'M', 'S'];
+                pos += 1;
+                if pos == s.len() {
+                    return Err(Error::unrecognized_at(
+                        s,
+                        pos - 1..pos,
+                        expected,
+                    ));
+                }
+                continue;
+            }
+            let end = s[pos..]
+                .find(|c: char| !matches!(c, '0'..='9' | '.' | ','))
+                .map_or(s.len(), |n| pos + n);
+            let value_span = pos..end;
+            let Some(unit) = s[end..].chars().next() else {
+                return Err(Error::unrecognized_at(s, pos..end, expected));
+            };
+            let unit_span = end..end + unit.len_utf8();
+            let Some(i) = units.iter().position(|&u| u == unit) else {
+                return Err(Error::unrecognized_at(s, unit_span, expected));
+            };
+            units = &units[i + 1..];
+            pos = unit_span.end;
+
+            let too_large = || {
+                Error::out_of_range_at(
+                    s,
+                    value_span.clone(),
+                    "too large for a delta",
+                )
+            };
+            let value = &s[value_span.clone()];
+            if unit == 'S' {
+                let nanos = parse_seconds(value).ok_or_else(|| {
+                    Error::unrecognized_at(s, value_span.clone(), "a number")
+                })?;
+                let exact = i64::try_from(nanos)
+                    .ok()
+                    .map(Duration::nanoseconds)
+                    .and_then(|d| delta.exact.checked_add(&d))
+                    .ok_or_else(too_large)?;
+                delta.exact = exact;
+                continue;
+            }
+            let n: i64 = value.parse().map_err(|_| {
+                Error::unrecognized_at(s, value_span.clone(), "a whole number")
+            })?;
+            match (time, unit) {
+                (false, 'Y' | 'M') => {
+                    let per_unit = if unit == 'Y' { 12 } else { 1 };
+                    delta.months = i32::try_from(n)
+                        .ok()
+                        .and_then(|n| n.checked_mul(per_unit))
+                        .and_then(|n| delta.months.checked_add(n))
+                        .ok_or_else(too_large)?;
+                }
+                _ => {
+                    let seconds = match (time, unit) {
+                        (false, 'W') => 7 * 86400,
+                        (false, _) => 86400,
+                        (true, 'H') => 3600,
+                        _ => 60,
+                    };
+                    delta.exact = n
+                        .checked_mul(seconds)
+                        .and_then(Duration::try_seconds)
+                        .and_then(|d| delta.exact.checked_add(&d))
+                        .ok_or_else(too_large)?;
+                }
+            }
+        }
+        Ok(delta)
+    }
+
+    /// Returns a delta of `months` calendar months.
+    pub fn months(months: i32) -> CalendarDelta {
+        CalendarDelta { months, exact: Duration::zero() }
+    }
+
+    /// Returns a delta of `years` calendar years (12 months each), or `None`
+    /// if that many months would overflow.
+    pub fn years(years: i32) -> Option<CalendarDelta> {
+        years.checked_mul(12).map(CalendarDelta::months)
+    }
+
+    /// Returns this delta repeated `n` times, or `None` if that overflows.
+    pub fn checked_mul(&self, n: i32) -> Option<CalendarDelta> {
+        Some(CalendarDelta {
+            months: self.months.checked_mul(n)?,
+            exact: self.exact.checked_mul(n)?,
+        })
+    }
+
+    /// Returns true if this delta has no calendar part, so that it's exactly
+    /// [`CalendarDelta::exact`] long no matter where it's applied.
+    pub fn is_exact(&self) -> bool {
+        self.months == 0
+    }
+
+    /// Adds this delta to `t`, interpreting months on the wall clock in
+    /// `zone`, as described in the [module documentation](self).
+    ///
+    /// Returns `None` if the result is out of range or if the intermediate
+    /// wall-clock time doesn't exist in `zone`.
+    pub fn add_to(
+        &self,
+        t: DateTime<Utc>,
+        zone: Zone,
+    ) -> Option<DateTime<Utc>> {
+        self.add_to_with(t, zone, Rules::default(), ADD_TO_POLICY)
+    }
+
+    /// Like [`CalendarDelta::add_to`], but following `rules` for days past
+    /// the end of the resulting month and for the exact part, and `policy`
+    /// for wall-clock times that occur twice or not at all.
+    ///
+    /// Returns `None` if the result is out of range or if `policy` says to
+    /// fail on the intermediate wall-clock time.
+    pub fn add_to_with(
+        &self,
+        t: DateTime<Utc>,
+        zone: Zone,
+        rules: Rules,
+        policy: DstPolicy,
+    ) -> Option<DateTime<Utc>> {
+        let on_wall = rules.arith == Arith::Wall && !self.exact.is_zero();
+        if self.months == 0 && !on_wall {
+            return t.checked_add_signed(self.exact);
+        }
+
+        let wall = zone.wall_clock(t);
+        let wall = match (self.months, rules.month_end) {
+            (0, _) => wall,
+            (months, MonthEnd::Clamp) => add_months(wall, months)?,
+            (months, MonthEnd::Overflow) => {
+                // Add the months to the first of the month, where there's
+                // nothing to clamp, then add back the days.
+                let excess = Duration::days(i64::from(wall.day0()));
+                let first = wall.checked_sub_signed(excess)?;
+                add_months(first, months)?.checked_add_signed(excess)?
+            }
+        };
+        if on_wall {
+            let wall = wall.checked_add_signed(self.exact)?;
+            resolve(zone, wall, policy)
+        } else {
+            resolve(zone, wall, policy)?.checked_add_signed(self.exact)
+        }
+    }
+}
+
+/// How [`CalendarDelta::add_to`] resolves wall-clock times
+const ADD_TO_POLICY: DstPolicy = DstPolicy {
+    ambiguous: AmbiguousPolicy::Earliest,
+    nonexistent: NonexistentPolicy::Error,
+};
+
+/// Returns the instant at which the wall clock in `zone` reads `wall`, as
+/// `policy` says, or `None` if it says to fail.
+fn resolve(
+    zone: Zone,
+    wall: NaiveDateTime,
+    policy: DstPolicy,
+) -> Option<DateTime<Utc>> {
+    match zone.instant_of(wall) {
+        MappedLocalTime::Single(t) => Some(t),
+        MappedLocalTime::Ambiguous(t1, t2) => match policy.ambiguous {
+            AmbiguousPolicy::Earliest => Some(t1),
+            AmbiguousPolicy::Latest => Some(t2),
+            AmbiguousPolicy::Error => None,
+        },
+        MappedLocalTime::None => match policy.nonexistent {
+            NonexistentPolicy::Shift => zone.shift(wall),
+            NonexistentPolicy::Error => None,
+        },
+    }
+}
+
+/// Parses a number of seconds like "6", "6.5", or "6,5" (ISO 8601 allows
+/// either decimal sign) into nanoseconds, ignoring digits past the ninth
+/// decimal place.
+fn parse_seconds(s: &str) -> Option<i128> {
+    let (whole, fraction) = s.split_once(['.', ',']).unwrap_or((s, ""));
+    if whole.is_empty() || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let whole: i128 = whole.parse().ok()?;
+    let nanos = fraction
+        .bytes()
+        .chain(core::iter::repeat(b'0'))
+        .take(9)
+        .fold(0, |n, b| n * 10 + i128::from(b - b'0'));
+    whole.checked_mul(1_000_000_000)?.checked_add(nanos)
+}
+
+/// Adds `months` (which may be negative) to `wall`, clamping the day.
+fn add_months(wall: NaiveDateTime, months: i32) -> Option<NaiveDateTime> {
+    let n = Months::new(months.unsigned_abs());
+    if months > 0 {
+        wall.checked_add_months(n)
+    } else {
+        wall.checked_sub_months(n)
+    }
+}
+
+impl From<Duration> for CalendarDelta {
+    fn from(exact: Duration) -> CalendarDelta {
+        CalendarDelta { months: 0, exact }
+    }
+}
+
+impl Neg for CalendarDelta {
+    type Output = CalendarDelta;
+
+    fn neg(self) -> CalendarDelta {
+        CalendarDelta {
+            months: self.months.saturating_neg(),
+            exact: -self.exact,
+        }
+    }
+}
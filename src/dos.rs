@@ -0,0 +1,56 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! DOS date and time, as in FAT filesystems and ZIP archives
+//!
+//! A DOS date and time is packed into 32 bits: the date in the high 16 (7
+//! bits of years since 1980, 4 of month, and 5 of day) and the time of day
+//! in the low 16 (5 bits of hour, 6 of minute, and 5 of seconds divided by
+//! two).  So it covers 1980 through 2107, to two seconds.  It's a local time
+//! with no zone, for whatever zone the machine that wrote it was set to.
+//! FAT directory entries and ZIP headers store the time first, then the
+//! date, each as a 16-bit little-endian integer, so the 32 bits appear in
+//! little-endian order too.
+
+use crate::Error;
+use alloc::format;
+use alloc::string::ToString;
+use chrono::{Datelike, NaiveDate, NaiveDateTime, Timelike};
+
+/// Decodes a packed DOS date and time.
+pub fn decode(packed: u32) -> Result<NaiveDateTime, Error> {
+    let field = |shift: u32, bits: u32| (packed >> shift) & ((1 << bits) - 1);
+    let year = 1980 + field(25, 7) as i32;
+    let (month, day) = (field(21, 4), field(16, 5));
+    let (hour, minute, second) = (field(11, 5), field(5, 6), field(0, 5) * 2);
+    NaiveDate::from_ymd_opt(year, month, day)
+        .and_then(|d| d.and_hms_opt(hour, minute, second))
+        .ok_or_else(|| {
+            Error::out_of_range(
+                &format!("0x{packed:08x}"),
+                format!(
+                    "not a DOS date and time ({year:04}-{month:02}-{day:02} \
+                     {hour:02}:{minute:02}:{second:02})"
+                ),
+            )
+        })
+}
+
+/// Packs `t` as a DOS date and time, rounding down to an even second.
+pub fn encode(t: NaiveDateTime) -> Result<u32, Error> {
+    let years = u32::try_from(t.year() - 1980)
+        .ok()
+        .filter(|&y| y < 128)
+        .ok_or_else(|| {
+            Error::out_of_range(
+                &t.to_string(),
+                "DOS times are only from 1980 through 2107",
+            )
+        })?;
+    let date = years << 9 | t.month() << 5 | t.day();
+    // A leap second is recorded as the second before it.
+    let second = t.second().min(59) / 2;
+    let time = t.hour() << 11 | t.minute() << 5 | second;
+    Ok(date << 16 | time)
+}
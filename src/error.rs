@@ -0,0 +1,156 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Errors from parsing user input
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::fmt;
+use core::ops::Range;
+
+/// An error from parsing a timestamp, delta, or other user input
+///
+/// Every variant records the complete input and the byte range within it that
+/// caused the problem, so that callers can point at it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The input doesn't match any supported format.
+    Unrecognized {
+        input: String,
+        span: Range<usize>,
+        /// what sort of thing we were trying to parse (e.g., "a timestamp")
+        expected: String,
+    },
+    /// The input is well-formed but denotes a value that can't be
+    /// represented (e.g., February 30, or a year too large for the
+    /// supported range).
+    OutOfRange { input: String, span: Range<usize>, detail: String },
+    /// The input could reasonably mean more than one thing.
+    Ambiguous { input: String, span: Range<usize>, detail: String },
+}
+
+impl Error {
+    /// Reports that all of `input` could not be parsed as `expected`.
+    pub fn unrecognized(input: &str, expected: impl Into<String>) -> Error {
+        Error::unrecognized_at(input, 0..input.len(), expected)
+    }
+
+    /// Reports that `span` of `input` could not be parsed as `expected`.
+    pub fn unrecognized_at(
+        input: &str,
+        span: Range<usize>,
+        expected: impl Into<String>,
+    ) -> Error {
+        Error::Unrecognized {
+            input: input.to_string(),
+            span,
+            expected: expected.into(),
+        }
+    }
+
+    /// Reports that `span` of `input` is out of range.
+    pub fn out_of_range_at(
+        input: &str,
+        span: Range<usize>,
+        detail: impl Into<String>,
+    ) -> Error {
+        Error::OutOfRange {
+            input: input.to_string(),
+            span,
+            detail: detail.into(),
+        }
+    }
+
+    /// Reports that all of `input` is out of range.
+    pub fn out_of_range(input: &str, detail: impl Into<String>) -> Error {
+        Error::out_of_range_at(input, 0..input.len(), detail)
+    }
+
+    /// Reports that `span` of `input` is ambiguous.
+    pub fn ambiguous_at(
+        input: &str,
+        span: Range<usize>,
+        detail: impl Into<String>,
+    ) -> Error {
+        Error::Ambiguous {
+            input: input.to_string(),
+            span,
+            detail: detail.into(),
+        }
+    }
+
+    /// Returns this error about a part of `input` that starts at byte
+    /// `offset`, restated as an error about all of `input`.
+    pub fn within(self, input: &str, offset: usize) -> Error {
+        let shift = |span: Range<usize>| span.start + offset..span.end + offset;
+        match self {
+            Error::Unrecognized { span, expected, .. } => {
+                Error::unrecognized_at(input, shift(span), expected)
+            }
+            Error::OutOfRange { span, detail, .. } => {
+                Error::out_of_range_at(input, shift(span), detail)
+            }
+            Error::Ambiguous { span, detail, .. } => {
+                Error::ambiguous_at(input, shift(span), detail)
+            }
+        }
+    }
+
+    /// Returns the complete input that failed to parse.
+    pub fn input(&self) -> &str {
+        match self {
+            Error::Unrecognized { input, .. }
+            | Error::OutOfRange { input, .. }
+            | Error::Ambiguous { input, .. } => input,
+        }
+    }
+
+    /// Returns the byte range of [`Error::input()`] that caused the error.
+    pub fn span(&self) -> Range<usize> {
+        match self {
+            Error::Unrecognized { span, .. }
+            | Error::OutOfRange { span, .. }
+            | Error::Ambiguous { span, .. } => span.clone(),
+        }
+    }
+
+    /// Returns the part of the input that caused the error.
+    pub fn fragment(&self) -> &str {
+        self.input().get(self.span()).unwrap_or_else(|| self.input())
+    }
+
+    /// Returns two lines showing the input with the offending span
+    /// underlined, like:
+    ///
+    /// ```text
+    /// +3x
+    ///   ^
+    /// ```
+    pub fn highlight(&self) -> String {
+        let input = self.input();
+        let span = self.span();
+        let lead = input.get(..span.start).map_or(0, |s| s.chars().count());
+        let width = self.fragment().chars().count().max(1);
+        format!("{input}\n{}{}", " ".repeat(lead), "^".repeat(width))
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fragment = self.fragment();
+        match self {
+            Error::Unrecognized { expected, .. } => {
+                write!(f, "could not parse {fragment:?} as {expected}")
+            }
+            Error::OutOfRange { detail, .. } => {
+                write!(f, "{fragment:?} is out of range: {detail}")
+            }
+            Error::Ambiguous { detail, .. } => {
+                write!(f, "{fragment:?} is ambiguous: {detail}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for Error {}
@@ -0,0 +1,207 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Finding timestamps in arbitrary text
+//!
+//! [`extract`] scans a stream, a line at a time, for anything that a
+//! [`Registry`] accepts as a timestamp.  Candidates are runs of up to
+//! [`MAX_WORDS`] adjacent whitespace-separated words (so that "2024-03-05
+//! 13:55:36 PST" is found whole), tried longest first.  Each candidate is
+//! tried as-is and then with surrounding punctuation like brackets, quotes,
+//! and a `key=` prefix removed.  A candidate that looks like a timestamp but
+//! can't be interpreted (say, one with an ambiguous zone abbreviation) is
+//! skipped whole, rather than reported as some shorter timestamp within it.
+//!
+//! Bare numbers are only considered if they have at least 10 digits before
+//! any decimal point, so that counts, ports, and status codes elsewhere in
+//! the text aren't reported as times in 1970.
+
+use crate::Error;
+use crate::parser::Registry;
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+use chrono::{DateTime, Utc};
+use core::ops::Range;
+use std::io::{self, BufRead, BufReader, Read};
+
+/// The most words that a single timestamp can span
+pub const MAX_WORDS: usize = 3;
+
+/// A timestamp found in a stream
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Match {
+    /// the byte offsets of the timestamp within the whole stream
+    pub range: Range<usize>,
+    pub time: DateTime<Utc>,
+    /// the name of the format that matched (see
+    /// [`crate::parser::TimestampParser::name`])
+    pub format: String,
+}
+
+/// Iterator over the timestamps in a stream, returned by [`extract`]
+pub struct Extractor<'a, R> {
+    registry: &'a Registry,
+    reader: R,
+    /// the stream offset of the start of `line`
+    offset: usize,
+    line: Vec<u8>,
+    pending: VecDeque<Match>,
+    done: bool,
+}
+
+/// Returns an iterator over the timestamps in `input` that `registry`
+/// recognizes, in the order they appear.
+///
+/// The input is read as needed, one line at a time, so it may be arbitrarily
+/// long.  Lines need not be valid UTF-8, but timestamps must be.
+pub fn extract<R: Read>(
+    registry: &Registry,
+    input: R,
+) -> Extractor<'_, BufReader<R>> {
+    Extractor {
+        registry,
+        reader: BufReader::new(input),
+        offset: 0,
+        line: Vec::new(),
+        pending: VecDeque::new(),
+        done: false,
+    }
+}
+
+impl<R: BufRead> Iterator for Extractor<'_, R> {
+    type Item = io::Result<Match>;
+
+    fn next(&mut self) -> Option<io::Result<Match>> {
+        while self.pending.is_empty() && !self.done {
+            self.offset += self.line.len();
+            self.line.clear();
+            match self.reader.read_until(b'\n', &mut self.line) {
+                Ok(0) => self.done = true,
+                Ok(_) => {
+                    let found = scan_line(self.registry, &self.line);
+                    self.pending.extend(found.into_iter().map(|mut m| {
+                        m.range = m.range.start + self.offset
+                            ..m.range.end + self.offset;
+                        m
+                    }));
+                }
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => (),
+                Err(error) => {
+                    self.done = true;
+                    return Some(Err(error));
+                }
+            }
+        }
+        self.pending.pop_front().map(Ok)
+    }
+}
+
+/// Returns the timestamps in `line`, with ranges relative to its start.
+fn scan_line(registry: &Registry, line: &[u8]) -> Vec<Match> {
+    let words = words(line);
+    let mut found = Vec::new();
+    let mut i = 0;
+    'words: while i < words.len() {
+        for n in (1..=MAX_WORDS.min(words.len() - i)).rev() {
+            let span = words[i].start..words[i + n - 1].end;
+            match try_span(registry, line, span) {
+                Ok(m) => found.push(m),
+                Err(Error::Unrecognized { .. }) => continue,
+                Err(_) => (),
+            }
+            i += n;
+            continue 'words;
+        }
+        i += 1;
+    }
+    found
+}
+
+/// Returns the byte ranges of the whitespace-separated words in `line`.
+fn words(line: &[u8]) -> Vec<Range<usize>> {
+    let mut words = Vec::new();
+    let mut start = None;
+    for (i, b) in line.iter().enumerate() {
+        match (b.is_ascii_whitespace(), start) {
+            (true, Some(s)) => {
+                words.push(s..i);
+                start = None;
+            }
+            (false, None) => start = Some(i),
+            _ => (),
+        }
+    }
+    if let Some(s) = start {
+        words.push(s..line.len());
+    }
+    words
+}
+
+/// Tries `span` of `line` as a timestamp, first as-is and then without
+/// surrounding punctuation.
+///
+/// If neither matches, this returns the more informative of the errors, as
+/// [`Registry::parse`] does.
+fn try_span(
+    registry: &Registry,
+    line: &[u8],
+    span: Range<usize>,
+) -> Result<Match, Error> {
+    let mismatch = || Error::unrecognized("", "a timestamp");
+    let text =
+        core::str::from_utf8(&line[span.clone()]).map_err(|_| mismatch())?;
+    let trimmed = trim(text);
+    let offset = trimmed.as_ptr() as usize - text.as_ptr() as usize;
+
+    let mut best = mismatch();
+    for (s, skip) in [(text, 0), (trimmed, offset)] {
+        if s.is_empty() || (is_number(s) && !plausible_number(s)) {
+            continue;
+        }
+        match registry.explain(s) {
+            Ok(parsed) => {
+                let start = span.start + skip;
+                return Ok(Match {
+                    range: start..start + s.len(),
+                    time: parsed.time,
+                    format: parsed.provenance.format,
+                });
+            }
+            Err(Error::Unrecognized { .. }) => (),
+            Err(error) => best = error,
+        }
+    }
+    Err(best)
+}
+
+/// Removes a leading `key=` and surrounding brackets, quotes, and
+/// punctuation from `s`.
+fn trim(s: &str) -> &str {
+    let s = match s.split_once('=') {
+        Some((key, value)) if !key.is_empty() && !key.contains(' ') => value,
+        _ => s,
+    };
+    s.trim_start_matches(['(', '[', '{', '<', '"', '\''])
+        .trim_end_matches([')', ']', '}', '>', '"', '\'', ',', ';', '.', ':'])
+}
+
+fn is_number(s: &str) -> bool {
+    s.strip_prefix(['+', '-'])
+        .unwrap_or(s)
+        .chars()
+        .all(|c| c.is_ascii_digit() || c == '.')
+}
+
+/// Returns whether a number `s` is long enough to plausibly be a timestamp.
+fn plausible_number(s: &str) -> bool {
+    let digits = s.trim_start_matches(['+', '-']);
+    digits.split('.').next().map_or(0, str::len) >= 10
+}
+
+impl From<Match> for (Range<usize>, DateTime<Utc>, String) {
+    fn from(m: Match) -> Self {
+        (m.range, m.time, m.format)
+    }
+}
@@ -0,0 +1,201 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! C bindings for parsing and formatting
+//!
+//! These are available with the "ffi" feature, when building the crate as a
+//! `staticlib` or `cdylib` (with `cargo rustc --crate-type`).  The
+//! corresponding header is `include/dates_tool.h`, generated with `cbindgen`
+//! (see `cbindgen.toml`).
+//!
+//! Times and deltas cross the boundary as signed microseconds (since the Unix
+//! epoch, for times).  Strings returned by this library are allocated here
+//! and must be released with [`dates_string_free`].
+
+use crate::Error;
+use chrono::{DateTime, Duration, Utc};
+use std::ffi::{CStr, CString, c_char, c_int};
+use std::ptr;
+use std::string::{String, ToString};
+
+/// Returned when the call succeeded
+pub const DATES_OK: c_int = 0;
+/// Returned when the input doesn't match any supported format
+pub const DATES_ERR_UNRECOGNIZED: c_int = 1;
+/// Returned when the input is well-formed but out of range
+pub const DATES_ERR_OUT_OF_RANGE: c_int = 2;
+/// Returned when the input could mean more than one thing
+pub const DATES_ERR_AMBIGUOUS: c_int = 3;
+/// Returned when an argument is NULL or not valid UTF-8, or a value can't be
+/// represented
+pub const DATES_ERR_INVALID: c_int = -1;
+
+/// Parses a timestamp in one of the formats built into `dates`, storing
+/// microseconds since the Unix epoch into `*out_micros`.
+///
+/// These are the formats that `dates_tool::parse_timestamp` accepts, which,
+/// unlike `dates TIME`, don't include relative times, "now", or times
+/// without an offset (other than the few formats defined to be in UTC).
+///
+/// On failure, returns a nonzero `DATES_ERR_*` code and, if `error_out` is
+/// not NULL, stores into `*error_out` a message that the caller must free
+/// with [`dates_string_free`].
+///
+/// # Safety
+///
+/// `input` must be a NUL-terminated string.  `out_micros` must be valid for
+/// writes.  `error_out` must be NULL or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dates_parse_timestamp(
+    input: *const c_char,
+    out_micros: *mut i64,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    // SAFETY: guaranteed by the caller
+    unsafe {
+        parse_with(input, out_micros, error_out, |s| {
+            crate::parse_timestamp(s).map(|dt| dt.timestamp_micros())
+        })
+    }
+}
+
+/// Parses a delta the way `dates [+-]DELTA` does, storing its length in
+/// microseconds into `*out_micros`.
+///
+/// Errors are reported as for [`dates_parse_timestamp`].
+///
+/// # Safety
+///
+/// As for [`dates_parse_timestamp`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dates_parse_duration(
+    input: *const c_char,
+    out_micros: *mut i64,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    // SAFETY: guaranteed by the caller
+    unsafe {
+        parse_with(input, out_micros, error_out, |s| {
+            let delta = crate::parse_duration(s)?;
+            delta
+                .num_microseconds()
+                .ok_or_else(|| Error::out_of_range(s, "too large for a delta"))
+        })
+    }
+}
+
+/// Formats a time (given as microseconds since the Unix epoch) the way `dates`
+/// prints it, under `label`.
+///
+/// Returns NULL if `label` is invalid or the time can't be represented.
+/// Otherwise, the caller must free the result with [`dates_string_free`].
+///
+/// # Safety
+///
+/// `label` must be a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dates_format_timestamp(
+    label: *const c_char,
+    micros: i64,
+) -> *mut c_char {
+    // SAFETY: guaranteed by the caller
+    let Some(label) = (unsafe { to_str(label) }) else {
+        return ptr::null_mut();
+    };
+    match DateTime::<Utc>::from_timestamp_micros(micros) {
+        Some(dt) => to_c_string(crate::format_timestamp(label, dt)),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Formats a delta (given in microseconds) the way `dates` prints it, under
+/// `label`.
+///
+/// The result is as for [`dates_format_timestamp`].
+///
+/// # Safety
+///
+/// `label` must be a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dates_format_duration(
+    label: *const c_char,
+    micros: i64,
+) -> *mut c_char {
+    // SAFETY: guaranteed by the caller
+    let Some(label) = (unsafe { to_str(label) }) else {
+        return ptr::null_mut();
+    };
+    to_c_string(crate::format_duration(label, Duration::microseconds(micros)))
+}
+
+/// Frees a string returned by this library.  Does nothing if `s` is NULL.
+///
+/// # Safety
+///
+/// `s` must be NULL or a string returned by this library that has not
+/// already been freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dates_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        // SAFETY: guaranteed by the caller
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
+
+/// Implements the `dates_parse_*` functions on top of `parse`.
+///
+/// # Safety
+///
+/// As for [`dates_parse_timestamp`].
+unsafe fn parse_with(
+    input: *const c_char,
+    out_micros: *mut i64,
+    error_out: *mut *mut c_char,
+    parse: impl FnOnce(&str) -> Result<i64, Error>,
+) -> c_int {
+    // SAFETY: guaranteed by the caller
+    let input = unsafe { to_str(input) };
+    let (Some(input), false) = (input, out_micros.is_null()) else {
+        return DATES_ERR_INVALID;
+    };
+
+    match parse(input) {
+        Ok(micros) => {
+            // SAFETY: checked for NULL above; otherwise guaranteed by caller
+            unsafe { *out_micros = micros };
+            DATES_OK
+        }
+        Err(error) => {
+            if !error_out.is_null() {
+                // SAFETY: checked for NULL above; otherwise guaranteed by
+                // caller
+                unsafe { *error_out = to_c_string(error.to_string()) };
+            }
+            match error {
+                Error::Unrecognized { .. } => DATES_ERR_UNRECOGNIZED,
+                Error::OutOfRange { .. } => DATES_ERR_OUT_OF_RANGE,
+                Error::Ambiguous { .. } => DATES_ERR_AMBIGUOUS,
+            }
+        }
+    }
+}
+
+/// Borrows `s` as a `&str`, if it's non-NULL and valid UTF-8.
+///
+/// # Safety
+///
+/// `s` must be NULL or a NUL-terminated string that outlives the result.
+unsafe fn to_str<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        return None;
+    }
+    // SAFETY: guaranteed by the caller
+    unsafe { CStr::from_ptr(s) }.to_str().ok()
+}
+
+/// Hands `s` to the caller as a C string.  Our output never contains NUL
+/// bytes, but if it somehow did, this returns NULL.
+fn to_c_string(s: String) -> *mut c_char {
+    CString::new(s).map_or(ptr::null_mut(), CString::into_raw)
+}
@@ -0,0 +1,335 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Configurable rendering of times and deltas in the `dates` layout
+
+use crate::Error;
+use crate::zone::Zone;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use core::fmt::{self, Write};
+
+/// One part of a formatted time or delta
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Field {
+    /// the numeric column: seconds since the Unix epoch for a time, or the
+    /// total seconds of a delta
+    Seconds,
+    /// for a time, its RFC 3339 form in each of the formatter's zones
+    Rfc3339,
+    /// for a delta, its breakdown into days, hours, minutes, and seconds (or
+    /// the units selected with [`Formatter::units`])
+    Breakdown,
+}
+
+/// A unit into which a delta is broken down
+///
+/// Only units of a fixed length are offered: a month or a year doesn't have
+/// one, so a delta can't be broken down into them without knowing when it
+/// starts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Unit {
+    Weeks,
+    Days,
+    Hours,
+    Minutes,
+    Seconds,
+}
+
+impl Unit {
+    /// Parses a unit like "w", "days", or "hours".
+    pub fn parse(s: &str) -> Result<Unit, Error> {
+        match s {
+            "w" | "week" | "weeks" => Ok(Unit::Weeks),
+            "d" | "day" | "days" => Ok(Unit::Days),
+            "h" | "hour" | "hours" => Ok(Unit::Hours),
+            "m" | "min" | "minute" | "minutes" => Ok(Unit::Minutes),
+            "s" | "sec" | "second" | "seconds" => Ok(Unit::Seconds),
+            _ => Err(Error::unrecognized(
+                s,
+                "a unit (weeks, days, hours, minutes, or seconds)",
+            )),
+        }
+    }
+
+    /// Parses a comma-separated list of units, like "weeks,days,hours".
+    /// The result is sorted from largest to smallest, without duplicates.
+    pub fn parse_list(s: &str) -> Result<Vec<Unit>, Error> {
+        let mut units =
+            s.split(',').map(Unit::parse).collect::<Result<Vec<_>, _>>()?;
+        units.sort();
+        units.dedup();
+        Ok(units)
+    }
+
+    /// Returns `largest` and every smaller unit, down to seconds.
+    pub fn down_from(largest: Unit) -> Vec<Unit> {
+        [Unit::Weeks, Unit::Days, Unit::Hours, Unit::Minutes, Unit::Seconds]
+            .into_iter()
+            .filter(|u| *u >= largest)
+            .collect()
+    }
+
+    fn nanos(self) -> u128 {
+        let seconds = match self {
+            Unit::Weeks => 7 * 86400,
+            Unit::Days => 86400,
+            Unit::Hours => 3600,
+            Unit::Minutes => 60,
+            Unit::Seconds => 1,
+        };
+        seconds * 1_000_000_000
+    }
+
+    fn suffix(self) -> char {
+        match self {
+            Unit::Weeks => 'w',
+            Unit::Days => 'd',
+            Unit::Hours => 'h',
+            Unit::Minutes => 'm',
+            Unit::Seconds => 's',
+        }
+    }
+
+    /// Returns the number of digits needed for this unit's value when it
+    /// follows a larger unit: 1 for days (up to 6) and 2 for the rest.
+    fn width(self) -> usize {
+        match self {
+            Unit::Days => 1,
+            _ => 2,
+        }
+    }
+}
+
+/// Renders times and deltas in the aligned layout that `dates` prints
+///
+/// The defaults reproduce the command-line output exactly.
+#[derive(Clone, Debug)]
+pub struct Formatter {
+    zones: Vec<Zone>,
+    precision: SecondsFormat,
+    fields: Vec<Field>,
+    units: Vec<Unit>,
+}
+
+impl Default for Formatter {
+    fn default() -> Formatter {
+        Formatter::new()
+    }
+}
+
+impl Formatter {
+    /// Returns a formatter that renders times in the local zone and in UTC
+    /// (or only in UTC, without the "std" feature) with microsecond
+    /// precision, showing all fields.
+    pub fn new() -> Formatter {
+        Formatter {
+            #[cfg(feature = "std")]
+            zones: vec![Zone::Local, Zone::Utc],
+            #[cfg(not(feature = "std"))]
+            zones: vec![Zone::Utc],
+            precision: SecondsFormat::Micros,
+            fields: vec![Field::Seconds, Field::Rfc3339, Field::Breakdown],
+            units: vec![Unit::Days, Unit::Hours, Unit::Minutes, Unit::Seconds],
+        }
+    }
+
+    /// Renders times in `zone` only.
+    pub fn zone(self, zone: Zone) -> Formatter {
+        self.zones(&[zone])
+    }
+
+    /// Renders times in each of `zones`, one per line.
+    pub fn zones(mut self, zones: &[Zone]) -> Formatter {
+        self.zones = zones.to_vec();
+        self
+    }
+
+    /// Sets the number of fractional digits shown for seconds.  With
+    /// [`SecondsFormat::AutoSi`], each value uses as few digits (0, 3, 6, or
+    /// 9) as it needs.
+    pub fn precision(mut self, precision: SecondsFormat) -> Formatter {
+        self.precision = precision;
+        self
+    }
+
+    /// Selects which fields are shown.  Fields that don't apply to the value
+    /// being formatted are ignored.
+    pub fn fields(mut self, fields: &[Field]) -> Formatter {
+        self.fields = fields.to_vec();
+        self
+    }
+
+    /// Selects the units of a delta's breakdown, which are shown from largest
+    /// to smallest.  If seconds aren't among them, the smallest is
+    /// truncated.  With no units, the default (days, hours, minutes, and
+    /// seconds) is used.
+    pub fn units(mut self, units: &[Unit]) -> Formatter {
+        let mut units = units.to_vec();
+        units.sort();
+        units.dedup();
+        if !units.is_empty() {
+            self.units = units;
+        }
+        self
+    }
+
+    /// Formats `dt` under `label`.  The result does not end with a newline.
+    pub fn format_time(&self, label: &str, dt: DateTime<Utc>) -> String {
+        let nanos = i128::from(dt.timestamp()) * 1_000_000_000
+            + i128::from(dt.timestamp_subsec_nanos());
+        let seconds = self
+            .has(Field::Seconds)
+            .then(|| (decimal(nanos, self.precision), "s"));
+        let values: Vec<_> = if self.has(Field::Rfc3339) {
+            self.zones
+                .iter()
+                .map(|z| {
+                    let rfc3339 = z.format(dt, self.precision);
+                    match z.local_mean_time(dt) {
+                        Some(offset) => {
+                            mean_time(&rfc3339, &offset.to_string())
+                        }
+                        None => rfc3339,
+                    }
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let mut out = String::new();
+        layout(&mut out, label, seconds, &values).unwrap();
+        out
+    }
+
+    /// Formats `delta` under `label`.  The result does not end with a
+    /// newline.
+    pub fn format_delta(&self, label: &str, delta: Duration) -> String {
+        let nanos = i128::from(delta.num_seconds()) * 1_000_000_000
+            + i128::from(delta.subsec_nanos());
+        let seconds = self
+            .has(Field::Seconds)
+            .then(|| (decimal(nanos, self.precision), "s"));
+        let values: Vec<_> = if self.has(Field::Breakdown) {
+            vec![breakdown_into(nanos, self.precision, &self.units)]
+        } else {
+            Vec::new()
+        };
+        let mut out = String::new();
+        layout(&mut out, label, seconds, &values).unwrap();
+        out
+    }
+
+    fn has(&self, field: Field) -> bool {
+        self.fields.contains(&field)
+    }
+}
+
+/// Writes one labeled item: the label, an optional right-aligned number with
+/// its unit, and then each of `values` on its own line, aligned after a " = ".
+pub(crate) fn layout(
+    out: &mut impl Write,
+    label: &str,
+    number: Option<(String, &str)>,
+    values: &[String],
+) -> fmt::Result {
+    let mut indent = 8;
+    write!(out, "{label:<8}")?;
+    if let Some((number, unit)) = number {
+        write!(out, " {number:>20} {unit}")?;
+        indent += 2 + number.len().max(20) + unit.len();
+    }
+    for (i, value) in values.iter().enumerate() {
+        if i > 0 {
+            write!(out, "\n{:indent$}", "")?;
+        }
+        write!(out, " = {value}")?;
+    }
+    Ok(())
+}
+
+/// Notes that `rfc3339` is in local mean time, whose exact `offset` RFC 3339
+/// rounds to the minute.
+pub(crate) fn mean_time(rfc3339: &str, offset: &str) -> String {
+    format!("{rfc3339} (local mean time, {offset})")
+}
+
+/// Returns the number of fractional digits to show for `nanos` at
+/// `precision`.
+fn digits(nanos: i128, precision: SecondsFormat) -> usize {
+    match precision {
+        SecondsFormat::Secs => 0,
+        SecondsFormat::Millis => 3,
+        SecondsFormat::Micros => 6,
+        SecondsFormat::Nanos => 9,
+        _ => match nanos % 1_000_000_000 {
+            0 => 0,
+            n if n % 1_000_000 == 0 => 3,
+            n if n % 1_000 == 0 => 6,
+            _ => 9,
+        },
+    }
+}
+
+/// Formats `nanos` (nanoseconds) as decimal seconds, truncated to the digits
+/// selected by `precision`.
+pub(crate) fn decimal(nanos: i128, precision: SecondsFormat) -> String {
+    let sign = if nanos < 0 { "-" } else { "" };
+    let abs = nanos.unsigned_abs();
+    let (whole, frac) = (abs / 1_000_000_000, abs % 1_000_000_000);
+    match digits(nanos, precision) {
+        0 => format!("{sign}{whole}"),
+        n => {
+            let frac = frac / 10u128.pow(9 - n as u32);
+            format!("{sign}{whole}.{frac:0n$}")
+        }
+    }
+}
+
+/// Formats `nanos` (nanoseconds) as a fixed-width breakdown like
+/// " 0d 21h 01m 23.456000s", truncating seconds to the digits selected by
+/// `precision`.  Non-negative values have a leading space instead of a sign.
+pub(crate) fn breakdown(nanos: i128, precision: SecondsFormat) -> String {
+    breakdown_into(
+        nanos,
+        precision,
+        &[Unit::Days, Unit::Hours, Unit::Minutes, Unit::Seconds],
+    )
+}
+
+/// Like [`breakdown`], but into `units`, which are sorted from largest to
+/// smallest.  Every unit but the first is zero-padded to the width of its
+/// largest value, and if seconds aren't among the units, the smallest one
+/// is truncated.
+pub(crate) fn breakdown_into(
+    nanos: i128,
+    precision: SecondsFormat,
+    units: &[Unit],
+) -> String {
+    let sign = if nanos < 0 { "-" } else { " " };
+    let mut rest = nanos.unsigned_abs();
+    let mut parts = Vec::new();
+    for (i, &unit) in units.iter().enumerate() {
+        let value = if unit == Unit::Seconds {
+            // `rest` is less than a minute unless seconds come first, in
+            // which case it came from an i128 to begin with.
+            let seconds = decimal(rest as i128, precision);
+            rest = 0;
+            seconds
+        } else {
+            let value = rest / unit.nanos();
+            rest %= unit.nanos();
+            value.to_string()
+        };
+        let width = match (i, value.find('.')) {
+            (0, _) => 0,
+            (_, Some(point)) => value.len() - point + unit.width(),
+            (_, None) => unit.width(),
+        };
+        parts.push(format!("{value:0>width$}{}", unit.suffix()));
+    }
+    format!("{sign}{}", parts.join(" "))
+}
@@ -0,0 +1,378 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Guessing a `strftime` pattern from an example timestamp
+//!
+//! The example is split into runs of digits, runs of letters, and single
+//! punctuation characters.  Runs separated by colons are taken as a time of
+//! day, a sign followed by four digits (or two, a colon, and two) after the
+//! time is taken as a UTC offset, names are matched against months,
+//! weekdays, and AM/PM, and the remaining numbers are assigned to the year,
+//! month, and day using their sizes, their order, and the separators between
+//! them.  Anything else is kept literally.
+//!
+//! Some examples can't be resolved: in "03/05/2024", either number could be
+//! the month.  In that case, the guess follows the most common convention
+//! for the separator (month first with "/", day first otherwise), and the
+//! [`Inference`] says so.
+
+use crate::Error;
+use crate::parser::{Strftime, TimestampParser};
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+/// A guessed pattern for an example timestamp
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Inference {
+    /// the pattern, using the specifiers of [`chrono::format::strftime`]
+    pub pattern: String,
+    /// caveats about the guess, like fields that could have been assigned
+    /// differently
+    pub notes: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kind {
+    Digits,
+    Alpha,
+    Punct,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Token<'a> {
+    kind: Kind,
+    text: &'a str,
+}
+
+/// Splits `s` into runs of digits, runs of letters, and single other
+/// characters.
+fn tokenize(s: &str) -> Vec<Token<'_>> {
+    let kind_of = |c: char| {
+        if c.is_ascii_digit() {
+            Kind::Digits
+        } else if c.is_alphabetic() {
+            Kind::Alpha
+        } else {
+            Kind::Punct
+        }
+    };
+
+    let mut tokens = Vec::new();
+    let mut chars = s.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let kind = kind_of(c);
+        let mut end = start + c.len_utf8();
+        if kind != Kind::Punct {
+            while let Some(&(i, next)) = chars.peek() {
+                if kind_of(next) != kind {
+                    break;
+                }
+                end = i + next.len_utf8();
+                chars.next();
+            }
+        }
+        tokens.push(Token { kind, text: &s[start..end] });
+    }
+    tokens
+}
+
+const MONTHS: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+const WEEKDAYS: [&str; 7] = [
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+
+/// Returns "%B" or "%b" if `word` is a full or abbreviated name from
+/// `names` (passed as `full` and `short`).
+fn name_spec(
+    word: &str,
+    names: &[&str],
+    full: &'static str,
+    short: &'static str,
+) -> Option<&'static str> {
+    let word = word.to_lowercase();
+    if names.contains(&word.as_str()) {
+        Some(full)
+    } else if word.len() == 3 && names.iter().any(|n| n.starts_with(&word)) {
+        Some(short)
+    } else {
+        None
+    }
+}
+
+/// Guesses a `strftime` pattern that would parse `example`.
+///
+/// Fails if `example` contains nothing that looks like part of a date or
+/// time.
+pub fn infer_format(example: &str) -> Result<Inference, Error> {
+    let tokens = tokenize(example);
+    // The specifier for each token: `None` for a literal, and an empty
+    // string for punctuation that's part of the specifier for a neighboring
+    // token.
+    let mut specs: Vec<Option<String>> = vec![None; tokens.len()];
+    let mut notes = Vec::new();
+    let digits = |i: usize| -> Option<&str> {
+        tokens.get(i).filter(|t| t.kind == Kind::Digits).map(|t| t.text)
+    };
+    let punct = |i: usize, c: &str| {
+        tokens.get(i).is_some_and(|t| t.kind == Kind::Punct && t.text == c)
+    };
+
+    // Names of months and weekdays, and AM/PM.
+    let mut twelve_hour = false;
+    let mut have_month = false;
+    for (i, token) in tokens.iter().enumerate() {
+        if token.kind != Kind::Alpha {
+            continue;
+        }
+        let lower = token.text.to_lowercase();
+        let spec = if let Some(spec) =
+            name_spec(token.text, &MONTHS, "%B", "%b")
+        {
+            have_month = true;
+            Some(spec)
+        } else if let Some(spec) = name_spec(token.text, &WEEKDAYS, "%A", "%a")
+        {
+            Some(spec)
+        } else if lower == "am" || lower == "pm" {
+            twelve_hour = true;
+            Some("%p")
+        } else {
+            None
+        };
+        specs[i] = spec.map(String::from);
+    }
+
+    // The time of day: HH:MM, optionally followed by :SS and a fraction.
+    let mut time_end = None;
+    for i in 0..tokens.len() {
+        let (Some(h), Some(m)) = (digits(i), digits(i + 2)) else {
+            continue;
+        };
+        if !punct(i + 1, ":")
+            || h.len() > 2
+            || m.len() != 2
+            || specs[i].is_some()
+        {
+            continue;
+        }
+        specs[i] = Some(String::from(if twelve_hour { "%I" } else { "%H" }));
+        specs[i + 2] = Some(String::from("%M"));
+        let mut end = i + 3;
+        if punct(end, ":") && digits(end + 1).is_some_and(|s| s.len() == 2) {
+            specs[end + 1] = Some(String::from("%S"));
+            end += 2;
+            if (punct(end, ".") || punct(end, ","))
+                && let Some(frac) = digits(end + 1)
+            {
+                // "%.3f" and friends include the ".", but a "," has to be
+                // kept literally.
+                let spec = match (punct(end, "."), frac.len()) {
+                    (true, 3 | 6 | 9) => format!("%.{}f", frac.len()),
+                    (true, _) => String::from("%.f"),
+                    (false, n) => format!("%{n}f"),
+                };
+                if punct(end, ".") {
+                    specs[end] = Some(String::new());
+                }
+                specs[end + 1] = Some(spec);
+                end += 2;
+            }
+        }
+        time_end = Some(end);
+        break;
+    }
+
+    // A UTC offset after the time.
+    if let Some(start) = time_end {
+        for i in start..tokens.len() {
+            if !(punct(i, "+") || punct(i, "-")) {
+                continue;
+            }
+            match (digits(i + 1), digits(i + 3)) {
+                (Some(hm), _) if hm.len() == 4 => {
+                    specs[i] = Some(String::new());
+                    specs[i + 1] = Some(String::from("%z"));
+                }
+                (Some(h), Some(m))
+                    if h.len() == 2 && m.len() == 2 && punct(i + 2, ":") =>
+                {
+                    specs[i] = Some(String::new());
+                    specs[i + 1] = Some(String::from("%:z"));
+                    specs[i + 2] = Some(String::new());
+                    specs[i + 3] = Some(String::new());
+                }
+                _ => continue,
+            }
+            break;
+        }
+    }
+
+    // The remaining numbers make up the date, unless the whole thing is a
+    // Unix timestamp.
+    let numbers: Vec<usize> = (0..tokens.len())
+        .filter(|&i| digits(i).is_some() && specs[i].is_none())
+        .collect();
+    // A Unix timestamp before 1970 is negative.  The sign belongs to "%s",
+    // so it gets an empty spec of its own.
+    let negative = tokens.len() == 2 && tokens[0].text == "-";
+    let all_digits =
+        tokens.len() == 1 + usize::from(negative) && numbers.len() == 1;
+    let epoch = all_digits && [10, 13].contains(&tokens[numbers[0]].text.len());
+    if epoch && negative {
+        specs[0] = Some(String::new());
+        notes.push(String::from(
+            "this looks like a Unix timestamp before 1970, which `dates` \
+             accepts as-is but chrono can't parse with a pattern",
+        ));
+    }
+    match numbers.as_slice() {
+        [i] if all_digits && tokens[*i].text.len() == 10 => {
+            specs[*i] = Some(String::from("%s"));
+        }
+        [i] if all_digits && tokens[*i].text.len() == 13 => {
+            specs[*i] = Some(String::from("%s%3f"));
+            notes.push(String::from(
+                "this looks like milliseconds since the Unix epoch, which \
+                 `dates` accepts as-is but chrono can't parse with a pattern",
+            ));
+        }
+        _ => assign_date(&tokens, &numbers, have_month, &mut specs, &mut notes),
+    }
+
+    if specs.iter().all(|s| s.as_deref().is_none_or(str::is_empty)) {
+        return Err(Error::unrecognized(example, "a date or time"));
+    }
+
+    let mut pattern = String::new();
+    for (token, spec) in tokens.iter().zip(&specs) {
+        match spec {
+            Some(spec) => pattern.push_str(spec),
+            None if token.text == "%" => pattern.push_str("%%"),
+            None => pattern.push_str(token.text),
+        }
+    }
+
+    if Strftime::new("inferred", pattern.as_str()).parse(example).is_err() {
+        notes.push(String::from(
+            "the example doesn't parse with this pattern, so it may be wrong",
+        ));
+    }
+    Ok(Inference { pattern, notes })
+}
+
+/// Assigns the year, month, and day among `numbers` (indexes into `tokens`
+/// of digit runs that haven't been claimed by anything else).
+fn assign_date(
+    tokens: &[Token<'_>],
+    numbers: &[usize],
+    have_month: bool,
+    specs: &mut [Option<String>],
+    notes: &mut Vec<String>,
+) {
+    let set = |specs: &mut [Option<String>], i: usize, spec: &str| {
+        specs[i] = Some(String::from(spec));
+    };
+
+    // Compact forms like "20240305" or "20240305T135536".
+    if let Some(&i) = numbers.first() {
+        match tokens[i].text.len() {
+            8 => {
+                set(specs, i, "%Y%m%d");
+                if let Some(&j) = numbers.get(1)
+                    && tokens[j].text.len() == 6
+                {
+                    set(specs, j, "%H%M%S");
+                }
+                return;
+            }
+            14 => {
+                set(specs, i, "%Y%m%d%H%M%S");
+                return;
+            }
+            _ => (),
+        }
+    }
+
+    let year = numbers.iter().position(|&i| tokens[i].text.len() == 4);
+    if let Some(y) = year {
+        set(specs, numbers[y], "%Y");
+    }
+    let rest: Vec<usize> = numbers
+        .iter()
+        .enumerate()
+        .filter(|&(n, &i)| Some(n) != year && tokens[i].text.len() <= 3)
+        .map(|(_, &i)| i)
+        .collect();
+    let value = |i: usize| tokens[i].text.parse::<u32>().unwrap_or(0);
+
+    match (year, have_month, rest.as_slice()) {
+        // Ordinal dates, like "2024-065".
+        (Some(_), false, [d]) if tokens[*d].text.len() == 3 => {
+            set(specs, *d, "%j");
+        }
+        // With the month spelled out, a lone number is the day, and the
+        // other is a two-digit year.
+        (_, true, [d]) => set(specs, *d, "%d"),
+        (None, true, [d, y]) => {
+            set(specs, *d, "%d");
+            set(specs, *y, "%y");
+        }
+        // Year first is always year-month-day.
+        (Some(0), false, [m, d, ..]) => {
+            set(specs, *m, "%m");
+            set(specs, *d, "%d");
+        }
+        (_, false, [a, b, rest @ ..]) => {
+            let separator = tokens
+                .get(a + 1)
+                .filter(|t| t.kind == Kind::Punct)
+                .map_or("", |t| t.text);
+            let month_first = if value(*a) > 12 {
+                false
+            } else if value(*b) > 12 {
+                true
+            } else {
+                let month_first = separator == "/";
+                notes.push(format!(
+                    "either {:?} or {:?} could be the month; assuming {} \
+                     first",
+                    tokens[*a].text,
+                    tokens[*b].text,
+                    if month_first { "month" } else { "day" },
+                ));
+                month_first
+            };
+            let (m, d) = if month_first { (a, b) } else { (b, a) };
+            set(specs, *m, "%m");
+            set(specs, *d, "%d");
+            if year.is_none()
+                && let [y, ..] = rest
+            {
+                set(specs, *y, "%y");
+            }
+        }
+        _ => (),
+    }
+}
@@ -0,0 +1,236 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! ISO 8601 time intervals, like "2024-03-01T00:00Z/PT6H"
+//!
+//! An interval is written as two parts separated by "/": a start and an
+//! end, a start and a duration, or a duration and an end.  Durations are
+//! written as [`CalendarDelta::parse_iso8601`] describes.  The abbreviated
+//! form in which the end gives only the fields that differ from the start
+//! (like "2024-03-01/05") isn't supported.
+//!
+//! A [`Repeating`] interval has a prefix like "R5/", for an interval that
+//! occurs five times in a row.  (ISO 8601 calls these "recurrences", and
+//! standards differ on whether "R5" means five occurrences or five more
+//! after the first.  Like most software, this takes it to mean five.)  For
+//! an interval given by its duration and end, the end is that of the last
+//! occurrence.
+
+use crate::Error;
+use crate::delta::CalendarDelta;
+use crate::zone::Zone;
+use chrono::{DateTime, Utc};
+use core::ops::Range;
+
+/// One side of an interval: a time, or a duration measured from the other
+/// side
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Part<'a> {
+    Time(&'a str),
+    Duration(CalendarDelta),
+}
+
+/// The two sides of an interval, before its times are parsed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Interval<'a> {
+    pub start: Part<'a>,
+    pub end: Part<'a>,
+    /// byte offset of the end within the input
+    pub end_offset: usize,
+}
+
+impl<'a> Interval<'a> {
+    /// Splits `s` into the two sides of an interval.  Either side that
+    /// starts with "P" must be a duration, but not both may be.
+    pub fn split(s: &'a str) -> Result<Interval<'a>, Error> {
+        let Some((start, end)) = s.split_once('/') else {
+            return Err(Error::unrecognized(
+                s,
+                "an interval (like \"2024-03-01T00:00Z/PT6H\")",
+            ));
+        };
+        let end_offset = start.len() + 1;
+        let part = |text: &'a str, offset| -> Result<Part<'a>, Error> {
+            if text.starts_with('P') {
+                CalendarDelta::parse_iso8601(text)
+                    .map(Part::Duration)
+                    .map_err(|e| e.within(s, offset))
+            } else {
+                Ok(Part::Time(text))
+            }
+        };
+        let interval = Interval {
+            start: part(start, 0)?,
+            end: part(end, end_offset)?,
+            end_offset,
+        };
+        if let (Part::Duration(_), Part::Duration(_)) =
+            (interval.start, interval.end)
+        {
+            return Err(two_durations(s, end_offset));
+        }
+        Ok(interval)
+    }
+
+    /// Returns the instants at which this interval, which was split from
+    /// `s`, starts and ends.  Times are read with `parse`, and `add(t, text,
+    /// delta)` adds `delta` (parsed from `text`) to `t`.  Errors refer to
+    /// all of `s`.
+    pub fn resolve(
+        &self,
+        s: &str,
+        parse: impl Fn(&str) -> Result<DateTime<Utc>, Error>,
+        add: impl Fn(
+            DateTime<Utc>,
+            &str,
+            CalendarDelta,
+        ) -> Result<DateTime<Utc>, Error>,
+    ) -> Result<Range<DateTime<Utc>>, Error> {
+        self.resolve_nth(s, 0, parse, add)
+    }
+
+    /// Like [`Interval::resolve`], but returns the `n`th repetition of the
+    /// interval, counting the interval itself as the 0th: `n` durations
+    /// later, or for an interval given by its duration and end, `n`
+    /// durations earlier.
+    ///
+    /// Each repetition is computed from the time given, rather than from the
+    /// one before, so that repeating "P1M" from January 31 lands on the last
+    /// day of every month rather than on the 28th after February.
+    pub fn resolve_nth(
+        &self,
+        s: &str,
+        n: u32,
+        parse: impl Fn(&str) -> Result<DateTime<Utc>, Error>,
+        add: impl Fn(
+            DateTime<Utc>,
+            &str,
+            CalendarDelta,
+        ) -> Result<DateTime<Utc>, Error>,
+    ) -> Result<Range<DateTime<Utc>>, Error> {
+        let start_text = &s[..self.end_offset - 1];
+        let end_text = &s[self.end_offset..];
+        let start = |t| parse(t).map_err(|e| e.within(s, 0));
+        let end = |t| parse(t).map_err(|e| e.within(s, self.end_offset));
+        let start_span = 0..self.end_offset - 1;
+        let end_span = self.end_offset..s.len();
+        let times = |d: CalendarDelta, k: u32, span: &Range<usize>| {
+            i32::try_from(k).ok().and_then(|k| d.checked_mul(k)).ok_or_else(
+                || {
+                    Error::out_of_range_at(
+                        s,
+                        span.clone(),
+                        "too many repetitions for a delta",
+                    )
+                },
+            )
+        };
+        match (self.start, self.end) {
+            (Part::Time(t1), Part::Time(t2)) => {
+                let (t1, t2) = (start(t1)?, end(t2)?);
+                let shift =
+                    times(CalendarDelta::from(t2 - t1), n, &end_span)?.exact;
+                let moved = |t: DateTime<Utc>| {
+                    t.checked_add_signed(shift).ok_or_else(|| {
+                        Error::out_of_range(s, "past the supported range")
+                    })
+                };
+                Ok(moved(t1)?..moved(t2)?)
+            }
+            (Part::Time(t1), Part::Duration(d)) => {
+                let t1 = start(t1)?;
+                let add = |k| {
+                    add(t1, end_text, times(d, k, &end_span)?)
+                        .map_err(|e| e.within(s, self.end_offset))
+                };
+                Ok(add(n)?..add(n + 1)?)
+            }
+            (Part::Duration(d), Part::Time(t2)) => {
+                let t2 = end(t2)?;
+                let sub = |k| {
+                    add(t2, start_text, -times(d, k, &start_span)?)
+                        .map_err(|e| e.within(s, 0))
+                };
+                Ok(sub(n + 1)?..sub(n)?)
+            }
+            (Part::Duration(_), Part::Duration(_)) => {
+                Err(two_durations(s, self.end_offset))
+            }
+        }
+    }
+}
+
+/// A repeating interval, like "R5/2024-03-01T00:00Z/PT1H"
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Repeating<'a> {
+    /// how many times the interval occurs, or `None` for "R/" (forever)
+    pub count: Option<u32>,
+    pub interval: Interval<'a>,
+    /// byte offset of the interval within the input
+    pub offset: usize,
+}
+
+impl<'a> Repeating<'a> {
+    /// Splits `s` into its number of occurrences and the interval that
+    /// repeats.
+    pub fn split(s: &'a str) -> Result<Repeating<'a>, Error> {
+        let expected = "a repeating interval (like \"R5/2024-03-01/P1D\")";
+        let Some((count, rest)) =
+            s.strip_prefix('R').and_then(|r| r.split_once('/'))
+        else {
+            return Err(Error::unrecognized(s, expected));
+        };
+        let offset = count.len() + 2;
+        let count = match count {
+            "" => None,
+            _ => Some(count.parse().map_err(|_| {
+                Error::unrecognized_at(
+                    s,
+                    1..offset - 1,
+                    "a number of repetitions",
+                )
+            })?),
+        };
+        let interval =
+            Interval::split(rest).map_err(|e| e.within(s, offset))?;
+        Ok(Repeating { count, interval, offset })
+    }
+
+    /// Returns the instants at which the `n`th occurrence starts and ends
+    /// (counting from 0), as [`Interval::resolve_nth`] describes.  `s` is
+    /// the input this was split from, to which errors refer.
+    pub fn nth(
+        &self,
+        s: &str,
+        n: u32,
+        parse: impl Fn(&str) -> Result<DateTime<Utc>, Error>,
+        add: impl Fn(
+            DateTime<Utc>,
+            &str,
+            CalendarDelta,
+        ) -> Result<DateTime<Utc>, Error>,
+    ) -> Result<Range<DateTime<Utc>>, Error> {
+        self.interval
+            .resolve_nth(&s[self.offset..], n, parse, add)
+            .map_err(|e| e.within(s, self.offset))
+    }
+}
+
+fn two_durations(s: &str, end_offset: usize) -> Error {
+    Error::unrecognized_at(
+        s,
+        end_offset..s.len(),
+        "a time (only one side of an interval can be a duration)",
+    )
+}
+
+/// Parses `s` as an interval, reading its times with
+/// [`crate::parse_timestamp`] and adding calendar months in UTC.
+pub fn parse_interval(s: &str) -> Result<Range<DateTime<Utc>>, Error> {
+    Interval::split(s)?.resolve(s, crate::parse_timestamp, |t, text, d| {
+        d.add_to(t, Zone::Utc).ok_or_else(|| {
+            Error::out_of_range(text, "the result is past the supported range")
+        })
+    })
+}
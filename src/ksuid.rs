@@ -0,0 +1,78 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Times embedded in KSUIDs
+//!
+//! A KSUID, as Segment defined it, is 20 bytes: the number of seconds since
+//! its own epoch, 2014-05-13T16:53:20Z, as a 32-bit big-endian integer,
+//! followed by 16 random bytes.  It's written as 27 base 62 digits ("0"
+//! through "9", then "A" through "Z", then "a" through "z").
+
+use crate::Error;
+use chrono::{DateTime, Utc};
+
+/// Seconds from the Unix epoch to the KSUID epoch
+const KSUID_EPOCH: i64 = 1_400_000_000;
+
+/// A KSUID
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Ksuid(pub [u8; 20]);
+
+impl Ksuid {
+    /// Parses a KSUID written as 27 base 62 digits.
+    pub fn parse(s: &str) -> Result<Ksuid, Error> {
+        if s.len() != 27 {
+            return Err(Error::unrecognized(
+                s,
+                "a KSUID (like \"0ujtsYcgvSTl8PAuAdqWYSMnLOv\")",
+            ));
+        }
+        let mut bytes = [0u8; 20];
+        for (i, c) in s.char_indices() {
+            let digit = match c {
+                '0'..='9' => c as u32 - '0' as u32,
+                'A'..='Z' => c as u32 - 'A' as u32 + 10,
+                'a'..='z' => c as u32 - 'a' as u32 + 36,
+                _ => {
+                    return Err(Error::unrecognized_at(
+                        s,
+                        i..i + c.len_utf8(),
+                        "a base 62 digit",
+                    ));
+                }
+            };
+            // Multiply the bytes so far by 62 and add the digit, from the
+            // least significant byte up.
+            let mut carry = digit;
+            for byte in bytes.iter_mut().rev() {
+                let n = u32::from(*byte) * 62 + carry;
+                *byte = n as u8;
+                carry = n >> 8;
+            }
+            if carry != 0 {
+                return Err(Error::out_of_range(
+                    s,
+                    "too large for a KSUID (the largest is \
+                     \"aWgEPTl1tmebfsQzFP4bxwgy80V\")",
+                ));
+            }
+        }
+        Ok(Ksuid(bytes))
+    }
+
+    /// Returns the time at which this KSUID was generated, to the second.
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        let [a, b, c, d, ..] = self.0;
+        let seconds = i64::from(u32::from_be_bytes([a, b, c, d]));
+        // 32 bits of seconds are always within chrono's range.
+        DateTime::from_timestamp(KSUID_EPOCH + seconds, 0).unwrap()
+    }
+
+    /// Returns the 16 bytes after the timestamp, which are random.
+    pub fn payload(&self) -> u128 {
+        let mut payload = [0; 16];
+        payload.copy_from_slice(&self.0[4..]);
+        u128::from_be_bytes(payload)
+    }
+}
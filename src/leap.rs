@@ -0,0 +1,439 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Conversions between UTC, TAI, and GPS time
+//!
+//! TAI (International Atomic Time) and GPS time count SI seconds without
+//! interruption, while UTC occasionally inserts a leap second (23:59:60) to
+//! stay close to the Earth's rotation.  Converting between them requires a
+//! table of when leap seconds happened.  [`LeapSeconds::builtin`] has the
+//! table as of the leap second at the end of 2016, and
+//! [`LeapSeconds::parse_list`] reads a newer one in the format of the
+//! `leap-seconds.list` file published by the IERS and NIST.
+//!
+//! chrono has no types for TAI or GPS time, so TAI instants are represented
+//! as a [`NaiveDateTime`] (a TAI calendar date and time), and GPS instants as
+//! a [`Duration`] since the GPS epoch (1980-01-06T00:00:00 UTC).  A UTC
+//! instant during a leap second is a [`DateTime<Utc>`] with more than a
+//! billion nanoseconds in its 23:59:59 second, as chrono represents it.
+//!
+//! Some clocks, like those of Google and AWS, don't show leap seconds at all.
+//! Instead, they "smear" each one over the 24 hours around it, running
+//! slightly slow from noon UTC the day before until noon UTC the day after.
+//! [`LeapSeconds::utc_to_smeared`] and [`LeapSeconds::smeared_to_utc`]
+//! convert readings of such a clock.
+//!
+//! Times after the last entry in the table are assumed to have the same
+//! offset as that entry, which is wrong if an older table misses a leap
+//! second.  Times before 1972, when UTC was not offset from TAI by a whole
+//! number of seconds, can't be converted.
+
+use crate::Error;
+use alloc::borrow::Cow;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, Timelike, Utc};
+
+/// Seconds from the NTP epoch (1900-01-01) to the Unix epoch
+const NTP_TO_UNIX: i64 = 2_208_988_800;
+
+/// TAI - GPS time, which is constant
+const TAI_MINUS_GPS: i64 = 19;
+
+/// Seconds of a leap smear on each side of the leap second
+const SMEAR_HALF: i64 = 12 * 3600;
+
+const NANOS: i128 = 1_000_000_000;
+
+/// Leap seconds, as (the Unix time at which an offset took effect, TAI - UTC
+/// in seconds from then on)
+const BUILTIN: &[(i64, i32)] = &[
+    (63_072_000, 10),    // 1972-01-01
+    (78_796_800, 11),    // 1972-07-01
+    (94_694_400, 12),    // 1973-01-01
+    (126_230_400, 13),   // 1974-01-01
+    (157_766_400, 14),   // 1975-01-01
+    (189_302_400, 15),   // 1976-01-01
+    (220_924_800, 16),   // 1977-01-01
+    (252_460_800, 17),   // 1978-01-01
+    (283_996_800, 18),   // 1979-01-01
+    (315_532_800, 19),   // 1980-01-01
+    (362_793_600, 20),   // 1981-07-01
+    (394_329_600, 21),   // 1982-07-01
+    (425_865_600, 22),   // 1983-07-01
+    (489_024_000, 23),   // 1985-07-01
+    (567_993_600, 24),   // 1988-01-01
+    (631_152_000, 25),   // 1990-01-01
+    (662_688_000, 26),   // 1991-01-01
+    (709_948_800, 27),   // 1992-07-01
+    (741_484_800, 28),   // 1993-07-01
+    (773_020_800, 29),   // 1994-07-01
+    (820_454_400, 30),   // 1996-01-01
+    (867_715_200, 31),   // 1997-07-01
+    (915_148_800, 32),   // 1999-01-01
+    (1_136_073_600, 33), // 2006-01-01
+    (1_230_768_000, 34), // 2009-01-01
+    (1_341_100_800, 35), // 2012-07-01
+    (1_435_708_800, 36), // 2015-07-01
+    (1_483_228_800, 37), // 2017-01-01
+];
+
+/// A table of leap seconds
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LeapSeconds {
+    /// as for [`BUILTIN`], sorted by time
+    entries: Cow<'static, [(i64, i32)]>,
+    /// when the table stops being authoritative (as Unix time), if known
+    expires: Option<i64>,
+}
+
+impl LeapSeconds {
+    /// Returns the table built into this library.
+    pub const fn builtin() -> LeapSeconds {
+        LeapSeconds { entries: Cow::Borrowed(BUILTIN), expires: None }
+    }
+
+    /// Parses a table in the format of `leap-seconds.list`: lines holding
+    /// an NTP timestamp (seconds since 1900) and the offset TAI - UTC that
+    /// applies from then on, with comments starting with "#".  A comment
+    /// starting with "#@" gives the NTP timestamp at which the table expires.
+    pub fn parse_list(text: &str) -> Result<LeapSeconds, Error> {
+        let mut entries = Vec::new();
+        let mut expires = None;
+        for line in text.lines() {
+            if let Some(ntp) = line.strip_prefix("#@") {
+                let ntp = ntp.trim().parse::<i64>().map_err(|_| {
+                    Error::unrecognized(line, "an NTP expiration timestamp")
+                })?;
+                expires = Some(ntp - NTP_TO_UNIX);
+                continue;
+            }
+            let data = line.split('#').next().unwrap_or("");
+            let mut fields = data.split_whitespace();
+            let (Some(ntp), Some(offset)) = (fields.next(), fields.next())
+            else {
+                continue;
+            };
+            let (Ok(ntp), Ok(offset)) = (ntp.parse::<i64>(), offset.parse())
+            else {
+                return Err(Error::unrecognized(
+                    line,
+                    "an NTP timestamp and a TAI - UTC offset",
+                ));
+            };
+            entries.push((ntp - NTP_TO_UNIX, offset));
+        }
+        if entries.is_empty() {
+            return Err(Error::unrecognized(text, "a table of leap seconds"));
+        }
+        entries.sort_unstable();
+        Ok(LeapSeconds { entries: Cow::Owned(entries), expires })
+    }
+
+    /// Returns each time at which TAI - UTC changed, with the new offset in
+    /// seconds, in order.  The first entry is the start of the table in 1972
+    /// rather than a leap second.
+    pub fn entries(&self) -> impl Iterator<Item = (DateTime<Utc>, i32)> + '_ {
+        self.entries.iter().filter_map(|&(start, offset)| {
+            Some((DateTime::from_timestamp(start, 0)?, offset))
+        })
+    }
+
+    /// Returns when the table expires, after which a leap second may have
+    /// happened that it doesn't include.  The built-in table doesn't record
+    /// this.
+    pub fn expires(&self) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp(self.expires?, 0)
+    }
+
+    /// Returns whether a leap second was inserted just before `t`, so that
+    /// UTC clocks read 23:59:60 for the second before it.
+    pub fn inserted_before(&self, t: DateTime<Utc>) -> bool {
+        self.entries.windows(2).any(|pair| {
+            pair[1].0 == t.timestamp()
+                && t.timestamp_subsec_nanos() == 0
+                && pair[1].1 > pair[0].1
+        })
+    }
+
+    /// Returns TAI - UTC, in seconds, at UTC instant `t`.
+    pub fn offset_at(&self, t: DateTime<Utc>) -> Result<i32, Error> {
+        let i =
+            self.entries.partition_point(|&(start, _)| start <= t.timestamp());
+        match i.checked_sub(1) {
+            Some(i) => Ok(self.entries[i].1),
+            None => Err(before_table(t.naive_utc())),
+        }
+    }
+
+    /// Converts UTC instant `t` to TAI.
+    pub fn utc_to_tai(&self, t: DateTime<Utc>) -> Result<NaiveDateTime, Error> {
+        let offset = self.offset_at(t)?;
+        // This includes the extra second for a time during a leap second.
+        let nanos = t.timestamp_subsec_nanos();
+        let second = t.with_nanosecond(0).unwrap().naive_utc();
+        second
+            .checked_add_signed(
+                Duration::seconds(i64::from(offset))
+                    + Duration::nanoseconds(i64::from(nanos)),
+            )
+            .ok_or_else(|| {
+                Error::out_of_range(&t.to_rfc3339(), "too large for TAI")
+            })
+    }
+
+    /// Converts TAI time `tai` to UTC.  A leap second (23:59:60) is an error,
+    /// since TAI has none.
+    pub fn tai_to_utc(
+        &self,
+        tai: NaiveDateTime,
+    ) -> Result<DateTime<Utc>, Error> {
+        let secs = tai.and_utc().timestamp();
+        let nanos = tai.and_utc().timestamp_subsec_nanos();
+        if nanos >= 1_000_000_000 {
+            return Err(Error::out_of_range(
+                &tai.to_string(),
+                "it doesn't exist on TAI, which has no leap seconds",
+            ));
+        }
+        let i = self.entries.partition_point(|&(start, offset)| {
+            start + i64::from(offset) <= secs
+        });
+        let Some(last) = i.checked_sub(1) else {
+            return Err(before_table(tai));
+        };
+
+        // Between one offset taking effect and the next, UTC is just TAI
+        // minus that offset, except during a leap second inserted at the end
+        // of the interval.
+        let (_, offset) = self.entries[last];
+        let utc = secs - i64::from(offset);
+        if let Some(&(next, _)) = self.entries.get(i)
+            && utc >= next
+        {
+            return Ok(DateTime::from_timestamp(
+                next - 1,
+                nanos + 1_000_000_000,
+            )
+            .unwrap());
+        }
+        DateTime::from_timestamp(utc, nanos).ok_or_else(|| {
+            Error::out_of_range(&tai.to_string(), "too large for a timestamp")
+        })
+    }
+
+    /// Returns what a clock using a 24-hour leap smear reads at UTC instant
+    /// `t`.  Outside the smear around each leap second, that's just UTC.
+    pub fn utc_to_smeared(
+        &self,
+        t: DateTime<Utc>,
+    ) -> Result<NaiveDateTime, Error> {
+        if self.offset_at(t).is_err() {
+            return Ok(t.naive_utc());
+        }
+        let elapsed = nanos(self.utc_to_tai(t)?);
+        for (start, start_tai, length) in self.smears() {
+            let into = elapsed - start_tai;
+            if (0..length).contains(&into) {
+                let smeared = mul_div(into, smear_window(), length);
+                return from_nanos(i128::from(start) * NANOS + smeared)
+                    .ok_or_else(|| {
+                        Error::out_of_range(&t.to_rfc3339(), "too large")
+                    });
+            }
+        }
+        Ok(t.naive_utc())
+    }
+
+    /// Returns the UTC instant at which a clock using a 24-hour leap smear
+    /// reads `smeared`.
+    pub fn smeared_to_utc(
+        &self,
+        smeared: NaiveDateTime,
+    ) -> Result<DateTime<Utc>, Error> {
+        for (start, start_tai, length) in self.smears() {
+            let into = nanos(smeared) - i128::from(start) * NANOS;
+            if (0..smear_window()).contains(&into) {
+                let tai = from_nanos(
+                    start_tai + mul_div(into, length, smear_window()),
+                )
+                .ok_or_else(|| {
+                    Error::out_of_range(&smeared.to_string(), "too large")
+                })?;
+                return self.tai_to_utc(tai);
+            }
+        }
+        Ok(smeared.and_utc())
+    }
+
+    /// Returns the smear around each leap second in the table, as (the Unix
+    /// time at which it starts, the same instant as TAI nanoseconds, and how
+    /// many nanoseconds it lasts).
+    fn smears(&self) -> impl Iterator<Item = (i64, i128, i128)> + '_ {
+        self.entries.windows(2).map(|pair| {
+            let [(_, before), (leap, after)] = [pair[0], pair[1]];
+            let start = leap - SMEAR_HALF;
+            let start_tai = i128::from(start + i64::from(before)) * NANOS;
+            let length =
+                i128::from(2 * SMEAR_HALF + i64::from(after - before)) * NANOS;
+            (start, start_tai, length)
+        })
+    }
+
+    /// Converts GPS time, given as the time since the GPS epoch, to UTC.
+    pub fn gps_to_utc(
+        &self,
+        since_epoch: Duration,
+    ) -> Result<DateTime<Utc>, Error> {
+        let tai = gps_epoch_tai().checked_add_signed(since_epoch).ok_or_else(
+            || {
+                Error::out_of_range(
+                    &since_epoch.to_string(),
+                    "too large for a GPS time",
+                )
+            },
+        )?;
+        self.tai_to_utc(tai)
+    }
+
+    /// Converts UTC instant `t` to GPS time, as the time since the GPS epoch.
+    pub fn utc_to_gps(&self, t: DateTime<Utc>) -> Result<Duration, Error> {
+        Ok(self.utc_to_tai(t)? - gps_epoch_tai())
+    }
+}
+
+/// A timescale on which a clock can read an instant
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Scale {
+    #[default]
+    Utc,
+    Tai,
+    /// GPS time, which is a constant 19 seconds behind TAI
+    Gps,
+    /// UTC with each leap second smeared over 24 hours (see
+    /// [`LeapSeconds::utc_to_smeared`])
+    Smear,
+}
+
+impl Scale {
+    /// Parses "utc", "tai", "gps", or "smear".
+    pub fn parse(s: &str) -> Result<Scale, Error> {
+        match s {
+            "utc" => Ok(Scale::Utc),
+            "tai" => Ok(Scale::Tai),
+            "gps" => Ok(Scale::Gps),
+            "smear" => Ok(Scale::Smear),
+            _ => Err(Error::unrecognized(
+                s,
+                "a timescale (utc, tai, gps, or smear)",
+            )),
+        }
+    }
+
+    /// Returns the usual abbreviation for this scale, like "TAI" (or
+    /// "smeared" for [`Scale::Smear`]).
+    pub fn name(self) -> &'static str {
+        match self {
+            Scale::Utc => "UTC",
+            Scale::Tai => "TAI",
+            Scale::Gps => "GPS",
+            Scale::Smear => "smeared",
+        }
+    }
+
+    /// Returns what a clock on this scale reads at UTC instant `t`, using
+    /// `table` for leap seconds.
+    pub fn from_utc(
+        self,
+        table: &LeapSeconds,
+        t: DateTime<Utc>,
+    ) -> Result<NaiveDateTime, Error> {
+        match self {
+            Scale::Utc => Ok(t.naive_utc()),
+            Scale::Tai => table.utc_to_tai(t),
+            Scale::Gps => Ok(gps_epoch() + table.utc_to_gps(t)?),
+            Scale::Smear => table.utc_to_smeared(t),
+        }
+    }
+
+    /// Returns the UTC instant at which a clock on this scale reads
+    /// `reading`, using `table` for leap seconds.
+    pub fn to_utc(
+        self,
+        table: &LeapSeconds,
+        reading: NaiveDateTime,
+    ) -> Result<DateTime<Utc>, Error> {
+        match self {
+            Scale::Utc => Ok(reading.and_utc()),
+            Scale::Tai => table.tai_to_utc(reading),
+            Scale::Gps => table.gps_to_utc(reading - gps_epoch()),
+            Scale::Smear => table.smeared_to_utc(reading),
+        }
+    }
+}
+
+/// Returns the GPS epoch, which is 1980-01-06T00:00:00 in both UTC and GPS
+/// time.
+fn gps_epoch() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(1980, 1, 6).unwrap().and_hms_opt(0, 0, 0).unwrap()
+}
+
+/// Returns the GPS epoch in TAI.
+fn gps_epoch_tai() -> NaiveDateTime {
+    gps_epoch() + Duration::seconds(TAI_MINUS_GPS)
+}
+
+/// Returns how many nanoseconds a smeared clock counts during a smear.
+fn smear_window() -> i128 {
+    i128::from(2 * SMEAR_HALF) * NANOS
+}
+
+/// Returns `n * numerator / denominator`, rounded to the nearest integer, so
+/// that converting a time to a smeared clock and back gets the same time.
+/// `n` must not be negative.
+fn mul_div(n: i128, numerator: i128, denominator: i128) -> i128 {
+    (n * numerator + denominator / 2) / denominator
+}
+
+/// Returns `t` as nanoseconds since 1970-01-01T00:00:00.
+fn nanos(t: NaiveDateTime) -> i128 {
+    let t = t.and_utc();
+    i128::from(t.timestamp()) * NANOS + i128::from(t.timestamp_subsec_nanos())
+}
+
+/// Returns the time `nanos` nanoseconds after 1970-01-01T00:00:00.
+fn from_nanos(nanos: i128) -> Option<NaiveDateTime> {
+    let secs = i64::try_from(nanos.div_euclid(NANOS)).ok()?;
+    let subsec = nanos.rem_euclid(NANOS) as u32;
+    Some(DateTime::from_timestamp(secs, subsec)?.naive_utc())
+}
+
+fn before_table(t: NaiveDateTime) -> Error {
+    Error::out_of_range(
+        &t.to_string(),
+        "before the first entry in the leap second table",
+    )
+}
+
+/// Converts UTC instant `t` to TAI with the built-in table.
+pub fn utc_to_tai(t: DateTime<Utc>) -> Result<NaiveDateTime, Error> {
+    LeapSeconds::builtin().utc_to_tai(t)
+}
+
+/// Converts TAI time `tai` to UTC with the built-in table.
+pub fn tai_to_utc(tai: NaiveDateTime) -> Result<DateTime<Utc>, Error> {
+    LeapSeconds::builtin().tai_to_utc(tai)
+}
+
+/// Converts GPS time, given as the time since the GPS epoch, to UTC with the
+/// built-in table.
+pub fn gps_to_utc(since_epoch: Duration) -> Result<DateTime<Utc>, Error> {
+    LeapSeconds::builtin().gps_to_utc(since_epoch)
+}
+
+/// Converts UTC instant `t` to GPS time, as the time since the GPS epoch,
+/// with the built-in table.
+pub fn utc_to_gps(t: DateTime<Utc>) -> Result<Duration, Error> {
+    LeapSeconds::builtin().utc_to_gps(t)
+}
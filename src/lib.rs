@@ -0,0 +1,244 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Parsing and formatting of timestamps and deltas
+//!
+//! This is the logic behind the `dates` command-line tool.  Timestamps are
+//! parsed with [`parse_timestamp`] and deltas with [`parse_duration`], using
+//! the same heuristics as the command line.  [`format_timestamp`] and
+//! [`format_duration`] render them exactly the way `dates` prints them, and
+//! [`format::Formatter`] renders them in the same layout with other zones,
+//! precision, or fields.
+//!
+//! Without the "std" feature, the library is `no_std` (but requires
+//! `alloc`).  Everything that depends on the local time zone is then
+//! unavailable, and nothing in the library ever reads the current time.
+
+#![no_std]
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+use alloc::format;
+use alloc::string::String;
+use chrono::{DateTime, Duration, Utc, Weekday};
+#[cfg(feature = "std")]
+use chrono::{Local, NaiveDate};
+use core::ops::Range;
+
+pub mod abbrev;
+pub mod arrow;
+pub mod context;
+pub mod delta;
+pub mod dos;
+mod error;
+#[cfg(feature = "std")]
+pub mod extract;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod format;
+pub mod infer;
+pub mod interval;
+pub mod ksuid;
+pub mod leap;
+pub mod locale;
+pub mod objectid;
+pub mod parser;
+pub mod period;
+pub mod postgres;
+pub mod protobuf;
+#[cfg(feature = "python")]
+mod python;
+pub mod quarter;
+pub mod relative;
+pub mod report;
+pub mod roundtrip;
+pub mod snap;
+pub mod snowflake;
+pub mod sqlite;
+pub mod timecode;
+pub mod ulid;
+pub mod uuid;
+pub mod w3c;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod week;
+pub mod zone;
+
+pub use error::Error;
+
+/// Parses a timestamp in one of the formats built into `dates`.
+///
+/// Integers are interpreted as milliseconds since the Unix epoch, and other
+/// numbers as (fractional) seconds since the epoch.  Otherwise, the input may
+/// be an RFC 3339 timestamp (with a signed, expanded year like "+10000" for
+/// years after 9999), an ISO 8601 timestamp with millisecond precision
+/// (like "2018-02-08T13:43:00.000-0700") or to the minute (like
+/// "2018-02-08T13:43Z"), a bare date (like "2018-02-08", meaning midnight
+/// UTC), an ISO week date (like "2018-W06-4"), an ASN.1 UTCTime or
+/// GeneralizedTime (like "180208204300Z" or "20180208204300.123Z", as in
+/// X.509 certificates), a journald `__REALTIME_TIMESTAMP` field (like
+/// "__REALTIME_TIMESTAMP=1518122580000000"), an NMEA RMC or ZDA sentence
+/// from a GPS receiver (like "$GPZDA,204300.00,08,02,2018,00,00*62"), an
+/// AWS SigV4 time (like "20180208T204300Z"), an HTTP date (like "Thu, 08 Feb
+/// 2018 20:43:00 GMT", optionally after a header name like
+/// "Last-Modified:"), the tab-separated date and time columns of a CloudFront
+/// access log (like "2018-02-08\t20:43:00", in UTC), the `#Date` directive
+/// of a W3C extended log (like "#Date: 2018-02-08 20:43:00", also in UTC), or
+/// a date and time followed by a zone abbreviation (like "2018-02-08
+/// 13:43:00 MST").
+/// Abbreviations with more than one meaning (like "IST") are reported as
+/// ambiguous; see [`abbrev`] to resolve them.  A time of "24:00" and a leap
+/// second that didn't happen are read as [`parser::Normalization`] describes.
+///
+/// This is less than `dates TIME` accepts, since it has none of the options
+/// and no notion of the current time or the local zone: "now" and relative
+/// times (like "now-1h") aren't recognized, nor are times without an offset
+/// other than those above (like "2018-02-08 13:43"), and "2018" is 2018
+/// milliseconds since the epoch rather than the start of the year.
+///
+/// To accept additional formats, see [`parser::Registry`].
+pub fn parse_timestamp(s: &str) -> Result<DateTime<Utc>, Error> {
+    parser::parse_builtin(s)
+}
+
+/// Parses a timestamp like [`parse_timestamp`], and describes which format
+/// matched, what was assumed, and how confident the interpretation is.
+pub fn explain_timestamp(s: &str) -> Result<parser::Parsed, Error> {
+    parser::explain_builtin(s)
+}
+
+/// Interprets `s` as a calendar date.
+///
+/// A bare date like "2018-02-08" is taken as-is.  Anything else is parsed as
+/// a timestamp and converted to a date in the local time zone, matching the
+/// first form printed by `dates TIME`.
+#[cfg(feature = "std")]
+pub fn parse_date(s: &str) -> Result<NaiveDate, Error> {
+    if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return Ok(date);
+    }
+    let time = parse_timestamp(s)?;
+    Ok(time.with_timezone(&Local).date_naive())
+}
+
+/// Parses a weekday name like "fri" or "Friday".
+pub fn parse_weekday(s: &str) -> Result<Weekday, Error> {
+    s.parse::<Weekday>().map_err(|_| Error::unrecognized(s, "a weekday"))
+}
+
+/// Parses a delta the way `dates [+-]DELTA` does.
+///
+/// The input must have a leading sign and a unit suffix: "ms", "s", "m", "h",
+/// or "d" (e.g., "+30ms" or "-7d").  Several of these may follow the sign, as
+/// in "+1h30m", to mean their sum.  Calendar months and years, which have no
+/// fixed length, are parsed by [`delta::CalendarDelta::parse`] instead.
+pub fn parse_duration(s: &str) -> Result<Duration, Error> {
+    let (sign, value_span, unit) = split_delta(s)?;
+    let value: f64 = match s[value_span.clone()].parse() {
+        Ok(value) => value,
+        Err(_) => match s[value_span.clone()].rfind(char::is_alphabetic) {
+            Some(i) => return parse_compound(s, sign, value_span, i),
+            None => {
+                return Err(Error::unrecognized_at(s, value_span, "a number"));
+            }
+        },
+    };
+
+    let seconds = match unit {
+        "ms" => value / 1000.0,
+        "s" => value,
+        "m" => value * 60.0,
+        "h" => value * 3600.0,
+        "d" => value * 86400.0,
+        _ => {
+            return Err(Error::unrecognized_at(
+                s,
+                value_span.end..s.len(),
+                "a unit (ms, s, m, h, or d)",
+            ));
+        }
+    };
+
+    let millis = sign as f64 * seconds * 1000.0;
+    if !millis.is_finite() || millis.abs() >= i64::MAX as f64 {
+        return Err(Error::out_of_range_at(
+            s,
+            value_span,
+            "too large for a delta",
+        ));
+    }
+    Duration::try_milliseconds(millis as i64).ok_or_else(|| {
+        Error::out_of_range_at(s, value_span, "too large for a delta")
+    })
+}
+
+/// Parses a compound delta like "+1h30m" as the sum of its parts: here,
+/// "+1h" and "30m".  `i` is the position within `value_span` of the last
+/// letter of the unit before the last part.
+fn parse_compound(
+    s: &str,
+    sign: i32,
+    value_span: Range<usize>,
+    i: usize,
+) -> Result<Duration, Error> {
+    let split = value_span.start + i + 1;
+    let head = parse_duration(&s[..split]).map_err(|e| e.within(s, 0))?;
+    if !s[split..].starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+        let next = s[split..].chars().next().map_or(0, char::len_utf8);
+        return Err(Error::unrecognized_at(s, split..split + next, "a number"));
+    }
+    // The last part is parsed as "+30m", with the sign where the previous unit
+    // ended.
+    let tail = parse_duration(&format!("+{}", &s[split..]))
+        .map_err(|e| e.within(s, split - 1))?;
+    let tail = if sign < 0 { -tail } else { tail };
+    head.checked_add(&tail).ok_or_else(|| {
+        Error::out_of_range_at(s, value_span, "too large for a delta")
+    })
+}
+
+/// Splits a delta like "+30ms" into its sign (1 or -1), the span of its
+/// value, and its unit.
+pub(crate) fn split_delta(s: &str) -> Result<(i32, Range<usize>, &str), Error> {
+    let (sign, rest) = match s.chars().next() {
+        Some('+') => (1, &s[1..]),
+        Some('-') => (-1, &s[1..]),
+        _ => {
+            let first = s.chars().next().map_or(0, char::len_utf8);
+            return Err(Error::unrecognized_at(
+                s,
+                0..first,
+                "the sign of a delta (\"+\" or \"-\")",
+            ));
+        }
+    };
+    let value = rest.trim_end_matches(char::is_alphabetic);
+    Ok((sign, 1..1 + value.len(), &rest[value.len()..]))
+}
+
+/// Formats `dt` the way `dates` prints a time: two lines showing the Unix
+/// timestamp and the time in the local zone, then the time in UTC.
+///
+/// The result does not end with a newline.
+///
+/// See [`format::Formatter`] to customize this.
+#[cfg(feature = "std")]
+pub fn format_timestamp(label: &str, dt: DateTime<Utc>) -> String {
+    format::Formatter::new().format_time(label, dt)
+}
+
+/// Formats `delta` the way `dates` prints a delta: one line showing the
+/// total seconds and the [`format_breakdown`] of `delta`.
+pub fn format_duration(label: &str, delta: Duration) -> String {
+    format::Formatter::new().format_delta(label, delta)
+}
+
+/// Formats `delta` as a fixed-width breakdown like " 0d 21h 01m 23.456000s".
+pub fn format_breakdown(delta: Duration) -> String {
+    let nanos = i128::from(delta.num_seconds()) * 1_000_000_000
+        + i128::from(delta.subsec_nanos());
+    format::breakdown(nanos, chrono::SecondsFormat::Micros)
+}
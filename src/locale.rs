@@ -0,0 +1,432 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Month and weekday names and duration phrases in several languages
+//!
+//! A [`Lang`] supplies the names that `dates` prints (in calendars, after
+//! dates, and in phrases like "in 3 days") and the names it accepts for
+//! options like `--weekday`.  Parsing in any language also accepts the
+//! English names, so scripts keep working whatever the user's locale.
+//!
+//! Phrases in the output of `dates`, like "25th anniversary", are in the
+//! same language, but labels (like "date" and "next") stay in English.
+
+use crate::Error;
+use crate::quarter::parse_month;
+use alloc::format;
+use alloc::string::String;
+use chrono::{Duration, Month, NaiveDate, Weekday};
+
+/// A language for names and phrases
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Lang {
+    #[default]
+    En,
+    De,
+    Es,
+    Fr,
+}
+
+/// A unit of time, for phrases like "3 days"
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Unit {
+    Year,
+    Month,
+    Week,
+    Day,
+    Hour,
+    Minute,
+    Second,
+}
+
+/// Everything that differs between languages
+struct Names {
+    code: &'static str,
+    months: [&'static str; 12],
+    /// full weekday names, starting with Monday
+    weekdays: [&'static str; 7],
+    /// abbreviated weekday names, starting with Monday
+    weekdays_short: [&'static str; 7],
+    /// for each [`Unit`], the singular and plural forms on their own and
+    /// within [`Names::future`] and [`Names::past`] (which differ in German)
+    units: [[&'static str; 4]; 7],
+    /// whether a count takes the plural form
+    plural: fn(i64) -> bool,
+    /// how to say "in {}" and "{} ago"
+    future: (&'static str, &'static str),
+    past: (&'static str, &'static str),
+    /// how to write an ordinal number, like "25th"
+    ordinal: fn(u32) -> String,
+    /// what follows an ordinal number in "25th anniversary"
+    anniversary: &'static str,
+    /// what comes before "25th anniversary" on the day of it
+    today: &'static str,
+    /// what follows a count of days in "{} total"
+    total: &'static str,
+    /// how to say "weeks start {}, week 1 has at least {} of the year"
+    week_rules: [&'static str; 3],
+    /// what goes between the dates in "2018-02-05 to 2018-02-11"
+    to: &'static str,
+}
+
+const EN: Names = Names {
+    code: "en",
+    months: [
+        "January",
+        "February",
+        "March",
+        "April",
+        "May",
+        "June",
+        "July",
+        "August",
+        "September",
+        "October",
+        "November",
+        "December",
+    ],
+    weekdays: [
+        "Monday",
+        "Tuesday",
+        "Wednesday",
+        "Thursday",
+        "Friday",
+        "Saturday",
+        "Sunday",
+    ],
+    weekdays_short: ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"],
+    units: [
+        ["year", "years", "year", "years"],
+        ["month", "months", "month", "months"],
+        ["week", "weeks", "week", "weeks"],
+        ["day", "days", "day", "days"],
+        ["hour", "hours", "hour", "hours"],
+        ["minute", "minutes", "minute", "minutes"],
+        ["second", "seconds", "second", "seconds"],
+    ],
+    plural: |n| n != 1,
+    future: ("in ", ""),
+    past: ("", " ago"),
+    ordinal: |n| {
+        let suffix = match (n % 10, n % 100) {
+            (_, 11..=13) => "th",
+            (1, _) => "st",
+            (2, _) => "nd",
+            (3, _) => "rd",
+            _ => "th",
+        };
+        format!("{n}{suffix}")
+    },
+    anniversary: "anniversary",
+    today: "today is the ",
+    total: "total",
+    week_rules: ["weeks start ", ", week 1 has at least ", " of the year"],
+    to: "to",
+};
+
+const DE: Names = Names {
+    code: "de",
+    months: [
+        "Januar",
+        "Februar",
+        "März",
+        "April",
+        "Mai",
+        "Juni",
+        "Juli",
+        "August",
+        "September",
+        "Oktober",
+        "November",
+        "Dezember",
+    ],
+    weekdays: [
+        "Montag",
+        "Dienstag",
+        "Mittwoch",
+        "Donnerstag",
+        "Freitag",
+        "Samstag",
+        "Sonntag",
+    ],
+    weekdays_short: ["Mo", "Di", "Mi", "Do", "Fr", "Sa", "So"],
+    units: [
+        ["Jahr", "Jahre", "Jahr", "Jahren"],
+        ["Monat", "Monate", "Monat", "Monaten"],
+        ["Woche", "Wochen", "Woche", "Wochen"],
+        ["Tag", "Tage", "Tag", "Tagen"],
+        ["Stunde", "Stunden", "Stunde", "Stunden"],
+        ["Minute", "Minuten", "Minute", "Minuten"],
+        ["Sekunde", "Sekunden", "Sekunde", "Sekunden"],
+    ],
+    plural: |n| n != 1,
+    future: ("in ", ""),
+    past: ("vor ", ""),
+    ordinal: |n| format!("{n}."),
+    anniversary: "Jahrestag",
+    today: "heute ist der ",
+    total: "insgesamt",
+    week_rules: [
+        "Wochen beginnen am ",
+        ", Woche 1 hat mindestens ",
+        " des Jahres",
+    ],
+    to: "bis",
+};
+
+const ES: Names = Names {
+    code: "es",
+    months: [
+        "enero",
+        "febrero",
+        "marzo",
+        "abril",
+        "mayo",
+        "junio",
+        "julio",
+        "agosto",
+        "septiembre",
+        "octubre",
+        "noviembre",
+        "diciembre",
+    ],
+    weekdays: [
+        "lunes",
+        "martes",
+        "miércoles",
+        "jueves",
+        "viernes",
+        "sábado",
+        "domingo",
+    ],
+    weekdays_short: ["lun", "mar", "mié", "jue", "vie", "sáb", "dom"],
+    units: [
+        ["año", "años", "año", "años"],
+        ["mes", "meses", "mes", "meses"],
+        ["semana", "semanas", "semana", "semanas"],
+        ["día", "días", "día", "días"],
+        ["hora", "horas", "hora", "horas"],
+        ["minuto", "minutos", "minuto", "minutos"],
+        ["segundo", "segundos", "segundo", "segundos"],
+    ],
+    plural: |n| n != 1,
+    future: ("en ", ""),
+    past: ("hace ", ""),
+    ordinal: |n| format!("{n}.º"),
+    anniversary: "aniversario",
+    today: "hoy es el ",
+    total: "en total",
+    week_rules: [
+        "las semanas empiezan el ",
+        ", la semana 1 tiene al menos ",
+        " del año",
+    ],
+    to: "a",
+};
+
+const FR: Names = Names {
+    code: "fr",
+    months: [
+        "janvier",
+        "février",
+        "mars",
+        "avril",
+        "mai",
+        "juin",
+        "juillet",
+        "août",
+        "septembre",
+        "octobre",
+        "novembre",
+        "décembre",
+    ],
+    weekdays: [
+        "lundi", "mardi", "mercredi", "jeudi", "vendredi", "samedi", "dimanche",
+    ],
+    weekdays_short: ["lun", "mar", "mer", "jeu", "ven", "sam", "dim"],
+    units: [
+        ["an", "ans", "an", "ans"],
+        ["mois", "mois", "mois", "mois"],
+        ["semaine", "semaines", "semaine", "semaines"],
+        ["jour", "jours", "jour", "jours"],
+        ["heure", "heures", "heure", "heures"],
+        ["minute", "minutes", "minute", "minutes"],
+        ["seconde", "secondes", "seconde", "secondes"],
+    ],
+    // French uses the singular for zero.
+    plural: |n| n > 1,
+    future: ("dans ", ""),
+    past: ("il y a ", ""),
+    ordinal: |n| if n == 1 { format!("{n}er") } else { format!("{n}e") },
+    anniversary: "anniversaire",
+    today: "c'est aujourd'hui le ",
+    total: "au total",
+    week_rules: [
+        "les semaines commencent le ",
+        ", la semaine 1 a au moins ",
+        " de l'année",
+    ],
+    to: "au",
+};
+
+const LANGS: [(Lang, &Names); 4] =
+    [(Lang::En, &EN), (Lang::De, &DE), (Lang::Es, &ES), (Lang::Fr, &FR)];
+
+impl Lang {
+    /// Parses a language code like "de", or a POSIX locale name like
+    /// "de_DE.UTF-8" or "C" (meaning English).
+    pub fn parse(s: &str) -> Result<Lang, Error> {
+        let code = s.split(['_', '-', '.', '@']).next().unwrap_or(s);
+        if code == "C" || code == "POSIX" {
+            return Ok(Lang::En);
+        }
+        LANGS
+            .iter()
+            .find(|(_, names)| names.code.eq_ignore_ascii_case(code))
+            .map(|(lang, _)| *lang)
+            .ok_or_else(|| {
+                Error::unrecognized(s, "a language (en, de, es, or fr)")
+            })
+    }
+
+    /// Returns the language for times from the environment: `LC_ALL`,
+    /// `LC_TIME`, or `LANG`, whichever is set first, as for other POSIX
+    /// tools.  Unsupported or missing locales mean English.
+    #[cfg(feature = "std")]
+    pub fn from_env() -> Lang {
+        ["LC_ALL", "LC_TIME", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Lang::parse(&value).ok())
+            .unwrap_or_default()
+    }
+
+    fn names(self) -> &'static Names {
+        LANGS.iter().find(|(lang, _)| *lang == self).unwrap().1
+    }
+
+    /// Returns the name of `month`, like "October".
+    pub fn month_name(self, month: Month) -> &'static str {
+        self.names().months[month.number_from_month() as usize - 1]
+    }
+
+    /// Returns the name of `day`, like "Friday".
+    pub fn weekday_name(self, day: Weekday) -> &'static str {
+        self.names().weekdays[day.num_days_from_monday() as usize]
+    }
+
+    /// Returns the abbreviated name of `day`, like "Fri".
+    pub fn weekday_short(self, day: Weekday) -> &'static str {
+        self.names().weekdays_short[day.num_days_from_monday() as usize]
+    }
+
+    /// Parses a weekday name, full or abbreviated, in this language or in
+    /// English (without regard to case).
+    pub fn parse_weekday(self, s: &str) -> Result<Weekday, Error> {
+        let names = self.names();
+        let lower = s.to_lowercase();
+        let found = names.weekdays.iter().zip(names.weekdays_short).position(
+            |(full, short)| {
+                full.to_lowercase() == lower || short.to_lowercase() == lower
+            },
+        );
+        match found {
+            Some(i) => Ok(Weekday::try_from(i as u8).unwrap()),
+            None => crate::parse_weekday(s),
+        }
+    }
+
+    /// Parses a month given by name in this language or in English (without
+    /// regard to case), or by number (1-12).  Names may be abbreviated to any
+    /// prefix of at least three letters that only one month has.
+    pub fn parse_month(self, s: &str) -> Result<Month, Error> {
+        let lower = s.to_lowercase();
+        let months = self.names().months.map(str::to_lowercase);
+        let exact = months.iter().position(|m| *m == lower);
+        let mut prefixed = (0..12).filter(|&i| months[i].starts_with(&lower));
+        let found = match (exact, prefixed.next(), prefixed.next()) {
+            (Some(i), _, _) => Some(i),
+            (None, Some(i), None) if lower.chars().count() >= 3 => Some(i),
+            _ => None,
+        };
+        match found {
+            Some(i) => Ok(Month::try_from(i as u8 + 1).unwrap()),
+            None => parse_month(s),
+        }
+    }
+
+    /// Returns the form of `unit` to use with count `n`.  `relative` selects
+    /// the form used with [`Lang::relative`].
+    fn unit(self, n: i64, unit: Unit, relative: bool) -> &'static str {
+        let names = self.names();
+        let forms = names.units[unit as usize];
+        let plural = (names.plural)(n.abs());
+        forms[usize::from(relative) * 2 + usize::from(plural)]
+    }
+
+    /// Describes `n` of `unit`, like "3 days".
+    pub fn quantity(self, n: i64, unit: Unit) -> String {
+        format!("{n} {}", self.unit(n, unit, false))
+    }
+
+    /// Describes `n` of `unit` from now, like "in 3 days" (or "3 days ago"
+    /// if `n` is negative).
+    pub fn relative(self, n: i64, unit: Unit) -> String {
+        let names = self.names();
+        let (prefix, suffix) = if n < 0 { names.past } else { names.future };
+        let unit = self.unit(n, unit, true);
+        format!("{prefix}{} {unit}{suffix}", n.abs())
+    }
+
+    /// Describes the `n`th anniversary, like "25th anniversary".
+    pub fn anniversary(self, n: u32) -> String {
+        let names = self.names();
+        format!("{} {}", (names.ordinal)(n), names.anniversary)
+    }
+
+    /// Says that today is the `n`th anniversary, like "today is the 25th
+    /// anniversary".
+    pub fn anniversary_today(self, n: u32) -> String {
+        format!("{}{}", self.names().today, self.anniversary(n))
+    }
+
+    /// Describes a total of `n` days, like "9000 days total".
+    pub fn total_days(self, n: i64) -> String {
+        format!("{} {}", self.quantity(n, Unit::Day), self.names().total)
+    }
+
+    /// Describes the dates from `start` through `end`, like "2018-02-05 to
+    /// 2018-02-11".
+    pub fn date_range(self, start: NaiveDate, end: NaiveDate) -> String {
+        format!("{start} {} {end}", self.names().to)
+    }
+
+    /// Describes the rules for numbering weeks: that they start on `first`,
+    /// and that week 1 is the first with at least `min_days` days of the
+    /// year.
+    pub fn week_rules(self, first: Weekday, min_days: u32) -> String {
+        let [start, days, of_year] = self.names().week_rules;
+        format!(
+            "{start}{}{days}{}{of_year}",
+            self.weekday_short(first),
+            self.quantity(min_days.into(), Unit::Day),
+        )
+    }
+
+    /// Describes `delta` from now in its largest whole unit (weeks at most),
+    /// like "in 3 days" or "2 hours ago".
+    pub fn humanize(self, delta: Duration) -> String {
+        let (n, unit) = [
+            (delta.num_weeks(), Unit::Week),
+            (delta.num_days(), Unit::Day),
+            (delta.num_hours(), Unit::Hour),
+            (delta.num_minutes(), Unit::Minute),
+        ]
+        .into_iter()
+        .find(|(n, _)| *n != 0)
+        .unwrap_or((delta.num_seconds(), Unit::Second));
+        self.relative(n, unit)
+    }
+}
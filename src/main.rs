@@ -3,9 +3,35 @@
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
 use anyhow::{Context, bail};
-use chrono::{
-    DateTime, Duration, Local, NaiveDate, SecondsFormat, TimeZone, Utc,
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
+use cmd::{
+    age, arrow, aws, bson, cal, cbor, cert, check, count_weekdays, dmesg, dos,
+    dtrace, eval, exif, extreme, infer, journald, jwt, ksuid, leap_seconds,
+    mail, midpoint, msgpack, objectid, overflow, pcap, perf, postgres,
+    protobuf, ptp, quarter, sct, snowflake, sqlite, strace, sum, timecode,
+    timeline, tod, totp_step, tsc, ulid, uuid, vms, w3c, week,
 };
+use dates_tool::abbrev::{AbbreviationParser, Region};
+use dates_tool::context;
+use dates_tool::delta::{Arith, CalendarDelta, MonthEnd, Rules};
+use dates_tool::format::{Formatter, Unit};
+use dates_tool::interval::{Interval, Part, Repeating};
+use dates_tool::leap::{LeapSeconds, Scale};
+use dates_tool::locale::Lang;
+use dates_tool::parser::{
+    BrowserTime, Epoch, EpochTime, EpochUnit, Registry, SinceBoot, Strftime,
+    UnixTime,
+};
+use dates_tool::period::PartialDateParser;
+use dates_tool::relative::Relative;
+use dates_tool::report::{self, Entry, Report, Timestamp};
+use dates_tool::roundtrip::{RoundTripFailure, verify_timestamp};
+use dates_tool::snap::{Mode, Step};
+use dates_tool::zone::{
+    AmbiguousPolicy, DstPolicy, NonexistentPolicy, WallClockParser, Zone,
+};
+use dates_tool::{format_duration, parse_duration, parse_timestamp};
+use std::ops::Range;
 
 const USAGE: &str = r#"
 usage: dates              # prints current time (in several forms)
@@ -13,149 +39,1324 @@ usage: dates              # prints current time (in several forms)
        dates [+-]DELTA    # prints current time offset by DELTA
        dates T1 T2        # prints T1, T2, and the delta between them
        dates T1 [+-]DELTA # prints T1, DELTA, and T2 = T1 + DELTA
+       dates [+-]DELTA1 [+-]DELTA2
+                          # prints both deltas, their sum and difference, and
+                          # their ratio
+       dates [+-]DELTA xN # prints DELTA multiplied by N
+       dates --divide N ...
+                          # also prints the delta (or the total) divided by N,
+                          # like the interval between N events
+       dates --month-end clamp|overflow ...
+                          # chooses what +1mo does to January 31
+       dates --arith wall|absolute ...
+                          # chooses whether +1d means the same time tomorrow
+                          # or 24 hours later (the default)
+       dates --scale utc|tai|gps|smear ...
+                          # reads and also prints times on that timescale
+       dates --json ...   # prints any of the above as JSON
+       dates --prefer-region REGION[,REGION...] ...
+                          # resolves ambiguous zone abbreviations like "IST"
+       dates --schema     # prints the JSON Schema for --json output
+       dates --help       # prints this message
+       dates --explain ...
+                          # also prints how each time was parsed
+       dates --strict ... # rejects times that aren't fully specified, like
+                          # bare numbers and dates
+       dates --candidates TIME...
+                          # prints every way each TIME could be read
+       dates --verify ... # fails unless each time printed reads back as
+                          # itself
+       dates --epoch-unit s|ms|us|ns|d ...
+                          # reads bare numbers in that unit since 1970
+       dates --epoch unix|sas|spss|stata|prtime|webkit ...
+                          # reads bare numbers as SAS, SPSS, or Stata times,
+                          # or Firefox (PRTime) or Chrome (WebKit) times (in
+                          # seconds, or for Stata, milliseconds, and for
+                          # browsers, microseconds, unless --epoch-unit says
+                          # otherwise)
+       dates --since-boot [--boot-time TIME] ...
+                          # reads bare numbers as seconds (or --epoch-unit)
+                          # since the system booted, as from ftrace, systemd,
+                          # or BPF
+       dates START/END | START/DURATION | DURATION/END
+                          # prints an ISO 8601 interval's start, end, and
+                          # length
+       dates Rn/INTERVAL  # prints the start and end of each of n intervals
+                          # in a row
+       dates --end-of-period DATE...
+                          # reads a year or month (like 2024 or 2024-03) as
+                          # its end, rather than its start
+       dates --as-range DATE
+                          # prints when the year, month, or day DATE starts
+                          # and ends, and how long it is
+       dates now[+-N UNIT...][@UNIT...]
+                          # prints a relative time like now-7d@d or -1h@h
+       dates [TIME||][+-N UNIT...][/UNIT...]
+                          # prints Elasticsearch date math like now+1h/d
+       dates --floor STEP | --ceil STEP | --round STEP ...
+                          # rounds each time to a multiple of STEP (like 15m
+                          # or 1d) in --tz or the local zone
+       dates --break-into UNIT[,UNIT...] | --largest-unit UNIT ...
+                          # breaks deltas down into those units (w, d, h,
+                          # m, s) rather than days, hours, minutes, and
+                          # seconds
+       dates --days [--tz ZONE] T1 T2
+                          # prints the number of calendar days from T1 to T2
+       dates --tz ZONE [--ambiguous earliest|latest|error]
+                  [--nonexistent shift|error] ...
+                          # reads times without an offset in ZONE
+       dates --input-tz ZONE ...
+                          # likewise, without changing the zone for --days
+                          # and calendar arithmetic
+       dates T1 T2 T3...  # prints each time, the deltas between them, and
+                          # the total delta from first to last
+       dates pcap [--packets] [--deltas] FILE
+                          # prints when the first and last packets in a pcap
+                          # or pcapng FILE were captured
+       dates perf [--anchor PERFTIME=WALLTIME] [PERFTIME...]
+                          # prints the time of each perf script time, or of
+                          # each sample in perf script output on stdin
+       dates postgres VALUE...
+                          # prints each PostgreSQL timestamp (or infinity)
+                          # with its microseconds since 2000, as stored
+                          # internally
+       dates protobuf VALUE | [--duration] SECONDS NANOS
+                          # prints a protobuf Timestamp or Duration as JSON
+                          # and as seconds and nanos
+       dates ptp [--file PATH] SECONDS NANOS | HEX | TIME
+                          # prints a PTP timestamp (or its 80-bit wire form)
+                          # on the TAI and UTC timescales
+       dates sqlite [TIME] | --julianday JD | --unixepoch SECONDS
+                          # prints TIME as SQLite's julianday(), unixepoch(),
+                          # and datetime() would
+       dates quarter [--fy-start MONTH] [--tz ZONE] [TIME]
+                          # prints the calendar and fiscal quarter of TIME
+       dates strace [--to ttt|r|time] [--start TIME]
+                          # converts the -ttt or -r times in strace output on
+                          # stdin to another form (by default, local time)
+       dates sum [--daily] [--mean] [DURATION...]
+                          # adds up DURATIONs (or stdin lines), like 1h30m,
+                          # 1:30, or PT1H30M
+       dates timecode --fps RATE[df] TIMECODE|FRAMES|DURATION...
+                          # converts between SMPTE timecodes (like 01:02:03;12),
+                          # frame counts, and durations
+       dates timeline [TIME...] # draws TIMEs (or stdin lines) on an axis
+       dates journald [TIME [TIME]]
+                          # prints TIME as journald records it, and as
+                          # journalctl --since and --until arguments
+       dates jwt TOKEN    # prints when a JSON Web Token was issued, when it's
+                          # valid from and until, and how long it has left
+       dates totp-step [--period SECONDS] [TIME]
+                          # prints the TOTP counter at TIME, and when the
+                          # next step begins
+       dates mail [FILE]  # prints when an email message was sent and received
+                          # at each hop, and how long each hop took
+       dates ntp [SERVER] # prints how far the local clock is from an NTP
+                          # server's (with the "ntp" feature)
+       dates skew-http URL
+                          # prints how far the local clock is from a web
+                          # server's Date header (with the "http" feature)
+       dates ksuid KSUID... # prints when each KSUID was generated
+       dates objectid ID... # prints when each MongoDB ObjectId was generated
+       dates objectid --encode [TIME]
+                          # prints the smallest and largest ObjectIds for
+                          # TIME, for a range query
+       dates snowflake [--epoch twitter|discord|MILLIS] ID...
+                          # prints when each Snowflake ID was generated, and
+                          # its worker and sequence number
+       dates tod [--leap-seconds] VALUE...
+                          # converts IBM mainframe TOD clock values
+       dates tsc --freq FREQ [--anchor TSC=WALLTIME] CYCLES...
+                          # prints how long CYCLES counter cycles take, or
+                          # with --anchor, when the counter read CYCLES
+       dates ulid ULID... # prints when each ULID was generated
+       dates ulid --encode [TIME]
+                          # makes a ULID for TIME, and the smallest and
+                          # largest ULIDs for that millisecond
+       dates uuid UUID... # prints when each version 1, 6, or 7 UUID was
+                          # generated (and for 1 and 6, its clock sequence
+                          # and node)
+       dates uuid --generate [TIME]
+                          # makes a version 7 UUID for TIME
+       dates vms [--tz ZONE] VALUE...
+                          # converts OpenVMS binary times
+       dates w3c [--csv] | DATE TIME
+                          # converts the UTC times in a W3C extended (IIS)
+                          # log on stdin to local time, or the log to CSV
+       dates week [--weeks iso|us] [--week-start DAY] [--min-days N]
+                  [DATE | WEEKDATE]
+                          # prints the week number and week date of DATE
+       dates age DATE [ASOF] # prints age and the next anniversary of DATE
+       dates arrow TYPE VALUE...
+                          # prints what each integer VALUE means in an Arrow
+                          # or Parquet TYPE, like timestamp[us, tz=UTC]
+       dates aws [TIME]   # prints TIME as AWS request signing (SigV4) and S3
+                          # write it, and whether AWS would accept a request
+                          # signed with it
+       dates bson [JSON...]
+                          # prints the $date and $timestamp values in
+                          # MongoDB Extended JSON (or on stdin)
+       dates cal [-y] [-w] [--first-day DAY] [--weeks iso|us] [DATE]
+                          # prints a calendar
+       dates cbor DATA... # prints the tag 0 and tag 1 times in CBOR DATA
+                          # (in hexadecimal or base64)
+       dates cert [--der] [--warn-within DELTA] FILE
+                          # prints when each certificate in FILE is valid
+       dates sct DATA...  # prints when each Certificate Transparency log in
+                          # an SCT list (in hexadecimal or base64) signed it
+       dates check A --before B | --after B | --within DELTA B
+                          # exits 0 if the condition holds, 1 if not (A and
+                          # B are read as TIME is, so they may be relative
+                          # times like now-5m)
+       dates count-weekdays START END [--weekday DAY[,DAY...]]
+                          # counts each weekday from START to END inclusive
+       dates dmesg [--boot-time TIME] [STAMP...]
+                          # prints the time of each kernel log STAMP (like
+                          # [12.345678]), or of each line of dmesg on stdin
+       dates dos [--tz ZONE] VALUE...
+                          # decodes or packs FAT and ZIP (DOS) dates and times
+       dates dtrace --anchor HRTIME=WALLTIME [HRTIME...]
+                          # prints the time of each DTrace timestamp HRTIME,
+                          # or of those in D script output on stdin
+       dates eval EXPR [NAME=VALUE...] # evaluates arithmetic on times
+       dates exif [--tz ZONE] FILE...
+                          # prints when each photo was taken, created, and
+                          # modified, and its GPS time, from EXIF metadata
+       dates infer EXAMPLE # guesses a strftime pattern for EXAMPLE
+       dates leap-seconds [--file PATH] [TIME]
+                          # prints the leap seconds and TAI - UTC at TIME
+       dates min|max [TIME...]
+                          # prints the earliest or latest TIME (or stdin
+                          # line) and how far each other one is from it
+       dates msgpack DATA...
+                          # prints the timestamps in MessagePack DATA (in
+                          # hexadecimal or base64)
+       dates midpoint [--fraction F] T1 T2
+                          # prints the time halfway (or F of the way) from T1
+                          # to T2
+       dates overflow [--width BITS] [--signed | --unsigned]
+                      [--unit s|ms|us|ns] [TIME]
+                          # checks whether TIME fits in fixed-width integers
+
+Any form accepts --lang LANG (en, de, es, or fr) to choose the language of
+month and weekday names and of phrases like "in 3 days", which otherwise comes
+from LC_ALL, LC_TIME, or LANG, and --now TIME to use TIME as the current
+time.  Without --now, the current time comes from SOURCE_DATE_EPOCH (in seconds
+since 1970), if it's set.
+Forms that read times read them as `dates TIME` does, with the same options
+(like --input-tz and --strict), which may also come before the subcommand.
 "#;
 
+mod cmd;
+
+/// Exit status when an input is out of range (see [`exit_status`])
+const EXIT_OUT_OF_RANGE: i32 = 3;
+/// Exit status when an input is ambiguous (see [`exit_status`])
+const EXIT_AMBIGUOUS: i32 = 4;
+/// Exit status when `--verify` fails (see [`exit_status`])
+const EXIT_VERIFY: i32 = 5;
+
 fn main() {
     if let Err(error) = doit() {
         eprintln!("dates: {:#}", error);
-        eprintln!("{USAGE}");
-        std::process::exit(2);
+        match parse_error(&error) {
+            Some(e) if e.span() != (0..e.input().len()) => {
+                for line in e.highlight().lines() {
+                    eprintln!("    {line}");
+                }
+            }
+            Some(_) => (),
+            None if roundtrip_failure(&error).is_some() => (),
+            None if error.downcast_ref::<UsageError>().is_some() => {
+                eprintln!("{USAGE}")
+            }
+            None => eprintln!("see `dates --help` for usage"),
+        }
+        std::process::exit(exit_status(&error));
+    }
+}
+
+/// An error in how `dates` was invoked, like an unknown option or one that's
+/// missing its value, after which [`main`] prints the usage message
+#[derive(Debug)]
+struct UsageError(String);
+
+impl UsageError {
+    fn new(message: impl std::fmt::Display) -> UsageError {
+        UsageError(message.to_string())
+    }
+}
+
+impl std::fmt::Display for UsageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for UsageError {}
+
+/// Returns the first [`dates_tool::Error`] in `error`'s chain, if any.
+fn parse_error(error: &anyhow::Error) -> Option<&dates_tool::Error> {
+    error.chain().find_map(|e| e.downcast_ref::<dates_tool::Error>())
+}
+
+/// Returns the [`RoundTripFailure`] in `error`'s chain, if any.
+fn roundtrip_failure(error: &anyhow::Error) -> Option<&RoundTripFailure> {
+    error.chain().find_map(|e| e.downcast_ref::<RoundTripFailure>())
+}
+
+/// Returns the exit status for a failed invocation.
+///
+/// This is 2 for usage errors and unrecognized input, 3 for input that was
+/// understood but is out of range, 4 for ambiguous input, and 5 when
+/// `--verify` finds a time that doesn't read back as itself.  (`dates check`
+/// additionally exits 1 when its condition does not hold.)
+fn exit_status(error: &anyhow::Error) -> i32 {
+    if roundtrip_failure(error).is_some() {
+        return EXIT_VERIFY;
+    }
+    match parse_error(error) {
+        Some(dates_tool::Error::OutOfRange { .. }) => EXIT_OUT_OF_RANGE,
+        Some(dates_tool::Error::Ambiguous { .. }) => EXIT_AMBIGUOUS,
+        _ => 2,
     }
 }
 
 fn doit() -> anyhow::Result<()> {
-    let args: Vec<_> = std::env::args().skip(1).collect();
+    let mut args: Vec<_> = std::env::args().skip(1).collect();
+    // These options apply to every form of the command, so they're accepted
+    // anywhere.
+    let lang = match take_option(&mut args, "--lang")? {
+        Some(value) => Lang::parse(&value)?,
+        None => Lang::from_env(),
+    };
+    let mut ctx = context::Context::system();
+    if let Some(value) = take_option(&mut args, "--now")? {
+        ctx = ctx.with_now(parse_timestamp(&value)?);
+    } else if let Some(value) = std::env::var_os("SOURCE_DATE_EPOCH")
+        && !value.is_empty()
+    {
+        let value = value.to_string_lossy();
+        ctx = ctx.with_now(context::parse_source_date_epoch(&value)?);
+    }
+    let ctx = &ctx;
 
-    match args.len() {
-        0 => print_time("now", Utc::now()),
-        1 => handle_one(&args[0])?,
-        2 => handle_two(&args[0], &args[1])?,
-        _ => {
-            bail!("too many arguments");
+    if args.iter().any(|a| a == "--help") {
+        print!("{}", USAGE.trim_start());
+        return Ok(());
+    }
+
+    // Options for reading times may come before a subcommand, as in
+    // `dates --tz UTC min ...`, so they're moved after its name.  (Any other
+    // form accepts them anywhere.)
+    let mut leading = args.iter();
+    let mut start = 0;
+    let mut options = ReadOptions::default();
+    while let Some(arg) = leading.next()
+        && options.parse_option(arg, &mut leading)?
+    {
+        start = args.len() - leading.len();
+    }
+    if start < args.len() {
+        args[..=start].rotate_right(1);
+    }
+
+    match args.first().map(String::as_str) {
+        Some("age") => age::run(&args[1..], ctx, lang),
+        Some("arrow") => arrow::run(&args[1..], ctx),
+        Some("aws") => aws::run(&args[1..], ctx),
+        Some("bson") => bson::run(&args[1..], ctx),
+        Some("cal") => cal::run(&args[1..], ctx, lang),
+        Some("cbor") => cbor::run(&args[1..], ctx),
+        Some("cert") => cert::run(&args[1..], ctx),
+        Some("check") => check::run(&args[1..], ctx),
+        Some("count-weekdays") => count_weekdays::run(&args[1..], ctx, lang),
+        Some("dmesg") => dmesg::run(&args[1..], ctx),
+        Some("dos") => dos::run(&args[1..], ctx),
+        Some("dtrace") => dtrace::run(&args[1..], ctx),
+        Some("eval") => eval::run(&args[1..], ctx),
+        Some("exif") => exif::run(&args[1..], ctx),
+        Some("infer") => infer::run(&args[1..], ctx),
+        Some("journald") => journald::run(&args[1..], ctx),
+        Some("jwt") => jwt::run(&args[1..], ctx),
+        Some("ksuid") => ksuid::run(&args[1..], ctx),
+        Some("leap-seconds") => leap_seconds::run(&args[1..], ctx),
+        Some("mail") => mail::run(&args[1..], ctx),
+        Some("max") => extreme::run(&args[1..], ctx, extreme::Extreme::Max),
+        Some("midpoint") => midpoint::run(&args[1..], ctx),
+        Some("min") => extreme::run(&args[1..], ctx, extreme::Extreme::Min),
+        Some("msgpack") => msgpack::run(&args[1..], ctx),
+        #[cfg(feature = "ntp")]
+        Some("ntp") => cmd::ntp::run(&args[1..], ctx),
+        #[cfg(not(feature = "ntp"))]
+        Some("ntp") => {
+            bail!("ntp: dates was built without the \"ntp\" feature")
+        }
+        Some("objectid") => objectid::run(&args[1..], ctx),
+        Some("overflow") => overflow::run(&args[1..], ctx),
+        Some("pcap") => pcap::run(&args[1..], ctx),
+        Some("perf") => perf::run(&args[1..], ctx),
+        Some("postgres") => postgres::run(&args[1..], ctx),
+        Some("protobuf") => protobuf::run(&args[1..], ctx),
+        Some("ptp") => ptp::run(&args[1..], ctx),
+        Some("quarter") => quarter::run(&args[1..], ctx, lang),
+        Some("sct") => sct::run(&args[1..], ctx),
+        #[cfg(feature = "http")]
+        Some("skew-http") => cmd::skew_http::run(&args[1..], ctx),
+        #[cfg(not(feature = "http"))]
+        Some("skew-http") => {
+            bail!("skew-http: dates was built without the \"http\" feature")
         }
+        Some("snowflake") => snowflake::run(&args[1..], ctx),
+        Some("sqlite") => sqlite::run(&args[1..], ctx),
+        Some("strace") => strace::run(&args[1..], ctx),
+        Some("sum") => sum::run(&args[1..], ctx),
+        Some("timecode") => timecode::run(&args[1..]),
+        Some("timeline") => timeline::run(&args[1..], ctx),
+        Some("tod") => tod::run(&args[1..], ctx),
+        Some("totp-step") => totp_step::run(&args[1..], ctx),
+        Some("tsc") => tsc::run(&args[1..], ctx),
+        Some("ulid") => ulid::run(&args[1..], ctx),
+        Some("uuid") => uuid::run(&args[1..], ctx),
+        Some("vms") => vms::run(&args[1..], ctx),
+        Some("w3c") => w3c::run(&args[1..], ctx),
+        Some("week") => week::run(&args[1..], ctx, lang),
+        _ => handle_positional(&args, ctx),
     }
+}
 
-    Ok(())
+/// Removes `FLAG VALUE` from `args`, wherever it appears, and returns the
+/// value.
+fn take_option(
+    args: &mut Vec<String>,
+    flag: &str,
+) -> anyhow::Result<Option<String>> {
+    let Some(i) = args.iter().position(|a| a == flag) else {
+        return Ok(None);
+    };
+    let value = args
+        .get(i + 1)
+        .with_context(|| {
+            UsageError::new(format!("{flag} requires an argument"))
+        })?
+        .clone();
+    args.drain(i..i + 2);
+    Ok(Some(value))
 }
 
-fn handle_one(arg: &str) -> anyhow::Result<()> {
-    if let Ok(delta) = parse_delta(arg) {
-        let now = Utc::now();
-        let then = now + delta;
-        print_time("time 1", now);
-        print_delta("delta", delta);
-        print_time("time 2", then);
-    } else if let Ok(time) = parse_time(arg) {
-        print_time("time", time);
+fn handle_positional(
+    args: &[String],
+    ctx: &context::Context,
+) -> anyhow::Result<()> {
+    let mut options = ReadOptions::default();
+    let mut any_read_option = false;
+    let mut calendar_days = false;
+    let mut json = false;
+    let mut schema = false;
+    let mut explain = false;
+    let mut units = Vec::new();
+    let mut divide = None;
+    let mut values = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if options.parse_option(arg, &mut iter)? {
+            any_read_option = true;
+            continue;
+        }
+        match arg.as_str() {
+            "--days" => calendar_days = true,
+            "--json" => json = true,
+            "--schema" => schema = true,
+            "--explain" => explain = true,
+            "--divide" => {
+                let value = iter.next().with_context(|| {
+                    UsageError::new("--divide requires an argument")
+                })?;
+                let n: f64 = value.parse().map_err(|_| {
+                    dates_tool::Error::unrecognized(value, "a number")
+                })?;
+                if !(n > 0.0 && n.is_finite()) {
+                    return Err(dates_tool::Error::out_of_range(
+                        value,
+                        "it must be more than 0",
+                    )
+                    .into());
+                }
+                divide = Some((value.clone(), n));
+            }
+            "--break-into" => {
+                let value = iter.next().with_context(|| {
+                    UsageError::new("--break-into requires an argument")
+                })?;
+                units = Unit::parse_list(value)?;
+            }
+            "--largest-unit" => {
+                let value = iter.next().with_context(|| {
+                    UsageError::new("--largest-unit requires an argument")
+                })?;
+                units = Unit::down_from(Unit::parse(value)?);
+            }
+            flag if flag.starts_with("--") => {
+                bail!(UsageError::new(format!("unknown option {flag:?}")))
+            }
+            _ => values.push(arg.clone()),
+        }
+    }
+
+    if schema {
+        if json
+            || calendar_days
+            || explain
+            || any_read_option
+            || !units.is_empty()
+            || divide.is_some()
+            || !values.is_empty()
+        {
+            bail!("--schema must be used alone");
+        }
+        print!("{}", report::JSON_SCHEMA);
+        return Ok(());
+    }
+
+    let candidates = options.candidates;
+    let as_range = options.as_range;
+    let guess_local = options.guesses_local();
+    let reader = &options.reader(ctx)?;
+    let parser = &reader.registry;
+
+    let args = &values;
+    let arith = Arithmetic {
+        zone: reader.display_zone,
+        rules: reader.rules,
+        policy: reader.arith_policy,
+    };
+    let mut entries = if candidates {
+        handle_candidates(ctx, reader, args)?
+    } else if as_range {
+        match &args[..] {
+            [arg] => handle_range(ctx, reader, arg)?,
+            _ => bail!("--as-range requires exactly one date"),
+        }
+    } else if calendar_days {
+        handle_days(ctx, reader, args, arith.zone)?
     } else {
-        bail!("Could not parse {arg:?} as either a time or a delta");
+        match args.len() {
+            0 => vec![reader.time(ctx, "now", None, ctx.now())?],
+            1 => handle_one(ctx, reader, arith, &args[0])?,
+            2 => handle_two(ctx, reader, arith, &args[0], &args[1])?,
+            _ => handle_many(ctx, reader, args)?,
+        }
+    };
+
+    // With --divide N, the delta (or the total, for several times) is split
+    // into N equal parts, as for the interval between N events.
+    if let Some((input, n)) = &divide {
+        let Some(delta) = entries.iter().rev().find_map(|e| match e {
+            Entry::Delta { delta, .. } => Some(delta.duration()),
+            _ => None,
+        }) else {
+            bail!("--divide requires a delta, or two or more times");
+        };
+        entries.push(Entry::delta("each", scale_delta(input, delta, 1.0 / n)?));
     }
-    Ok(())
-}
 
-fn handle_two(a: &str, b: &str) -> anyhow::Result<()> {
-    let t1 =
-        parse_time(a).with_context(|| format!("parsing {a:?} as a time"))?;
-    if let Ok(t2) = parse_time(b) {
-        print_time("time 1", t1);
-        print_time("time 2", t2);
-        print_delta("delta", t2 - t1);
-    } else if let Ok(d) = parse_delta(b) {
-        let t2 = t1 + d;
-        print_time("time 1", t1);
-        print_delta("delta", d);
-        print_time("time 2", t2);
+    // A time that was read in the local zone only because it had no offset
+    // may not mean what was intended, so that's always explained.
+    if !candidates && (explain || guess_local) {
+        for entry in &mut entries {
+            // Relative times like "now-1d" aren't in any format, so they
+            // have nothing to explain.
+            if let Entry::Time { time, .. } = entry
+                && let Some(input) = &time.input
+                && let Ok(parsed) = parser.explain(input)
+            {
+                let provenance = parsed.provenance;
+                if explain || provenance.format == "wall-clock" {
+                    *time = time.clone().with_provenance(provenance);
+                }
+            }
+        }
+    }
+
+    if json {
+        let report = Report::new(entries);
+        println!("{}", serde_json::to_string_pretty(&report)?);
     } else {
-        bail!("Could not parse {b:?} as either a time or a delta");
+        let formatter = Formatter::new().units(&units);
+        for entry in entries {
+            match &entry {
+                Entry::Delta { label, delta } if !units.is_empty() => {
+                    let delta = delta.duration();
+                    println!("{}", formatter.format_delta(label, delta));
+                }
+                _ => println!("{entry}"),
+            }
+        }
     }
     Ok(())
 }
 
-fn parse_time(s: &str) -> Result<DateTime<Utc>, chrono::ParseError> {
-    if let Ok(ts) = s.parse::<i64>() {
-        // treat as milliseconds since epoch
-        return Ok(Utc.timestamp_millis_opt(ts).unwrap());
+/// The options that say how to read times from the command line, which
+/// `dates check` accepts too
+#[derive(Default)]
+struct ReadOptions {
+    /// see `--strict`
+    strict: bool,
+    /// see `--candidates`
+    candidates: bool,
+    /// see `--verify`
+    verify: bool,
+    /// see `--end-of-period`
+    end_of_period: bool,
+    /// see `--as-range`
+    as_range: bool,
+    /// see `--floor`, `--ceil`, and `--round`
+    snap: Option<(Mode, Step)>,
+    /// see `--epoch`
+    epoch: Option<Epoch>,
+    /// see `--epoch-unit`
+    epoch_unit: Option<EpochUnit>,
+    /// see `--since-boot`
+    since_boot: bool,
+    /// see `--boot-time`
+    boot_time: Option<DateTime<Utc>>,
+    /// see `--tz`
+    zone: Option<Zone>,
+    /// see `--input-tz`
+    input_zone: Option<Zone>,
+    /// see `--ambiguous`
+    ambiguous: Option<AmbiguousPolicy>,
+    /// see `--nonexistent`
+    nonexistent: Option<NonexistentPolicy>,
+    /// see `--month-end` and `--arith`
+    rules: Rules,
+    /// see `--scale`
+    scale: Scale,
+    /// see `--prefer-region`
+    prefer: Vec<Region>,
+}
+
+impl ReadOptions {
+    /// Handles `arg` (taking its value from `iter`) if it's one of these
+    /// options, returning whether it was.
+    fn parse_option(
+        &mut self,
+        arg: &str,
+        iter: &mut std::slice::Iter<'_, String>,
+    ) -> anyhow::Result<bool> {
+        match arg {
+            "--strict" => self.strict = true,
+            "--candidates" => self.candidates = true,
+            "--verify" => self.verify = true,
+            "--end-of-period" => self.end_of_period = true,
+            "--as-range" => self.as_range = true,
+            flag @ ("--floor" | "--ceil" | "--round") => {
+                let value = iter.next().with_context(|| {
+                    UsageError::new(format!("{flag} requires an argument"))
+                })?;
+                let mode = match flag {
+                    "--floor" => Mode::Floor,
+                    "--ceil" => Mode::Ceil,
+                    _ => Mode::Round,
+                };
+                self.snap = Some((mode, Step::parse(value)?));
+            }
+            "--epoch" => {
+                let value = iter.next().with_context(|| {
+                    UsageError::new("--epoch requires an argument")
+                })?;
+                self.epoch = Some(Epoch::parse(value)?);
+            }
+            "--epoch-unit" => {
+                let value = iter.next().with_context(|| {
+                    UsageError::new("--epoch-unit requires an argument")
+                })?;
+                self.epoch_unit = Some(EpochUnit::parse(value)?);
+            }
+            "--since-boot" => self.since_boot = true,
+            "--boot-time" => {
+                let value = iter.next().with_context(|| {
+                    UsageError::new("--boot-time requires an argument")
+                })?;
+                self.boot_time = Some(parse_timestamp(value)?);
+                self.since_boot = true;
+            }
+            "--tz" => {
+                let value = iter.next().with_context(|| {
+                    UsageError::new("--tz requires an argument")
+                })?;
+                self.zone = Some(Zone::parse(value)?);
+            }
+            "--input-tz" => {
+                let value = iter.next().with_context(|| {
+                    UsageError::new("--input-tz requires an argument")
+                })?;
+                self.input_zone = Some(Zone::parse(value)?);
+            }
+            "--ambiguous" => {
+                let value = iter.next().with_context(|| {
+                    UsageError::new("--ambiguous requires an argument")
+                })?;
+                self.ambiguous = Some(AmbiguousPolicy::parse(value)?);
+            }
+            "--nonexistent" => {
+                let value = iter.next().with_context(|| {
+                    UsageError::new("--nonexistent requires an argument")
+                })?;
+                self.nonexistent = Some(NonexistentPolicy::parse(value)?);
+            }
+            "--month-end" => {
+                let value = iter.next().with_context(|| {
+                    UsageError::new("--month-end requires an argument")
+                })?;
+                self.rules.month_end = MonthEnd::parse(value)?;
+            }
+            "--arith" => {
+                let value = iter.next().with_context(|| {
+                    UsageError::new("--arith requires an argument")
+                })?;
+                self.rules.arith = Arith::parse(value)?;
+            }
+            "--scale" => {
+                let value = iter.next().with_context(|| {
+                    UsageError::new("--scale requires an argument")
+                })?;
+                self.scale = Scale::parse(value)?;
+            }
+            "--prefer-region" => {
+                let value = iter.next().with_context(|| {
+                    UsageError::new("--prefer-region requires an argument")
+                })?;
+                self.prefer = Region::parse_list(value)?;
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Returns whether a date and time without an offset is read in the
+    /// local zone only because no option said which zone to read it in.
+    fn guesses_local(&self) -> bool {
+        self.input_zone.or(self.zone).is_none() && self.scale == Scale::Utc
+    }
+
+    /// Returns a reader that reads times as these options say.
+    fn reader(&self, ctx: &context::Context) -> anyhow::Result<Reader> {
+        // With --input-tz or --tz, times without an offset are wall-clock times
+        // in that zone, rather than UTC as the built-in formats would have it.
+        let input_zone = self.input_zone.or(self.zone);
+        let policy = DstPolicy {
+            ambiguous: self.ambiguous.unwrap_or_default(),
+            nonexistent: self.nonexistent.unwrap_or_default(),
+        };
+        let mut registry = Registry::empty();
+        match input_zone {
+            Some(zone) => {
+                registry.register(WallClockParser::new(zone, policy));
+            }
+            // A clock on another timescale has no time zone, so times read from
+            // one usually don't have an offset either.
+            None if self.scale != Scale::Utc => {
+                registry.register(WallClockParser::new(Zone::Utc, policy));
+            }
+            None => (),
+        }
+        // Four digits are a year, and "2024-03" is a month, each meaning the
+        // start of the period, unless --epoch or --epoch-unit says bare numbers
+        // count something.
+        let period_zone = input_zone.unwrap_or(Zone::Utc);
+        let periods = || PartialDateParser::new(period_zone, policy);
+        let counts_epoch = self.epoch.is_some()
+            || self.epoch_unit.is_some()
+            || self.since_boot;
+        if !counts_epoch {
+            registry.register(periods());
+        }
+        // The built-in formats reject ambiguous abbreviations, but the registry
+        // keeps going after an error, so this gets a chance to resolve them.
+        registry
+            .register_builtin()
+            .register(AbbreviationParser::new(self.prefer.clone()));
+        // With --epoch-unit, bare numbers are always in that unit, rather than
+        // milliseconds for integers and seconds otherwise.  With --epoch, they
+        // count from that epoch, in the unit it usually counts.  With
+        // --since-boot, they count from when the system booted, in seconds.
+        if counts_epoch {
+            if self.since_boot && self.epoch.is_some() {
+                bail!("--since-boot and --epoch can't be used together");
+            }
+            let epoch = self.epoch.unwrap_or(Epoch::Unix);
+            let unit = self.epoch_unit.unwrap_or(epoch.default_unit());
+            registry.retain(|p| {
+                !matches!(p.name(), "unix-millis" | "unix-seconds")
+            });
+            if self.since_boot {
+                let boot = match self.boot_time {
+                    Some(t) => t,
+                    None => {
+                        let boot = dmesg::uptime_boot_time()?;
+                        dmesg::warn_if_suspended();
+                        boot
+                    }
+                };
+                registry.register(SinceBoot::new(boot, unit));
+            } else {
+                match epoch {
+                    Epoch::Unix => registry.register(UnixTime(unit)),
+                    _ => registry.register(EpochTime::new(epoch, unit)),
+                };
+            }
+            registry.register(periods());
+        }
+        // Dates like "03/05/2024" are too ambiguous to accept by themselves,
+        // but they're worth listing as candidates, as are other readings of
+        // bare numbers.
+        if self.candidates {
+            if self.strict {
+                bail!("--candidates and --strict can't be used together");
+            }
+            registry
+                .register(Strftime::from_static("month-first", "%m/%d/%Y"))
+                .register(Strftime::from_static("day-first", "%d/%m/%Y"));
+            // Browser histories mix PRTime and WebKit times, which are easy to
+            // confuse with each other and with milliseconds.
+            if !counts_epoch {
+                registry
+                    .register(BrowserTime::new(Epoch::Prtime))
+                    .register(BrowserTime::new(Epoch::Webkit));
+            }
+        }
+        // Otherwise, a date and time without an offset is read in the local
+        // zone (which comes from TZ, if it's set).  Bare dates aren't, so they
+        // still mean midnight UTC.
+        if self.guesses_local() {
+            registry.register(WallClockParser::assuming(ctx.local(), policy));
+        }
+        if self.as_range && self.end_of_period {
+            bail!("--as-range and --end-of-period can't be used together");
+        }
+        Ok(Reader {
+            registry,
+            strict: self.strict,
+            scale: self.scale,
+            table: LeapSeconds::builtin(),
+            periods: periods(),
+            end_of_period: self.end_of_period,
+            snap: self.snap,
+            display_zone: self.zone.unwrap_or(ctx.local()),
+            now: ctx.now(),
+            rules: self.rules,
+            // Arithmetic that lands on a repeated wall-clock time uses the
+            // earlier instant unless --ambiguous says otherwise.
+            arith_policy: DstPolicy {
+                ambiguous: self.ambiguous.unwrap_or(AmbiguousPolicy::Earliest),
+                ..policy
+            },
+            verify: self.verify,
+        })
+    }
+}
+
+/// How to read times from the command line and show them
+struct Reader {
+    registry: Registry,
+    /// whether to reject times that aren't fully specified (see `--strict`)
+    strict: bool,
+    /// the timescale of the times given, which are also shown on that scale
+    /// (see `--scale`)
+    scale: Scale,
+    table: LeapSeconds,
+    /// reads partial dates like "2024" as periods
+    periods: PartialDateParser,
+    /// whether a partial date means the end of its period, rather than the
+    /// start (see `--end-of-period`)
+    end_of_period: bool,
+    /// how to round each time (see `--floor`, `--ceil`, and `--round`)
+    snap: Option<(Mode, Step)>,
+    /// the zone on whose wall clock times are rounded, and relative times
+    /// snapped
+    display_zone: Zone,
+    /// the time relative to which relative times like "now-1d@d" are read
+    now: DateTime<Utc>,
+    rules: Rules,
+    /// how to resolve the wall-clock times that arithmetic lands on
+    arith_policy: DstPolicy,
+    /// whether to check that each time shown reads back as the same time
+    /// (see `--verify`)
+    verify: bool,
+}
+
+impl Reader {
+    /// Parses `s` as a time on this reader's timescale.
+    fn parse(&self, s: &str) -> Result<DateTime<Utc>, dates_tool::Error> {
+        let parsed = if self.end_of_period
+            && let Ok(range) = self.periods.range(s)
+        {
+            Ok(range.end)
+        } else if self.strict {
+            self.registry.parse_strict(s)
+        } else {
+            self.registry.parse(s)
+        };
+        // Relative times like "now-1d@d" are already on UTC.  (Without the
+        // "now", a snap, or an anchor, it'd be a delta instead.)
+        let t = match parsed {
+            Err(dates_tool::Error::Unrecognized { .. })
+                if s.starts_with("now")
+                    || s.contains('@')
+                    || s.contains("||") =>
+            {
+                return self.relative(s);
+            }
+            parsed => parsed?,
+        };
+        let t = self.scale.to_utc(&self.table, t.naive_utc())?;
+        self.snapped(s, t)
+    }
+
+    /// Evaluates relative time `s`, like "now-1d@d" or "2024.03.01||+1M/d".
+    fn relative(&self, s: &str) -> Result<DateTime<Utc>, dates_tool::Error> {
+        let relative = Relative::parse(s)?;
+        let start = match &relative.anchor {
+            // Elasticsearch anchors are often dates like "2024.03.01", as in
+            // the names of daily indices.
+            Some(anchor) => self.parse(anchor).or_else(|error| {
+                NaiveDate::parse_from_str(anchor, "%Y.%m.%d")
+                    .map(|d| d.and_time(NaiveTime::MIN).and_utc())
+                    .map_err(|_| error.within(s, 0))
+            })?,
+            None => self.now,
+        };
+        let t = relative
+            .apply(start, self.display_zone, self.rules, self.arith_policy)
+            .ok_or_else(|| {
+                dates_tool::Error::out_of_range(
+                    s,
+                    "the result is past the supported range of times",
+                )
+            })?;
+        self.snapped(s, t)
+    }
+
+    /// Rounds `t`, which was parsed from `s`, as `--floor`, `--ceil`, or
+    /// `--round` says (if any of them was given).
+    fn snapped(
+        &self,
+        s: &str,
+        t: DateTime<Utc>,
+    ) -> Result<DateTime<Utc>, dates_tool::Error> {
+        match self.snap {
+            Some((mode, step)) => {
+                step.snap(mode, t, self.display_zone).ok_or_else(|| {
+                    dates_tool::Error::out_of_range(
+                        s,
+                        "rounding it goes past the supported range of times",
+                    )
+                })
+            }
+            None => Ok(t),
+        }
+    }
+
+    /// Describes `dt`, which was parsed from `input` (if any), the way
+    /// `dates` prints a time, in the zones of `ctx` and on this reader's
+    /// timescale.
+    fn time(
+        &self,
+        ctx: &context::Context,
+        label: &str,
+        input: Option<&str>,
+        dt: DateTime<Utc>,
+    ) -> anyhow::Result<Entry> {
+        let mut time = Timestamp::new(dt, &ctx.zones());
+        if let Some(input) = input {
+            time = time.with_input(input);
+        }
+        if self.scale != Scale::Utc {
+            let reading = self.scale.from_utc(&self.table, dt)?;
+            time = time.with_reading(self.scale.name(), reading);
+        }
+        if self.verify {
+            verify_timestamp(&time, dt).with_context(|| {
+                format!("--verify: {label} doesn't read back as the same time")
+            })?;
+        }
+        Ok(Entry::Time { label: label.to_string(), time })
+    }
+
+    /// Returns the time elapsed from `t1` to `t2` on this reader's
+    /// timescale.  Unlike on UTC, this includes any leap seconds in between
+    /// on TAI.
+    fn elapsed(
+        &self,
+        t1: DateTime<Utc>,
+        t2: DateTime<Utc>,
+    ) -> Result<Duration, dates_tool::Error> {
+        Ok(self.scale.from_utc(&self.table, t2)?
+            - self.scale.from_utc(&self.table, t1)?)
+    }
+}
+
+/// How to add a delta to a time
+#[derive(Clone, Copy)]
+struct Arithmetic {
+    /// the zone on whose wall clock calendar months (and, with
+    /// `--arith wall`, everything else) are added
+    zone: Zone,
+    rules: Rules,
+    /// how to resolve wall-clock times that occur twice or not at all (see
+    /// `--ambiguous` and `--nonexistent`)
+    policy: DstPolicy,
+}
+
+impl Arithmetic {
+    /// Adds `delta`, which was parsed from `input`, to `t`.  On timescales
+    /// other than UTC, the delta is added to the time on that scale.
+    fn add(
+        &self,
+        reader: &Reader,
+        t: DateTime<Utc>,
+        input: &str,
+        delta: CalendarDelta,
+    ) -> Result<DateTime<Utc>, dates_tool::Error> {
+        if reader.scale != Scale::Utc {
+            let reading = reader.scale.from_utc(&reader.table, t)?.and_utc();
+            let sum =
+                delta.add_to_with(reading, Zone::Utc, self.rules, self.policy);
+            return match sum {
+                Some(sum) => {
+                    reader.scale.to_utc(&reader.table, sum.naive_utc())
+                }
+                None => Err(dates_tool::Error::out_of_range(
+                    input,
+                    "the result is past the supported range of times",
+                )),
+            };
+        }
+        let sum = delta.add_to_with(t, self.zone, self.rules, self.policy);
+        sum.ok_or_else(|| {
+            dates_tool::Error::out_of_range(
+                input,
+                format!(
+                    "the result is past the supported range of times, or its \
+                     wall-clock time in {} doesn't exist or occurs twice (see \
+                     --nonexistent and --ambiguous)",
+                    self.zone
+                ),
+            )
+        })
     }
-    if let Ok(ts) = s.parse::<f64>() {
-        // treat as seconds.fractional
-        let millis = (ts * 1000.0).round() as i64;
-        return Ok(Utc.timestamp_millis_opt(millis).unwrap());
+}
+
+fn handle_one(
+    ctx: &context::Context,
+    reader: &Reader,
+    arith: Arithmetic,
+    arg: &str,
+) -> anyhow::Result<Vec<Entry>> {
+    if let Ok(delta) = CalendarDelta::parse(arg) {
+        let now = ctx.now();
+        let then = arith.add(reader, now, arg, delta)?;
+        return Ok(vec![
+            reader.time(ctx, "time 1", None, now)?,
+            Entry::delta("delta", reader.elapsed(now, then)?),
+            reader.time(ctx, "time 2", None, then)?,
+        ]);
     }
 
-    // Try parsing with chrono
-    let dt = DateTime::parse_from_rfc3339(s)
-        .or_else(|_| DateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.3f%z"))
-        .map(|n| n.to_utc())
-        .or_else(|_| {
-            NaiveDate::parse_from_str(s, "%Y-%m-%d")
-                .map(|n| n.and_hms_opt(0, 0, 0).unwrap().and_utc())
+    match reader.parse(arg) {
+        Ok(time) => Ok(vec![reader.time(ctx, "time", Some(arg), time)?]),
+        // Nothing else has a "/", so this is probably an ISO 8601 interval.
+        Err(dates_tool::Error::Unrecognized { .. })
+            if arg.starts_with('R') && arg.contains('/') =>
+        {
+            handle_repeating(ctx, reader, arith, arg)
+        }
+        Err(dates_tool::Error::Unrecognized { .. }) if arg.contains('/') => {
+            let range = Interval::split(arg)?.resolve(
+                arg,
+                |t| reader.parse(t),
+                |t, text, delta| arith.add(reader, t, text, delta),
+            )?;
+            range_entries(ctx, reader, None, range)
+        }
+        // Something that starts with a sign but isn't a number was probably
+        // meant to be a delta, so report why it isn't one, unless it was a
+        // timestamp that's out of range (like "+262143-01-01").
+        Err(dates_tool::Error::Unrecognized { .. })
+            if arg.starts_with(['+', '-']) =>
+        {
+            Err(CalendarDelta::parse(arg).unwrap_err().into())
+        }
+        Err(error) => Err(error.into()),
+    }
+}
+
+fn handle_two(
+    ctx: &context::Context,
+    reader: &Reader,
+    arith: Arithmetic,
+    a: &str,
+    b: &str,
+) -> anyhow::Result<Vec<Entry>> {
+    if let (Ok(d1), Ok(d2)) = (parse_duration(a), parse_duration(b)) {
+        return handle_deltas(b, d1, d2);
+    }
+    if let (Ok(d), Some(factor)) = (parse_duration(a), b.strip_prefix('x')) {
+        let factor: f64 = factor.parse().map_err(|_| {
+            dates_tool::Error::unrecognized_at(b, 1..b.len(), "a number")
         })?;
-    Ok(dt.with_timezone(&Utc))
+        return Ok(vec![
+            Entry::delta("delta", d),
+            Entry::Ratio { label: String::from("factor"), ratio: factor },
+            Entry::delta("product", scale_delta(b, d, factor)?),
+        ]);
+    }
+    let t1 = reader.parse(a)?;
+    let error = match reader.parse(b) {
+        Ok(t2) => {
+            return Ok(vec![
+                reader.time(ctx, "time 1", Some(a), t1)?,
+                reader.time(ctx, "time 2", Some(b), t2)?,
+                Entry::delta("delta", reader.elapsed(t1, t2)?),
+            ]);
+        }
+        Err(error) => error,
+    };
+    match CalendarDelta::parse(b) {
+        Ok(d) => {
+            let t2 = arith.add(reader, t1, b, d)?;
+            Ok(vec![
+                reader.time(ctx, "time 1", Some(a), t1)?,
+                Entry::delta("delta", reader.elapsed(t1, t2)?),
+                reader.time(ctx, "time 2", None, t2)?,
+            ])
+        }
+        // Neither parse succeeded.  Report the failure that's more likely to
+        // be relevant, as for a single argument.
+        Err(delta_error)
+            if b.starts_with(['+', '-'])
+                && matches!(error, dates_tool::Error::Unrecognized { .. }) =>
+        {
+            Err(delta_error.into())
+        }
+        Err(_) => Err(error.into()),
+    }
 }
 
-fn parse_delta(s: &str) -> Result<Duration, ()> {
-    let (sign, rest) = match s.chars().next() {
-        Some('+') => (1, &s[1..]),
-        Some('-') => (-1, &s[1..]),
-        _ => return Err(()),
+/// Describes two deltas, their sum and difference, and how many times the
+/// second goes into the first.
+fn handle_deltas(
+    b: &str,
+    d1: Duration,
+    d2: Duration,
+) -> anyhow::Result<Vec<Entry>> {
+    let too_large = || {
+        dates_tool::Error::out_of_range(b, "the sum or difference is too large")
     };
+    let sum = d1.checked_add(&d2).ok_or_else(too_large)?;
+    let difference = d1.checked_sub(&d2).ok_or_else(too_large)?;
+    let mut entries = vec![
+        Entry::delta("delta 1", d1),
+        Entry::delta("delta 2", d2),
+        Entry::delta("sum", sum),
+        Entry::delta("diff", difference),
+    ];
+    // There's no ratio to a zero-length delta.
+    if !d2.is_zero() {
+        let seconds = |d: Duration| {
+            d.num_seconds() as f64 + f64::from(d.subsec_nanos()) / 1e9
+        };
+        entries.push(Entry::Ratio {
+            label: String::from("ratio"),
+            ratio: seconds(d1) / seconds(d2),
+        });
+    }
+    Ok(entries)
+}
+
+/// Returns `delta` multiplied by `factor`, to the nanosecond, or an error
+/// about `input` (which gave the factor) if that's out of range.
+fn scale_delta(
+    input: &str,
+    delta: Duration,
+    factor: f64,
+) -> Result<Duration, dates_tool::Error> {
+    let nanos =
+        delta.num_seconds() as f64 * 1e9 + f64::from(delta.subsec_nanos());
+    let scaled = (nanos * factor).round();
+    let seconds = (scaled / 1e9).trunc();
+    let too_large =
+        || dates_tool::Error::out_of_range(input, "the result is too large");
+    if !seconds.is_finite() || seconds.abs() >= i64::MAX as f64 {
+        return Err(too_large());
+    }
+    let subsec = Duration::nanoseconds((scaled - seconds * 1e9) as i64);
+    Duration::try_seconds(seconds as i64)
+        .and_then(|d| d.checked_add(&subsec))
+        .ok_or_else(too_large)
+}
 
-    let unit = rest
-        .chars()
-        .rev()
-        .take_while(|c| c.is_alphabetic())
-        .collect::<String>()
-        .chars()
-        .rev()
-        .collect::<String>();
-
-    let value_str = &rest[..rest.len() - unit.len()];
-    let value: f64 = value_str.parse().map_err(|_| ())?;
-
-    let seconds = match unit.as_str() {
-        "ms" => value / 1000.0,
-        "s" => value,
-        "m" => value * 60.0,
-        "h" => value * 3600.0,
-        "d" => value * 86400.0,
-        _ => return Err(()),
+/// Describes each occurrence of repeating interval `arg`, like
+/// "R5/2024-03-01T00:00Z/PT1H".
+fn handle_repeating(
+    ctx: &context::Context,
+    reader: &Reader,
+    arith: Arithmetic,
+    arg: &str,
+) -> anyhow::Result<Vec<Entry>> {
+    let repeating = Repeating::split(arg)?;
+    let Some(count) = repeating.count else {
+        return Err(dates_tool::Error::out_of_range_at(
+            arg,
+            0..repeating.offset,
+            "it repeats forever; give a count, like \"R10/\"",
+        )
+        .into());
     };
+    // An interval given by its duration and end repeats backwards from the
+    // end, but the occurrences are still listed in order.
+    let backwards = matches!(repeating.interval.start, Part::Duration(_));
+    let mut entries = Vec::new();
+    for label in 1..=count {
+        let n = if backwards { count - label } else { label - 1 };
+        let range = repeating.nth(
+            arg,
+            n,
+            |t| reader.parse(t),
+            |t, text, delta| arith.add(reader, t, text, delta),
+        )?;
+        entries.push(reader.time(
+            ctx,
+            &format!("start {label}"),
+            None,
+            range.start,
+        )?);
+        entries.push(reader.time(
+            ctx,
+            &format!("end {label}"),
+            None,
+            range.end,
+        )?);
+    }
+    Ok(entries)
+}
 
-    Ok(Duration::milliseconds((sign as f64 * seconds * 1000.0) as i64))
+/// Describes the period given by partial date `arg` (see `--as-range`):
+/// when it starts and ends, and how long it is.
+fn handle_range(
+    ctx: &context::Context,
+    reader: &Reader,
+    arg: &str,
+) -> anyhow::Result<Vec<Entry>> {
+    let range = reader.periods.range(arg)?;
+    let start = reader.scale.to_utc(&reader.table, range.start.naive_utc())?;
+    let end = reader.scale.to_utc(&reader.table, range.end.naive_utc())?;
+    range_entries(ctx, reader, Some(arg), start..end)
+}
+
+/// Describes `range`: when it starts and ends, and how long it is.  The start
+/// is described as having been parsed from `input`, if given.
+fn range_entries(
+    ctx: &context::Context,
+    reader: &Reader,
+    input: Option<&str>,
+    range: Range<DateTime<Utc>>,
+) -> anyhow::Result<Vec<Entry>> {
+    Ok(vec![
+        reader.time(ctx, "start", input, range.start)?,
+        reader.time(ctx, "end", None, range.end)?,
+        Entry::delta("length", reader.elapsed(range.start, range.end)?),
+    ])
+}
+
+/// Describes two times and the number of calendar days between them
+///
+/// Unlike the delta printed for two times, this counts midnights crossed in
+/// `zone`, so it isn't thrown off by days that are longer or shorter than
+/// 24 hours because of daylight saving time.
+fn handle_days(
+    ctx: &context::Context,
+    reader: &Reader,
+    args: &[String],
+    zone: Zone,
+) -> anyhow::Result<Vec<Entry>> {
+    let [a, b] = args else {
+        bail!("--days requires exactly two times");
+    };
+    let t1 = reader.parse(a)?;
+    let t2 = reader.parse(b)?;
+    let (d1, d2) = (zone.date_of(t1), zone.date_of(t2));
+
+    Ok(vec![
+        reader.time(ctx, "time 1", Some(a), t1)?,
+        reader.time(ctx, "time 2", Some(b), t2)?,
+        Entry::CalendarDays {
+            label: String::from("days"),
+            days: (d2 - d1).num_days(),
+            start: d1,
+            end: d2,
+            zone: zone.spec(),
+        },
+    ])
+}
+
+/// Describes every interpretation of each of `args`, with how it was parsed.
+fn handle_candidates(
+    ctx: &context::Context,
+    reader: &Reader,
+    args: &[String],
+) -> anyhow::Result<Vec<Entry>> {
+    if args.is_empty() {
+        bail!("--candidates requires at least one time");
+    }
+    let mut entries = Vec::new();
+    for input in args {
+        for (i, parsed) in
+            reader.registry.candidates(input)?.into_iter().enumerate()
+        {
+            let t =
+                reader.scale.to_utc(&reader.table, parsed.time.naive_utc())?;
+            let label = format!("as {}", i + 1);
+            let mut entry = reader.time(ctx, &label, Some(input), t)?;
+            if let Entry::Time { time, .. } = &mut entry {
+                *time = time.clone().with_provenance(parsed.provenance);
+            }
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+/// Describes three or more times, each followed by the delta from the
+/// previous one, and finally the total delta from the first to the last.
+fn handle_many(
+    ctx: &context::Context,
+    reader: &Reader,
+    args: &[String],
+) -> anyhow::Result<Vec<Entry>> {
+    let times =
+        args.iter().map(|a| reader.parse(a)).collect::<Result<Vec<_>, _>>()?;
+
+    let mut entries = Vec::new();
+    for (i, (&t, input)) in times.iter().zip(args).enumerate() {
+        if i > 0 {
+            let delta = reader.elapsed(times[i - 1], t)?;
+            entries.push(Entry::delta("delta", delta));
+        }
+        let label = format!("time {}", i + 1);
+        entries.push(reader.time(ctx, &label, Some(input), t)?);
+    }
+    let total = reader.elapsed(times[0], times[times.len() - 1])?;
+    entries.push(Entry::delta("total", total));
+    Ok(entries)
 }
 
-fn print_time(label: &str, dt: DateTime<Utc>) {
-    let timestamp = dt.timestamp() as f64
-        + (dt.timestamp_subsec_micros() as f64 / 1_000_000.0);
-    println!(
-        "{:<8} {:>20.6} s = {}",
-        label,
-        timestamp,
-        dt.with_timezone(&Local).to_rfc3339_opts(SecondsFormat::Micros, true),
-    );
-    println!(
-        "         {:>20.6}   = {}",
-        "",
-        dt.to_rfc3339_opts(SecondsFormat::Micros, true)
-    );
+fn print_time(ctx: &context::Context, label: &str, dt: DateTime<Utc>) {
+    let formatter = Formatter::new().zones(&ctx.zones());
+    println!("{}", formatter.format_time(label, dt));
 }
 
 fn print_delta(label: &str, delta: Duration) {
-    let total_micros = delta.num_microseconds().unwrap_or(0);
-    let total_secs = total_micros as f64 / 1_000_000.0;
-
-    let sign = if total_micros < 0 { "-" } else { " " };
-    let abs_us = total_micros.abs();
-    let secs = abs_us / 1_000_000;
-    let micros = abs_us % 1_000_000;
-
-    let d = secs / 86400;
-    let h = (secs % 86400) / 3600;
-    let m = (secs % 3600) / 60;
-    let s = secs % 60;
-
-    println!(
-        "{:<8} {:>20.6} s = {}{}d {:02}h {:02}m {:02}.{:06}s",
-        label, total_secs, sign, d, h, m, s, micros
-    );
+    println!("{}", format_duration(label, delta));
 }
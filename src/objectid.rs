@@ -0,0 +1,91 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Times embedded in MongoDB ObjectIds
+//!
+//! An ObjectId is 12 bytes: the number of seconds since the Unix epoch, as a
+//! 32-bit big-endian integer, then 5 bytes that are random for each process
+//! that generates them, then a 3-byte counter.  It's written as 24
+//! hexadecimal digits.  ObjectIds sort by time, so the smallest and largest
+//! ObjectIds for a second ([`ObjectId::min`] and [`ObjectId::max`]) can bound
+//! a range query on `_id`, for a collection without a date field.
+
+use crate::Error;
+use chrono::{DateTime, Utc};
+use core::fmt;
+
+/// A MongoDB ObjectId
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ObjectId(pub [u8; 12]);
+
+impl ObjectId {
+    /// Parses an ObjectId written as 24 hexadecimal digits.
+    pub fn parse(s: &str) -> Result<ObjectId, Error> {
+        if s.len() != 24 {
+            return Err(Error::unrecognized(
+                s,
+                "an ObjectId (like \"507f1f77bcf86cd799439011\")",
+            ));
+        }
+        let mut bytes = [0u8; 12];
+        for (i, c) in s.char_indices() {
+            let Some(digit) = c.to_digit(16) else {
+                return Err(Error::unrecognized_at(
+                    s,
+                    i..i + c.len_utf8(),
+                    "a hexadecimal digit",
+                ));
+            };
+            bytes[i / 2] = bytes[i / 2] << 4 | digit as u8;
+        }
+        Ok(ObjectId(bytes))
+    }
+
+    /// Returns the smallest ObjectId for time `t`, which is truncated to the
+    /// second, or `None` if `t` is before 1970 or after 2106, when 32 bits of
+    /// seconds run out.
+    pub fn min(t: DateTime<Utc>) -> Option<ObjectId> {
+        ObjectId::with_rest(t, 0)
+    }
+
+    /// Returns the largest ObjectId for time `t`, like [`ObjectId::min`].
+    pub fn max(t: DateTime<Utc>) -> Option<ObjectId> {
+        ObjectId::with_rest(t, 0xff)
+    }
+
+    /// Returns an ObjectId for time `t` in which every byte after the time
+    /// is `fill`.
+    fn with_rest(t: DateTime<Utc>, fill: u8) -> Option<ObjectId> {
+        let seconds = u32::try_from(t.timestamp()).ok()?;
+        let mut bytes = [fill; 12];
+        bytes[..4].copy_from_slice(&seconds.to_be_bytes());
+        Some(ObjectId(bytes))
+    }
+
+    /// Returns the time at which this ObjectId was generated, to the second.
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        let [a, b, c, d, ..] = self.0;
+        let seconds = i64::from(u32::from_be_bytes([a, b, c, d]));
+        // 32 bits of seconds are always within chrono's range.
+        DateTime::from_timestamp(seconds, 0).unwrap()
+    }
+
+    /// Returns the 5 bytes after the time, which are random for each process
+    /// that generates ObjectIds.
+    pub fn random(&self) -> u64 {
+        self.0[4..9].iter().fold(0, |n, &b| n << 8 | u64::from(b))
+    }
+
+    /// Returns the 3-byte counter.
+    pub fn counter(&self) -> u32 {
+        self.0[9..].iter().fold(0, |n, &b| n << 8 | u32::from(b))
+    }
+}
+
+impl fmt::Display for ObjectId {
+    /// Writes the ObjectId as 24 hexadecimal digits, in lowercase.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|b| write!(f, "{b:02x}"))
+    }
+}
@@ -0,0 +1,1490 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Timestamp formats that participate in auto-detection
+//!
+//! [`crate::parse_timestamp`] tries each of the built-in formats until one
+//! matches.  Programs that need to accept other formats can build their own
+//! registry, add [`TimestampParser`]s to it, and call [`Registry::parse`]
+//! instead.  The `dates` command itself has no configuration file (or option)
+//! from which to register more formats, so only programs built on this
+//! library can add them.
+//!
+//! [`Registry::explain`] also reports which format matched, what it assumed
+//! about parts of the input that weren't spelled out, and which other formats
+//! would have produced a different instant, as a [`Provenance`].
+
+use crate::Error;
+use crate::abbrev::AbbreviationParser;
+use crate::w3c::DateDirective;
+use crate::zone::Zone;
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use chrono::format::ParseErrorKind;
+use chrono::{
+    DateTime, Datelike, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime,
+    TimeZone, Timelike, Utc,
+};
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+/// One format of timestamp
+pub trait TimestampParser: Send + Sync {
+    /// Returns a short name for this format, like "rfc3339".
+    fn name(&self) -> &str;
+
+    /// Parses `s`, which may or may not be in this format.
+    ///
+    /// If `s` is in this format but doesn't denote a valid time, this should
+    /// return an error other than [`Error::Unrecognized`] (usually
+    /// [`Error::OutOfRange`]) so that it can be reported in preference to
+    /// mismatches from other formats.
+    fn parse(&self, s: &str) -> Result<DateTime<Utc>, Error>;
+
+    /// Describes what this format assumed about `s`, which it parsed
+    /// successfully, because `s` didn't say.  The default implementation
+    /// reports no assumptions.
+    fn assumptions(&self, _s: &str) -> Vec<Assumption> {
+        Vec::new()
+    }
+}
+
+/// Something a format assumed about its input because the input didn't say
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Assumption {
+    /// a bare number was taken to count `unit` since the Unix epoch
+    Unit { unit: String },
+    /// no UTC offset was given, so UTC was assumed
+    Utc,
+    /// no UTC offset was given, so the time was read on the wall clock of
+    /// `zone` (as described by its `Display` impl, like "local time")
+    Zone { zone: String },
+    /// no time of day was given, so midnight was assumed
+    Midnight,
+    /// only a year or month was given, so the start of that `period` (like
+    /// "year") was assumed
+    Start { period: String },
+    /// a time zone abbreviation with several meanings was taken to mean
+    /// `offset` (as used in `zone`) because of a preferred region
+    Abbreviation { name: String, offset: String, zone: String },
+    /// a two-digit year was taken to mean `year`
+    Century { year: i32 },
+}
+
+impl fmt::Display for Assumption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Assumption::Unit { unit } => {
+                write!(f, "{unit} since the Unix epoch")
+            }
+            Assumption::Utc => write!(f, "UTC"),
+            Assumption::Zone { zone } => write!(f, "{zone}"),
+            Assumption::Midnight => write!(f, "midnight"),
+            Assumption::Start { period } => {
+                write!(f, "the start of the {period}")
+            }
+            Assumption::Abbreviation { name, offset, zone } => {
+                write!(f, "{name} is UTC{offset} ({zone})")
+            }
+            Assumption::Century { year } => {
+                write!(f, "{year} for the two-digit year")
+            }
+        }
+    }
+}
+
+/// A change made to a timestamp's time of day before reading it
+///
+/// ISO 8601 allows "24:00" for the end of a day, which is the same instant as
+/// 00:00 on the next day.  A leap second (23:59:60 UTC) is kept as such if
+/// the table of leap seconds has one then; otherwise, it's the same instant
+/// as 00:00:00 on the next day.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Normalization {
+    /// "24:00" was read as 00:00 on the next day
+    EndOfDay,
+    /// a leap second was read as 00:00:00 on the next day, because there was
+    /// no leap second then
+    LeapSecond,
+}
+
+impl fmt::Display for Normalization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Normalization::EndOfDay => write!(f, "24:00 as 00:00 the next day"),
+            Normalization::LeapSecond => write!(
+                f,
+                "23:59:60 as 00:00:00 the next day (no leap second then)"
+            ),
+        }
+    }
+}
+
+/// How likely it is that a timestamp was interpreted as intended
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Confidence {
+    /// another format would have produced a different instant, or an
+    /// ambiguous abbreviation was resolved by preference
+    Low,
+    /// the format is clear, but something was assumed (like the zone)
+    Medium,
+    /// the input was fully specified and matched only one way
+    High,
+}
+
+impl fmt::Display for Confidence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Confidence::Low => write!(f, "low"),
+            Confidence::Medium => write!(f, "medium"),
+            Confidence::High => write!(f, "high"),
+        }
+    }
+}
+
+/// Another interpretation of a timestamp, from a format that was tried later
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Alternative {
+    /// the name of the format
+    pub format: String,
+    pub time: DateTime<Utc>,
+}
+
+/// How a timestamp was parsed
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Provenance {
+    /// the name of the format that matched (see [`TimestampParser::name`])
+    pub format: String,
+    pub assumptions: Vec<Assumption>,
+    /// later formats that also accept the input but produce a different
+    /// instant
+    pub alternatives: Vec<Alternative>,
+    pub confidence: Confidence,
+    /// how the time of day was changed before reading it, if it was
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub normalized: Vec<Normalization>,
+}
+
+impl Provenance {
+    /// Describes a match by format `format` with `assumptions`, given the
+    /// `alternatives`, and computes the resulting confidence.
+    fn new(
+        format: &str,
+        assumptions: Vec<Assumption>,
+        alternatives: Vec<Alternative>,
+    ) -> Provenance {
+        let resolved = assumptions
+            .iter()
+            .any(|a| matches!(a, Assumption::Abbreviation { .. }));
+        let confidence = if resolved || !alternatives.is_empty() {
+            Confidence::Low
+        } else if !assumptions.is_empty() {
+            Confidence::Medium
+        } else {
+            Confidence::High
+        };
+        Provenance {
+            format: format.to_string(),
+            assumptions,
+            alternatives,
+            confidence,
+            normalized: Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for Provenance {
+    /// Formats the provenance as `dates --explain` prints it, like
+    /// "date (medium confidence), assuming midnight and UTC", with each
+    /// alternative on its own line.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({} confidence)", self.format, self.confidence)?;
+        let assumed: Vec<String> =
+            self.assumptions.iter().map(|a| a.to_string()).collect();
+        if !assumed.is_empty() {
+            write!(f, ", assuming {}", assumed.join(" and "))?;
+        }
+        for normalization in &self.normalized {
+            write!(f, ", reading {normalization}")?;
+        }
+        for alternative in &self.alternatives {
+            write!(
+                f,
+                "\nbut as {}: {}",
+                alternative.format,
+                Zone::Utc.rfc3339(alternative.time)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A timestamp along with how it was parsed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Parsed {
+    pub time: DateTime<Utc>,
+    pub provenance: Provenance,
+}
+
+/// An ordered collection of timestamp formats
+///
+/// When parsing, each format is tried in the order it was registered, and
+/// the first to succeed wins.
+pub struct Registry {
+    parsers: Vec<Box<dyn TimestampParser>>,
+}
+
+/// The formats that `dates` accepts, in the order described by
+/// [`crate::parse_timestamp`]
+static BUILTIN: [&dyn TimestampParser; 17] = [
+    &UnixMillis,
+    &UnixSeconds,
+    &Rfc3339,
+    &ExpandedRfc3339,
+    &Strftime::from_static("iso8601-millis", "%Y-%m-%dT%H:%M:%S%.3f%z"),
+    &Iso8601Minutes,
+    &Strftime::from_static("date", "%Y-%m-%d"),
+    &Strftime::from_static("week-date", "%G-W%V-%u"),
+    &UtcTime,
+    &GeneralizedTime,
+    &JournaldRealtime,
+    &NmeaSentence,
+    &AmzDate,
+    &HttpDate,
+    &CloudFrontLog,
+    &DateDirective,
+    &AbbreviationParser::new(Vec::new()),
+];
+
+/// Parses `s` with the built-in formats, as [`Registry::builtin`] would.
+pub(crate) fn parse_builtin(s: &str) -> Result<DateTime<Utc>, Error> {
+    parse_first(BUILTIN.iter().copied(), s)
+}
+
+/// Explains `s` with the built-in formats, as [`Registry::builtin`] would.
+pub(crate) fn explain_builtin(s: &str) -> Result<Parsed, Error> {
+    explain_first(BUILTIN.iter().copied(), s)
+}
+
+impl Registry {
+    /// Returns a registry with no formats at all.
+    pub fn empty() -> Registry {
+        Registry { parsers: Vec::new() }
+    }
+
+    /// Returns a registry with the formats that `dates` accepts, in the order
+    /// described by [`crate::parse_timestamp`].
+    pub fn builtin() -> Registry {
+        let mut registry = Registry::empty();
+        registry.register_builtin();
+        registry
+    }
+
+    /// Adds the formats of [`Registry::builtin`] after all formats
+    /// registered so far.
+    pub fn register_builtin(&mut self) -> &mut Registry {
+        for parser in BUILTIN {
+            self.register(parser);
+        }
+        self
+    }
+
+    /// Adds `parser` after all formats registered so far.
+    pub fn register(
+        &mut self,
+        parser: impl TimestampParser + 'static,
+    ) -> &mut Registry {
+        self.parsers.push(Box::new(parser));
+        self
+    }
+
+    /// Removes every format for which `keep` returns false, such as built-in
+    /// formats that a program wants to replace.
+    pub fn retain(
+        &mut self,
+        mut keep: impl FnMut(&dyn TimestampParser) -> bool,
+    ) -> &mut Registry {
+        self.parsers.retain(|p| keep(p.as_ref()));
+        self
+    }
+
+    /// Returns the registered formats, in the order they're tried.
+    pub fn parsers(&self) -> impl Iterator<Item = &dyn TimestampParser> {
+        self.parsers.iter().map(|p| p.as_ref())
+    }
+
+    /// Parses `s` with the first registered format that accepts it.
+    ///
+    /// If none does, this reports the first error that isn't a mere mismatch
+    /// (e.g., a format that matched but produced an impossible value like
+    /// February 30), since that's more useful than a mismatch.  Otherwise, it
+    /// reports that `s` isn't a timestamp.
+    pub fn parse(&self, s: &str) -> Result<DateTime<Utc>, Error> {
+        parse_first(self.parsers(), s)
+    }
+
+    /// Parses `s` like [`Registry::parse`], and describes how.
+    ///
+    /// Unlike [`Registry::parse`], this tries every format after the one
+    /// that matches, to find other interpretations.
+    pub fn explain(&self, s: &str) -> Result<Parsed, Error> {
+        explain_first(self.parsers(), s)
+    }
+
+    /// Returns every interpretation of `s`, in order: one for each
+    /// registered format that accepts it and produces an instant that no
+    /// earlier format did.  The provenance of each lists no alternatives,
+    /// since the other interpretations are the alternatives.
+    ///
+    /// If no format accepts `s`, this reports the same error as
+    /// [`Registry::parse`].
+    pub fn candidates(&self, s: &str) -> Result<Vec<Parsed>, Error> {
+        let input = Input::new(s);
+        let mut found: Vec<Parsed> = Vec::new();
+        for parser in self.parsers() {
+            if let Ok((time, normalized)) = input.parse(parser)
+                && !found.iter().any(|c| c.time == time)
+            {
+                let mut provenance = Provenance::new(
+                    parser.name(),
+                    parser.assumptions(&input.text),
+                    Vec::new(),
+                );
+                provenance.normalized = normalized;
+                found.push(Parsed { time, provenance });
+            }
+        }
+        if found.is_empty() {
+            self.parse(s)?;
+        }
+        Ok(found)
+    }
+
+    /// Parses `s` like [`Registry::parse`], but only if it's fully specified:
+    /// the format that matches must not have assumed anything (like the
+    /// unit of a bare number, or the zone), and no other format may read it
+    /// differently.  Otherwise, this reports that `s` is ambiguous.
+    pub fn parse_strict(&self, s: &str) -> Result<DateTime<Utc>, Error> {
+        let Parsed { time, provenance } = self.explain(s)?;
+        if let Some(alternative) = provenance.alternatives.first() {
+            return Err(Error::ambiguous_at(
+                s,
+                0..s.len(),
+                format!(
+                    "it could be {} or {}",
+                    provenance.format, alternative.format
+                ),
+            ));
+        }
+        if !provenance.assumptions.is_empty() {
+            let assumed: Vec<String> =
+                provenance.assumptions.iter().map(|a| a.to_string()).collect();
+            return Err(Error::ambiguous_at(
+                s,
+                0..s.len(),
+                format!(
+                    "reading it as {} means assuming {}",
+                    provenance.format,
+                    assumed.join(" and ")
+                ),
+            ));
+        }
+        Ok(time)
+    }
+}
+
+/// Implements [`Registry::parse`] for an arbitrary sequence of parsers.
+fn parse_first<'a>(
+    parsers: impl IntoIterator<Item = &'a dyn TimestampParser>,
+    s: &str,
+) -> Result<DateTime<Utc>, Error> {
+    let input = Input::new(s);
+    let mut best = None;
+    for parser in parsers {
+        match input.parse(parser) {
+            Ok((dt, _)) => return Ok(dt),
+            Err(Error::Unrecognized { .. }) => (),
+            Err(error) => {
+                best.get_or_insert(error);
+            }
+        }
+    }
+    Err(best.unwrap_or_else(|| Error::unrecognized(s, "a timestamp")))
+}
+
+/// Implements [`Registry::explain`] for an arbitrary sequence of parsers.
+fn explain_first<'a>(
+    parsers: impl IntoIterator<Item = &'a dyn TimestampParser>,
+    s: &str,
+) -> Result<Parsed, Error> {
+    let parsers: Vec<_> = parsers.into_iter().collect();
+    let time = parse_first(parsers.iter().copied(), s)?;
+    // `parse_first` stops at the first success, so every format before this
+    // one failed.
+    let input = Input::new(s);
+    let (i, normalized) = parsers
+        .iter()
+        .enumerate()
+        .find_map(|(i, p)| Some((i, input.parse(*p).ok()?.1)))
+        .unwrap();
+    let alternatives = parsers[i + 1..]
+        .iter()
+        .filter_map(|p| {
+            let other = input.parse(*p).ok().filter(|t| t.0 != time)?.0;
+            Some(Alternative { format: p.name().to_string(), time: other })
+        })
+        .collect();
+    let mut provenance = Provenance::new(
+        parsers[i].name(),
+        parsers[i].assumptions(&input.text),
+        alternatives,
+    );
+    provenance.normalized = normalized;
+    Ok(Parsed { time, provenance })
+}
+
+/// A timestamp to parse, with an end-of-day time like "24:00" rewritten as
+/// described for [`Normalization`]
+struct Input<'a> {
+    /// the input as given
+    original: &'a str,
+    /// the input as formats see it: with "24:00" as "23:59" (or "24:00:00" as
+    /// "23:59:59"), so that the result is a minute (or second) early
+    text: Cow<'a, str>,
+    /// how early the result is, if the input was rewritten
+    early: Option<chrono::Duration>,
+}
+
+impl<'a> Input<'a> {
+    fn new(s: &'a str) -> Input<'a> {
+        let unchanged =
+            Input { original: s, text: Cow::Borrowed(s), early: None };
+        // Look for "24:00" after the "T" or space separating a date and time.
+        let Some(i) = s.find("T24:00").or_else(|| s.find(" 24:00")) else {
+            return unchanged;
+        };
+        let hour = i + 1;
+        let rest = &s[hour + 5..];
+        let (replacement, early, after) = match rest.strip_prefix(":00") {
+            Some(after) => ("23:59:59", chrono::Duration::seconds(1), after),
+            None => ("23:59", chrono::Duration::minutes(1), rest),
+        };
+        // Only a whole number of seconds (or minutes) is the end of the day.
+        let after = match after.strip_prefix(['.', ',']) {
+            Some(fraction) => fraction.trim_start_matches('0'),
+            None => after,
+        };
+        if after.starts_with(|c: char| c.is_ascii_digit() || c == ':') {
+            return unchanged;
+        }
+        let end = s.len() - rest.len() + (replacement.len() - 5);
+        let text = format!("{}{replacement}{}", &s[..hour], &s[end..]);
+        Input { original: s, text: Cow::Owned(text), early: Some(early) }
+    }
+
+    /// Parses this input with `parser`, returning the instant and how the
+    /// input was normalized to get it.
+    fn parse(
+        &self,
+        parser: &dyn TimestampParser,
+    ) -> Result<(DateTime<Utc>, Vec<Normalization>), Error> {
+        let mut normalized = Vec::new();
+        let mut t =
+            parser.parse(&self.text).map_err(|e| e.within(self.original, 0))?;
+        if let Some(early) = self.early {
+            normalized.push(Normalization::EndOfDay);
+            t = t.checked_add_signed(early).ok_or_else(|| {
+                Error::out_of_range(self.original, "no such date or time")
+            })?;
+        }
+        let nanos = t.timestamp_subsec_nanos();
+        if nanos >= 1_000_000_000 {
+            let next = DateTime::from_timestamp(
+                t.timestamp() + 1,
+                nanos - 1_000_000_000,
+            )
+            .unwrap();
+            let second = next.with_nanosecond(0).unwrap();
+            if !crate::leap::LeapSeconds::builtin().inserted_before(second)
+                || next.time().num_seconds_from_midnight() != 0
+            {
+                normalized.push(Normalization::LeapSecond);
+                t = next;
+            }
+        }
+        Ok((t, normalized))
+    }
+}
+
+impl<T: TimestampParser + ?Sized> TimestampParser for &T {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn parse(&self, s: &str) -> Result<DateTime<Utc>, Error> {
+        (**self).parse(s)
+    }
+
+    fn assumptions(&self, s: &str) -> Vec<Assumption> {
+        (**self).assumptions(s)
+    }
+}
+
+/// Integers, interpreted as milliseconds since the Unix epoch
+struct UnixMillis;
+
+impl TimestampParser for UnixMillis {
+    fn name(&self) -> &str {
+        "unix-millis"
+    }
+
+    fn parse(&self, s: &str) -> Result<DateTime<Utc>, Error> {
+        let ts = s.parse::<i64>().map_err(|_| {
+            Error::unrecognized(s, "an integer number of milliseconds")
+        })?;
+        Utc.timestamp_millis_opt(ts).single().ok_or_else(|| {
+            Error::out_of_range(s, "too large for a millisecond timestamp")
+        })
+    }
+
+    fn assumptions(&self, _s: &str) -> Vec<Assumption> {
+        vec![Assumption::Unit { unit: String::from("milliseconds") }]
+    }
+}
+
+/// Other numbers, interpreted as (fractional) seconds since the Unix epoch
+///
+/// A plain decimal, like "1709294400.123456" (as `strace -ttt` prints), is
+/// read exactly, to the nanosecond.  Anything else Rust reads as a float,
+/// like "1.7e9", is read to the millisecond.
+struct UnixSeconds;
+
+impl TimestampParser for UnixSeconds {
+    fn name(&self) -> &str {
+        "unix-seconds"
+    }
+
+    fn parse(&self, s: &str) -> Result<DateTime<Utc>, Error> {
+        let digits = s.strip_prefix(['-', '+']).unwrap_or(s);
+        if digits.bytes().next().is_some_and(|b| b.is_ascii_digit())
+            && digits.bytes().filter(|&b| b == b'.').count() <= 1
+            && digits.bytes().all(|b| b == b'.' || b.is_ascii_digit())
+        {
+            return count_since(s, EpochUnit::Seconds, 0);
+        }
+        let ts = s
+            .parse::<f64>()
+            .map_err(|_| Error::unrecognized(s, "a number of seconds"))?;
+        let millis = ts * 1000.0;
+        if !millis.is_finite() || millis.abs() >= i64::MAX as f64 {
+            return Err(Error::out_of_range(s, "too large for a timestamp"));
+        }
+        // Round half away from zero.  (`f64::round()` isn't available
+        // without std.)
+        let millis = (millis + 0.5f64.copysign(millis)) as i64;
+        Utc.timestamp_millis_opt(millis)
+            .single()
+            .ok_or_else(|| Error::out_of_range(s, "too large for a timestamp"))
+    }
+
+    fn assumptions(&self, _s: &str) -> Vec<Assumption> {
+        vec![Assumption::Unit { unit: String::from("seconds") }]
+    }
+}
+
+/// A unit in which a Unix time can be counted
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EpochUnit {
+    Seconds,
+    Millis,
+    Micros,
+    Nanos,
+    Days,
+}
+
+impl EpochUnit {
+    /// Parses "s", "ms", "us", "ns", or "d".
+    pub fn parse(s: &str) -> Result<EpochUnit, Error> {
+        match s {
+            "s" => Ok(EpochUnit::Seconds),
+            "ms" => Ok(EpochUnit::Millis),
+            "us" => Ok(EpochUnit::Micros),
+            "ns" => Ok(EpochUnit::Nanos),
+            "d" => Ok(EpochUnit::Days),
+            _ => Err(Error::unrecognized(s, "a unit (s, ms, us, ns, or d)")),
+        }
+    }
+
+    /// Returns the name of the unit, like "milliseconds".
+    pub fn name(self) -> &'static str {
+        match self {
+            EpochUnit::Seconds => "seconds",
+            EpochUnit::Millis => "milliseconds",
+            EpochUnit::Micros => "microseconds",
+            EpochUnit::Nanos => "nanoseconds",
+            EpochUnit::Days => "days",
+        }
+    }
+
+    /// Returns the number of nanoseconds in one of this unit.
+    pub fn nanos(self) -> i128 {
+        match self {
+            EpochUnit::Seconds => 1_000_000_000,
+            EpochUnit::Millis => 1_000_000,
+            EpochUnit::Micros => 1000,
+            EpochUnit::Nanos => 1,
+            EpochUnit::Days => 86_400 * 1_000_000_000,
+        }
+    }
+}
+
+/// Numbers (with an optional fraction), interpreted as a count of a
+/// particular unit since the Unix epoch
+///
+/// Unlike the built-in formats for bare numbers, this doesn't guess the
+/// unit, so it reports no assumptions.  Fractions of a nanosecond are
+/// truncated.
+pub struct UnixTime(pub EpochUnit);
+
+impl TimestampParser for UnixTime {
+    fn name(&self) -> &str {
+        match self.0 {
+            EpochUnit::Seconds => "unix-seconds",
+            EpochUnit::Millis => "unix-millis",
+            EpochUnit::Micros => "unix-micros",
+            EpochUnit::Nanos => "unix-nanos",
+            EpochUnit::Days => "unix-days",
+        }
+    }
+
+    fn parse(&self, s: &str) -> Result<DateTime<Utc>, Error> {
+        count_since(s, self.0, 0)
+    }
+}
+
+/// An epoch from which some software counts times
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Epoch {
+    /// 1970-01-01T00:00:00Z
+    Unix,
+    /// 1960-01-01, from which SAS counts seconds (in datetime values) and
+    /// days (in date values)
+    Sas,
+    /// 1582-10-14, the first day of the Gregorian calendar (as SPSS has it),
+    /// from which SPSS counts seconds
+    Spss,
+    /// 1960-01-01, from which Stata counts milliseconds (in `%tc` values)
+    /// and days (in `%td` values)
+    Stata,
+    /// The Unix epoch, from which Mozilla's PRTime counts microseconds, as
+    /// in Firefox's `places.sqlite` and NSS
+    Prtime,
+    /// 1601-01-01, from which WebKit (and Chrome, in its history database)
+    /// counts microseconds
+    Webkit,
+}
+
+impl Epoch {
+    /// Parses "unix", "sas", "spss", "stata", "prtime", or "webkit".
+    pub fn parse(s: &str) -> Result<Epoch, Error> {
+        match s {
+            "unix" => Ok(Epoch::Unix),
+            "sas" => Ok(Epoch::Sas),
+            "spss" => Ok(Epoch::Spss),
+            "stata" => Ok(Epoch::Stata),
+            "prtime" => Ok(Epoch::Prtime),
+            "webkit" => Ok(Epoch::Webkit),
+            _ => Err(Error::unrecognized(
+                s,
+                "an epoch (unix, sas, spss, stata, prtime, or webkit)",
+            )),
+        }
+    }
+
+    /// Returns the name of the epoch, like "stata".
+    pub fn name(self) -> &'static str {
+        match self {
+            Epoch::Unix => "unix",
+            Epoch::Sas => "sas",
+            Epoch::Spss => "spss",
+            Epoch::Stata => "stata",
+            Epoch::Prtime => "prtime",
+            Epoch::Webkit => "webkit",
+        }
+    }
+
+    /// Returns the epoch in seconds since the Unix epoch.
+    pub fn unix_seconds(self) -> i64 {
+        match self {
+            Epoch::Unix | Epoch::Prtime => 0,
+            Epoch::Sas | Epoch::Stata => -315_619_200,
+            Epoch::Spss => -12_219_379_200,
+            Epoch::Webkit => -11_644_473_600,
+        }
+    }
+
+    /// Returns the unit that times usually count from this epoch: seconds,
+    /// except for Stata's `%tc`, which counts milliseconds, and PRTime and
+    /// WebKit times, which count microseconds.
+    pub fn default_unit(self) -> EpochUnit {
+        match self {
+            Epoch::Stata => EpochUnit::Millis,
+            Epoch::Prtime | Epoch::Webkit => EpochUnit::Micros,
+            _ => EpochUnit::Seconds,
+        }
+    }
+}
+
+/// Numbers (with an optional fraction), interpreted as a count of a
+/// particular unit since an [`Epoch`]
+///
+/// Like [`UnixTime`], this doesn't guess the unit, so it reports no
+/// assumptions.  None of the software that counts from these epochs counts
+/// leap seconds, so neither does this.
+pub struct EpochTime {
+    epoch: Epoch,
+    unit: EpochUnit,
+    name: String,
+}
+
+impl EpochTime {
+    /// Returns a parser for counts of `unit` since `epoch`.
+    pub fn new(epoch: Epoch, unit: EpochUnit) -> EpochTime {
+        let unit_name = UnixTime(unit).name()["unix-".len()..].to_string();
+        EpochTime { epoch, unit, name: format!("{}-{unit_name}", epoch.name()) }
+    }
+}
+
+impl TimestampParser for EpochTime {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn parse(&self, s: &str) -> Result<DateTime<Utc>, Error> {
+        count_since(
+            s,
+            self.unit,
+            i128::from(self.epoch.unix_seconds()) * 1_000_000_000,
+        )
+    }
+}
+
+/// Integers of 16 or 17 digits, interpreted as microseconds since an
+/// [`Epoch`], as browsers record times
+///
+/// Firefox's PRTime ([`Epoch::Prtime`]) and Chrome's WebKit time
+/// ([`Epoch::Webkit`]) both count microseconds, 369 years apart, and have
+/// had 16 and 17 digits for as long as either browser has existed.  Read as
+/// milliseconds since 1970, as bare integers are by default, they're tens of
+/// thousands of years from now.  This only tries numbers that look like
+/// them, so that it can be a candidate without being one for every number.
+pub struct BrowserTime(EpochTime);
+
+impl BrowserTime {
+    /// Returns a parser for microseconds since `epoch`.
+    pub fn new(epoch: Epoch) -> BrowserTime {
+        BrowserTime(EpochTime::new(epoch, EpochUnit::Micros))
+    }
+}
+
+impl TimestampParser for BrowserTime {
+    fn name(&self) -> &str {
+        self.0.name()
+    }
+
+    fn parse(&self, s: &str) -> Result<DateTime<Utc>, Error> {
+        if !(16..=17).contains(&s.len())
+            || !s.bytes().all(|b| b.is_ascii_digit())
+        {
+            return Err(Error::unrecognized(
+                s,
+                "a 16- or 17-digit number of microseconds",
+            ));
+        }
+        self.0.parse(s)
+    }
+}
+
+/// Numbers (with an optional fraction), interpreted as a count of a
+/// particular unit since a system booted, like the times that ftrace,
+/// systemd, and BPF programs report
+///
+/// Those usually come from a clock that stops while the system is suspended
+/// (like Linux's `CLOCK_MONOTONIC`), so on a system that has been suspended,
+/// times after the suspension are really later than these by however long
+/// it was suspended.
+pub struct SinceBoot {
+    boot: DateTime<Utc>,
+    unit: EpochUnit,
+    name: String,
+}
+
+impl SinceBoot {
+    /// Returns a parser for counts of `unit` since `boot`.
+    pub fn new(boot: DateTime<Utc>, unit: EpochUnit) -> SinceBoot {
+        let unit_name = UnixTime(unit).name()["unix-".len()..].to_string();
+        SinceBoot { boot, unit, name: format!("boot-{unit_name}") }
+    }
+}
+
+impl TimestampParser for SinceBoot {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn parse(&self, s: &str) -> Result<DateTime<Utc>, Error> {
+        let boot = i128::from(self.boot.timestamp()) * 1_000_000_000
+            + i128::from(self.boot.timestamp_subsec_nanos());
+        count_since(s, self.unit, boot)
+    }
+}
+
+/// Parses `s` as a number (with an optional fraction) of `unit` since
+/// `epoch` nanoseconds after the Unix epoch.  Fractions of a nanosecond are
+/// truncated.
+fn count_since(
+    s: &str,
+    unit: EpochUnit,
+    epoch: i128,
+) -> Result<DateTime<Utc>, Error> {
+    let mismatch =
+        || Error::unrecognized(s, format!("a number of {}", unit.name()));
+    let too_large = || Error::out_of_range(s, "too large for a timestamp");
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    if whole.is_empty()
+        || !whole.bytes().chain(fraction.bytes()).all(|b| b.is_ascii_digit())
+    {
+        return Err(mismatch());
+    }
+
+    let whole: i128 = whole.parse().map_err(|_| too_large())?;
+    let mut nanos = whole.checked_mul(unit.nanos()).ok_or_else(too_large)?;
+    let mut scale = unit.nanos();
+    for digit in fraction.bytes().take(scale.ilog10() as usize) {
+        scale /= 10;
+        nanos += i128::from(digit - b'0') * scale;
+    }
+    if negative {
+        nanos = -nanos;
+    }
+    let nanos = nanos + epoch;
+
+    let secs = i64::try_from(nanos.div_euclid(1_000_000_000))
+        .map_err(|_| too_large())?;
+    let subsec = nanos.rem_euclid(1_000_000_000) as u32;
+    DateTime::from_timestamp(secs, subsec).ok_or_else(too_large)
+}
+
+/// RFC 3339 timestamps, like "2018-02-08T13:43:00-07:00"
+struct Rfc3339;
+
+impl TimestampParser for Rfc3339 {
+    fn name(&self) -> &str {
+        "rfc3339"
+    }
+
+    fn parse(&self, s: &str) -> Result<DateTime<Utc>, Error> {
+        DateTime::parse_from_rfc3339(s)
+            .map(|dt| dt.to_utc())
+            .map_err(|e| chrono_error(s, e.kind(), "an RFC 3339 timestamp"))
+    }
+}
+
+/// RFC 3339 timestamps with a sign and more than four digits of year, like
+/// "+10000-01-01T00:00:00Z", which is how chrono writes years after 9999
+/// (and before 0)
+struct ExpandedRfc3339;
+
+impl TimestampParser for ExpandedRfc3339 {
+    fn name(&self) -> &str {
+        "rfc3339-expanded"
+    }
+
+    fn parse(&self, s: &str) -> Result<DateTime<Utc>, Error> {
+        let expected = "an RFC 3339 timestamp with an expanded year";
+        if !s.starts_with(['+', '-']) {
+            return Err(Error::unrecognized(s, expected));
+        }
+        DateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f%#z")
+            .map(|dt| dt.to_utc())
+            .map_err(|e| chrono_error(s, e.kind(), expected))
+    }
+}
+
+/// ISO 8601 timestamps with no seconds, like "2024-03-01T00:00Z", which
+/// often appear in intervals
+///
+/// Unlike [`Strftime`], this requires the UTC offset.
+struct Iso8601Minutes;
+
+impl TimestampParser for Iso8601Minutes {
+    fn name(&self) -> &str {
+        "iso8601-minutes"
+    }
+
+    fn parse(&self, s: &str) -> Result<DateTime<Utc>, Error> {
+        DateTime::parse_from_str(s, "%Y-%m-%dT%H:%M%#z")
+            .map(|dt| dt.to_utc())
+            .map_err(|e| {
+                chrono_error(s, e.kind(), "an ISO 8601 timestamp to the minute")
+            })
+    }
+}
+
+/// ASN.1 UTCTime, like "240301123456Z", as X.509 certificates use for
+/// times before 2050
+///
+/// The two-digit year is read as 1950 through 2049, as RFC 5280 specifies.
+/// The seconds may be left out, and the time may end with an offset like
+/// "-0800" rather than "Z".
+pub struct UtcTime;
+
+impl UtcTime {
+    const EXPECTED: &str = "an ASN.1 UTCTime (like \"240301123456Z\")";
+
+    /// Returns the year that the two digits `yy` mean.
+    fn year(yy: i32) -> i32 {
+        if yy < 50 { 2000 + yy } else { 1900 + yy }
+    }
+}
+
+impl TimestampParser for UtcTime {
+    fn name(&self) -> &str {
+        "asn1-utctime"
+    }
+
+    fn parse(&self, s: &str) -> Result<DateTime<Utc>, Error> {
+        let fields = asn1_fields(s, UtcTime::EXPECTED, false)?;
+        if fields.digits.len() != 10 && fields.digits.len() != 12 {
+            return Err(Error::unrecognized(s, UtcTime::EXPECTED));
+        }
+        let (yy, rest) = fields.digits.split_at(2);
+        fields.time(s, UtcTime::year(yy.parse().unwrap()), rest)
+    }
+
+    fn assumptions(&self, s: &str) -> Vec<Assumption> {
+        let year = s.get(..2).and_then(|yy| yy.parse().ok());
+        year.map(|yy| Assumption::Century { year: UtcTime::year(yy) })
+            .into_iter()
+            .collect()
+    }
+}
+
+/// journald's `__REALTIME_TIMESTAMP` field, like
+/// "__REALTIME_TIMESTAMP=1709294400123456", as `journalctl -o export` prints
+/// it: microseconds since the Unix epoch
+pub struct JournaldRealtime;
+
+impl TimestampParser for JournaldRealtime {
+    fn name(&self) -> &str {
+        "journald-realtime"
+    }
+
+    fn parse(&self, s: &str) -> Result<DateTime<Utc>, Error> {
+        let expected = "a journald __REALTIME_TIMESTAMP field";
+        match s.strip_prefix("__REALTIME_TIMESTAMP=") {
+            Some(digits)
+                if !digits.is_empty()
+                    && digits.bytes().all(|b| b.is_ascii_digit()) =>
+            {
+                let offset = s.len() - digits.len();
+                count_since(digits, EpochUnit::Micros, 0)
+                    .map_err(|e| e.within(s, offset))
+            }
+            _ => Err(Error::unrecognized(s, expected)),
+        }
+    }
+}
+
+/// The time in an NMEA 0183 sentence from a GPS receiver: a `$GPRMC` (or
+/// other talker's RMC) sentence, like
+/// "$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6A",
+/// or a ZDA sentence, like "$GPZDA,201530.00,04,07,2002,00,00*60"
+///
+/// Both are in UTC.  RMC gives the year with two digits, read as 1980
+/// through 2079, since GPS started in 1980.  ZDA gives all four, and the
+/// receiver's local zone, which doesn't change the time and is ignored.  A
+/// checksum after "*" is checked, if present.
+pub struct NmeaSentence;
+
+impl NmeaSentence {
+    const EXPECTED: &str = "an NMEA RMC or ZDA sentence";
+
+    /// Returns the year that the two digits `yy` of an RMC date mean.
+    fn year(yy: i32) -> i32 {
+        if yy < 80 { 2000 + yy } else { 1900 + yy }
+    }
+
+    /// Splits a sentence into its type ("RMC" or "ZDA") and its fields
+    /// after the first, having checked the checksum.
+    fn fields(s: &str) -> Result<(&str, Vec<&str>), Error> {
+        let body = s
+            .strip_prefix('$')
+            .ok_or_else(|| Error::unrecognized(s, NmeaSentence::EXPECTED))?;
+        let (body, checksum) = match body.split_once('*') {
+            Some((body, checksum)) => (body, Some(checksum)),
+            None => (body, None),
+        };
+        let mut fields = body.split(',');
+        let address = fields.next().unwrap();
+        let kind = match address.len() {
+            5 if address.bytes().all(|b| b.is_ascii_uppercase()) => {
+                &address[2..]
+            }
+            _ => return Err(Error::unrecognized(s, NmeaSentence::EXPECTED)),
+        };
+        if kind != "RMC" && kind != "ZDA" {
+            return Err(Error::unrecognized(s, NmeaSentence::EXPECTED));
+        }
+        if let Some(checksum) = checksum {
+            let expected = body.bytes().fold(0, |sum, b| sum ^ b);
+            let offset = s.len() - checksum.len();
+            match u8::from_str_radix(checksum, 16) {
+                Ok(found) if checksum.len() == 2 => {
+                    if found != expected {
+                        return Err(Error::out_of_range_at(
+                            s,
+                            offset..s.len(),
+                            format!(
+                                "the checksum doesn't match (expected \
+                                 {expected:02X})"
+                            ),
+                        ));
+                    }
+                }
+                _ => {
+                    return Err(Error::unrecognized_at(
+                        s,
+                        offset..s.len(),
+                        "a two-digit hexadecimal checksum",
+                    ));
+                }
+            }
+        }
+        Ok((kind, fields.collect()))
+    }
+
+    /// Returns the error for a sentence with a malformed time or date
+    /// field among `fields`.  A receiver that doesn't know the time yet
+    /// leaves them empty.
+    fn malformed(s: &str, fields: &[&str]) -> Error {
+        if fields.iter().any(|f| f.is_empty()) {
+            Error::out_of_range(s, "the sentence has no time (no GPS fix yet)")
+        } else {
+            Error::unrecognized(s, NmeaSentence::EXPECTED)
+        }
+    }
+}
+
+impl TimestampParser for NmeaSentence {
+    fn name(&self) -> &str {
+        "nmea"
+    }
+
+    fn parse(&self, s: &str) -> Result<DateTime<Utc>, Error> {
+        let (kind, fields) = NmeaSentence::fields(s)?;
+        let digits = |f: &str, n: usize| {
+            f.len() == n && f.bytes().all(|b| b.is_ascii_digit())
+        };
+        let field = |i: usize| fields.get(i).copied().unwrap_or("");
+        let time = field(0);
+        let (hhmmss, fraction) = time.split_once('.').unwrap_or((time, ""));
+        let (year, month, day) = match kind {
+            "RMC" => {
+                let date = field(8);
+                if !digits(date, 6) {
+                    return Err(NmeaSentence::malformed(s, &[time, date]));
+                }
+                let yy = date[4..].parse().unwrap();
+                (NmeaSentence::year(yy), &date[2..4], &date[..2])
+            }
+            _ => {
+                let (day, month, year) = (field(1), field(2), field(3));
+                if !digits(day, 2) || !digits(month, 2) || !digits(year, 4) {
+                    return Err(NmeaSentence::malformed(
+                        s,
+                        &[time, day, month, year],
+                    ));
+                }
+                (year.parse().unwrap(), month, day)
+            }
+        };
+        if !digits(hhmmss, 6) || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(NmeaSentence::malformed(s, &[time]));
+        }
+        let fields = Asn1Fields { digits: "", fraction, offset: "" };
+        fields.time(s, year, &format!("{month}{day}{hhmmss}"))
+    }
+
+    fn assumptions(&self, s: &str) -> Vec<Assumption> {
+        match NmeaSentence::fields(s) {
+            Ok(("RMC", fields)) => fields
+                .get(8)
+                .and_then(|date| date.get(4..)?.parse().ok())
+                .map(|yy| Assumption::Century { year: NmeaSentence::year(yy) })
+                .into_iter()
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Returns the value of `s` if it's an HTTP header line for a time, like
+/// "Last-Modified: Fri, 01 Mar 2024 12:00:00 GMT", or else `s` itself, so
+/// that such lines can be pasted from `curl -I` output.
+fn header_value(s: &str) -> &str {
+    const HEADERS: [&str; 5] =
+        ["date", "expires", "if-modified-since", "last-modified", "x-amz-date"];
+    match s.split_once(':') {
+        Some((name, value))
+            if HEADERS.iter().any(|h| name.eq_ignore_ascii_case(h)) =>
+        {
+            value.trim()
+        }
+        _ => s,
+    }
+}
+
+/// The time of an AWS Signature Version 4 request, like "20240301T120000Z",
+/// as in the `X-Amz-Date` header and the `X-Amz-Date` parameter of a
+/// pre-signed URL: ISO 8601's basic format, in UTC, to the second
+pub struct AmzDate;
+
+impl TimestampParser for AmzDate {
+    fn name(&self) -> &str {
+        "aws-sigv4"
+    }
+
+    fn parse(&self, s: &str) -> Result<DateTime<Utc>, Error> {
+        let value = header_value(s);
+        let mismatch = || {
+            Error::unrecognized(s, "a SigV4 time (like \"20240301T120000Z\")")
+        };
+        let (date, time) = value
+            .strip_suffix('Z')
+            .and_then(|v| v.split_once('T'))
+            .ok_or_else(mismatch)?;
+        let digits = |f: &str, n: usize| {
+            f.len() == n && f.bytes().all(|b| b.is_ascii_digit())
+        };
+        if !digits(date, 8) || !digits(time, 6) {
+            return Err(mismatch());
+        }
+        let fields = Asn1Fields { digits: "", fraction: "", offset: "" };
+        fields.time(
+            s,
+            date[..4].parse().unwrap(),
+            &format!("{}{time}", &date[4..]),
+        )
+    }
+}
+
+/// An HTTP date, as in the `Last-Modified` header that S3 and CloudFront
+/// send, like "Fri, 01 Mar 2024 12:00:00 GMT"
+///
+/// RFC 9110's two obsolete forms, like "Friday, 01-Mar-24 12:00:00 GMT" and
+/// "Fri Mar  1 12:00:00 2024", are also accepted, as is "UTC" or "+0000"
+/// rather than "GMT", which some S3-compatible servers send.  All of them
+/// are in UTC.  The day of the week must match the date.
+pub struct HttpDate;
+
+impl HttpDate {
+    /// The forms of an HTTP date, without the zone
+    const FORMATS: [&str; 3] = [
+        "%a, %d %b %Y %H:%M:%S",
+        "%A, %d-%b-%y %H:%M:%S",
+        "%a %b %e %H:%M:%S %Y",
+    ];
+
+    /// Splits the zone off an HTTP date, returning the rest and the forms
+    /// it could be in.  Only the asctime form has no zone.
+    fn forms(value: &str) -> (&str, &'static [&'static str]) {
+        match value.rsplit_once(' ') {
+            Some((rest, "GMT" | "UTC" | "+0000")) => {
+                (rest, &HttpDate::FORMATS[..2])
+            }
+            _ => (value, &HttpDate::FORMATS[2..]),
+        }
+    }
+}
+
+impl TimestampParser for HttpDate {
+    fn name(&self) -> &str {
+        "http-date"
+    }
+
+    fn parse(&self, s: &str) -> Result<DateTime<Utc>, Error> {
+        let (value, formats) = HttpDate::forms(header_value(s));
+        let mut error = None;
+        for format in formats {
+            match NaiveDateTime::parse_from_str(value, format) {
+                Ok(t) => return Ok(t.and_utc()),
+                Err(e) => {
+                    error.get_or_insert(e.kind());
+                }
+            }
+        }
+        Err(chrono_error(
+            s,
+            error.unwrap(),
+            "an HTTP date (like \"Fri, 01 Mar 2024 12:00:00 GMT\")",
+        ))
+    }
+
+    fn assumptions(&self, s: &str) -> Vec<Assumption> {
+        // Only the obsolete RFC 850 form has a two-digit year.
+        let (value, _) = HttpDate::forms(header_value(s));
+        NaiveDateTime::parse_from_str(value, HttpDate::FORMATS[1])
+            .map(|t| vec![Assumption::Century { year: t.year() }])
+            .unwrap_or_default()
+    }
+}
+
+/// The date and time columns of a CloudFront standard access log, like
+/// "2024-03-01\t12:00:00": tab-separated, to the second, in UTC
+///
+/// With a space rather than a tab, the same text is an ordinary date and
+/// time, which has no zone, so it's not accepted here.  (The times in ALB
+/// and ELB access logs are RFC 3339, like "2024-03-01T12:00:00.123456Z", and
+/// CloudFront's real-time logs use seconds since the epoch, like
+/// "1709294400.123", so neither needs a format of its own.)
+pub struct CloudFrontLog;
+
+impl TimestampParser for CloudFrontLog {
+    fn name(&self) -> &str {
+        "cloudfront-log"
+    }
+
+    fn parse(&self, s: &str) -> Result<DateTime<Utc>, Error> {
+        let mismatch = || {
+            Error::unrecognized(
+                s,
+                "CloudFront log columns (like \"2024-03-01\\t12:00:00\")",
+            )
+        };
+        let (date, time) = s.split_once('\t').ok_or_else(mismatch)?;
+        let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|e| chrono_error(s, e.kind(), "a date"))?;
+        let time = NaiveTime::parse_from_str(time, "%H:%M:%S")
+            .map_err(|e| chrono_error(s, e.kind(), "a time of day"))?;
+        Ok(date.and_time(time).and_utc())
+    }
+
+    fn assumptions(&self, _s: &str) -> Vec<Assumption> {
+        vec![Assumption::Utc]
+    }
+}
+
+/// ASN.1 GeneralizedTime, like "20240301123456Z" or "20240301123456.123Z",
+/// as X.509 certificates use for times from 2050
+///
+/// The time may have a fraction of a second, and may end with an offset like
+/// "-0800" rather than "Z".  Without either, a GeneralizedTime is on an
+/// unspecified local clock, so it's not accepted.
+pub struct GeneralizedTime;
+
+impl GeneralizedTime {
+    const EXPECTED: &str =
+        "an ASN.1 GeneralizedTime (like \"20240301123456Z\")";
+}
+
+impl TimestampParser for GeneralizedTime {
+    fn name(&self) -> &str {
+        "asn1-generalized-time"
+    }
+
+    fn parse(&self, s: &str) -> Result<DateTime<Utc>, Error> {
+        let fields = asn1_fields(s, GeneralizedTime::EXPECTED, true)?;
+        if fields.digits.len() != 14 {
+            return Err(Error::unrecognized(s, GeneralizedTime::EXPECTED));
+        }
+        let (yyyy, rest) = fields.digits.split_at(4);
+        fields.time(s, yyyy.parse().unwrap(), rest)
+    }
+}
+
+/// The parts of an ASN.1 time: the digits of the date and time of day, any
+/// fraction of a second, and the offset (empty for "Z")
+struct Asn1Fields<'a> {
+    digits: &'a str,
+    fraction: &'a str,
+    offset: &'a str,
+}
+
+/// Splits `s` into [`Asn1Fields`], allowing a fraction only if `fraction`
+/// is set.  Only the form is checked, not the values.
+fn asn1_fields<'a>(
+    s: &'a str,
+    expected: &str,
+    fraction: bool,
+) -> Result<Asn1Fields<'a>, Error> {
+    let mismatch = || Error::unrecognized(s, expected);
+    let all_digits = |d: &str| d.bytes().all(|b| b.is_ascii_digit());
+    let (body, offset) = match s.strip_suffix('Z') {
+        Some(body) => (body, ""),
+        None => {
+            let split = s.len().checked_sub(5).ok_or_else(mismatch)?;
+            if !s.is_char_boundary(split) {
+                return Err(mismatch());
+            }
+            let (body, offset) = s.split_at(split);
+            if !offset.starts_with(['+', '-']) || !all_digits(&offset[1..]) {
+                return Err(mismatch());
+            }
+            (body, offset)
+        }
+    };
+    let (digits, fraction_digits) = match body.split_once(['.', ',']) {
+        Some((digits, f)) if fraction && !f.is_empty() => (digits, f),
+        Some(_) => return Err(mismatch()),
+        None => (body, ""),
+    };
+    if digits.is_empty() || !all_digits(digits) || !all_digits(fraction_digits)
+    {
+        return Err(mismatch());
+    }
+    Ok(Asn1Fields { digits, fraction: fraction_digits, offset })
+}
+
+impl Asn1Fields<'_> {
+    /// Returns the time in year `year`, given the `rest` of the digits
+    /// after the year: the month, day, hour, minute, and (if present)
+    /// second.  A second of 60 is read as a leap second.
+    fn time(
+        &self,
+        s: &str,
+        year: i32,
+        rest: &str,
+    ) -> Result<DateTime<Utc>, Error> {
+        let field = |s: &str, i: usize| {
+            s.get(i..i + 2).map_or(0, |f| f.parse().unwrap())
+        };
+        let month = field(rest, 0);
+        let day = field(rest, 2);
+        let hour = field(rest, 4);
+        let minute = field(rest, 6);
+        let mut second = field(rest, 8);
+        let mut nanos = 0;
+        let mut scale = 1_000_000_000;
+        for digit in self.fraction.bytes().take(9) {
+            scale /= 10;
+            nanos += u32::from(digit - b'0') * scale;
+        }
+        if second == 60 {
+            second = 59;
+            nanos += 1_000_000_000;
+        }
+        let no_such = || Error::out_of_range(s, "no such date or time");
+        let time = NaiveDate::from_ymd_opt(year, month, day)
+            .and_then(|d| d.and_hms_nano_opt(hour, minute, second, nanos))
+            .ok_or_else(no_such)?;
+
+        let (sign, hhmm) = self.offset.split_at(self.offset.len().min(1));
+        let (hours, minutes) = (field(hhmm, 0), field(hhmm, 2));
+        let seconds = i32::try_from(hours * 3600 + minutes * 60).unwrap();
+        let offset =
+            FixedOffset::east_opt(if sign == "-" { -seconds } else { seconds })
+                .filter(|_| minutes < 60)
+                .ok_or_else(|| Error::out_of_range(s, "no such UTC offset"))?;
+        offset
+            .from_local_datetime(&time)
+            .single()
+            .map(|t| t.to_utc())
+            .ok_or_else(no_such)
+    }
+}
+
+/// Timestamps described by a `strftime`-style format string
+///
+/// The format may omit the UTC offset (meaning UTC) or the time of day as
+/// well (meaning midnight UTC).  See [`chrono::format::strftime`] for the
+/// supported specifiers.
+pub struct Strftime {
+    name: Cow<'static, str>,
+    format: Cow<'static, str>,
+}
+
+impl Strftime {
+    pub fn new(name: impl Into<String>, format: impl Into<String>) -> Strftime {
+        Strftime {
+            name: Cow::Owned(name.into()),
+            format: Cow::Owned(format.into()),
+        }
+    }
+
+    /// Like [`Strftime::new`], but usable in constants.
+    pub const fn from_static(
+        name: &'static str,
+        format: &'static str,
+    ) -> Strftime {
+        Strftime { name: Cow::Borrowed(name), format: Cow::Borrowed(format) }
+    }
+}
+
+impl TimestampParser for Strftime {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn parse(&self, s: &str) -> Result<DateTime<Utc>, Error> {
+        let format = &*self.format;
+        let result = DateTime::parse_from_str(s, format)
+            .map(|dt| dt.to_utc())
+            .or_else(|e| match e.kind() {
+                ParseErrorKind::NotEnough => {
+                    NaiveDateTime::parse_from_str(s, format)
+                        .map(|n| n.and_utc())
+                }
+                _ => Err(e),
+            })
+            .or_else(|e| match e.kind() {
+                ParseErrorKind::NotEnough => {
+                    NaiveDate::parse_from_str(s, format)
+                        .map(|n| n.and_hms_opt(0, 0, 0).unwrap().and_utc())
+                }
+                _ => Err(e),
+            });
+        result.map_err(|e| {
+            chrono_error(s, e.kind(), format!("a timestamp like {format:?}"))
+        })
+    }
+
+    fn assumptions(&self, s: &str) -> Vec<Assumption> {
+        // Retrace which of the fallbacks in `parse` was needed.
+        let format = &*self.format;
+        if DateTime::parse_from_str(s, format).is_ok() {
+            Vec::new()
+        } else if NaiveDateTime::parse_from_str(s, format).is_ok() {
+            vec![Assumption::Utc]
+        } else {
+            vec![Assumption::Midnight, Assumption::Utc]
+        }
+    }
+}
+
+/// Converts a chrono parse failure for `s` into an [`Error`].
+fn chrono_error(
+    s: &str,
+    kind: ParseErrorKind,
+    expected: impl Into<String>,
+) -> Error {
+    match kind {
+        ParseErrorKind::OutOfRange | ParseErrorKind::Impossible => {
+            Error::out_of_range(s, "no such date or time")
+        }
+        _ => Error::unrecognized(s, expected),
+    }
+}
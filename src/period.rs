@@ -0,0 +1,151 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Partial dates, like "2024" or "2024-03", that stand for a whole period
+//!
+//! As a timestamp, a partial date means the start of its period: "2024" is
+//! midnight on 2024-01-01, and "2024-03" is midnight on 2024-03-01.  The
+//! period ends where the next one starts, so "2024" covers every instant
+//! from 2024-01-01T00:00 up to (but not including) 2025-01-01T00:00.  A full
+//! date like "2024-03-15" is treated the same way, as a period of one day.
+
+use crate::Error;
+use crate::parser::{Assumption, TimestampParser};
+use crate::zone::{DstPolicy, Zone};
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+use chrono::{DateTime, Days, Months, NaiveDate, Utc};
+use core::fmt;
+use core::ops::Range;
+
+/// How much of a date was given
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Precision {
+    Year,
+    Month,
+    Day,
+}
+
+impl fmt::Display for Precision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Precision::Year => write!(f, "year"),
+            Precision::Month => write!(f, "month"),
+            Precision::Day => write!(f, "day"),
+        }
+    }
+}
+
+/// A date given as "YYYY", "YYYY-MM", or "YYYY-MM-DD"
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PartialDate {
+    /// first day of the period
+    pub start: NaiveDate,
+    pub precision: Precision,
+}
+
+impl PartialDate {
+    /// Parses `s` as a year ("2024", exactly four digits), a month
+    /// ("2024-03"), or a day ("2024-03-15").
+    pub fn parse(s: &str) -> Result<PartialDate, Error> {
+        let expected = "a year, month, or date (like \"2024-03\")";
+        let mut fields = s.split('-');
+        let year = fields.next().unwrap_or("");
+        let month = fields.next();
+        let day = fields.next();
+        if fields.next().is_some()
+            || year.len() != 4
+            || [Some(year), month, day]
+                .iter()
+                .flatten()
+                .any(|f| f.is_empty() || !f.bytes().all(|b| b.is_ascii_digit()))
+            || month.is_some_and(|m| m.len() != 2)
+            || day.is_some_and(|d| d.len() != 2)
+        {
+            return Err(Error::unrecognized(s, expected));
+        }
+        let number = |f: Option<&str>| f.map_or(1, |f| f.parse().unwrap());
+        let precision = match (month, day) {
+            (None, _) => Precision::Year,
+            (Some(_), None) => Precision::Month,
+            (Some(_), Some(_)) => Precision::Day,
+        };
+        let start = NaiveDate::from_ymd_opt(
+            year.parse().unwrap(),
+            number(month),
+            number(day),
+        )
+        .ok_or_else(|| Error::out_of_range(s, "no such date"))?;
+        Ok(PartialDate { start, precision })
+    }
+
+    /// Returns the first day after the period, or `None` if that's past the
+    /// range of supported dates.
+    pub fn next(&self) -> Option<NaiveDate> {
+        match self.precision {
+            Precision::Year => self.start.checked_add_months(Months::new(12)),
+            Precision::Month => self.start.checked_add_months(Months::new(1)),
+            Precision::Day => self.start.checked_add_days(Days::new(1)),
+        }
+    }
+}
+
+/// Partial dates ("2024" or "2024-03"), meaning the start of the period on
+/// the wall clock of a particular zone
+///
+/// Full dates aren't accepted, since other formats already read them as
+/// midnight, but [`PartialDateParser::range`] accepts them too.
+pub struct PartialDateParser {
+    zone: Zone,
+    policy: DstPolicy,
+}
+
+impl PartialDateParser {
+    /// Returns a parser for periods on the wall clock of `zone`, resolving
+    /// a period that starts or ends during a daylight saving transition
+    /// according to `policy`.
+    pub const fn new(zone: Zone, policy: DstPolicy) -> PartialDateParser {
+        PartialDateParser { zone, policy }
+    }
+
+    /// Returns the instants at which the period given by `s` (which may also
+    /// be a full date) starts and ends.
+    pub fn range(&self, s: &str) -> Result<Range<DateTime<Utc>>, Error> {
+        let partial = PartialDate::parse(s)?;
+        let next = partial.next().ok_or_else(|| {
+            Error::out_of_range(s, "the period ends past the supported range")
+        })?;
+        let midnight = |d: NaiveDate| d.and_hms_opt(0, 0, 0).unwrap();
+        let start =
+            self.zone.resolve(s, midnight(partial.start), self.policy)?;
+        let end = self.zone.resolve(s, midnight(next), self.policy)?;
+        Ok(start..end)
+    }
+}
+
+impl TimestampParser for PartialDateParser {
+    fn name(&self) -> &str {
+        "partial-date"
+    }
+
+    fn parse(&self, s: &str) -> Result<DateTime<Utc>, Error> {
+        match PartialDate::parse(s)?.precision {
+            Precision::Day => Err(Error::unrecognized(s, "a year or month")),
+            _ => Ok(self.range(s)?.start),
+        }
+    }
+
+    fn assumptions(&self, s: &str) -> Vec<Assumption> {
+        let Ok(partial) = PartialDate::parse(s) else {
+            return Vec::new();
+        };
+        let mut assumptions =
+            vec![Assumption::Start { period: partial.precision.to_string() }];
+        if matches!(self.zone, Zone::Utc) {
+            assumptions.push(Assumption::Utc);
+        }
+        assumptions
+    }
+}
@@ -0,0 +1,134 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Timestamps as PostgreSQL represents them internally
+//!
+//! PostgreSQL keeps a `timestamp` or `timestamptz` as a signed 64-bit count
+//! of microseconds since 2000-01-01 00:00:00 (in UTC, for `timestamptz`),
+//! and that's what appears in the binary `COPY` format, in the logical
+//! replication protocol (as commit times), and in raw page and WAL dumps.
+//! The largest and smallest 64-bit integers are the special values
+//! `infinity` and `-infinity`, which sort after and before every other time.
+//! Finite times run from 4714-11-24 BC to 294276-12-31 AD, though only those
+//! through the year 262142 can be represented here.
+
+use crate::Error;
+use alloc::format;
+use alloc::string::String;
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use core::fmt;
+
+/// The PostgreSQL epoch, 2000-01-01T00:00:00Z, in microseconds since the Unix
+/// epoch
+const EPOCH_UNIX_MICROS: i64 = 946_684_800_000_000;
+
+/// The smallest and largest finite timestamps PostgreSQL supports, in
+/// microseconds since its epoch: 4714-11-24 00:00:00 BC and 294276-12-31
+/// 23:59:59.999999
+const RANGE_MICROS: (i64, i64) =
+    (-211_813_488_000_000_000, 9_223_371_331_199_999_999);
+
+/// A PostgreSQL timestamp
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Timestamp {
+    /// `-infinity`, before every other time
+    NegativeInfinity,
+    /// a time, to the microsecond
+    Finite(DateTime<Utc>),
+    /// `infinity`, after every other time
+    Infinity,
+}
+
+impl Timestamp {
+    /// Returns the timestamp with the internal representation `micros`, or
+    /// `None` if it's outside PostgreSQL's range (or after the year 262142).
+    pub fn from_micros(micros: i64) -> Option<Timestamp> {
+        match micros {
+            i64::MIN => Some(Timestamp::NegativeInfinity),
+            i64::MAX => Some(Timestamp::Infinity),
+            _ if !(RANGE_MICROS.0..=RANGE_MICROS.1).contains(&micros) => None,
+            _ => DateTime::from_timestamp_micros(
+                micros.checked_add(EPOCH_UNIX_MICROS)?,
+            )
+            .map(Timestamp::Finite),
+        }
+    }
+
+    /// Returns the timestamp for `t`, rounded to the nearest microsecond as
+    /// PostgreSQL rounds input, or `None` if it's outside PostgreSQL's range.
+    pub fn finite(t: DateTime<Utc>) -> Option<Timestamp> {
+        let nanos = i128::from(t.timestamp()) * 1_000_000_000
+            + i128::from(t.timestamp_subsec_nanos());
+        let micros =
+            (nanos + 500).div_euclid(1000) - i128::from(EPOCH_UNIX_MICROS);
+        // The infinities aren't finite.
+        let micros = i64::try_from(micros)
+            .ok()
+            .filter(|&m| m != i64::MIN && m != i64::MAX)?;
+        Timestamp::from_micros(micros)
+    }
+
+    /// Parses "infinity", "-infinity" (or "+infinity"), or a time in any
+    /// form [`crate::parse_timestamp`] accepts.
+    pub fn parse(s: &str) -> Result<Timestamp, Error> {
+        match &s.to_ascii_lowercase()[..] {
+            "infinity" | "+infinity" => Ok(Timestamp::Infinity),
+            "-infinity" => Ok(Timestamp::NegativeInfinity),
+            _ => {
+                Timestamp::finite(crate::parse_timestamp(s)?).ok_or_else(|| {
+                    Error::out_of_range(
+                        s,
+                        "PostgreSQL supports times from 4714 BC through \
+                         294276 AD",
+                    )
+                })
+            }
+        }
+    }
+
+    /// Returns the internal representation: microseconds since
+    /// 2000-01-01T00:00:00Z, or the largest or smallest 64-bit integer for
+    /// `infinity` or `-infinity`
+    pub fn micros(&self) -> i64 {
+        match self {
+            Timestamp::NegativeInfinity => i64::MIN,
+            Timestamp::Infinity => i64::MAX,
+            // `finite` and `from_micros` ensure that this is in range.
+            Timestamp::Finite(t) => t.timestamp_micros() - EPOCH_UNIX_MICROS,
+        }
+    }
+}
+
+/// Formats the timestamp as PostgreSQL prints a `timestamptz` in UTC, like
+/// "2024-03-01 12:00:00.5+00" or "0044-03-15 12:00:00+00 BC"
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let t = match self {
+            Timestamp::NegativeInfinity => return f.write_str("-infinity"),
+            Timestamp::Infinity => return f.write_str("infinity"),
+            Timestamp::Finite(t) => t,
+        };
+        // There's no year 0: 1 BC is followed by 1 AD.
+        let (year, era) = match t.year() {
+            year if year <= 0 => (1 - year, " BC"),
+            year => (year, ""),
+        };
+        let fraction = match t.timestamp_subsec_micros() {
+            0 => String::new(),
+            micros => {
+                let digits = format!(".{micros:06}");
+                String::from(digits.trim_end_matches('0'))
+            }
+        };
+        write!(
+            f,
+            "{year:04}-{:02}-{:02} {:02}:{:02}:{:02}{fraction}+00{era}",
+            t.month(),
+            t.day(),
+            t.hour(),
+            t.minute(),
+            t.second(),
+        )
+    }
+}
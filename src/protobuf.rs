@@ -0,0 +1,131 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Protocol Buffers' well-known `Timestamp` and `Duration` types
+//!
+//! Both are a pair of integers: `seconds` (an `int64`) and `nanos` (an
+//! `int32`).  A `Timestamp` counts from the Unix epoch, with `nanos` from 0
+//! to 999,999,999 added to `seconds`, between 0001-01-01T00:00:00Z and
+//! 9999-12-31T23:59:59.999999999Z, and without leap seconds.  A `Duration`
+//! is at most 10,000 years either way, and its `nanos` have the same sign as
+//! its `seconds`.
+//!
+//! In protobuf's JSON mapping, a `Timestamp` is an RFC 3339 string in UTC,
+//! like "2024-03-01T12:00:00.500Z", and a `Duration` is a number of seconds
+//! with an "s" suffix, like "3.500s".  Either has 0, 3, 6, or 9 digits of
+//! fraction, as few as are needed.
+
+use crate::Error;
+use alloc::format;
+use alloc::string::String;
+use chrono::{DateTime, Datelike, Duration, SecondsFormat, Utc};
+
+/// The largest number of seconds in a `Duration` (10,000 years of 365.25
+/// days)
+const MAX_DURATION_SECONDS: i64 = 315_576_000_000;
+
+/// Returns the `Timestamp` for `seconds` and `nanos`, or `None` if they're
+/// outside the ranges above.
+pub fn timestamp(seconds: i64, nanos: i32) -> Option<DateTime<Utc>> {
+    let nanos = u32::try_from(nanos).ok().filter(|&n| n < 1_000_000_000)?;
+    DateTime::from_timestamp(seconds, nanos)
+        .filter(|t| (1..=9999).contains(&t.year()))
+}
+
+/// Returns the `seconds` and `nanos` of the `Timestamp` for `t`, or `None`
+/// if `t` is outside its range.  A leap second reads as the second after
+/// it, since a `Timestamp` can't express one.
+pub fn timestamp_parts(t: DateTime<Utc>) -> Option<(i64, i32)> {
+    let mut seconds = t.timestamp();
+    let mut nanos = t.timestamp_subsec_nanos();
+    if nanos >= 1_000_000_000 {
+        seconds += 1;
+        nanos -= 1_000_000_000;
+    }
+    // `nanos` is now less than a second, so it fits.
+    let nanos = nanos as i32;
+    timestamp(seconds, nanos).map(|_| (seconds, nanos))
+}
+
+/// Formats `t` as protobuf's JSON mapping does, like
+/// "2024-03-01T12:00:00.500Z", or returns `None` if it's outside the range
+/// of a `Timestamp`.
+pub fn timestamp_json(t: DateTime<Utc>) -> Option<String> {
+    let (seconds, nanos) = timestamp_parts(t)?;
+    let t = timestamp(seconds, nanos)?;
+    Some(t.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+}
+
+/// Returns the `Duration` for `seconds` and `nanos`, or `None` if they're
+/// outside the ranges above or have different signs.
+pub fn duration(seconds: i64, nanos: i32) -> Option<Duration> {
+    let signs_agree =
+        (seconds >= 0 && nanos >= 0) || (seconds <= 0 && nanos <= 0);
+    if !signs_agree
+        || nanos.unsigned_abs() >= 1_000_000_000
+        || seconds.unsigned_abs() > MAX_DURATION_SECONDS as u64
+    {
+        return None;
+    }
+    Some(Duration::seconds(seconds) + Duration::nanoseconds(i64::from(nanos)))
+}
+
+/// Returns the `seconds` and `nanos` of the `Duration` for `d`, or `None` if
+/// `d` is more than 10,000 years.
+pub fn duration_parts(d: Duration) -> Option<(i64, i32)> {
+    let seconds = d.num_seconds();
+    let nanos = d.subsec_nanos();
+    duration(seconds, nanos).map(|_| (seconds, nanos))
+}
+
+/// Formats `d` as protobuf's JSON mapping does, like "3.500s" or "-0.5s",
+/// or returns `None` if `d` is more than 10,000 years.
+pub fn duration_json(d: Duration) -> Option<String> {
+    let (seconds, nanos) = duration_parts(d)?;
+    let sign = if seconds < 0 || nanos < 0 { "-" } else { "" };
+    let (seconds, nanos) = (seconds.unsigned_abs(), nanos.unsigned_abs());
+    Some(match nanos {
+        0 => format!("{sign}{seconds}s"),
+        _ if nanos % 1_000_000 == 0 => {
+            format!("{sign}{seconds}.{:03}s", nanos / 1_000_000)
+        }
+        _ if nanos % 1000 == 0 => {
+            format!("{sign}{seconds}.{:06}s", nanos / 1000)
+        }
+        _ => format!("{sign}{seconds}.{nanos:09}s"),
+    })
+}
+
+/// Parses a `Duration` in protobuf's JSON mapping, like "3.5s" or "-0.5s":
+/// an optional minus sign, the seconds, up to 9 digits of fraction, and "s".
+pub fn parse_duration_json(s: &str) -> Result<Duration, Error> {
+    let expected = "a protobuf Duration (like \"3.5s\")";
+    let mismatch = || Error::unrecognized(s, expected);
+    let number = s.strip_suffix('s').ok_or_else(mismatch)?;
+    let (negative, digits) = match number.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, number),
+    };
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    if whole.is_empty()
+        || fraction.len() > 9
+        || (digits.contains('.') && fraction.is_empty())
+        || !whole.bytes().chain(fraction.bytes()).all(|b| b.is_ascii_digit())
+    {
+        return Err(mismatch());
+    }
+    let too_large = || {
+        Error::out_of_range(s, "a protobuf Duration is at most 10,000 years")
+    };
+    let seconds: i64 = whole.parse().map_err(|_| too_large())?;
+    let mut nanos = 0;
+    let mut scale = 1_000_000_000;
+    for digit in fraction.bytes() {
+        scale /= 10;
+        nanos += i32::from(digit - b'0') * scale;
+    }
+    let (seconds, nanos) =
+        if negative { (-seconds, -nanos) } else { (seconds, nanos) };
+    duration(seconds, nanos).ok_or_else(too_large)
+}
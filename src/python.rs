@@ -0,0 +1,61 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Python bindings, as the extension module `dates_py`
+//!
+//! These are available with the "python" feature, for building with
+//! `maturin`.  Times are exchanged as timezone-aware `datetime.datetime`
+//! objects and deltas as `datetime.timedelta`.
+
+use pyo3::prelude::*;
+
+#[pymodule]
+mod dates_py {
+    use crate::Error;
+    use chrono::{DateTime, Duration, Utc};
+    use pyo3::exceptions::PyValueError;
+    use pyo3::prelude::*;
+    use std::string::{String, ToString};
+
+    /// Converts a parse failure into a `ValueError` with the same message
+    /// that `dates` prints.
+    fn value_error(error: Error) -> PyErr {
+        PyValueError::new_err(error.to_string())
+    }
+
+    /// Parses a timestamp in one of the formats built into `dates` (as
+    /// [`crate::parse_timestamp`] does, which is less than `dates TIME`
+    /// accepts).
+    #[pyfunction]
+    fn parse_timestamp(input: &str) -> PyResult<DateTime<Utc>> {
+        crate::parse_timestamp(input).map_err(value_error)
+    }
+
+    /// Parses a delta the way `dates [+-]DELTA` does.
+    #[pyfunction]
+    fn parse_duration(input: &str) -> PyResult<Duration> {
+        crate::parse_duration(input).map_err(value_error)
+    }
+
+    /// Parses two timestamps and returns the delta from the first to the
+    /// second, as `dates T1 T2` prints it.
+    #[pyfunction]
+    fn diff(t1: &str, t2: &str) -> PyResult<Duration> {
+        let t1 = crate::parse_timestamp(t1).map_err(value_error)?;
+        let t2 = crate::parse_timestamp(t2).map_err(value_error)?;
+        Ok(t2 - t1)
+    }
+
+    /// Formats a time the way `dates` prints it.
+    #[pyfunction]
+    fn format_timestamp(label: &str, time: DateTime<Utc>) -> String {
+        crate::format_timestamp(label, time)
+    }
+
+    /// Formats a delta the way `dates` prints it.
+    #[pyfunction]
+    fn format_duration(label: &str, delta: Duration) -> String {
+        crate::format_duration(label, delta)
+    }
+}
@@ -0,0 +1,79 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Calendar quarters and fiscal years
+//!
+//! A fiscal year may start on the first of any month.  Fiscal years are named
+//! for the calendar year in which they end, so with an October start, FY2025
+//! runs from 2024-10-01 through 2025-09-30.  With the default January start,
+//! fiscal years and quarters coincide with calendar ones.
+
+use crate::Error;
+use chrono::{Datelike, Month, Months, NaiveDate};
+
+/// One quarter (or whole year) of a fiscal calendar
+pub struct Period {
+    /// fiscal year, named for the calendar year in which it ends
+    pub year: i32,
+    /// quarter number (1-4) within the fiscal year
+    pub quarter: u32,
+    /// first day of the period
+    pub start: NaiveDate,
+    /// last day of the period (inclusive)
+    pub end: NaiveDate,
+}
+
+/// Returns the fiscal quarter containing `date` for fiscal years starting on
+/// the first of `fy_start`, or `None` if the quarter extends past the range
+/// of supported dates.
+pub fn quarter_of(date: NaiveDate, fy_start: Month) -> Option<Period> {
+    let year = fiscal_year_of(date, fy_start);
+    let months_in =
+        (date.month0() + 12 - fy_start.number_from_month() + 1) % 12;
+    let quarter = months_in / 3 + 1;
+    let start = fiscal_year_start(year, fy_start)?
+        .checked_add_months(Months::new((quarter - 1) * 3))?;
+    let end = last_of_month(start.checked_add_months(Months::new(2))?);
+    Some(Period { year, quarter, start, end })
+}
+
+/// Returns the whole fiscal year containing `date`, or `None` if it extends
+/// past the range of supported dates.  The `quarter` field of the result is
+/// always 1.
+pub fn year_of(date: NaiveDate, fy_start: Month) -> Option<Period> {
+    let year = fiscal_year_of(date, fy_start);
+    let start = fiscal_year_start(year, fy_start)?;
+    let end = last_of_month(start.checked_add_months(Months::new(11))?);
+    Some(Period { year, quarter: 1, start, end })
+}
+
+fn fiscal_year_of(date: NaiveDate, fy_start: Month) -> i32 {
+    if fy_start == Month::January || date.month() < fy_start.number_from_month()
+    {
+        date.year()
+    } else {
+        date.year() + 1
+    }
+}
+
+fn fiscal_year_start(year: i32, fy_start: Month) -> Option<NaiveDate> {
+    let start_year = if fy_start == Month::January { year } else { year - 1 };
+    NaiveDate::from_ymd_opt(start_year, fy_start.number_from_month(), 1)
+}
+
+/// Returns the last day of the month of `date`.  (This avoids going through
+/// the first of the next month, which may be past the supported range.)
+fn last_of_month(date: NaiveDate) -> NaiveDate {
+    (28..=31).rev().find_map(|day| date.with_day(day)).unwrap()
+}
+
+/// Parses a month given by name ("oct", "October") or number (1-12).
+pub fn parse_month(s: &str) -> Result<Month, Error> {
+    if let Ok(n) = s.parse::<u8>() {
+        return Month::try_from(n).map_err(|_| {
+            Error::out_of_range(s, "months are numbered 1 through 12")
+        });
+    }
+    s.parse::<Month>().map_err(|_| Error::unrecognized(s, "a month"))
+}
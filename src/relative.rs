@@ -0,0 +1,207 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Relative times like "now-7d@d" or "now-7d/d", as Grafana, Splunk, and
+//! Elasticsearch write them
+//!
+//! A relative time starts at "now" (which may be left out) and applies each
+//! of a sequence of terms in turn: an offset like "-7d" or "+1h" (where the
+//! number may be left out to mean 1), or a snap like "@d" or "/d", which
+//! rounds down to the start of the unit as [`Step::floor`] does.  So
+//! "-1d@d" is midnight yesterday, and "@d+8h" is 08:00 today.
+//!
+//! Elasticsearch's date math can also start from a given time, followed by
+//! "||", as in "2024.03.01||+1M/d".  (Elasticsearch itself rounds up instead
+//! of down for some range queries, like `lte`.)
+//!
+//! Units are "s", "m", "h" (or "H"), "d", "w", "mon" (or "M", as Grafana and
+//! Elasticsearch write it), "q", and "y", or their longer names (like "min",
+//! "hours", or "months").  Weeks snapped to with "/" start on Monday, as in
+//! Elasticsearch, and those snapped to with "@" start on Sunday, as in
+//! Splunk, unless a digit after the unit says otherwise: "@w0" is Sunday,
+//! "@w1" Monday, and so on through "@w6" for Saturday.
+
+use crate::Error;
+use crate::delta::{CalendarDelta, Rules};
+use crate::snap::{self, Step, Unit};
+use crate::zone::{DstPolicy, Zone};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use chrono::{DateTime, Duration, Utc, Weekday};
+
+/// One step of a relative time
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Term {
+    /// add this delta
+    Offset(CalendarDelta),
+    /// round down to the start of this unit
+    Snap(Step),
+    /// round down to the start of the week, which starts on this day
+    SnapToWeek(Weekday),
+}
+
+/// A relative time, like "now-7d@d"
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Relative {
+    /// the time from which the terms start, if it was given before "||"
+    /// (rather than being now)
+    pub anchor: Option<String>,
+    pub terms: Vec<Term>,
+}
+
+impl Relative {
+    /// Parses a relative time like "now-7d@d", "-1h@h", "now/d", or
+    /// "2024-03-01||+1M/d".
+    pub fn parse(s: &str) -> Result<Relative, Error> {
+        if s.is_empty() {
+            return Err(Error::unrecognized(s, "a relative time"));
+        }
+        let (anchor, mut pos) = match s.split_once("||") {
+            Some((anchor, _)) => (Some(anchor.to_string()), anchor.len() + 2),
+            None if s.starts_with("now") => (None, 3),
+            None => (None, 0),
+        };
+        let mut terms = Vec::new();
+        while pos < s.len() {
+            let marker = s.as_bytes()[pos];
+            let sign = match marker {
+                b'+' => Some(1),
+                b'-' => Some(-1),
+                b'@' | b'/' => None,
+                _ => {
+                    let next = s[pos..].chars().next().unwrap();
+                    return Err(Error::unrecognized_at(
+                        s,
+                        pos..pos + next.len_utf8(),
+                        "\"+\", \"-\", \"@\", or \"/\"",
+                    ));
+                }
+            };
+            pos += 1;
+            let digits =
+                s[pos..].bytes().take_while(u8::is_ascii_digit).count();
+            let value_span = pos..pos + digits;
+            let letters = s[value_span.end..]
+                .bytes()
+                .take_while(u8::is_ascii_alphabetic)
+                .count();
+            let unit_span = value_span.end..value_span.end + letters;
+            let unit = parse_unit(&s[unit_span.clone()]).ok_or_else(|| {
+                Error::unrecognized_at(s, unit_span.clone(), "a unit of time")
+            })?;
+            pos = unit_span.end;
+
+            let Some(sign) = sign else {
+                if digits > 0 {
+                    return Err(Error::unrecognized_at(
+                        s,
+                        value_span,
+                        "a unit to snap to (with no number)",
+                    ));
+                }
+                if unit != Unit::Weeks || marker == b'/' {
+                    terms.push(Term::Snap(Step { count: 1, unit }));
+                    continue;
+                }
+                let first = match s.as_bytes().get(pos) {
+                    Some(digit @ b'0'..=b'6') => {
+                        pos += 1;
+                        SPLUNK_DAYS[usize::from(digit - b'0')]
+                    }
+                    Some(digit) if digit.is_ascii_digit() => {
+                        return Err(Error::unrecognized_at(
+                            s,
+                            pos..pos + 1,
+                            "a day of the week from 0 (Sunday) to 6",
+                        ));
+                    }
+                    _ => Weekday::Sun,
+                };
+                terms.push(Term::SnapToWeek(first));
+                continue;
+            };
+            let too_large = || {
+                Error::out_of_range_at(
+                    s,
+                    value_span.clone(),
+                    "too large for a delta",
+                )
+            };
+            let n: i64 = match &s[value_span.clone()] {
+                "" => 1,
+                n => n.parse().map_err(|_| too_large())?,
+            };
+            terms.push(Term::Offset(
+                offset(sign * n, unit).ok_or_else(too_large)?,
+            ));
+        }
+        Ok(Relative { anchor, terms })
+    }
+
+    /// Applies the terms to `start`, which is now or the anchor, adding
+    /// offsets and making snaps on the wall clock of `zone`.  Returns `None`
+    /// if the result is past the range of supported times, or if `policy`
+    /// says to fail on a wall-clock time that an offset lands on.
+    pub fn apply(
+        &self,
+        start: DateTime<Utc>,
+        zone: Zone,
+        rules: Rules,
+        policy: DstPolicy,
+    ) -> Option<DateTime<Utc>> {
+        self.terms.iter().try_fold(start, |t, term| match term {
+            Term::Offset(delta) => delta.add_to_with(t, zone, rules, policy),
+            Term::Snap(step) => step.floor(t, zone),
+            Term::SnapToWeek(first) => snap::floor_to_week(t, zone, *first),
+        })
+    }
+}
+
+/// The days of the week that Splunk numbers 0 through 6 in snaps like "@w1"
+const SPLUNK_DAYS: [Weekday; 7] = [
+    Weekday::Sun,
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+];
+
+/// Parses the name of a unit, like "h", "hr", or "hours".
+fn parse_unit(s: &str) -> Option<Unit> {
+    Some(match s {
+        "s" | "sec" | "secs" | "second" | "seconds" => Unit::Seconds,
+        "m" | "min" | "mins" | "minute" | "minutes" => Unit::Minutes,
+        "h" | "H" | "hr" | "hrs" | "hour" | "hours" => Unit::Hours,
+        "d" | "day" | "days" => Unit::Days,
+        "w" | "week" | "weeks" => Unit::Weeks,
+        "M" | "mon" | "month" | "months" => Unit::Months,
+        "q" | "qtr" | "qtrs" | "quarter" | "quarters" => Unit::Quarters,
+        "y" | "yr" | "yrs" | "year" | "years" => Unit::Years,
+        _ => return None,
+    })
+}
+
+/// Returns a delta of `n` `unit`s, or `None` if it's too large.
+fn offset(n: i64, unit: Unit) -> Option<CalendarDelta> {
+    let seconds = match unit {
+        Unit::Millis => return Duration::try_milliseconds(n).map(Into::into),
+        Unit::Seconds => 1,
+        Unit::Minutes => 60,
+        Unit::Hours => 3600,
+        Unit::Days => 86400,
+        Unit::Weeks => 7 * 86400,
+        Unit::Months | Unit::Quarters | Unit::Years => {
+            let per_unit = match unit {
+                Unit::Quarters => 3,
+                Unit::Years => 12,
+                _ => 1,
+            };
+            let months = i32::try_from(n).ok()?.checked_mul(per_unit)?;
+            return Some(CalendarDelta::months(months));
+        }
+    };
+    Duration::try_seconds(n.checked_mul(seconds)?).map(Into::into)
+}
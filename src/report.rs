@@ -0,0 +1,337 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Results of interpreting timestamps and deltas
+//!
+//! These are the values that `dates` prints.  The text output is produced by
+//! their `Display` impls, and `dates --json` serializes them directly, so
+//! both forms always agree.
+
+use crate::format::{decimal, layout, mean_time};
+use crate::parser::Provenance;
+use crate::zone::Zone;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use chrono::{
+    DateTime, Duration, NaiveDate, NaiveDateTime, SecondsFormat, Utc,
+};
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+const MICROS: SecondsFormat = SecondsFormat::Micros;
+
+/// The version of the serialized form of [`Report`]
+///
+/// This changes whenever a change to these types could break a consumer of
+/// the serialized form, like removing or renaming a field or changing its
+/// meaning.  Adding fields or new kinds of [`Entry`] does not change it.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The JSON Schema for the serialized form of [`Report`], at
+/// [`SCHEMA_VERSION`]
+pub const JSON_SCHEMA: &str = include_str!("../schema/v1.json");
+
+/// A complete set of output from `dates`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Report {
+    /// always [`SCHEMA_VERSION`] for reports created by this version
+    pub schema_version: u32,
+    pub entries: Vec<Entry>,
+}
+
+impl Report {
+    pub fn new(entries: Vec<Entry>) -> Report {
+        Report { schema_version: SCHEMA_VERSION, entries }
+    }
+}
+
+/// A single instant, along with its renderings in several zones
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Timestamp {
+    /// the input from which this was parsed, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input: Option<String>,
+    /// microseconds since the Unix epoch
+    pub unix_micros: i64,
+    /// the instant as shown in each requested zone
+    pub renderings: Vec<Rendering>,
+    /// how the input was parsed, if requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+    /// what clocks on other timescales (like TAI) read at the instant, if
+    /// requested
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub readings: Vec<Reading>,
+}
+
+/// What a clock on some timescale reads at an instant
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reading {
+    /// the timescale, like "TAI"
+    pub scale: String,
+    /// the clock's date and time, like "2017-01-01T00:00:37.000000"
+    pub time: String,
+}
+
+/// An instant as shown in one time zone
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rendering {
+    /// the zone, in the form accepted by [`Zone::parse()`]
+    pub zone: String,
+    /// the instant as an RFC 3339 timestamp in that zone
+    pub rfc3339: String,
+    /// the zone's exact offset, like "-04:56:02", if the instant is in its
+    /// local mean time (see [`Zone::local_mean_time`]), whose offset RFC 3339
+    /// can't express
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub local_mean_time: Option<String>,
+}
+
+impl Timestamp {
+    /// Describes `dt` as rendered in each of `zones`.
+    pub fn new(dt: DateTime<Utc>, zones: &[Zone]) -> Timestamp {
+        Timestamp {
+            input: None,
+            unix_micros: dt.timestamp_micros(),
+            renderings: zones
+                .iter()
+                .map(|z| Rendering {
+                    zone: z.spec(),
+                    rfc3339: z.rfc3339(dt),
+                    local_mean_time: z
+                        .local_mean_time(dt)
+                        .map(|offset| offset.to_string()),
+                })
+                .collect(),
+            provenance: None,
+            readings: Vec::new(),
+        }
+    }
+
+    /// Describes `dt` the way `dates` prints it by default: in the local
+    /// zone and in UTC.
+    #[cfg(feature = "std")]
+    pub fn local_and_utc(dt: DateTime<Utc>) -> Timestamp {
+        Timestamp::new(dt, &[Zone::Local, Zone::Utc])
+    }
+
+    /// Records the input from which this timestamp was parsed.
+    pub fn with_input(mut self, input: &str) -> Timestamp {
+        self.input = Some(input.to_string());
+        self
+    }
+
+    /// Records how this timestamp was parsed.
+    pub fn with_provenance(mut self, provenance: Provenance) -> Timestamp {
+        self.provenance = Some(provenance);
+        self
+    }
+
+    /// Records what a clock on `scale` reads at this instant.
+    pub fn with_reading(
+        mut self,
+        scale: &str,
+        time: NaiveDateTime,
+    ) -> Timestamp {
+        self.readings.push(Reading {
+            scale: scale.to_string(),
+            time: time.format("%Y-%m-%dT%H:%M:%S%.6f").to_string(),
+        });
+        self
+    }
+
+    /// Returns the Unix time in (fractional) seconds.
+    pub fn unix_seconds(&self) -> f64 {
+        self.unix_micros as f64 / 1_000_000.0
+    }
+}
+
+/// A delta broken down into days, hours, minutes, and seconds
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Breakdown {
+    /// the total length of the delta in microseconds (negative for deltas
+    /// that go backwards in time)
+    pub total_micros: i64,
+    /// whole days in the magnitude of the delta
+    pub days: i64,
+    /// whole hours remaining after `days` (0-23)
+    pub hours: u8,
+    /// whole minutes remaining after `hours` (0-59)
+    pub minutes: u8,
+    /// whole seconds remaining after `minutes` (0-59)
+    pub seconds: u8,
+    /// microseconds remaining after `seconds`
+    pub micros: u32,
+}
+
+impl Breakdown {
+    /// Breaks down `delta`.  For deltas too large to express in
+    /// microseconds (hundreds of thousands of years), `total_micros` is
+    /// saturated at the smallest or largest `i64`, but the other fields are
+    /// still exact.
+    pub fn new(delta: Duration) -> Breakdown {
+        let total_micros =
+            delta.num_microseconds().unwrap_or(if delta < Duration::zero() {
+                i64::MIN
+            } else {
+                i64::MAX
+            });
+        let secs = delta.num_seconds().unsigned_abs();
+        Breakdown {
+            total_micros,
+            days: (secs / 86400) as i64,
+            hours: ((secs % 86400) / 3600) as u8,
+            minutes: ((secs % 3600) / 60) as u8,
+            seconds: (secs % 60) as u8,
+            micros: delta.subsec_nanos().unsigned_abs() / 1000,
+        }
+    }
+
+    /// Returns the total length in microseconds, computed from the other
+    /// fields so that it's exact even when `total_micros` is saturated.
+    fn exact_micros(&self) -> i128 {
+        let secs = ((i128::from(self.days) * 24 + i128::from(self.hours)) * 60
+            + i128::from(self.minutes))
+            * 60
+            + i128::from(self.seconds);
+        let micros = secs * 1_000_000 + i128::from(self.micros);
+        if self.total_micros < 0 { -micros } else { micros }
+    }
+
+    /// Returns the delta that was broken down, to the microsecond.
+    pub fn duration(&self) -> Duration {
+        let micros = self.exact_micros();
+        let secs = (micros / 1_000_000) as i64;
+        Duration::seconds(secs)
+            + Duration::microseconds((micros % 1_000_000) as i64)
+    }
+
+    /// Returns the total length in (fractional) seconds.
+    pub fn total_seconds(&self) -> f64 {
+        self.exact_micros() as f64 / 1_000_000.0
+    }
+}
+
+impl fmt::Display for Breakdown {
+    /// Formats the breakdown with a fixed width, like "-0d 21h 01m
+    /// 23.456000s".  Non-negative values have a leading space instead of a
+    /// sign.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.total_micros < 0 { "-" } else { " " };
+        write!(
+            f,
+            "{}{}d {:02}h {:02}m {:02}.{:06}s",
+            sign,
+            self.days,
+            self.hours,
+            self.minutes,
+            self.seconds,
+            self.micros
+        )
+    }
+}
+
+/// One labeled item of output from `dates`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Entry {
+    Time {
+        label: String,
+        #[serde(flatten)]
+        time: Timestamp,
+    },
+    Delta {
+        label: String,
+        #[serde(flatten)]
+        delta: Breakdown,
+    },
+    /// how many times one delta goes into another
+    Ratio { label: String, ratio: f64 },
+    /// a count of calendar days (midnights crossed) between two dates
+    CalendarDays {
+        label: String,
+        days: i64,
+        start: NaiveDate,
+        end: NaiveDate,
+        /// the zone in which the dates were determined, in the form
+        /// accepted by [`Zone::parse()`]
+        zone: String,
+    },
+}
+
+impl Entry {
+    /// Describes `dt` the way `dates` prints a time.
+    #[cfg(feature = "std")]
+    pub fn time(label: &str, dt: DateTime<Utc>) -> Entry {
+        Entry::Time {
+            label: label.to_string(),
+            time: Timestamp::local_and_utc(dt),
+        }
+    }
+
+    /// Describes `delta` the way `dates` prints a delta.
+    pub fn delta(label: &str, delta: Duration) -> Entry {
+        Entry::Delta { label: label.to_string(), delta: Breakdown::new(delta) }
+    }
+}
+
+impl fmt::Display for Entry {
+    /// Formats the entry as `dates` prints it.  A time is shown as its Unix
+    /// timestamp followed by each of its renderings and readings on its own
+    /// line, and then its provenance, if any.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Entry::Time { label, time } => {
+                let nanos = i128::from(time.unix_micros) * 1000;
+                let renderings: Vec<_> = time
+                    .renderings
+                    .iter()
+                    .map(|r| match &r.local_mean_time {
+                        Some(offset) => mean_time(&r.rfc3339, offset),
+                        None => r.rfc3339.clone(),
+                    })
+                    .chain(
+                        time.readings
+                            .iter()
+                            .map(|r| format!("{} {}", r.time, r.scale)),
+                    )
+                    .collect();
+                layout(
+                    f,
+                    label,
+                    Some((decimal(nanos, MICROS), "s")),
+                    &renderings,
+                )?;
+                if let Some(provenance) = &time.provenance {
+                    let lines: Vec<_> = provenance
+                        .to_string()
+                        .lines()
+                        .map(String::from)
+                        .collect();
+                    writeln!(f)?;
+                    layout(f, "parsed", None, &lines)?;
+                }
+                Ok(())
+            }
+            Entry::Delta { label, delta } => {
+                let nanos = delta.exact_micros() * 1000;
+                let number = Some((decimal(nanos, MICROS), "s"));
+                layout(f, label, number, &[delta.to_string()])
+            }
+            Entry::Ratio { label, ratio } => {
+                layout(f, label, Some((format!("{ratio:.6}"), "x")), &[])
+            }
+            Entry::CalendarDays { label, days, start, end, zone } => {
+                let number = Some((days.to_string(), "d"));
+                layout(
+                    f,
+                    label,
+                    number,
+                    &[format!("{start} to {end} ({zone})")],
+                )
+            }
+        }
+    }
+}
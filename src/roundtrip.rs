@@ -0,0 +1,189 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Checking that formatted times parse back to the same time
+//!
+//! Every way that `dates` writes a time should be something it can read back
+//! without losing more than the written precision.  [`roundtrip_check`]
+//! verifies that for one format and one time, and [`verify_timestamp`]
+//! verifies that everything `dates` shows for a time reads back as exactly
+//! that time, as `dates --verify` does.
+
+use crate::format::decimal;
+use crate::leap::{LeapSeconds, Scale};
+use crate::report::Timestamp;
+use crate::zone::Zone;
+use crate::{Error, parse_timestamp};
+use alloc::string::{String, ToString};
+use chrono::{
+    DateTime, Duration, NaiveDateTime, SecondsFormat, SubsecRound, Utc,
+};
+use core::fmt;
+
+/// A way of writing a time
+#[derive(Clone, Copy, Debug)]
+pub enum TimeFormat {
+    /// RFC 3339 in a particular zone, with the given fractional seconds
+    Rfc3339(Zone, SecondsFormat),
+    /// decimal seconds since the Unix epoch, as in the numeric column of
+    /// `dates` output
+    UnixSeconds(SecondsFormat),
+    /// integer milliseconds since the Unix epoch
+    UnixMillis,
+    /// the UTC date, like "2018-02-08"
+    Date,
+    /// the UTC ISO week date, like "2018-W06-4"
+    WeekDate,
+}
+
+impl TimeFormat {
+    /// Writes `t` in this format.
+    pub fn format(&self, t: DateTime<Utc>) -> String {
+        match self {
+            TimeFormat::Rfc3339(zone, precision) => zone.format(t, *precision),
+            TimeFormat::UnixSeconds(precision) => {
+                let nanos = i128::from(t.timestamp()) * 1_000_000_000
+                    + i128::from(t.timestamp_subsec_nanos());
+                decimal(nanos, *precision)
+            }
+            TimeFormat::UnixMillis => t.timestamp_millis().to_string(),
+            TimeFormat::Date => t.format("%Y-%m-%d").to_string(),
+            TimeFormat::WeekDate => t.format("%G-W%V-%u").to_string(),
+        }
+    }
+
+    /// Returns the smallest difference between two times that this format
+    /// can express.
+    pub fn resolution(&self) -> Duration {
+        match self {
+            TimeFormat::Rfc3339(_, precision)
+            | TimeFormat::UnixSeconds(precision) => match precision {
+                SecondsFormat::Secs => Duration::seconds(1),
+                SecondsFormat::Millis => Duration::milliseconds(1),
+                SecondsFormat::Micros => Duration::microseconds(1),
+                _ => Duration::nanoseconds(1),
+            },
+            TimeFormat::UnixMillis => Duration::milliseconds(1),
+            TimeFormat::Date | TimeFormat::WeekDate => Duration::days(1),
+        }
+    }
+}
+
+/// Why a time didn't survive being formatted and parsed again
+#[derive(Clone, Debug, PartialEq)]
+pub struct RoundTripFailure {
+    /// the time that was formatted
+    pub value: DateTime<Utc>,
+    /// the formatted text
+    pub text: String,
+    /// the result of parsing `text`
+    pub parsed: Result<DateTime<Utc>, Error>,
+}
+
+impl fmt::Display for RoundTripFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.parsed {
+            Ok(parsed) => write!(
+                f,
+                "{} was written as {:?}, which parsed as {}",
+                self.value, self.text, parsed
+            ),
+            Err(error) => write!(
+                f,
+                "{} was written as {:?}, which failed to parse: {}",
+                self.value, self.text, error
+            ),
+        }
+    }
+}
+
+impl core::error::Error for RoundTripFailure {}
+
+/// Verifies that `value`, written in `format`, parses back to `value` (to
+/// within the resolution of `format`).
+pub fn roundtrip_check(
+    format: TimeFormat,
+    value: DateTime<Utc>,
+) -> Result<(), RoundTripFailure> {
+    let text = format.format(value);
+    let parsed = parse_timestamp(&text);
+    match &parsed {
+        Ok(t) if (*t - value).abs() < format.resolution() => Ok(()),
+        _ => Err(RoundTripFailure { value, text, parsed }),
+    }
+}
+
+/// Verifies that each way `timestamp` shows `value` parses back to `value`,
+/// to the microsecond that `timestamp` shows it to: its RFC 3339 renderings,
+/// its readings on other timescales (which are converted back with the
+/// built-in leap second table, to within a microsecond), and its Unix time.
+/// This fails if a rendering has the wrong offset for its zone, for example,
+/// or if a reading can't be converted back exactly.
+///
+/// Unix time doesn't count leap seconds, so it's compared as Unix time, in
+/// which a leap second is the second after it.  Renderings in local mean
+/// time are skipped, since their exact offset is given separately from the
+/// RFC 3339 text (see [`Zone::local_mean_time`]).
+pub fn verify_timestamp(
+    timestamp: &Timestamp,
+    value: DateTime<Utc>,
+) -> Result<(), RoundTripFailure> {
+    let failure = |text: &str, parsed| RoundTripFailure {
+        value,
+        text: text.to_string(),
+        parsed,
+    };
+    let shown = value.trunc_subsecs(6);
+
+    for rendering in &timestamp.renderings {
+        if rendering.local_mean_time.is_some() {
+            continue;
+        }
+        let parsed = parse_timestamp(&rendering.rfc3339);
+        if parsed.as_ref() != Ok(&shown) {
+            return Err(failure(&rendering.rfc3339, parsed));
+        }
+    }
+
+    let table = LeapSeconds::builtin();
+    for reading in &timestamp.readings {
+        let scale = [Scale::Utc, Scale::Tai, Scale::Gps, Scale::Smear]
+            .into_iter()
+            .find(|s| s.name() == reading.scale)
+            .ok_or_else(|| {
+                let error = Error::unrecognized(&reading.scale, "a timescale");
+                failure(&reading.time, Err(error))
+            })?;
+        let parsed = NaiveDateTime::parse_from_str(
+            &reading.time,
+            "%Y-%m-%dT%H:%M:%S%.f",
+        )
+        .map_err(|_| Error::unrecognized(&reading.time, "a reading"))
+        .and_then(|t| scale.to_utc(&table, t));
+        // A smeared clock doesn't tick at the same rate as UTC, so its
+        // reading may be shown to a different fraction of a microsecond.
+        match &parsed {
+            Ok(t) if (*t - shown).abs() < Duration::microseconds(1) => (),
+            _ => return Err(failure(&reading.time, parsed)),
+        }
+    }
+
+    let unix = decimal(
+        i128::from(timestamp.unix_micros) * 1000,
+        SecondsFormat::Micros,
+    );
+    let parsed = parse_timestamp(&unix);
+    match &parsed {
+        Ok(t) if (unix_nanos(*t) - unix_nanos(shown)).abs() < 1_000 => (),
+        _ => return Err(failure(&unix, parsed)),
+    }
+    Ok(())
+}
+
+/// Returns `t` as nanoseconds of Unix time, which counts a leap second as
+/// the second after it.
+fn unix_nanos(t: DateTime<Utc>) -> i128 {
+    i128::from(t.timestamp()) * 1_000_000_000
+        + i128::from(t.timestamp_subsec_nanos())
+}
@@ -0,0 +1,269 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Rounding times to unit boundaries, like the start of the hour
+//!
+//! Boundaries are on the wall clock of a particular zone, so rounding down
+//! to a day means midnight in that zone.  Steps of several units count from
+//! a fixed start: "15m" snaps to :00, :15, :30, and :45 past the hour, "6h"
+//! to midnight, 06:00, 12:00, and 18:00, "3mo" to the start of a calendar
+//! quarter, and "10y" to the start of a decade.  A step that doesn't divide
+//! its period evenly (like "7h") starts over at the next one (midnight).
+//! Weeks start on Monday.
+//!
+//! If the wall clock reads a boundary twice (when clocks are set back), the
+//! occurrence nearer the time being rounded counts.  If it never reads it
+//! (when clocks are set forward), the boundary is the instant at which the
+//! clock skipped past it.
+
+use crate::Error;
+use crate::zone::Zone;
+use chrono::{
+    DateTime, Datelike, Days, Duration, MappedLocalTime, Months, NaiveDate,
+    NaiveDateTime, NaiveTime, Timelike, Utc, Weekday,
+};
+
+/// The unit of a [`Step`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Unit {
+    Millis,
+    Seconds,
+    Minutes,
+    Hours,
+    Days,
+    Weeks,
+    Months,
+    Quarters,
+    Years,
+}
+
+/// How to round a time to a boundary
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// the boundary at or before the time
+    Floor,
+    /// the boundary at or after the time
+    Ceil,
+    /// whichever of those is nearer, or the later one if they're equally
+    /// near
+    Round,
+}
+
+/// The spacing of the boundaries to round to, like "15m" or "1d"
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Step {
+    pub count: u32,
+    pub unit: Unit,
+}
+
+impl Step {
+    /// Parses a step like "15m": a positive whole number (which may be left
+    /// out to mean 1) followed by a unit: "ms", "s", "m", "h", "d", "w",
+    /// "mo", "q", or "y".
+    pub fn parse(s: &str) -> Result<Step, Error> {
+        let digits = s.bytes().take_while(u8::is_ascii_digit).count();
+        let unit = match &s[digits..] {
+            "ms" => Unit::Millis,
+            "s" => Unit::Seconds,
+            "m" => Unit::Minutes,
+            "h" => Unit::Hours,
+            "d" => Unit::Days,
+            "w" => Unit::Weeks,
+            "mo" => Unit::Months,
+            "q" => Unit::Quarters,
+            "y" => Unit::Years,
+            _ => {
+                return Err(Error::unrecognized_at(
+                    s,
+                    digits..s.len(),
+                    "a unit (ms, s, m, h, d, w, mo, q, or y)",
+                ));
+            }
+        };
+        let count = match &s[..digits] {
+            "" => 1,
+            count => count.parse().map_err(|_| {
+                Error::out_of_range_at(s, 0..digits, "too large for a step")
+            })?,
+        };
+        if count == 0 {
+            return Err(Error::out_of_range_at(
+                s,
+                0..digits,
+                "a step must be at least 1",
+            ));
+        }
+        Ok(Step { count, unit })
+    }
+
+    /// Rounds `t` to a boundary on the wall clock of `zone` according to
+    /// `mode`, or returns `None` if the boundary is past the range of
+    /// supported times.
+    pub fn snap(
+        &self,
+        mode: Mode,
+        t: DateTime<Utc>,
+        zone: Zone,
+    ) -> Option<DateTime<Utc>> {
+        match mode {
+            Mode::Floor => self.floor(t, zone),
+            Mode::Ceil => self.ceil(t, zone),
+            Mode::Round => {
+                let (floor, ceil) = (self.floor(t, zone)?, self.ceil(t, zone)?);
+                Some(if t - floor < ceil - t { floor } else { ceil })
+            }
+        }
+    }
+
+    /// Returns the last boundary at or before `t` on the wall clock of
+    /// `zone`.
+    pub fn floor(&self, t: DateTime<Utc>, zone: Zone) -> Option<DateTime<Utc>> {
+        let wall = self.floor_wall(zone.wall_clock(t))?;
+        boundary_before(t, zone, wall)
+    }
+
+    /// Returns the first boundary at or after `t` on the wall clock of
+    /// `zone`.
+    pub fn ceil(&self, t: DateTime<Utc>, zone: Zone) -> Option<DateTime<Utc>> {
+        let floor = self.floor(t, zone)?;
+        if floor == t {
+            return Some(t);
+        }
+        // When clocks are set back, the boundary before `t` on the wall
+        // clock may come again after it.
+        let floor_wall = self.floor_wall(zone.wall_clock(t))?;
+        if let MappedLocalTime::Ambiguous(_, b2) = zone.instant_of(floor_wall)
+            && b2 > t
+        {
+            return Some(b2);
+        }
+        let wall = self.next_wall(floor_wall)?;
+        match zone.instant_of(wall) {
+            MappedLocalTime::Single(b) => Some(b),
+            MappedLocalTime::Ambiguous(b1, b2) => {
+                Some(if b1 >= t { b1 } else { b2 })
+            }
+            MappedLocalTime::None => zone.shift(wall),
+        }
+    }
+
+    /// Returns the last boundary at or before wall-clock time `wall`.
+    fn floor_wall(&self, wall: NaiveDateTime) -> Option<NaiveDateTime> {
+        let count = i64::from(self.count);
+        let date = wall.date();
+        let midnight = |d: NaiveDate| d.and_time(NaiveTime::MIN);
+        let within_day = |unit_nanos: i64| {
+            let time = wall.time();
+            let nanos = i64::from(time.num_seconds_from_midnight())
+                * 1_000_000_000
+                + i64::from(time.nanosecond().min(999_999_999));
+            let step = unit_nanos.saturating_mul(count);
+            midnight(date).checked_add_signed(Duration::nanoseconds(
+                nanos - nanos.rem_euclid(step),
+            ))
+        };
+        match self.unit {
+            Unit::Millis => within_day(1_000_000),
+            Unit::Seconds => within_day(1_000_000_000),
+            Unit::Minutes => within_day(60_000_000_000),
+            Unit::Hours => within_day(3_600_000_000_000),
+            Unit::Days | Unit::Weeks => {
+                // Count days from a Monday, so that weeks start on one.
+                let days = (date - monday()).num_days();
+                let per_step = match self.unit {
+                    Unit::Weeks => 7 * count,
+                    _ => count,
+                };
+                let back = days.rem_euclid(per_step);
+                Some(midnight(date.checked_sub_signed(Duration::days(back))?))
+            }
+            Unit::Months | Unit::Quarters | Unit::Years => {
+                let months =
+                    i64::from(date.year()) * 12 + i64::from(date.month0());
+                let back = months.rem_euclid(self.months());
+                let first = date.with_day(1)?;
+                let first = first.checked_sub_months(Months::new(
+                    u32::try_from(back).ok()?,
+                ))?;
+                Some(midnight(first))
+            }
+        }
+    }
+
+    /// Returns the boundary after `boundary`, which is one.
+    fn next_wall(&self, boundary: NaiveDateTime) -> Option<NaiveDateTime> {
+        let count = i64::from(self.count);
+        let within_day = |unit: Duration| {
+            let next = boundary.checked_add_signed(
+                unit.checked_mul(i32::try_from(self.count).ok()?)?,
+            )?;
+            let midnight = boundary.date().succ_opt()?.and_time(NaiveTime::MIN);
+            Some(next.min(midnight))
+        };
+        match self.unit {
+            Unit::Millis => within_day(Duration::milliseconds(1)),
+            Unit::Seconds => within_day(Duration::seconds(1)),
+            Unit::Minutes => within_day(Duration::minutes(1)),
+            Unit::Hours => within_day(Duration::hours(1)),
+            Unit::Days => {
+                boundary.checked_add_signed(Duration::try_days(count)?)
+            }
+            Unit::Weeks => {
+                boundary.checked_add_signed(Duration::try_days(7 * count)?)
+            }
+            Unit::Months | Unit::Quarters | Unit::Years => boundary
+                .checked_add_months(Months::new(
+                    u32::try_from(self.months()).ok()?,
+                )),
+        }
+    }
+
+    /// Returns the length of this step in months, for steps of months,
+    /// quarters, or years.
+    fn months(&self) -> i64 {
+        let per_unit = match self.unit {
+            Unit::Quarters => 3,
+            Unit::Years => 12,
+            _ => 1,
+        };
+        i64::from(self.count) * per_unit
+    }
+}
+
+/// Returns the start of the week that `t` is in on the wall clock of `zone`,
+/// for weeks that start on `first` (rather than on Monday, as [`Step`]'s
+/// do).
+pub fn floor_to_week(
+    t: DateTime<Utc>,
+    zone: Zone,
+    first: Weekday,
+) -> Option<DateTime<Utc>> {
+    let date = zone.wall_clock(t).date();
+    let back = (7 + date.weekday().num_days_from_monday()
+        - first.num_days_from_monday())
+        % 7;
+    let start = date.checked_sub_days(Days::new(u64::from(back)))?;
+    boundary_before(t, zone, start.and_time(NaiveTime::MIN))
+}
+
+/// Returns the instant at which the wall clock in `zone` read `wall`, a
+/// boundary at or before `t` there.
+fn boundary_before(
+    t: DateTime<Utc>,
+    zone: Zone,
+    wall: NaiveDateTime,
+) -> Option<DateTime<Utc>> {
+    match zone.instant_of(wall) {
+        MappedLocalTime::Single(b) => Some(b),
+        MappedLocalTime::Ambiguous(b1, b2) => {
+            Some(if b2 <= t { b2 } else { b1 })
+        }
+        MappedLocalTime::None => zone.shift(wall),
+    }
+}
+
+/// Returns a Monday from which to count days, so that weeks start on one.
+fn monday() -> NaiveDate {
+    NaiveDate::from_ymd_opt(1970, 1, 5).unwrap()
+}
@@ -0,0 +1,83 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Times embedded in Snowflake IDs
+//!
+//! A Snowflake ID, as Twitter introduced them, is a 64-bit integer whose
+//! top 42 bits (the first of which is always 0) count milliseconds since an
+//! epoch, followed by 10 bits identifying the worker that generated it and
+//! 12 bits of sequence number, which count IDs generated by that worker in
+//! the same millisecond.  Discord and many others use the same layout with
+//! an epoch of their own, so the epoch is an [`Epoch`] given separately.
+
+use crate::Error;
+use chrono::{DateTime, Duration, Utc};
+
+/// The time from which a kind of Snowflake ID counts
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Epoch {
+    /// milliseconds since the Unix epoch
+    pub unix_millis: i64,
+}
+
+impl Epoch {
+    /// Twitter's epoch, 2010-11-04T01:42:54.657Z
+    pub const TWITTER: Epoch = Epoch { unix_millis: 1_288_834_974_657 };
+    /// Discord's epoch, 2015-01-01T00:00:00Z
+    pub const DISCORD: Epoch = Epoch { unix_millis: 1_420_070_400_000 };
+
+    /// Parses "twitter", "discord", or a number of milliseconds since the
+    /// Unix epoch.
+    pub fn parse(s: &str) -> Result<Epoch, Error> {
+        match s {
+            "twitter" => Ok(Epoch::TWITTER),
+            "discord" => Ok(Epoch::DISCORD),
+            _ => s.parse().map(|unix_millis| Epoch { unix_millis }).map_err(
+                |_| {
+                    Error::unrecognized(
+                        s,
+                        "an epoch (twitter, discord, or milliseconds since \
+                         1970)",
+                    )
+                },
+            ),
+        }
+    }
+}
+
+/// A Snowflake ID
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Snowflake(pub u64);
+
+impl Snowflake {
+    /// Parses a Snowflake ID written as a decimal integer.
+    pub fn parse(s: &str) -> Result<Snowflake, Error> {
+        if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(Error::unrecognized(s, "a Snowflake ID (an integer)"));
+        }
+        s.parse().map(Snowflake).map_err(|_| {
+            Error::out_of_range(s, "too large for a Snowflake ID (64 bits)")
+        })
+    }
+
+    /// Returns the time at which this ID was generated, counting from
+    /// `epoch`, or `None` if that's past the range of supported times.
+    pub fn timestamp(&self, epoch: Epoch) -> Option<DateTime<Utc>> {
+        let millis = Duration::milliseconds((self.0 >> 22) as i64);
+        DateTime::from_timestamp_millis(epoch.unix_millis)?
+            .checked_add_signed(millis)
+    }
+
+    /// Returns the 10-bit ID of the worker that generated this ID.  (Twitter
+    /// splits this into a 5-bit datacenter and a 5-bit worker, and Discord
+    /// into a 5-bit worker and a 5-bit process.)
+    pub fn worker(&self) -> u16 {
+        (self.0 >> 12) as u16 & 0x3ff
+    }
+
+    /// Returns the 12-bit sequence number.
+    pub fn sequence(&self) -> u16 {
+        self.0 as u16 & 0xfff
+    }
+}
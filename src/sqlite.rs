@@ -0,0 +1,83 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Times as SQLite's date and time functions represent them
+//!
+//! SQLite has no date type.  Its functions read and write times as text
+//! like "2024-03-01 12:00:00", which compares correctly as text only when
+//! every value is in UTC and has the same number of fractional digits; as a
+//! Julian day number (`julianday()`), which counts days since noon UTC on
+//! November 24, 4714 BC in the proleptic Gregorian calendar; or as seconds
+//! since the Unix epoch (`unixepoch()`).  Internally, SQLite keeps a time
+//! as a whole number of milliseconds of Julian day, so that's the precision
+//! of all of these, and it supports years 0000 through 9999 only.
+
+use alloc::format;
+use alloc::string::String;
+use chrono::{DateTime, Utc};
+
+/// The Julian day number of the Unix epoch, in milliseconds (as SQLite
+/// keeps it)
+const UNIX_EPOCH_JD_MILLIS: i64 = 210_866_760_000_000;
+
+/// The smallest and largest times SQLite supports, in milliseconds of Julian
+/// day: 0000-01-01T00:00:00.000 and 9999-12-31T23:59:59.999
+const RANGE_JD_MILLIS: (i64, i64) = (148_699_540_800_000, 464_269_060_799_999);
+
+/// Returns the number of milliseconds of Julian day for `t`, truncated as
+/// SQLite does, or `None` if `t` is outside SQLite's range.
+fn jd_millis(t: DateTime<Utc>) -> Option<i64> {
+    let ms = t.timestamp_millis().checked_add(UNIX_EPOCH_JD_MILLIS)?;
+    (RANGE_JD_MILLIS.0..=RANGE_JD_MILLIS.1).contains(&ms).then_some(ms)
+}
+
+/// Returns `t` truncated to the millisecond, as SQLite would store it, or
+/// `None` if `t` is outside SQLite's range.
+pub fn truncate(t: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let ms = jd_millis(t)?;
+    DateTime::from_timestamp_millis(ms - UNIX_EPOCH_JD_MILLIS)
+}
+
+/// Returns what `julianday()` returns for `t`, or `None` if `t` is outside
+/// SQLite's range.
+pub fn julian_day(t: DateTime<Utc>) -> Option<f64> {
+    Some(jd_millis(t)? as f64 / 86_400_000.0)
+}
+
+/// Returns the time for a Julian day number, as SQLite reads one, to the
+/// millisecond, or `None` if it's outside SQLite's range.
+pub fn from_julian_day(jd: f64) -> Option<DateTime<Utc>> {
+    let ms = jd * 86_400_000.0 + 0.5;
+    let range = RANGE_JD_MILLIS.0 as f64..=RANGE_JD_MILLIS.1 as f64;
+    if !range.contains(&ms) {
+        return None;
+    }
+    DateTime::from_timestamp_millis(ms as i64 - UNIX_EPOCH_JD_MILLIS)
+}
+
+/// Returns what `unixepoch()` returns for `t` (the whole seconds since the
+/// Unix epoch, rounded down), or `None` if `t` is outside SQLite's range.
+pub fn unix_epoch(t: DateTime<Utc>) -> Option<i64> {
+    Some(truncate(t)?.timestamp())
+}
+
+/// Returns what `datetime()` returns for `t`, like "2024-03-01 12:00:00",
+/// or with `subsec`, what `datetime(..., 'subsec')` and
+/// `strftime('%Y-%m-%d %H:%M:%f')` return, like "2024-03-01 12:00:00.000".
+/// Returns `None` if `t` is outside SQLite's range.
+pub fn datetime(t: DateTime<Utc>, subsec: bool) -> Option<String> {
+    let t = truncate(t)?;
+    let format =
+        if subsec { "%Y-%m-%d %H:%M:%S%.3f" } else { "%Y-%m-%d %H:%M:%S" };
+    Some(format!("{}", t.format(format)))
+}
+
+/// Formats a Julian day number the way SQLite prints one, like "2460371.0"
+/// or "2460371.00001157": with 15 significant digits, which for a day in
+/// SQLite's range is 8 decimal places, without trailing zeros.
+pub fn format_julian_day(jd: f64) -> String {
+    let s = format!("{jd:.8}");
+    let s = s.trim_end_matches('0');
+    if s.ends_with('.') { format!("{s}0") } else { String::from(s) }
+}
@@ -0,0 +1,220 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! SMPTE timecodes
+//!
+//! A timecode like "01:02:03:12" labels a frame of video by hours, minutes,
+//! seconds, and frames, counting a whole number of frames per second: 24,
+//! 25, 30, and so on.  NTSC-derived rates like 29.97 are really 30000/1001
+//! frames per second, so their timecodes still count 30 frames per "second",
+//! and fall behind real time by 3.6 seconds an hour.  Drop-frame timecode
+//! (written with a semicolon, like "01:02:03;12") makes up for that by
+//! skipping the first two frame numbers (four, at 59.94) of every minute
+//! except every tenth.  No frames are dropped, only labels.
+
+use crate::Error;
+use alloc::format;
+use alloc::string::String;
+use chrono::Duration;
+use core::fmt;
+
+/// The number of hours from which timecodes aren't supported, which is far
+/// more than any video
+const MAX_HOURS: i64 = 1_000_000;
+
+/// A frame rate, and whether timecodes at that rate are drop-frame
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rate {
+    /// frames counted per timecode second
+    nominal: u32,
+    /// whether the real rate is `nominal` * 1000/1001
+    ntsc: bool,
+    /// whether timecodes skip frame numbers to keep up with real time
+    drop: bool,
+}
+
+impl Rate {
+    /// Parses a frame rate like "25", "23.976", "29.97", or "29.97df" (or
+    /// "29.97ndf", to say explicitly that it's not drop-frame).  Drop-frame
+    /// is only defined for 29.97 and 59.94.
+    pub fn parse(s: &str) -> Result<Rate, Error> {
+        let lower = s.to_ascii_lowercase();
+        let (number, drop) = if let Some(n) = lower.strip_suffix("ndf") {
+            (n, false)
+        } else if let Some(n) = lower.strip_suffix("df") {
+            (n, true)
+        } else {
+            (&lower[..], false)
+        };
+        let mismatch = || Error::unrecognized(s, "a frame rate (like 29.97df)");
+        let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+        if whole.is_empty()
+            || !whole
+                .bytes()
+                .chain(fraction.bytes())
+                .all(|b| b.is_ascii_digit())
+        {
+            return Err(mismatch());
+        }
+        let whole: u32 = whole.parse().map_err(|_| mismatch())?;
+        let rate = if fraction.bytes().all(|b| b == b'0') {
+            Rate { nominal: whole, ntsc: false, drop }
+        } else {
+            // An NTSC rate is written rounded, like 23.976 (or 23.98) for
+            // 24000/1001, which is just under the next integer.
+            let nominal = whole + 1;
+            let value: f64 = number.parse().map_err(|_| mismatch())?;
+            let error = value - f64::from(nominal) * 1000.0 / 1001.0;
+            if !(-0.01..0.01).contains(&error) {
+                return Err(mismatch());
+            }
+            Rate { nominal, ntsc: true, drop }
+        };
+        if rate.nominal == 0 || rate.nominal > 1000 {
+            return Err(Error::out_of_range(s, "not a frame rate"));
+        }
+        if rate.drop
+            && !(rate.ntsc && (rate.nominal == 30 || rate.nominal == 60))
+        {
+            return Err(Error::out_of_range(
+                s,
+                "drop-frame timecode is only defined for 29.97 and 59.94",
+            ));
+        }
+        Ok(rate)
+    }
+
+    /// Returns whether timecodes at this rate are drop-frame.
+    pub fn is_drop_frame(&self) -> bool {
+        self.drop
+    }
+
+    /// Returns the number of frames counted per timecode second.
+    pub fn nominal(&self) -> u32 {
+        self.nominal
+    }
+
+    /// Returns the real number of frames per second, as a numerator and
+    /// denominator.
+    pub fn frames_per(&self) -> (i64, i64) {
+        let nominal = i64::from(self.nominal);
+        if self.ntsc { (nominal * 1000, 1001) } else { (nominal, 1) }
+    }
+
+    /// Returns the number of frame labels skipped at the start of each
+    /// minute (except every tenth) in drop-frame timecode, or 0.
+    fn dropped(&self) -> i64 {
+        if self.drop { i64::from(self.nominal) / 15 } else { 0 }
+    }
+
+    /// Parses a timecode like "01:02:03:12" (or with a ";" or "." before the
+    /// frames), returning the number of the frame it labels, counting from
+    /// 00:00:00:00.
+    pub fn parse_timecode(&self, s: &str) -> Result<i64, Error> {
+        let mismatch = || Error::unrecognized(s, "a timecode (HH:MM:SS:FF)");
+        let fields = s
+            .split([':', ';', '.', ','])
+            .map(|field| {
+                (!field.is_empty() && field.bytes().all(|b| b.is_ascii_digit()))
+                    .then(|| field.parse::<i64>().ok())
+                    .flatten()
+            })
+            .collect::<Option<alloc::vec::Vec<_>>>()
+            .ok_or_else(mismatch)?;
+        let [hours, minutes, seconds, frames] = fields[..] else {
+            return Err(mismatch());
+        };
+        let nominal = i64::from(self.nominal);
+        if hours >= MAX_HOURS {
+            return Err(Error::out_of_range(s, "too many hours"));
+        }
+        if minutes >= 60 || seconds >= 60 || frames >= nominal {
+            return Err(Error::out_of_range(
+                s,
+                format!("not a timecode at {self} frames per second"),
+            ));
+        }
+        let total_minutes = hours * 60 + minutes;
+        let dropped = self.dropped();
+        if dropped > 0 && seconds == 0 && minutes % 10 != 0 && frames < dropped
+        {
+            return Err(Error::out_of_range(
+                s,
+                "that frame number is skipped in drop-frame timecode",
+            ));
+        }
+        let labels = (total_minutes * 60 + seconds) * nominal + frames;
+        Ok(labels - dropped * (total_minutes - total_minutes / 10))
+    }
+
+    /// Returns the timecode labeling frame number `frame` (counting from
+    /// 00:00:00:00), like "01:02:03;12".  Hours past 23 aren't wrapped, so
+    /// that long timecodes stay in order.
+    pub fn timecode(&self, frame: i64) -> Result<String, Error> {
+        if frame < 0 {
+            return Err(Error::out_of_range(
+                &format!("{frame}"),
+                "timecodes can't be negative",
+            ));
+        }
+        let nominal = i64::from(self.nominal);
+        let dropped = self.dropped();
+        let mut labels = frame;
+        if dropped > 0 {
+            let per_ten = nominal * 600 - dropped * 9;
+            let per_minute = nominal * 60 - dropped;
+            let (tens, rest) = (frame / per_ten, frame % per_ten);
+            labels += dropped * 9 * tens;
+            if rest > dropped {
+                labels += dropped * ((rest - dropped) / per_minute);
+            }
+        }
+        let frames = labels % nominal;
+        let seconds = labels / nominal;
+        let separator = if self.drop { ';' } else { ':' };
+        Ok(format!(
+            "{:02}:{:02}:{:02}{separator}{:02}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60,
+            frames
+        ))
+    }
+
+    /// Returns when frame number `frame` starts, after the first frame, to
+    /// the nearest nanosecond.
+    pub fn duration(&self, frame: i64) -> Option<Duration> {
+        let (num, den) = self.frames_per();
+        let nanos = i128::from(frame) * i128::from(den) * 1_000_000_000;
+        let nanos =
+            (2 * nanos + i128::from(num)).div_euclid(2 * i128::from(num));
+        Some(Duration::nanoseconds(i64::try_from(nanos).ok()?))
+    }
+
+    /// Returns the number of the frame showing at `elapsed` after the first
+    /// frame starts (rounded down), or `None` if that's out of range.
+    pub fn frame_at(&self, elapsed: Duration) -> Option<i64> {
+        let (num, den) = self.frames_per();
+        let nanos = i128::from(elapsed.num_nanoseconds()?);
+        let frames = (nanos * i128::from(num))
+            .div_euclid(i128::from(den) * 1_000_000_000);
+        i64::try_from(frames).ok()
+    }
+}
+
+/// Formats the rate like "29.97df", "23.976", or "25"
+impl fmt::Display for Rate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let drop = if self.drop { "df" } else { "" };
+        if !self.ntsc {
+            return write!(f, "{}{drop}", self.nominal);
+        }
+        // 23.976 is conventionally written with three digits, 29.97 and
+        // 59.94 with two.
+        let rate = f64::from(self.nominal) * 1000.0 / 1001.0;
+        let text = format!("{rate:.3}");
+        let text = text.trim_end_matches('0');
+        write!(f, "{text}{drop}")
+    }
+}
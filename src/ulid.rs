@@ -0,0 +1,115 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Times embedded in ULIDs
+//!
+//! A ULID is 128 bits: the number of milliseconds since the Unix epoch, as
+//! 48 bits, followed by 80 random bits.  It's written as 26 digits of
+//! Crockford's base 32 ("0" through "9" and the letters other than "I",
+//! "L", "O", and "U"), so ULIDs sort by time both as numbers and as text.
+//! That makes the smallest and largest ULIDs for a time ([`Ulid::min`] and
+//! [`Ulid::max`]) useful as bounds for a range query on ULID keys.
+
+use crate::Error;
+use chrono::{DateTime, Utc};
+use core::fmt;
+
+/// The digits of Crockford's base 32, in order
+const DIGITS: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// How many of the 128 bits are random
+const RANDOM_BITS: u32 = 80;
+
+/// A ULID
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Ulid(pub u128);
+
+impl Ulid {
+    /// Parses a ULID written as 26 digits of Crockford's base 32, in either
+    /// case.  As Crockford specifies, "I" and "L" are read as "1", and "O"
+    /// as "0".
+    pub fn parse(s: &str) -> Result<Ulid, Error> {
+        if s.len() != 26 {
+            return Err(Error::unrecognized(
+                s,
+                "a ULID (like \"01ARZ3NDEKTSV4RRFFQ69G5FAV\")",
+            ));
+        }
+        let mut value = 0u128;
+        for (i, c) in s.char_indices() {
+            let c = match c.to_ascii_uppercase() {
+                'I' | 'L' => '1',
+                'O' => '0',
+                c => c,
+            };
+            let Some(digit) = DIGITS.iter().position(|&d| char::from(d) == c)
+            else {
+                return Err(Error::unrecognized_at(
+                    s,
+                    i..i + c.len_utf8(),
+                    "a digit of Crockford's base 32",
+                ));
+            };
+            // 26 digits hold 130 bits, so the first can be at most "7".
+            if i == 0 && digit > 7 {
+                return Err(Error::out_of_range_at(
+                    s,
+                    0..1,
+                    "a ULID must start with a digit from 0 to 7",
+                ));
+            }
+            value = value << 5 | digit as u128;
+        }
+        Ok(Ulid(value))
+    }
+
+    /// Returns a ULID for time `t`, which is truncated to the millisecond,
+    /// with its last 80 bits taken from `random`.  Returns `None` if `t` is
+    /// before 1970 or after the year 10889, when 48 bits of milliseconds
+    /// run out.
+    pub fn new(t: DateTime<Utc>, random: u128) -> Option<Ulid> {
+        let millis = u128::try_from(t.timestamp_millis()).ok()?;
+        if millis >> 48 != 0 {
+            return None;
+        }
+        let random = random & ((1 << RANDOM_BITS) - 1);
+        Some(Ulid(millis << RANDOM_BITS | random))
+    }
+
+    /// Returns the smallest ULID for time `t`, which is no later than any
+    /// other ULID generated in the same millisecond.
+    pub fn min(t: DateTime<Utc>) -> Option<Ulid> {
+        Ulid::new(t, 0)
+    }
+
+    /// Returns the largest ULID for time `t`, which is no earlier than any
+    /// other ULID generated in the same millisecond.
+    pub fn max(t: DateTime<Utc>) -> Option<Ulid> {
+        Ulid::new(t, u128::MAX)
+    }
+
+    /// Returns the time at which this ULID was generated, to the
+    /// millisecond.
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        let millis = (self.0 >> RANDOM_BITS) as i64;
+        // 48 bits of milliseconds are always within chrono's range.
+        DateTime::from_timestamp_millis(millis).unwrap()
+    }
+
+    /// Returns the last 80 bits, which are random.
+    pub fn random(&self) -> u128 {
+        self.0 & ((1 << RANDOM_BITS) - 1)
+    }
+}
+
+impl fmt::Display for Ulid {
+    /// Writes the ULID as 26 digits, in uppercase.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for i in (0..26).rev() {
+            let digit = (self.0 >> (i * 5)) & 0x1f;
+            write!(f, "{}", char::from(DIGITS[digit as usize]))?;
+        }
+        Ok(())
+    }
+}
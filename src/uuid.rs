@@ -0,0 +1,179 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Times embedded in UUIDs
+//!
+//! Version 1 and version 6 UUIDs (RFC 9562) record when they were generated
+//! as a 60-bit count of 100-nanosecond intervals since the start of the
+//! Gregorian calendar, 1582-10-15T00:00:00Z.  Version 1 splits the count
+//! into three fields with the low bits first; version 6 stores the same
+//! fields in the opposite order, so that the UUIDs sort by time.  Both also
+//! have a clock sequence, which the generator changes when its clock might
+//! have gone backwards, and a node, which is either a MAC address of the
+//! generating machine or random (in which case its multicast bit is set).
+//!
+//! Version 7 UUIDs start with the number of milliseconds since the Unix
+//! epoch, as 48 bits, and are otherwise random.  [`Uuid::v7`] makes one.
+
+use crate::Error;
+use chrono::{DateTime, Utc};
+use core::fmt;
+
+/// Seconds from 1582-10-15T00:00:00Z, when UUID timestamps start, to the
+/// Unix epoch
+const GREGORIAN_TO_UNIX: i64 = 12_219_292_800;
+
+/// A UUID
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Uuid(pub u128);
+
+impl Uuid {
+    /// Parses a UUID written as 32 hexadecimal digits, either in the usual
+    /// groups of 8, 4, 4, 4, and 12 separated by "-" or all together, and
+    /// optionally in braces or after "urn:uuid:".
+    pub fn parse(s: &str) -> Result<Uuid, Error> {
+        let expected = "a UUID (like \"c232ab00-9414-11ec-b3c8-9f6bdeced846\")";
+        let (start, body) = if let Some(rest) = s.strip_prefix("urn:uuid:") {
+            (s.len() - rest.len(), rest)
+        } else if let Some(rest) =
+            s.strip_prefix('{').and_then(|r| r.strip_suffix('}'))
+        {
+            (1, rest)
+        } else {
+            (0, s)
+        };
+        let hyphens: &[usize] = match body.len() {
+            36 => &[8, 13, 18, 23],
+            32 => &[],
+            _ => return Err(Error::unrecognized(s, expected)),
+        };
+        let mut value = 0u128;
+        for (i, c) in body.char_indices() {
+            if hyphens.contains(&i) {
+                if c != '-' {
+                    let at = start + i;
+                    return Err(Error::unrecognized_at(s, at..at + 1, "\"-\""));
+                }
+                continue;
+            }
+            let Some(digit) = c.to_digit(16) else {
+                let at = start + i;
+                return Err(Error::unrecognized_at(
+                    s,
+                    at..at + c.len_utf8(),
+                    "a hexadecimal digit",
+                ));
+            };
+            value = value << 4 | u128::from(digit);
+        }
+        Ok(Uuid(value))
+    }
+
+    /// Returns the version, from 0 to 15.  (It's only meaningful for UUIDs
+    /// of the RFC 9562 variant; see [`Uuid::is_rfc9562`].)
+    pub fn version(&self) -> u8 {
+        (self.0 >> 76) as u8 & 0xf
+    }
+
+    /// Returns whether this UUID is of the variant that RFC 9562 (and RFC
+    /// 4122 before it) describes, rather than the NCS, Microsoft, or future
+    /// variant.
+    pub fn is_rfc9562(&self) -> bool {
+        (self.0 >> 62) & 0b11 == 0b10
+    }
+
+    /// Returns a version 7 UUID for time `t`, which is truncated to the
+    /// millisecond, with the rest of its bits taken from `random`.  Returns
+    /// `None` if `t` is before 1970 or after the year 10889, when 48 bits of
+    /// milliseconds run out.
+    pub fn v7(t: DateTime<Utc>, random: u128) -> Option<Uuid> {
+        let millis = u128::try_from(t.timestamp_millis()).ok()?;
+        if millis >> 48 != 0 {
+            return None;
+        }
+        // Leave room for the version (7) and the variant (binary 10).
+        let random = random & (0xfff << 64 | ((1 << 62) - 1));
+        Some(Uuid(millis << 80 | 7 << 76 | 0b10 << 62 | random))
+    }
+
+    /// Returns the time at which this UUID was generated, or `None` if it
+    /// isn't a version 1, 6, or 7 UUID of the RFC 9562 variant.  The time is
+    /// in 100-nanosecond intervals for version 1 and 6, and in milliseconds
+    /// for version 7.
+    pub fn timestamp(&self) -> Option<DateTime<Utc>> {
+        if !self.is_rfc9562() {
+            return None;
+        }
+        if self.version() == 7 {
+            let millis = i64::try_from(self.0 >> 80).ok()?;
+            return DateTime::from_timestamp_millis(millis);
+        }
+        // The time is in the first three fields, without the version.
+        let first = (self.0 >> 96) as u64;
+        let second = (self.0 >> 80) as u64 & 0xffff;
+        let third = (self.0 >> 64) as u64 & 0xfff;
+        let ticks = match self.version() {
+            1 => third << 48 | second << 32 | first,
+            6 => first << 28 | second << 12 | third,
+            _ => return None,
+        };
+        let ticks = i64::try_from(ticks).ok()?;
+        DateTime::from_timestamp(
+            ticks / 10_000_000 - GREGORIAN_TO_UNIX,
+            (ticks % 10_000_000) as u32 * 100,
+        )
+    }
+
+    /// Returns the 14-bit clock sequence of a version 1 or 6 UUID.
+    pub fn clock_sequence(&self) -> u16 {
+        (self.0 >> 48) as u16 & 0x3fff
+    }
+
+    /// Returns the 48-bit node of a version 1 or 6 UUID.
+    pub fn node(&self) -> Node {
+        Node((self.0 & 0xffff_ffff_ffff) as u64)
+    }
+}
+
+impl fmt::Display for Uuid {
+    /// Writes the UUID in the usual form, in lowercase.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let v = self.0;
+        write!(
+            f,
+            "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+            v >> 96,
+            (v >> 80) & 0xffff,
+            (v >> 64) & 0xffff,
+            (v >> 48) & 0xffff,
+            v & 0xffff_ffff_ffff
+        )
+    }
+}
+
+/// The node of a version 1 or 6 UUID
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Node(pub u64);
+
+impl Node {
+    /// Returns whether the multicast bit is set, which means that the node
+    /// is random rather than a MAC address (whose multicast bit is never set
+    /// for a network interface).
+    pub fn is_random(&self) -> bool {
+        (self.0 >> 40) & 1 == 1
+    }
+}
+
+impl fmt::Display for Node {
+    /// Writes the node like a MAC address, as "9f:6b:de:ce:d8:46".
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for i in (0..6).rev() {
+            write!(f, "{:02x}", (self.0 >> (i * 8)) & 0xff)?;
+            if i > 0 {
+                write!(f, ":")?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,99 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Times in W3C extended log files, as IIS writes them
+//!
+//! A W3C extended log starts with directives like these, and each entry
+//! after them is a line of space-separated fields, in the order that
+//! `#Fields` names them:
+//!
+//! ```text
+//! #Software: Microsoft Internet Information Services 10.0
+//! #Version: 1.0
+//! #Date: 2024-03-01 12:00:00
+//! #Fields: date time s-ip cs-method cs-uri-stem sc-status
+//! 2024-03-01 12:00:01 10.0.0.1 GET /index.htm 200
+//! ```
+//!
+//! Times are in UTC, with the `date` and `time` in separate fields.  The
+//! `date` field may be left out, in which case entries are on the date in
+//! `#Date`, when the log was started.
+
+use crate::Error;
+use crate::parser::TimestampParser;
+use alloc::string::String;
+use alloc::vec::Vec;
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+
+/// Parses a W3C `date` field, like "2024-03-01".
+pub fn parse_date(s: &str) -> Result<NaiveDate, Error> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .map_err(|_| Error::unrecognized(s, "a W3C date (like 2024-03-01)"))
+}
+
+/// Parses a W3C `time` field, like "12:00:01", "12:00", or "12:00:01.5".
+pub fn parse_time(s: &str) -> Result<NaiveTime, Error> {
+    NaiveTime::parse_from_str(s, "%H:%M:%S%.f")
+        .or_else(|_| NaiveTime::parse_from_str(s, "%H:%M"))
+        .map_err(|_| Error::unrecognized(s, "a W3C time (like 12:00:01)"))
+}
+
+/// Returns the time of the `date` and `time` fields of an entry.
+pub fn parse_date_time(date: &str, time: &str) -> Result<DateTime<Utc>, Error> {
+    Ok(parse_date(date)?.and_time(parse_time(time)?).and_utc())
+}
+
+/// Where the `date` and `time` fields are in each entry, as named by a
+/// `#Fields` directive
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Fields {
+    /// the names of all the fields, in order
+    pub names: Vec<String>,
+    /// the index of the `date` field, if there is one
+    pub date: Option<usize>,
+    /// the index of the `time` field
+    pub time: usize,
+}
+
+impl Fields {
+    /// Parses a `#Fields` directive, returning `None` if `line` isn't one,
+    /// or an error if it has no `time` field.
+    pub fn parse(line: &str) -> Option<Result<Fields, Error>> {
+        let rest = line.trim_end().strip_prefix("#Fields:")?;
+        let names: Vec<String> =
+            rest.split_whitespace().map(String::from).collect();
+        let find = |name: &str| names.iter().position(|n| n == name);
+        let (date, time) = (find("date"), find("time"));
+        Some(match time {
+            Some(time) => Ok(Fields { date, time, names }),
+            None => {
+                Err(Error::unrecognized(line, "a #Fields directive with time"))
+            }
+        })
+    }
+}
+
+/// The `#Date` directive of a W3C extended log, like "#Date: 2024-03-01
+/// 12:00:00": when the log was started, in UTC
+pub struct DateDirective;
+
+impl TimestampParser for DateDirective {
+    fn name(&self) -> &str {
+        "w3c-date"
+    }
+
+    fn parse(&self, s: &str) -> Result<DateTime<Utc>, Error> {
+        let mismatch = || Error::unrecognized(s, "a W3C #Date directive");
+        let line = s.trim_end();
+        let rest = line.strip_prefix("#Date:").ok_or_else(mismatch)?;
+        let rest = rest.trim_start();
+        let (date, time) = rest.split_once(' ').ok_or_else(mismatch)?;
+        let time = time.trim_start();
+        let date = parse_date(date)
+            .map_err(|e| e.within(s, line.len() - rest.len()))?;
+        let time = parse_time(time)
+            .map_err(|e| e.within(s, line.len() - time.len()))?;
+        Ok(date.and_time(time).and_utc())
+    }
+}
@@ -0,0 +1,68 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! JavaScript bindings for parsing, formatting, and converting
+//!
+//! These are available with the "wasm" feature, for building as a `cdylib`
+//! for the `wasm32-unknown-unknown` target and running through
+//! `wasm-bindgen`.
+//!
+//! Times and deltas are passed as (integral) JavaScript numbers of
+//! microseconds, which are exact for times within about 285 years of 1970.
+//! None of these functions consult the current time.  "Local" renderings
+//! use the browser's time zone.
+
+use crate::zone::Zone;
+use crate::{Error, parse_duration, parse_timestamp};
+use chrono::{DateTime, Duration, Utc};
+use std::string::String;
+use wasm_bindgen::prelude::*;
+
+/// Parses a timestamp in one of the formats built into `dates` (as
+/// [`crate::parse_timestamp`] does, which is less than `dates TIME`
+/// accepts), returning microseconds since the Unix epoch.
+#[wasm_bindgen(js_name = parseTimestamp)]
+pub fn js_parse_timestamp(input: &str) -> Result<f64, JsError> {
+    Ok(parse_timestamp(input)?.timestamp_micros() as f64)
+}
+
+/// Parses a delta the way `dates [+-]DELTA` does, returning microseconds.
+#[wasm_bindgen(js_name = parseDuration)]
+pub fn js_parse_duration(input: &str) -> Result<f64, JsError> {
+    let delta = parse_duration(input)?;
+    let micros = delta
+        .num_microseconds()
+        .ok_or_else(|| Error::out_of_range(input, "too large for a delta"))?;
+    Ok(micros as f64)
+}
+
+/// Formats a time (in microseconds since the Unix epoch) the way `dates`
+/// prints it.
+#[wasm_bindgen(js_name = formatTimestamp)]
+pub fn js_format_timestamp(
+    label: &str,
+    micros: f64,
+) -> Result<String, JsError> {
+    Ok(crate::format_timestamp(label, from_micros(micros)?))
+}
+
+/// Formats a delta (in microseconds) the way `dates` prints it.
+#[wasm_bindgen(js_name = formatDuration)]
+pub fn js_format_duration(label: &str, micros: f64) -> String {
+    crate::format_duration(label, Duration::microseconds(micros as i64))
+}
+
+/// Parses a timestamp and renders it as RFC 3339 in `zone`, which may be
+/// anything accepted by `dates --tz` (e.g., "UTC", "+05:30", or
+/// "America/New_York").
+#[wasm_bindgen]
+pub fn convert(input: &str, zone: &str) -> Result<String, JsError> {
+    let zone = Zone::parse(zone)?;
+    Ok(zone.rfc3339(parse_timestamp(input)?))
+}
+
+fn from_micros(micros: f64) -> Result<DateTime<Utc>, JsError> {
+    DateTime::from_timestamp_micros(micros as i64)
+        .ok_or_else(|| JsError::new("time out of range"))
+}
@@ -0,0 +1,102 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Week numbering
+//!
+//! Week numbering schemes differ in two ways: which day starts the week, and
+//! how many days of a new year the first week must contain to count as week 1
+//! of that year (rather than the last week of the previous one).  ISO 8601
+//! weeks start on Monday and require four days, so week 1 is the week
+//! containing the year's first Thursday.  US weeks start on Sunday and
+//! require only one day, so week 1 is the week containing January 1.
+//!
+//! Either way, days at the start or end of a calendar year can belong to a
+//! week of the adjacent year, so a week number is always paired with its own
+//! week-based year.
+
+use crate::Error;
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+
+/// A week numbering scheme
+#[derive(Clone, Copy, Debug)]
+pub struct WeekRules {
+    /// day on which each week starts
+    pub first_day: Weekday,
+    /// minimum number of days (1-7) of the new year in its week 1
+    pub min_days: u32,
+}
+
+impl WeekRules {
+    pub const ISO: WeekRules =
+        WeekRules { first_day: Weekday::Mon, min_days: 4 };
+    pub const US: WeekRules =
+        WeekRules { first_day: Weekday::Sun, min_days: 1 };
+
+    /// Parses the name of a scheme: "iso" or "us".
+    pub fn parse(s: &str) -> Result<WeekRules, Error> {
+        match s {
+            "iso" | "ISO" => Ok(WeekRules::ISO),
+            "us" | "US" => Ok(WeekRules::US),
+            _ => Err(Error::unrecognized(s, "a week numbering (iso or us)")),
+        }
+    }
+
+    /// Returns the first day of week 1 of week-based year `year`.
+    fn week1_start(&self, year: i32) -> Option<NaiveDate> {
+        let jan1 = NaiveDate::from_ymd_opt(year, 1, 1)?;
+        let lead = jan1.weekday().days_since(self.first_day);
+        let start = jan1.checked_sub_days(Days::new(lead.into()))?;
+        if 7 - lead >= self.min_days {
+            Some(start)
+        } else {
+            start.checked_add_days(Days::new(7))
+        }
+    }
+
+    /// Returns the week-based year and week number of `date`.
+    pub fn week_of(&self, date: NaiveDate) -> (i32, u32) {
+        let mut year = date.year();
+        if let Some(next) = self.week1_start(year + 1)
+            && date >= next
+        {
+            year += 1;
+        } else if self.week1_start(year).is_some_and(|start| date < start) {
+            year -= 1;
+        }
+        let start = self.week1_start(year).unwrap_or(NaiveDate::MIN);
+        (year, ((date - start).num_days() / 7 + 1) as u32)
+    }
+
+    /// Returns the day-of-week number (1-7) of `date`, counting from the day
+    /// that starts the week.
+    pub fn day_number(&self, date: NaiveDate) -> u32 {
+        date.weekday().days_since(self.first_day) + 1
+    }
+
+    /// Returns the date of day `day` (1-7) of week `week` in week-based year
+    /// `year`, or `None` if no such date exists.
+    pub fn date_of(&self, year: i32, week: u32, day: u32) -> Option<NaiveDate> {
+        if !(1..=7).contains(&day) || week == 0 {
+            return None;
+        }
+        let date = self
+            .week1_start(year)?
+            .checked_add_days(Days::new(u64::from((week - 1) * 7 + day - 1)))?;
+        (self.week_of(date) == (year, week)).then_some(date)
+    }
+
+    /// Parses a week date like "2024-W05-3" or "2024-W05" (meaning the first
+    /// day of that week) under these rules.
+    pub fn parse_week_date(&self, s: &str) -> Option<NaiveDate> {
+        let (year, rest) = s.split_once("-W")?;
+        let (week, day) = match rest.split_once('-') {
+            Some((w, d)) => (w, d.parse().ok()?),
+            None => (rest, 1),
+        };
+        if week.len() != 2 {
+            return None;
+        }
+        self.date_of(year.parse().ok()?, week.parse().ok()?, day)
+    }
+}
@@ -0,0 +1,402 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Time zones named on the command line
+//!
+//! A wall-clock time in a zone with daylight saving time may not denote
+//! exactly one instant: when clocks are set back, an hour of wall-clock
+//! times happens twice, and when they're set forward, an hour never happens
+//! at all.  [`Zone::resolve`] reports either case as an error unless a
+//! [`DstPolicy`] says how to pick an instant.
+
+use crate::Error;
+use crate::parser::{Assumption, TimestampParser};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+#[cfg(feature = "std")]
+use chrono::Local;
+use chrono::{
+    DateTime, Duration, FixedOffset, MappedLocalTime, NaiveDate, NaiveDateTime,
+    SecondsFormat, TimeZone, Utc,
+};
+use core::fmt;
+
+/// A time zone given by the user
+///
+/// This is either the local zone, UTC, a fixed offset like "+05:30", or an
+/// IANA zone name like "America/New_York".
+#[derive(Clone, Copy, Debug)]
+pub enum Zone {
+    /// the local time zone (only with the "std" feature)
+    #[cfg(feature = "std")]
+    Local,
+    Utc,
+    Fixed(FixedOffset),
+    #[cfg(feature = "tz")]
+    Named(chrono_tz::Tz),
+}
+
+impl Zone {
+    pub fn parse(s: &str) -> Result<Zone, Error> {
+        match s {
+            #[cfg(feature = "std")]
+            "local" => return Ok(Zone::Local),
+            "UTC" | "utc" | "Z" => return Ok(Zone::Utc),
+            _ => (),
+        }
+        if s.starts_with(['+', '-']) {
+            if let Ok(offset) = s.parse::<FixedOffset>() {
+                return Ok(Zone::Fixed(offset));
+            }
+            return Err(Error::unrecognized(s, "a UTC offset like \"+05:30\""));
+        }
+        Zone::parse_name(s)
+    }
+
+    #[cfg(feature = "tz")]
+    fn parse_name(s: &str) -> Result<Zone, Error> {
+        s.parse::<chrono_tz::Tz>()
+            .map(Zone::Named)
+            .map_err(|_| Error::unrecognized(s, "a time zone"))
+    }
+
+    #[cfg(not(feature = "tz"))]
+    fn parse_name(s: &str) -> Result<Zone, Error> {
+        Err(Error::unrecognized(
+            s,
+            "a time zone (this build supports only \"local\", \"UTC\", \
+             and offsets)",
+        ))
+    }
+
+    /// Returns the calendar date of instant `t` in this zone.
+    pub fn date_of(&self, t: DateTime<Utc>) -> NaiveDate {
+        self.wall_clock(t).date()
+    }
+
+    /// Returns the wall-clock date and time of instant `t` in this zone.
+    pub fn wall_clock(&self, t: DateTime<Utc>) -> NaiveDateTime {
+        match self {
+            #[cfg(feature = "std")]
+            Zone::Local => t.with_timezone(&Local).naive_local(),
+            Zone::Utc => t.naive_utc(),
+            Zone::Fixed(offset) => t.with_timezone(offset).naive_local(),
+            #[cfg(feature = "tz")]
+            Zone::Named(tz) => t.with_timezone(tz).naive_local(),
+        }
+    }
+
+    /// Returns the instant(s) at which the wall clock in this zone reads
+    /// `wall`.  Around daylight saving transitions, there may be two such
+    /// instants (the earlier first), or none.
+    pub fn instant_of(
+        &self,
+        wall: NaiveDateTime,
+    ) -> MappedLocalTime<DateTime<Utc>> {
+        // chrono's `Local` doesn't always give the earlier instant first.
+        match self.instants_of(wall) {
+            MappedLocalTime::Ambiguous(t1, t2) => {
+                MappedLocalTime::Ambiguous(t1.min(t2), t1.max(t2))
+            }
+            other => other,
+        }
+    }
+
+    /// Returns the instant(s) at which the wall clock in this zone reads
+    /// `wall`, in whatever order the zone gives them.
+    fn instants_of(
+        &self,
+        wall: NaiveDateTime,
+    ) -> MappedLocalTime<DateTime<Utc>> {
+        match self {
+            #[cfg(feature = "std")]
+            Zone::Local => Local.from_local_datetime(&wall).map(|t| t.to_utc()),
+            Zone::Utc => MappedLocalTime::Single(wall.and_utc()),
+            Zone::Fixed(offset) => {
+                offset.from_local_datetime(&wall).map(|t| t.to_utc())
+            }
+            #[cfg(feature = "tz")]
+            Zone::Named(tz) => {
+                tz.from_local_datetime(&wall).map(|t| t.to_utc())
+            }
+        }
+    }
+
+    /// Returns the instant at which the wall clock in this zone reads `wall`,
+    /// choosing according to `policy` if there are two such instants or
+    /// none.  `input` is the text that `wall` was parsed from, for errors.
+    pub fn resolve(
+        &self,
+        input: &str,
+        wall: NaiveDateTime,
+        policy: DstPolicy,
+    ) -> Result<DateTime<Utc>, Error> {
+        match self.instant_of(wall) {
+            MappedLocalTime::Single(t) => Ok(t),
+            MappedLocalTime::Ambiguous(t1, t2) => match policy.ambiguous {
+                AmbiguousPolicy::Earliest => Ok(t1),
+                AmbiguousPolicy::Latest => Ok(t2),
+                AmbiguousPolicy::Error => Err(Error::ambiguous_at(
+                    input,
+                    0..input.len(),
+                    format!(
+                        "it occurs twice in {self}, at {} and {}",
+                        offset_at(wall, t1),
+                        offset_at(wall, t2),
+                    ),
+                )),
+            },
+            MappedLocalTime::None => match policy.nonexistent {
+                NonexistentPolicy::Shift => self.shift(wall).ok_or_else(|| {
+                    Error::out_of_range(input, "too large for a timestamp")
+                }),
+                NonexistentPolicy::Error => Err(Error::out_of_range(
+                    input,
+                    format!("clocks in {self} skip over it"),
+                )),
+            },
+        }
+    }
+
+    /// Returns the instant at which a wall clock in this zone would read
+    /// `wall` if it hadn't been set forward, which is after the transition
+    /// by as long as `wall` is after the start of the gap.
+    pub(crate) fn shift(&self, wall: NaiveDateTime) -> Option<DateTime<Utc>> {
+        // Gaps are much shorter than a day, so the offset a day earlier is
+        // the one in effect until the gap.
+        let before = wall.checked_sub_signed(Duration::days(1))?;
+        let t = self.instant_of(before).earliest()?;
+        let offset = self.wall_clock(t) - t.naive_utc();
+        Some(wall.checked_sub_signed(offset)?.and_utc())
+    }
+
+    /// Formats instant `t` as an RFC 3339 timestamp (with microseconds) in
+    /// this zone.
+    pub fn rfc3339(&self, t: DateTime<Utc>) -> String {
+        self.format(t, SecondsFormat::Micros)
+    }
+
+    /// Formats instant `t` as an RFC 3339 timestamp in this zone, with the
+    /// fractional seconds given by `precision`.
+    pub fn format(&self, t: DateTime<Utc>, precision: SecondsFormat) -> String {
+        match self {
+            #[cfg(feature = "std")]
+            Zone::Local => {
+                t.with_timezone(&Local).to_rfc3339_opts(precision, true)
+            }
+            Zone::Utc => t.to_rfc3339_opts(precision, true),
+            Zone::Fixed(offset) => {
+                t.with_timezone(offset).to_rfc3339_opts(precision, true)
+            }
+            #[cfg(feature = "tz")]
+            Zone::Named(tz) => {
+                t.with_timezone(tz).to_rfc3339_opts(precision, true)
+            }
+        }
+    }
+
+    /// Returns this zone's offset at instant `t` if it's local mean time
+    /// there, or `None` otherwise.
+    ///
+    /// Before a zone adopted standard time, the tz database gives it the
+    /// local mean time of its principal city: an offset from Greenwich
+    /// reckoned from the city's longitude, usually not a whole number of
+    /// minutes (like -04:56:02 for New York before 1883).  For a named zone,
+    /// this is the period the database labels "LMT".  The local zone's labels
+    /// aren't available, so for it, any offset that isn't a whole number of
+    /// minutes is taken to be local mean time.
+    #[cfg_attr(
+        not(any(feature = "std", feature = "tz")),
+        allow(unused_variables)
+    )]
+    pub fn local_mean_time(&self, t: DateTime<Utc>) -> Option<FixedOffset> {
+        match self {
+            #[cfg(feature = "std")]
+            Zone::Local => {
+                let offset = offset_at(self.wall_clock(t), t);
+                (offset.local_minus_utc() % 60 != 0).then_some(offset)
+            }
+            Zone::Utc | Zone::Fixed(_) => None,
+            #[cfg(feature = "tz")]
+            Zone::Named(tz) => {
+                use chrono_tz::OffsetName;
+                let offset = tz.offset_from_utc_datetime(&t.naive_utc());
+                (offset.abbreviation() == Some("LMT"))
+                    .then(|| offset_at(self.wall_clock(t), t))
+            }
+        }
+    }
+
+    /// Returns a string that [`Zone::parse()`] would parse as this zone.
+    pub fn spec(&self) -> String {
+        match self {
+            #[cfg(feature = "std")]
+            Zone::Local => String::from("local"),
+            Zone::Utc => String::from("UTC"),
+            Zone::Fixed(offset) => offset.to_string(),
+            #[cfg(feature = "tz")]
+            Zone::Named(tz) => tz.name().to_string(),
+        }
+    }
+}
+
+/// Returns the UTC offset with which wall-clock time `wall` is instant `t`.
+fn offset_at(wall: NaiveDateTime, t: DateTime<Utc>) -> FixedOffset {
+    // Compare whole seconds, so that a leap second (which both readings
+    // share) doesn't count as one.
+    let seconds = wall.and_utc().timestamp() - t.timestamp();
+    FixedOffset::east_opt(seconds as i32).unwrap()
+}
+
+/// What to do with a wall-clock time that occurs twice
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AmbiguousPolicy {
+    /// use the first occurrence (before clocks were set back)
+    Earliest,
+    /// use the second occurrence (after clocks were set back)
+    Latest,
+    /// report [`Error::Ambiguous`]
+    #[default]
+    Error,
+}
+
+impl AmbiguousPolicy {
+    pub fn parse(s: &str) -> Result<AmbiguousPolicy, Error> {
+        match s {
+            "earliest" => Ok(AmbiguousPolicy::Earliest),
+            "latest" => Ok(AmbiguousPolicy::Latest),
+            "error" => Ok(AmbiguousPolicy::Error),
+            _ => Err(Error::unrecognized(
+                s,
+                "a policy for ambiguous times (earliest, latest, or error)",
+            )),
+        }
+    }
+}
+
+/// What to do with a wall-clock time that never occurs
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NonexistentPolicy {
+    /// move it forward by the length of the gap, so that "02:30" on a day
+    /// when clocks jump from 02:00 to 03:00 means 03:30
+    Shift,
+    /// report [`Error::OutOfRange`]
+    #[default]
+    Error,
+}
+
+impl NonexistentPolicy {
+    pub fn parse(s: &str) -> Result<NonexistentPolicy, Error> {
+        match s {
+            "shift" => Ok(NonexistentPolicy::Shift),
+            "error" => Ok(NonexistentPolicy::Error),
+            _ => Err(Error::unrecognized(
+                s,
+                "a policy for nonexistent times (shift or error)",
+            )),
+        }
+    }
+}
+
+/// How [`Zone::resolve`] handles wall-clock times around daylight saving
+/// transitions
+///
+/// The default reports both ambiguous and nonexistent times as errors.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DstPolicy {
+    pub ambiguous: AmbiguousPolicy,
+    pub nonexistent: NonexistentPolicy,
+}
+
+/// Dates and times without a UTC offset or zone abbreviation, interpreted
+/// as wall-clock times in a particular zone
+///
+/// This accepts a date and time like "2024-03-10 02:30" or
+/// "2024-03-10T02:30:00.5", or a bare date (meaning midnight) unless the zone
+/// was assumed.
+pub struct WallClockParser {
+    zone: Zone,
+    policy: DstPolicy,
+    /// whether nobody chose the zone, so that it's reported as an assumption
+    /// and bare dates are left to other parsers
+    assumed: bool,
+}
+
+/// The formats that [`WallClockParser`] accepts, most specific first
+const WALL_CLOCK_FORMATS: [&str; 4] = [
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M",
+];
+
+impl WallClockParser {
+    /// Returns a parser for wall-clock times in `zone` that resolves times
+    /// around daylight saving transitions according to `policy`.
+    pub const fn new(zone: Zone, policy: DstPolicy) -> WallClockParser {
+        WallClockParser { zone, policy, assumed: false }
+    }
+
+    /// Returns a parser like [`WallClockParser::new`] that also reports the
+    /// zone as an assumption, for when nobody chose it (as with the local
+    /// zone, by default).  This one accepts only dates with a time of day, so
+    /// that bare dates mean midnight UTC, as they do in ISO 8601 parsers.
+    pub const fn assuming(zone: Zone, policy: DstPolicy) -> WallClockParser {
+        WallClockParser { zone, policy, assumed: true }
+    }
+
+    /// Parses `s` as a wall-clock time, and returns whether it had a time
+    /// of day.
+    fn wall(&self, s: &str) -> Result<(NaiveDateTime, bool), Error> {
+        if let Some(wall) = WALL_CLOCK_FORMATS
+            .iter()
+            .find_map(|f| NaiveDateTime::parse_from_str(s, f).ok())
+        {
+            return Ok((wall, true));
+        }
+        if self.assumed {
+            return Err(Error::unrecognized(s, "a date and time"));
+        }
+        NaiveDate::parse_from_str(s, "%Y-%m-%d")
+            .map(|d| (d.and_hms_opt(0, 0, 0).unwrap(), false))
+            .map_err(|_| Error::unrecognized(s, "a date and time"))
+    }
+}
+
+impl TimestampParser for WallClockParser {
+    fn name(&self) -> &str {
+        "wall-clock"
+    }
+
+    fn parse(&self, s: &str) -> Result<DateTime<Utc>, Error> {
+        let (wall, _) = self.wall(s)?;
+        self.zone.resolve(s, wall, self.policy)
+    }
+
+    fn assumptions(&self, s: &str) -> Vec<Assumption> {
+        let mut assumptions = match self.wall(s) {
+            Ok((_, false)) => vec![Assumption::Midnight],
+            Ok((_, true)) => Vec::new(),
+            Err(_) => return Vec::new(),
+        };
+        if self.assumed {
+            assumptions.push(Assumption::Zone { zone: self.zone.to_string() });
+        }
+        assumptions
+    }
+}
+
+impl fmt::Display for Zone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            Zone::Local => write!(f, "local time"),
+            Zone::Utc => write!(f, "UTC"),
+            Zone::Fixed(offset) => write!(f, "UTC{offset}"),
+            #[cfg(feature = "tz")]
+            Zone::Named(tz) => write!(f, "{}", tz.name()),
+        }
+    }
+}
@@ -0,0 +1,74 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Tests of wall-clock times that occur twice when clocks are set back
+
+#![cfg(all(feature = "std", feature = "tz"))]
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use dates_tool::delta::{Arith, CalendarDelta, Rules};
+use dates_tool::zone::{AmbiguousPolicy, DstPolicy, NonexistentPolicy, Zone};
+use std::sync::Once;
+
+/// 01:30 on the morning clocks in New York were set back in 2024
+const WALL: &str = "2024-11-03T01:30:00";
+
+/// That time in EDT, before clocks were set back, and in EST, after
+const EDT: &str = "2024-11-03T05:30:00Z";
+const EST: &str = "2024-11-03T06:30:00Z";
+
+/// Returns the local zone, and New York by name, in which WALL occurs twice.
+fn zones() -> [Zone; 2] {
+    static SET_TZ: Once = Once::new();
+    // Every test that reads the local zone calls this first, so nothing
+    // reads the environment while it's being changed.
+    SET_TZ.call_once(|| unsafe { std::env::set_var("TZ", "America/New_York") });
+    [Zone::Local, Zone::parse("America/New_York").unwrap()]
+}
+
+fn time(s: &str) -> DateTime<Utc> {
+    s.parse().unwrap()
+}
+
+fn policy(ambiguous: AmbiguousPolicy) -> DstPolicy {
+    DstPolicy { ambiguous, nonexistent: NonexistentPolicy::Error }
+}
+
+#[test]
+fn instants_are_in_order() {
+    let wall: NaiveDateTime = WALL.parse().unwrap();
+    for zone in zones() {
+        let earliest =
+            zone.resolve(WALL, wall, policy(AmbiguousPolicy::Earliest));
+        let latest = zone.resolve(WALL, wall, policy(AmbiguousPolicy::Latest));
+        assert_eq!(earliest, Ok(time(EDT)), "in {zone}");
+        assert_eq!(latest, Ok(time(EST)), "in {zone}");
+
+        let error = zone
+            .resolve(WALL, wall, policy(AmbiguousPolicy::Error))
+            .unwrap_err();
+        assert!(
+            error.to_string().ends_with("at -04:00 and -05:00"),
+            "in {zone}: {error}"
+        );
+    }
+}
+
+#[test]
+fn wall_days_keep_the_offset() {
+    let day = CalendarDelta::parse("+1d").unwrap();
+    let rules = Rules { arith: Arith::Wall, ..Rules::default() };
+    let start = time("2024-11-02T05:30:00Z");
+    for zone in zones() {
+        for (ambiguous, expected) in
+            [(AmbiguousPolicy::Earliest, EDT), (AmbiguousPolicy::Latest, EST)]
+        {
+            assert_eq!(
+                day.add_to_with(start, zone, rules, policy(ambiguous)),
+                Some(time(expected)),
+                "in {zone}, {ambiguous:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,50 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Tests of relative times that snap to the start of a week
+
+use chrono::{DateTime, Utc};
+use dates_tool::delta::Rules;
+use dates_tool::relative::Relative;
+use dates_tool::zone::{DstPolicy, Zone};
+
+/// A Thursday
+const NOW: &str = "2018-02-08T21:01:23Z";
+
+fn apply(s: &str) -> Option<DateTime<Utc>> {
+    let now = NOW.parse().unwrap();
+    let relative = Relative::parse(s).unwrap();
+    relative.apply(now, Zone::Utc, Rules::default(), DstPolicy::default())
+}
+
+fn time(s: &str) -> Option<DateTime<Utc>> {
+    Some(s.parse().unwrap())
+}
+
+#[test]
+fn splunk_weeks_start_on_sunday() {
+    assert_eq!(apply("now@w"), time("2018-02-04T00:00:00Z"));
+    assert_eq!(apply("@week"), time("2018-02-04T00:00:00Z"));
+    assert_eq!(apply("-1w@w"), time("2018-01-28T00:00:00Z"));
+}
+
+#[test]
+fn splunk_weeks_start_on_the_given_day() {
+    for (s, expected) in [
+        ("now@w0", "2018-02-04T00:00:00Z"),
+        ("now@w1", "2018-02-05T00:00:00Z"),
+        ("now@w4", "2018-02-08T00:00:00Z"),
+        ("now@w5", "2018-02-02T00:00:00Z"),
+        ("now@w6", "2018-02-03T00:00:00Z"),
+        ("now@w1+8h", "2018-02-05T08:00:00Z"),
+    ] {
+        assert_eq!(apply(s), time(expected), "{s}");
+    }
+    assert!(Relative::parse("now@w7").is_err());
+}
+
+#[test]
+fn elasticsearch_weeks_start_on_monday() {
+    assert_eq!(apply("now/w"), time("2018-02-05T00:00:00Z"));
+}
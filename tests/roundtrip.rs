@@ -0,0 +1,126 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Property tests: every format `dates` writes parses back to the same time
+
+use chrono::{DateTime, FixedOffset, SecondsFormat, Utc};
+use dates_tool::parse_timestamp;
+use dates_tool::report::Timestamp;
+use dates_tool::roundtrip::{TimeFormat, roundtrip_check, verify_timestamp};
+use dates_tool::zone::Zone;
+use proptest::prelude::*;
+
+/// Times from 0002-01-01 through 9998-12-31, which stay within four-digit
+/// years (which all of the formats support) in any zone
+fn any_time() -> impl Strategy<Value = DateTime<Utc>> {
+    times_from(-62104060800)
+}
+
+/// Times from `start` (in seconds since the epoch) through 9998-12-31
+fn times_from(start: i64) -> impl Strategy<Value = DateTime<Utc>> {
+    (start..253370764800, 0u32..1_000_000_000).prop_map(|(secs, nanos)| {
+        DateTime::from_timestamp(secs, nanos).unwrap()
+    })
+}
+
+/// Zones whose offsets are always whole minutes, which is all that RFC 3339
+/// can express
+fn any_zone() -> impl Strategy<Value = Zone> {
+    let fixed = (-1439i32..=1439).prop_map(|minutes| {
+        Zone::Fixed(FixedOffset::east_opt(minutes * 60).unwrap())
+    });
+    let named = prop::sample::select(vec![
+        // Without std, there's no local zone.
+        #[cfg(feature = "std")]
+        Zone::Local,
+        Zone::Utc,
+    ]);
+    prop_oneof![named, fixed]
+}
+
+/// Named zones, whose historical offsets were not always whole minutes.
+/// These do round-trip for times since 1950.
+#[cfg(feature = "tz")]
+fn any_named_zone() -> impl Strategy<Value = Zone> {
+    prop::sample::select(vec![
+        "America/New_York",
+        "Asia/Kolkata",
+        "Australia/Lord_Howe",
+        "Europe/London",
+        "Pacific/Chatham",
+    ])
+    .prop_map(|name| Zone::parse(name).unwrap())
+}
+
+fn any_precision() -> impl Strategy<Value = SecondsFormat> {
+    prop::sample::select(vec![
+        SecondsFormat::Secs,
+        SecondsFormat::Millis,
+        SecondsFormat::Micros,
+        SecondsFormat::Nanos,
+        SecondsFormat::AutoSi,
+    ])
+}
+
+fn any_format() -> impl Strategy<Value = TimeFormat> {
+    prop_oneof![
+        (any_zone(), any_precision())
+            .prop_map(|(zone, precision)| TimeFormat::Rfc3339(zone, precision)),
+        // Decimal seconds are parsed with millisecond precision, and integers
+        // are taken as milliseconds, so only this precision round-trips.
+        Just(TimeFormat::UnixSeconds(SecondsFormat::Millis)),
+        Just(TimeFormat::UnixMillis),
+        Just(TimeFormat::Date),
+        Just(TimeFormat::WeekDate),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn formatted_times_parse_back(format in any_format(), t in any_time()) {
+        if let Err(failure) = roundtrip_check(format, t) {
+            return Err(TestCaseError::fail(failure.to_string()));
+        }
+    }
+
+    #[cfg(feature = "tz")]
+    #[test]
+    fn named_zones_parse_back(
+        zone in any_named_zone(),
+        precision in any_precision(),
+        t in times_from(-631152000),
+    ) {
+        let format = TimeFormat::Rfc3339(zone, precision);
+        if let Err(failure) = roundtrip_check(format, t) {
+            return Err(TestCaseError::fail(failure.to_string()));
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn printed_times_verify(zone in any_zone(), t in any_time()) {
+        let timestamp = Timestamp::new(t, &[zone, Zone::Utc]);
+        if let Err(failure) = verify_timestamp(&timestamp, t) {
+            return Err(TestCaseError::fail(failure.to_string()));
+        }
+    }
+}
+
+#[test]
+fn unix_seconds_read_back_exactly() {
+    let t = DateTime::from_timestamp(1518048000, 123_456_789).unwrap();
+    for (precision, text) in [
+        (SecondsFormat::Micros, "1518048000.123456"),
+        (SecondsFormat::Nanos, "1518048000.123456789"),
+    ] {
+        let format = TimeFormat::UnixSeconds(precision);
+        assert_eq!(format.format(t), text);
+        assert_eq!(roundtrip_check(format, t), Ok(()));
+    }
+    assert_eq!(
+        parse_timestamp("1518048000.123456"),
+        Ok(DateTime::from_timestamp(1518048000, 123_456_000).unwrap())
+    );
+}